@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE repo_issues;
+DROP TABLE repo_pulls;
+DROP TABLE repo_commits;
+DROP TABLE repo_syncs;
@@ -0,0 +1,83 @@
+-- Your SQL goes here
+CREATE TABLE repo_syncs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the project the repository is listed on
+    project_id INTEGER NOT NULL,
+    -- URL of the repository as it appears in projects.repos
+    repo TEXT NOT NULL,
+    -- ETag of the first page of commits from the last sync
+    commits_etag TEXT,
+    -- ETag of the first page of pull requests from the last sync
+    pulls_etag TEXT,
+    -- Oldest commit listed by a sync that didn't get back to cached history
+    commits_cursor TEXT,
+    -- When the repository was last synced successfully
+    synced_at DATETIME,
+    -- Error from the last failed sync, if any
+    last_error TEXT,
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    UNIQUE (project_id, repo)
+);
+
+CREATE TABLE repo_commits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the project the commit belongs to
+    project_id INTEGER NOT NULL,
+    -- URL of the repository the commit is in
+    repo TEXT NOT NULL,
+    -- The commit hash
+    sha TEXT NOT NULL,
+    -- Forge login of the author if it is linked to an account
+    author TEXT,
+    -- The commit message
+    message TEXT NOT NULL,
+    -- When the commit was authored
+    committed_at DATETIME NOT NULL,
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    UNIQUE (repo, sha)
+);
+
+CREATE TABLE repo_pulls (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the project the pull request belongs to
+    project_id INTEGER NOT NULL,
+    -- URL of the repository the pull request is in
+    repo TEXT NOT NULL,
+    -- The pull request number
+    number INTEGER NOT NULL,
+    -- Forge login of the user who opened it
+    author TEXT,
+    -- Title of the pull request
+    title TEXT NOT NULL,
+    -- Either "open" or "closed"
+    state TEXT NOT NULL,
+    -- Was the pull request merged?
+    merged BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    closed_at DATETIME,
+    merged_at DATETIME,
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    UNIQUE (repo, number)
+);
+
+CREATE TABLE repo_issues (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the project the issue belongs to
+    project_id INTEGER NOT NULL,
+    -- URL of the repository the issue is in
+    repo TEXT NOT NULL,
+    -- The issue number
+    number INTEGER NOT NULL,
+    -- Forge login of the user who opened it
+    author TEXT,
+    -- Title of the issue
+    title TEXT NOT NULL,
+    -- Either "open" or "closed"
+    state TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    closed_at DATETIME,
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    UNIQUE (repo, number)
+);
@@ -0,0 +1,25 @@
+-- Lines changed by each pull request
+ALTER TABLE repo_pulls ADD additions INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE repo_pulls ADD deletions INTEGER NOT NULL DEFAULT 0;
+-- Forge login of the user who closed the issue
+ALTER TABLE repo_issues ADD closed_by TEXT;
+
+CREATE TABLE repo_reviews (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the project the review belongs to
+    project_id INTEGER NOT NULL,
+    -- URL of the repository the review is in
+    repo TEXT NOT NULL,
+    -- ID of the review on the forge
+    review_id INTEGER NOT NULL,
+    -- Number of the pull request that was reviewed
+    pull_number INTEGER NOT NULL,
+    -- Forge login of the reviewer
+    author TEXT,
+    -- The review state such as "APPROVED" or "CHANGES_REQUESTED"
+    state TEXT NOT NULL,
+    -- When the review was submitted
+    submitted_at DATETIME NOT NULL,
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    UNIQUE (repo, review_id)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE status_update_comments;
+DROP TABLE status_updates;
@@ -0,0 +1,36 @@
+-- Your SQL goes here
+CREATE TABLE status_updates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the user who posted the update
+    user_id INTEGER NOT NULL,
+    -- ID of the project the update is about
+    project_id INTEGER NOT NULL,
+    -- The Monday of the week the update is for
+    week DATE NOT NULL,
+    -- The update itself as Markdown
+    body TEXT NOT NULL,
+    -- When the update was posted
+    created_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    -- ID of the mentor who reviewed the update
+    reviewed_by INTEGER,
+    -- When the update was reviewed
+    reviewed_at DATETIME,
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    FOREIGN KEY (reviewed_by) REFERENCES users (id),
+    UNIQUE (user_id, project_id, week)
+);
+
+CREATE TABLE status_update_comments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the update being commented on
+    update_id INTEGER NOT NULL,
+    -- ID of the user who wrote the comment
+    user_id INTEGER NOT NULL,
+    -- The comment as Markdown
+    body TEXT NOT NULL,
+    -- When the comment was posted
+    created_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (update_id) REFERENCES status_updates (id),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE evaluations;
+DROP TABLE eval_forms;
@@ -0,0 +1,42 @@
+-- Your SQL goes here
+CREATE TABLE eval_forms (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- Title of the form such as "Midterm Evaluation"
+    title TEXT NOT NULL,
+    -- Instructions for the people filling it out as Markdown
+    description TEXT NOT NULL,
+    -- The questions as a JSON array
+    questions TEXT NOT NULL,
+    -- Semester the form is for, like "2019-fall"
+    semester TEXT NOT NULL,
+    -- Can students evaluate themselves?
+    allow_self BOOLEAN NOT NULL DEFAULT 0,
+    -- Can students see the evaluations of themselves?
+    visible_to_student BOOLEAN NOT NULL DEFAULT 0,
+    -- Does the form count toward the grade?
+    graded BOOLEAN NOT NULL DEFAULT 0,
+    -- Is the form accepting evaluations?
+    open BOOLEAN NOT NULL DEFAULT 1,
+    -- ID of the coordinator who created the form
+    created_by INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (created_by) REFERENCES users (id)
+);
+
+CREATE TABLE evaluations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the form that was filled out
+    form_id INTEGER NOT NULL,
+    -- ID of the student being evaluated
+    student_id INTEGER NOT NULL,
+    -- ID of the user who filled out the form
+    evaluator_id INTEGER NOT NULL,
+    -- The answers as a JSON array in the same order as the questions
+    answers TEXT NOT NULL,
+    -- When the evaluation was last submitted
+    submitted_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (form_id) REFERENCES eval_forms (id),
+    FOREIGN KEY (student_id) REFERENCES users (id),
+    FOREIGN KEY (evaluator_id) REFERENCES users (id),
+    UNIQUE (form_id, student_id, evaluator_id)
+);
@@ -0,0 +1,43 @@
+-- Your SQL goes here
+CREATE TABLE grades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the student being graded
+    user_id INTEGER NOT NULL,
+    -- Semester of the grade, like "2019-fall"
+    semester TEXT NOT NULL,
+    -- Grade computed from attendance, status updates, and evaluations
+    computed INTEGER NOT NULL,
+    -- Grade set by a coordinator which replaces the computed one
+    override_value INTEGER,
+    -- Why the grade was overridden
+    reason TEXT,
+    -- ID of the coordinator who overrode the grade
+    overridden_by INTEGER,
+    overridden_at DATETIME,
+    -- Has the grade been signed off on?
+    submitted BOOLEAN NOT NULL DEFAULT 0,
+    -- ID of the coordinator who submitted the grade
+    submitted_by INTEGER,
+    submitted_at DATETIME,
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    FOREIGN KEY (overridden_by) REFERENCES users (id),
+    FOREIGN KEY (submitted_by) REFERENCES users (id),
+    UNIQUE (user_id, semester)
+);
+
+CREATE TABLE grade_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the grade that changed
+    grade_id INTEGER NOT NULL,
+    -- ID of the user who made the change
+    user_id INTEGER NOT NULL,
+    -- What was done, such as "override" or "submit"
+    action TEXT NOT NULL,
+    -- The final grade before and after the change
+    old_value INTEGER,
+    new_value INTEGER,
+    reason TEXT,
+    created_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (grade_id) REFERENCES grades (id),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+CREATE TABLE notifications (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the user being notified
+    user_id INTEGER NOT NULL,
+    -- The notification itself
+    message TEXT NOT NULL,
+    -- Where to go to see what it is about
+    link TEXT NOT NULL,
+    -- Has the user seen it?
+    seen BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
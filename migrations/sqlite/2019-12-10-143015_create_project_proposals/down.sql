@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE proposal_comments;
+DROP TABLE project_proposals;
@@ -0,0 +1,37 @@
+-- Your SQL goes here
+
+-- Existing projects were all live so they count as approved
+ALTER TABLE projects ADD status TEXT NOT NULL DEFAULT 'approved';
+
+CREATE TABLE project_proposals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the project being proposed
+    project_id INTEGER NOT NULL UNIQUE,
+    -- What the project aims to do this semester
+    goals TEXT NOT NULL,
+    -- Languages, frameworks, and tools the project will use
+    stack TEXT NOT NULL,
+    -- License the code will be released under
+    license TEXT NOT NULL,
+    -- How many students the project is looking for
+    team_size INTEGER NOT NULL,
+    submitted_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    -- ID of the coordinator who last reviewed the proposal
+    reviewed_by INTEGER,
+    reviewed_at DATETIME,
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    FOREIGN KEY (reviewed_by) REFERENCES users (id)
+);
+
+CREATE TABLE proposal_comments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the proposal being commented on
+    proposal_id INTEGER NOT NULL,
+    -- ID of the user who wrote the comment
+    user_id INTEGER NOT NULL,
+    -- The comment as Markdown
+    body TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (proposal_id) REFERENCES project_proposals (id),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- Who can join: open, approval, closed, or invite
+ALTER TABLE projects ADD join_policy TEXT NOT NULL DEFAULT 'open';
+-- Most members the project can have, no limit if NULL
+ALTER TABLE projects ADD max_size INTEGER;
+
+CREATE TABLE join_requests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the project the user wants to join
+    project_id INTEGER NOT NULL,
+    -- ID of the user who wants to join
+    user_id INTEGER NOT NULL,
+    -- Message to the project owner
+    message TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    UNIQUE (project_id, user_id)
+);
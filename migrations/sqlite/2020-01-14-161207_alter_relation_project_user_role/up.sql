@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+-- Role of the member in the project: lead, maintainer, member, or mentor
+ALTER TABLE relation_project_user ADD role TEXT NOT NULL DEFAULT 'member';
+
+-- The owner of each project is its lead
+UPDATE relation_project_user SET role = 'lead'
+WHERE user_id = (
+    SELECT owner_id FROM projects WHERE projects.id = relation_project_user.project_id
+);
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- Primary language set by the project, overrides the detected ones if set
+ALTER TABLE projects ADD language TEXT;
+-- Primary language of the repository as reported by the forge
+ALTER TABLE repo_syncs ADD language TEXT;
+
+CREATE TABLE project_tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the project the tag is on
+    project_id INTEGER NOT NULL,
+    -- The tag, always lowercase
+    tag TEXT NOT NULL,
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    UNIQUE (project_id, tag)
+);
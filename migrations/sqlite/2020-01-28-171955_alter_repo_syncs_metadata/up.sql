@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+-- Description of the repository
+ALTER TABLE repo_syncs ADD description TEXT;
+-- Name of the license such as "MIT License"
+ALTER TABLE repo_syncs ADD license TEXT;
+ALTER TABLE repo_syncs ADD stars INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE repo_syncs ADD open_issues INTEGER NOT NULL DEFAULT 0;
+-- The README as Markdown
+ALTER TABLE repo_syncs ADD readme TEXT;
+-- Tag of the latest release
+ALTER TABLE repo_syncs ADD latest_release TEXT;
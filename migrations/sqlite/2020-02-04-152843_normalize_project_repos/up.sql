@@ -0,0 +1,36 @@
+-- Your SQL goes here
+
+-- Repository lists are JSON arrays of URLs. This handles the common
+-- variations, anything else is reported at launch by the RepoCheck fairing.
+UPDATE projects SET repos = REPLACE(repos, '"http://', '"https://');
+UPDATE projects SET repos = REPLACE(repos, '"https://www.', '"https://');
+UPDATE projects SET repos = REPLACE(repos, '/"', '"');
+UPDATE projects SET repos = REPLACE(repos, '.git"', '"');
+UPDATE projects SET repos = REPLACE(repos, '/"', '"');
+UPDATE projects SET repos = REPLACE(repos, '"github.com/', '"https://github.com/');
+UPDATE projects SET repos = REPLACE(repos, '"www.github.com/', '"https://github.com/');
+
+-- The repository cache refers to repositories by the same URLs, so the
+-- same changes are made there with each URL quoted like in the JSON. A row
+-- that would clash with one already under the normalized URL is left as
+-- it is and pruned by the next sync.
+UPDATE OR IGNORE repo_syncs SET repo = TRIM(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(
+    '"' || repo || '"',
+    '"http://', '"https://'), '"https://www.', '"https://'), '/"', '"'), '.git"', '"'), '/"', '"'),
+    '"github.com/', '"https://github.com/'), '"www.github.com/', '"https://github.com/'), '"');
+UPDATE OR IGNORE repo_commits SET repo = TRIM(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(
+    '"' || repo || '"',
+    '"http://', '"https://'), '"https://www.', '"https://'), '/"', '"'), '.git"', '"'), '/"', '"'),
+    '"github.com/', '"https://github.com/'), '"www.github.com/', '"https://github.com/'), '"');
+UPDATE OR IGNORE repo_pulls SET repo = TRIM(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(
+    '"' || repo || '"',
+    '"http://', '"https://'), '"https://www.', '"https://'), '/"', '"'), '.git"', '"'), '/"', '"'),
+    '"github.com/', '"https://github.com/'), '"www.github.com/', '"https://github.com/'), '"');
+UPDATE OR IGNORE repo_reviews SET repo = TRIM(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(
+    '"' || repo || '"',
+    '"http://', '"https://'), '"https://www.', '"https://'), '/"', '"'), '.git"', '"'), '/"', '"'),
+    '"github.com/', '"https://github.com/'), '"www.github.com/', '"https://github.com/'), '"');
+UPDATE OR IGNORE repo_issues SET repo = TRIM(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(REPLACE(
+    '"' || repo || '"',
+    '"http://', '"https://'), '"https://www.', '"https://'), '/"', '"'), '.git"', '"'), '/"', '"'),
+    '"github.com/', '"https://github.com/'), '"www.github.com/', '"https://github.com/'), '"');
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+-- Archived projects and groups are read-only and hidden from the lists
+ALTER TABLE projects ADD archived BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE groups ADD archived BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+
+CREATE TABLE uploads (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- Name the files are stored under, based on the kind and a hash of the contents
+    key TEXT NOT NULL UNIQUE,
+    -- What the image is for: logo, avatar, or news
+    kind TEXT NOT NULL,
+    -- Content type of the file as uploaded
+    content_type TEXT NOT NULL,
+    -- Size of the file as uploaded in bytes
+    size INTEGER NOT NULL,
+    -- Dimensions of the full size image after resizing
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    -- ID of the user who uploaded it
+    uploaded_by INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (uploaded_by) REFERENCES users (id)
+);
+
+-- Keys of the uploads used as images
+ALTER TABLE projects ADD logo TEXT;
+ALTER TABLE users ADD avatar TEXT;
+ALTER TABLE news ADD image TEXT;
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+CREATE TABLE project_activity (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the project it happened on
+    project_id INTEGER NOT NULL,
+    -- ID of the user who did it, NULL for things like repository syncs
+    user_id INTEGER,
+    -- What kind of thing happened: member, update, commit, pull, meeting, or edit
+    kind TEXT NOT NULL,
+    -- What happened, shown after the user's name if there is one
+    message TEXT NOT NULL,
+    -- Where to go to see it
+    link TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
+
+CREATE INDEX project_activity_project ON project_activity (project_id, created_at);
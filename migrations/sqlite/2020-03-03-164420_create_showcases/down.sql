@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE showcase_scores;
+DROP TABLE showcase_votes;
+DROP TABLE showcase_judges;
+DROP TABLE showcase_entries;
+DROP TABLE showcases;
@@ -0,0 +1,64 @@
+-- Your SQL goes here
+CREATE TABLE showcases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the calendar event the showcase happens at
+    event_id INTEGER NOT NULL UNIQUE,
+    -- Categories the audience votes in as a JSON array
+    categories TEXT NOT NULL DEFAULT '[]',
+    -- Criteria the judges score against as a JSON array
+    rubric TEXT NOT NULL DEFAULT '[]',
+    -- Is the audience allowed to vote right now?
+    voting_open BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY (event_id) REFERENCES events (id)
+);
+
+CREATE TABLE showcase_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    showcase_id INTEGER NOT NULL,
+    -- ID of the project presenting
+    project_id INTEGER NOT NULL,
+    FOREIGN KEY (showcase_id) REFERENCES showcases (id),
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    UNIQUE (showcase_id, project_id)
+);
+
+CREATE TABLE showcase_judges (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    showcase_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    FOREIGN KEY (showcase_id) REFERENCES showcases (id),
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    UNIQUE (showcase_id, user_id)
+);
+
+CREATE TABLE showcase_votes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    showcase_id INTEGER NOT NULL,
+    -- ID of the entry voted for
+    entry_id INTEGER NOT NULL,
+    -- ID of the user voting
+    user_id INTEGER NOT NULL,
+    -- Category the vote is in
+    category TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (showcase_id) REFERENCES showcases (id),
+    FOREIGN KEY (entry_id) REFERENCES showcase_entries (id),
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    -- One vote per user per category
+    UNIQUE (showcase_id, user_id, category)
+);
+
+CREATE TABLE showcase_scores (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the entry being scored
+    entry_id INTEGER NOT NULL,
+    -- ID of the judge
+    judge_id INTEGER NOT NULL,
+    -- Score for each criterion of the rubric as a JSON array
+    scores TEXT NOT NULL,
+    submitted_at DATETIME NOT NULL DEFAULT (datetime('now','localtime')),
+    FOREIGN KEY (entry_id) REFERENCES showcase_entries (id),
+    FOREIGN KEY (judge_id) REFERENCES users (id),
+    -- Scoring again replaces the previous scores
+    UNIQUE (entry_id, judge_id)
+);
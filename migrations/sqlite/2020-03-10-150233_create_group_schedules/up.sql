@@ -0,0 +1,26 @@
+-- Your SQL goes here
+CREATE TABLE group_schedules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the group that meets
+    group_id INTEGER NOT NULL,
+    -- Day of the week the group meets, 0 is Monday
+    weekday INTEGER NOT NULL,
+    -- Time of day the meetings start
+    start_time TIME NOT NULL,
+    -- Where the group meets
+    location TEXT,
+    -- First day of the term, no meetings before it
+    starts_on DATE NOT NULL,
+    -- Last day of the term, no meetings after it
+    ends_on DATE NOT NULL,
+    -- Days there is no meeting such as holidays as a JSON array
+    skip_dates TEXT NOT NULL DEFAULT '[]',
+    FOREIGN KEY (group_id) REFERENCES groups (id)
+);
+
+-- Meetings generated from a schedule remember the day they were generated
+-- for so that rescheduling or cancelling one doesn't generate it again
+ALTER TABLE meetings ADD schedule_id INTEGER REFERENCES group_schedules (id);
+ALTER TABLE meetings ADD occurrence DATE;
+-- Cancelled meetings can't be attended and don't count toward grades
+ALTER TABLE meetings ADD cancelled BOOLEAN NOT NULL DEFAULT 0;
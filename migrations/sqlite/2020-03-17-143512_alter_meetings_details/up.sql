@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+-- What the meeting is about, shown instead of the time when set
+ALTER TABLE meetings ADD title TEXT;
+-- Plan for the meeting as Markdown
+ALTER TABLE meetings ADD agenda TEXT;
+-- What was discussed as Markdown
+ALTER TABLE meetings ADD notes TEXT;
+-- Where the meeting is when it isn't where the group usually meets
+ALTER TABLE meetings ADD location TEXT;
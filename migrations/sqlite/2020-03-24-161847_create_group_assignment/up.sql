@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+-- Most students the group's mentor and room can take, empty for no limit
+ALTER TABLE groups ADD capacity INTEGER;
+
+-- Projects a group's mentor would like to have in their group
+CREATE TABLE group_project_prefs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    group_id INTEGER NOT NULL,
+    project_id INTEGER NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups (id),
+    FOREIGN KEY (project_id) REFERENCES projects (id),
+    UNIQUE (group_id, project_id)
+);
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+CREATE TABLE rooms (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- Name of the room like "DCC 330"
+    name TEXT NOT NULL,
+    -- Building the room is in
+    building TEXT,
+    -- Most people the room fits
+    capacity INTEGER,
+    -- Accessibility notes like where the elevator is
+    accessibility TEXT
+);
+
+-- Room the event is in, `location` is kept for places that aren't rooms
+ALTER TABLE events ADD room_id INTEGER REFERENCES rooms(id);
+-- Room the meeting is in, copied from the schedule when generated
+ALTER TABLE meetings ADD room_id INTEGER REFERENCES rooms(id);
+-- Room the group meets in
+ALTER TABLE group_schedules ADD room_id INTEGER REFERENCES rooms(id);
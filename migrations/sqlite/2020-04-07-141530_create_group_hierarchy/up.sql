@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- Group this group is part of, like a small group in the large group
+ALTER TABLE groups ADD parent_id INTEGER REFERENCES groups(id);
+-- Are new users added to the group when they sign up?
+ALTER TABLE groups ADD join_on_signup BOOLEAN NOT NULL DEFAULT 0;
+-- Everyone used to be added to the large group
+UPDATE groups SET join_on_signup = 1 WHERE id = 0;
+
+-- Mentors helping the group's leader run the group
+CREATE TABLE group_mentors (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    group_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    -- Role of the mentor, see `GroupRole`
+    role TEXT NOT NULL DEFAULT 'mentor',
+    FOREIGN KEY (group_id) REFERENCES groups (id),
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    UNIQUE (group_id, user_id)
+);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+
+-- When the user joined, empty for members from before this was kept
+ALTER TABLE relation_group_user ADD joined_at TIMESTAMP;
+-- When the user left, empty while they are still a member
+ALTER TABLE relation_group_user ADD left_at TIMESTAMP;
+
+-- When the user joined, empty for members from before this was kept
+ALTER TABLE relation_project_user ADD joined_at TIMESTAMP;
+-- When the user left, empty while they are still a member
+ALTER TABLE relation_project_user ADD left_at TIMESTAMP;
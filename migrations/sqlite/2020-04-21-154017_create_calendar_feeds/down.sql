@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE calendar_tokens;
+DROP TABLE event_rsvps;
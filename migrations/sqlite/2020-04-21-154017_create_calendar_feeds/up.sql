@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- Users planning to go to an event, their feed includes the event
+CREATE TABLE event_rsvps (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    event_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    FOREIGN KEY (event_id) REFERENCES events (id),
+    FOREIGN KEY (user_id) REFERENCES users (id),
+    UNIQUE (event_id, user_id)
+);
+
+-- Secret tokens in the URLs of users' private calendar feeds
+CREATE TABLE calendar_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL UNIQUE,
+    token TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
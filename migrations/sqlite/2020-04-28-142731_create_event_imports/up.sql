@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- UID of the iCalendar entry the event was imported from
+ALTER TABLE events ADD import_uid TEXT;
+CREATE UNIQUE INDEX events_import_uid ON events (import_uid);
+
+-- Uploaded iCalendar files waiting to be previewed and imported
+CREATE TABLE event_imports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- ID of the user who uploaded the file
+    uploaded_by INTEGER NOT NULL,
+    -- The uploaded file
+    ics TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (uploaded_by) REFERENCES users (id)
+);
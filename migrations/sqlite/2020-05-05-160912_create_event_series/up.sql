@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+-- RRULE of a repeating event, empty for events that happen once
+ALTER TABLE events ADD rrule TEXT;
+-- Days a repeating event is skipped as a JSON array
+ALTER TABLE events ADD exdates TEXT NOT NULL DEFAULT '[]';
+-- ID of the repeating event this is one time of
+ALTER TABLE events ADD series_id INTEGER REFERENCES events (id);
+-- Day of the repeating event this time was made for
+ALTER TABLE events ADD occurrence DATE;
+
+CREATE UNIQUE INDEX events_series_occurrence ON events (series_id, occurrence);
@@ -0,0 +1,179 @@
+use std::io::Cursor;
+
+use diesel::insert_into;
+use diesel::prelude::*;
+use rocket::http::{ContentType, Status};
+use rocket::response::Response;
+
+use crate::guards::*;
+use crate::models::{Project, User};
+use crate::ObservDbConn;
+
+use super::models::*;
+use super::templates::*;
+
+/// How many entries a feed shows
+const FEED_LENGTH: i64 = 100;
+
+/// GET handler for `/projects/<h>/activity`
+///
+/// The activity feed of a project, optionally only one kind of activity.
+#[get("/projects/<h>/activity?<kind>")]
+pub fn project_activity(
+    conn: ObservDbConn,
+    l: MaybeLoggedIn,
+    h: i32,
+    kind: Option<ActivityKind>,
+) -> Option<ProjectActivityTemplate> {
+    use crate::schema::projects::dsl::*;
+
+    let p: Project = projects
+        .find(h)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get project from database")?;
+
+    Some(ProjectActivityTemplate {
+        logged_in: l.user(),
+        entries: recent_activity(&*conn, Some(h), kind, FEED_LENGTH),
+        project: p,
+        kind: kind.map(|k| k.as_str().to_string()).unwrap_or_default(),
+        kinds: ActivityKind::all(),
+        base_url: format!("/projects/{}/activity", h),
+        show_project: false,
+    })
+}
+
+/// GET handler for `/projects/<h>/activity.xml`
+///
+/// Atom feed of the project's activity.
+#[get("/projects/<h>/activity.xml")]
+pub fn project_activity_atom(conn: ObservDbConn, h: i32) -> Option<Response<'static>> {
+    use crate::schema::projects::dsl::*;
+    use askama::Template;
+
+    let p: Project = projects
+        .find(h)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get project from database")?;
+    let entries = recent_activity(&*conn, Some(h), None, FEED_LENGTH);
+
+    let xml = ActivityFeedTemplate {
+        updated: entries
+            .first()
+            .map(ActivityEntry::timestamp)
+            .unwrap_or_else(|| rfc3339(chrono::Local::now().naive_local())),
+        project: p,
+        entries,
+    }
+    .render()
+    .expect("Failed to render activity feed");
+
+    Some(
+        Response::build()
+            .status(Status::Ok)
+            .header(ContentType::new("application", "atom+xml"))
+            .sized_body(Cursor::new(xml))
+            .finalize(),
+    )
+}
+
+/// GET handler for `/activity`
+///
+/// Activity across every project, for coordinators.
+#[get("/activity?<kind>")]
+pub fn activity(conn: ObservDbConn, l: AdminGuard, kind: Option<ActivityKind>) -> ActivityTemplate {
+    ActivityTemplate {
+        logged_in: Some(l.0),
+        entries: recent_activity(&*conn, None, kind, FEED_LENGTH * 2),
+        kind: kind.map(|k| k.as_str().to_string()).unwrap_or_default(),
+        kinds: ActivityKind::all(),
+        base_url: String::from("/activity"),
+        show_project: true,
+    }
+}
+
+//# Helper Functions
+
+/// Record something that happened on a project
+///
+/// `uid` is the user who did it, if a user did it. The message is shown
+/// after their name so it should read like "joined the project".
+pub fn log_activity(
+    conn: &SqliteConnection,
+    pid: i32,
+    uid: Option<i32>,
+    what: ActivityKind,
+    msg: &str,
+    to: &str,
+) {
+    use crate::schema::project_activity::dsl::*;
+    insert_into(project_activity)
+        .values(&NewActivity {
+            project_id: pid,
+            user_id: uid,
+            kind: what.as_str().to_string(),
+            message: msg.to_string(),
+            link: to.to_string(),
+        })
+        .execute(conn)
+        .expect("Failed to insert activity into database");
+}
+
+/// The latest activity, newest first
+///
+/// Narrowed down to one project and one kind of activity if given.
+pub fn recent_activity(
+    conn: &SqliteConnection,
+    pid: Option<i32>,
+    what: Option<ActivityKind>,
+    limit: i64,
+) -> Vec<ActivityEntry> {
+    let found: Vec<Activity> = {
+        use crate::schema::project_activity::dsl::*;
+        let mut query = project_activity.into_boxed();
+        if let Some(pid) = pid {
+            query = query.filter(project_id.eq(pid));
+        }
+        if let Some(what) = what {
+            query = query.filter(kind.eq(what.as_str()));
+        }
+        query
+            .order((created_at.desc(), id.desc()))
+            .limit(limit)
+            .load(conn)
+            .expect("Failed to get activity from database")
+    };
+
+    let found_users: Vec<User> = {
+        use crate::schema::users::dsl::*;
+        users
+            .filter(id.eq_any(found.iter().filter_map(|a| a.user_id).collect::<Vec<i32>>()))
+            .load(conn)
+            .expect("Failed to get users from database")
+    };
+    let found_projects: Vec<Project> = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .filter(id.eq_any(found.iter().map(|a| a.project_id).collect::<Vec<i32>>()))
+            .load(conn)
+            .expect("Failed to get projects from database")
+    };
+
+    found
+        .into_iter()
+        .filter_map(|a| {
+            Some(ActivityEntry {
+                user: a
+                    .user_id
+                    .and_then(|uid| found_users.iter().find(|u| u.id == uid).cloned()),
+                project: found_projects
+                    .iter()
+                    .find(|p| p.id == a.project_id)?
+                    .clone(),
+                activity: a,
+            })
+        })
+        .collect()
+}
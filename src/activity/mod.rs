@@ -0,0 +1,17 @@
+//! Project activity
+//!
+//! A log of what happens on each project: members joining and leaving,
+//! status updates, synced commits and pull requests, meetings attended by
+//! members, and edits. Other modules write to it with
+//! `handlers::log_activity` and it is shown as a feed on each project, as
+//! an Atom feed, and as a global feed for coordinators.
+//!
+//! ## Routes
+//! - `/activity`
+//! - `/projects/<h>/activity`
+//! - `/projects/<h>/activity.xml`
+
+pub mod handlers;
+pub mod models;
+
+mod templates;
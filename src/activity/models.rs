@@ -0,0 +1,126 @@
+//! Models for project activity
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use rocket::http::RawStr;
+use rocket::request::FromFormValue;
+
+use crate::models::Project;
+use crate::schema::*;
+
+/// Something that happened on a project
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Project)]
+#[table_name = "project_activity"]
+pub struct Activity {
+    pub id: i32,
+    pub project_id: i32,
+    /// ID of the user who did it, if a user did it
+    pub user_id: Option<i32>,
+    /// What kind of thing happened, see `ActivityKind`
+    pub kind: String,
+    /// What happened, shown after the user's name if there is one
+    pub message: String,
+    /// Where to go to see it
+    pub link: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// Used to log activity
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "project_activity"]
+pub struct NewActivity {
+    pub project_id: i32,
+    pub user_id: Option<i32>,
+    pub kind: String,
+    pub message: String,
+    pub link: String,
+}
+
+/// What kind of thing happened on a project
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ActivityKind {
+    /// A member joined, left, or changed roles
+    Member,
+    /// A status update was posted
+    Update,
+    /// Commits were synced from a repository
+    Commit,
+    /// A pull request was opened or merged
+    Pull,
+    /// A member attended a meeting
+    Meeting,
+    /// The project itself was changed
+    Edit,
+}
+
+impl ActivityKind {
+    /// The value stored in the `kind` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ActivityKind::Member => "member",
+            ActivityKind::Update => "update",
+            ActivityKind::Commit => "commit",
+            ActivityKind::Pull => "pull",
+            ActivityKind::Meeting => "meeting",
+            ActivityKind::Edit => "edit",
+        }
+    }
+
+    /// All kinds, in the order they are offered as filters
+    pub fn all() -> [ActivityKind; 6] {
+        [
+            ActivityKind::Member,
+            ActivityKind::Update,
+            ActivityKind::Commit,
+            ActivityKind::Pull,
+            ActivityKind::Meeting,
+            ActivityKind::Edit,
+        ]
+    }
+}
+
+// Converts to a human readable string like "Status Updates"
+impl fmt::Display for ActivityKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ActivityKind::Member => "Members",
+                ActivityKind::Update => "Status Updates",
+                ActivityKind::Commit => "Commits",
+                ActivityKind::Pull => "Pull Requests",
+                ActivityKind::Meeting => "Meetings",
+                ActivityKind::Edit => "Edits",
+            }
+        )
+    }
+}
+
+// Converts from the value stored in the `kind` column
+impl FromStr for ActivityKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "member" => Ok(ActivityKind::Member),
+            "update" => Ok(ActivityKind::Update),
+            "commit" => Ok(ActivityKind::Commit),
+            "pull" => Ok(ActivityKind::Pull),
+            "meeting" => Ok(ActivityKind::Meeting),
+            "edit" => Ok(ActivityKind::Edit),
+            _ => Err(()),
+        }
+    }
+}
+
+impl<'v> FromFormValue<'v> for ActivityKind {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<ActivityKind, &'v RawStr> {
+        form_value.parse().map_err(|_| form_value)
+    }
+}
@@ -0,0 +1,80 @@
+//! HTML templates for project activity
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+
+use super::models::*;
+use crate::models::{Project, User};
+#[allow(unused_imports)]
+use crate::templates::{filters, OptUser};
+
+/// Activity along with who did it and the project it was on
+pub struct ActivityEntry {
+    pub activity: Activity,
+    pub user: Option<User>,
+    pub project: Project,
+}
+
+impl ActivityEntry {
+    /// When it happened as an RFC 3339 timestamp, used in the Atom feed
+    pub fn timestamp(&self) -> String {
+        rfc3339(self.activity.created_at)
+    }
+}
+
+/// Times are stored in local time, Atom needs the offset
+pub fn rfc3339(t: NaiveDateTime) -> String {
+    Local
+        .from_local_datetime(&t)
+        .earliest()
+        .map(|d| d.to_rfc3339())
+        .unwrap_or_else(|| format!("{}Z", t.format("%FT%T")))
+}
+
+/// Project activity template
+///
+/// HTML File: `activity/project.html`
+#[derive(Template)]
+#[template(path = "activity/project.html")]
+pub struct ProjectActivityTemplate {
+    pub logged_in: OptUser,
+    pub project: Project,
+    pub entries: Vec<ActivityEntry>,
+    /// The kind being filtered by, empty for all
+    pub kind: String,
+    pub kinds: [ActivityKind; 6],
+    /// Where the kind filters link to
+    pub base_url: String,
+    /// Show which project each entry is on
+    pub show_project: bool,
+}
+
+/// Global activity template
+///
+/// HTML File: `activity/activity.html`
+///
+/// Activity across every project, for coordinators.
+#[derive(Template)]
+#[template(path = "activity/activity.html")]
+pub struct ActivityTemplate {
+    pub logged_in: OptUser,
+    pub entries: Vec<ActivityEntry>,
+    /// The kind being filtered by, empty for all
+    pub kind: String,
+    pub kinds: [ActivityKind; 6],
+    /// Where the kind filters link to
+    pub base_url: String,
+    /// Show which project each entry is on
+    pub show_project: bool,
+}
+
+/// Atom feed of a project's activity
+///
+/// XML File: `activity/feed.xml`
+#[derive(Template)]
+#[template(path = "activity/feed.xml")]
+pub struct ActivityFeedTemplate {
+    pub project: Project,
+    pub entries: Vec<ActivityEntry>,
+    /// Time of the latest entry, or now if there are none
+    pub updated: String,
+}
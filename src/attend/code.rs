@@ -12,11 +12,13 @@ use crate::models::Meeting;
 ///
 /// Takes a reference to the database connection and the code you want
 /// to verify and returns the event that the code corresponds to if it exists.
+/// Repeating events are attended by the code of each time instead.
 pub fn verify_code(conn: &SqliteConnection, vcode: &String) -> Option<Box<dyn Attendable>> {
     if let Some(e) = {
         use crate::schema::events::dsl::*;
         events
             .filter(code.eq(vcode.to_lowercase()))
+            .filter(rrule.is_null())
             .first::<Event>(conn)
             .optional()
             .expect("Failed to get events from database")
@@ -6,9 +6,12 @@ use diesel::prelude::*;
 use rocket::request::Form;
 use rocket::response::Redirect;
 
+use crate::activity::handlers::log_activity;
+use crate::activity::models::ActivityKind;
 use crate::guards::*;
 use crate::models::RelationGroupUser;
 use crate::templates::FormError;
+use crate::users::handlers::user_projects;
 use crate::ObservDbConn;
 
 use super::code::*;
@@ -40,22 +43,26 @@ pub struct AttendCode {
 /// Otherwise redirects back to `/attend`.
 #[post("/attend", data = "<code>")]
 pub fn attend_post(conn: ObservDbConn, l: UserGuard, code: Form<AttendCode>) -> Redirect {
-    if let Some(m) = verify_code(&*conn, &code.code) {
+    if let Some(m) = verify_code(&*conn, &code.code).filter(|m| !m.is_cancelled()) {
         let (mid, eid, gid) = if m.is_event() {
             (None, Some(m.id()), None)
         } else {
             (Some(m.id()), None, m.group_id())
         };
 
-        let user_in_group = {
-            use crate::schema::relation_group_user::dsl::*;
-            relation_group_user
-                .filter(group_id.eq(gid.unwrap()).and(user_id.eq(l.0.id)))
-                .first::<RelationGroupUser>(&*conn)
-                .optional()
-                .expect("Failed to get relations from database")
-                .is_some()
-        };
+        // Events don't have a group so anyone can attend them
+        let user_in_group = gid
+            .map(|g| {
+                use crate::schema::relation_group_user::dsl::*;
+                relation_group_user
+                    .filter(group_id.eq(g).and(user_id.eq(l.0.id)))
+                    .filter(left_at.is_null())
+                    .first::<RelationGroupUser>(&*conn)
+                    .optional()
+                    .expect("Failed to get relations from database")
+                    .is_some()
+            })
+            .unwrap_or(false);
 
         use crate::schema::attendances::dsl::*;
         let user_attended = if m.is_event() {
@@ -85,6 +92,20 @@ pub fn attend_post(conn: ObservDbConn, l: UserGuard, code: Form<AttendCode>) ->
                 .values(&newattend)
                 .execute(&*conn)
                 .expect("Failed to insert attendance into database");
+
+            // Meetings show up on the activity of the member's projects
+            if let Some(gid) = gid {
+                for p in user_projects(&*conn, &l.0).iter().filter(|p| !p.archived) {
+                    log_activity(
+                        &*conn,
+                        p.id,
+                        Some(l.0.id),
+                        ActivityKind::Meeting,
+                        "attended a meeting",
+                        &format!("/groups/{}", gid),
+                    );
+                }
+            }
             Redirect::to("/dashboard")
         } else {
             Redirect::to(format!("/attend?e={}", FormError::InvalidCode))
@@ -6,8 +6,8 @@ use rocket::http::{Cookie, Cookies};
 use rocket::request::Form;
 use rocket::response::Redirect;
 
+use crate::groups::handlers::join_default_groups;
 use crate::guards::*;
-use crate::models::NewRelationGroupUser;
 use crate::models::{NewUser, User};
 use crate::templates::FormError;
 use crate::ObservDbConn;
@@ -121,16 +121,7 @@ pub fn signup_post(conn: ObservDbConn, mut cookies: Cookies, form: Form<SignUpFo
         .filter(&email.eq(&*newuser.email))
         .first(&*conn)
         .expect("Failed to get user from database");
-    {
-        use crate::schema::relation_group_user::dsl::*;
-        insert_into(relation_group_user)
-            .values(&NewRelationGroupUser {
-                group_id: 0,
-                user_id: user.id,
-            })
-            .execute(&*conn)
-            .expect("Failed to insert new relation into database");
-    }
+    join_default_groups(&*conn, user.id).expect("Failed to add user to groups in database");
 
     cookies.add_private(Cookie::new("user_id", format!("{}", user.id)));
 
@@ -0,0 +1,188 @@
+//! What is in each calendar feed
+//!
+//! Meetings are assumed to last `MEETING_MINUTES` like when booking rooms.
+//! Upcoming days of a schedule without a meeting yet are in the feeds too,
+//! with the same UID the meeting gets once it is generated. Repeating
+//! events are expanded the same way, each time gets the same UID whether
+//! it has an event of its own or not.
+
+use chrono::{Duration, Local, NaiveDate};
+use diesel::prelude::*;
+
+use super::ics::IcsEvent;
+use super::models::Event;
+use super::series::{all_entries, series_times, CalendarEntry};
+use crate::models::{Attendable, Group, GroupSchedule, Meeting, Room};
+use crate::rooms::bookings::MEETING_MINUTES;
+use crate::rooms::handlers::all_rooms;
+
+/// Every event on the calendar
+pub fn event_entries(conn: &SqliteConnection) -> Vec<IcsEvent> {
+    let rooms = all_rooms(conn);
+    all_entries(conn)
+        .iter()
+        .map(|e| event_entry(e, &rooms))
+        .collect()
+}
+
+/// The meetings of a group and the upcoming days of its schedules
+pub fn group_entries(conn: &SqliteConnection, g: &Group) -> Vec<IcsEvent> {
+    group_entries_with(conn, g, &all_rooms(conn))
+}
+
+/// A user's private feed, the meetings of their groups and the events
+/// they are going to
+///
+/// Going to a repeating event means going to every time of it.
+pub fn user_entries(conn: &SqliteConnection, uid: i32) -> Vec<IcsEvent> {
+    let rooms = all_rooms(conn);
+    let gs: Vec<Group> = {
+        use crate::schema::groups::dsl::groups;
+        use crate::schema::relation_group_user::dsl::*;
+        relation_group_user
+            .inner_join(groups)
+            .filter(user_id.eq(uid).and(left_at.is_null()))
+            .select(crate::schema::groups::all_columns)
+            .distinct()
+            .load(conn)
+            .expect("Failed to get groups from database")
+    };
+    let evs: Vec<Event> = {
+        use crate::schema::event_rsvps::dsl::*;
+        use crate::schema::events::dsl::events;
+        event_rsvps
+            .inner_join(events)
+            .filter(user_id.eq(uid))
+            .select(crate::schema::events::all_columns)
+            .load(conn)
+            .expect("Failed to get events from database")
+    };
+
+    let mut entries: Vec<IcsEvent> = Vec::new();
+    for e in evs {
+        if e.is_series() {
+            entries.extend(
+                series_times(conn, &e)
+                    .iter()
+                    .map(|t| event_entry(t, &rooms)),
+            );
+        } else {
+            let url = e.url();
+            entries.push(event_entry(&CalendarEntry { event: e, url }, &rooms));
+        }
+    }
+    for g in gs.iter() {
+        entries.extend(group_entries_with(conn, g, &rooms));
+    }
+    entries.sort_by_key(|e| e.start);
+    entries
+}
+
+//# Helper Functions
+
+fn event_entry(entry: &CalendarEntry, rooms: &[Room]) -> IcsEvent {
+    let e = &entry.event;
+    IcsEvent {
+        uid: event_uid(e),
+        summary: e.title.clone(),
+        description: e.description.clone(),
+        location: room_name(rooms, e.room_id).or_else(|| e.location.clone()),
+        start: e.start,
+        end: e.end,
+        url: entry.url.clone(),
+        cancelled: false,
+    }
+}
+
+/// The UID of an event, the same as its repeating event's day if it is a
+/// time of one
+fn event_uid(e: &Event) -> String {
+    match (e.series_id, e.occurrence) {
+        (Some(sid), Some(day)) => format!("series-{}-{}", sid, day.format("%Y%m%d")),
+        _ => format!("event-{}", e.id),
+    }
+}
+
+fn group_entries_with(conn: &SqliteConnection, g: &Group, rooms: &[Room]) -> Vec<IcsEvent> {
+    let length = Duration::minutes(MEETING_MINUTES);
+    let scheds: Vec<GroupSchedule> = GroupSchedule::belonging_to(g)
+        .load(conn)
+        .expect("Failed to get schedules from database");
+    let ms: Vec<Meeting> = {
+        use crate::schema::meetings::dsl::*;
+        Meeting::belonging_to(g)
+            .order(happened_at.asc())
+            .load(conn)
+            .expect("Failed to get meetings from database")
+    };
+
+    let mut entries: Vec<IcsEvent> = ms
+        .iter()
+        .map(|m| {
+            let sched = m
+                .schedule_id
+                .and_then(|sid| scheds.iter().find(|s| s.id == sid));
+            IcsEvent {
+                uid: meeting_uid(m),
+                summary: format!("{}: {}", g.name, m.title_or_default()),
+                description: m.agenda.clone(),
+                location: room_name(rooms, m.room_id)
+                    .or_else(|| m.location.clone())
+                    .or_else(|| sched.and_then(|s| s.location.clone()))
+                    .or_else(|| g.location.clone()),
+                start: m.happened_at,
+                end: m.happened_at + length,
+                url: m.url(),
+                cancelled: m.cancelled,
+            }
+        })
+        .collect();
+
+    // Archived groups don't meet anymore
+    if !g.archived {
+        let today = Local::today().naive_local();
+        for s in scheds.iter() {
+            for day in s.occurrences(today, s.ends_on) {
+                let generated = ms
+                    .iter()
+                    .any(|m| m.schedule_id == Some(s.id) && m.occurrence == Some(day));
+                if generated {
+                    continue;
+                }
+                let start = day.and_time(s.start_time);
+                entries.push(IcsEvent {
+                    uid: schedule_uid(s.id, day),
+                    summary: format!("{}: Meeting", g.name),
+                    description: None,
+                    location: room_name(rooms, s.room_id)
+                        .or_else(|| s.location.clone())
+                        .or_else(|| g.location.clone()),
+                    start,
+                    end: start + length,
+                    url: format!("/groups/{}", g.id),
+                    cancelled: false,
+                });
+            }
+        }
+    }
+
+    entries.sort_by_key(|e| e.start);
+    entries
+}
+
+/// The UID of a meeting, the same as its schedule's day if it has one
+fn meeting_uid(m: &Meeting) -> String {
+    match (m.schedule_id, m.occurrence) {
+        (Some(sid), Some(day)) => schedule_uid(sid, day),
+        _ => format!("meeting-{}", m.id),
+    }
+}
+
+fn schedule_uid(sid: i32, day: NaiveDate) -> String {
+    format!("schedule-{}-{}", sid, day.format("%Y%m%d"))
+}
+
+fn room_name(rooms: &[Room], rid: Option<i32>) -> Option<String> {
+    rid.and_then(|rid| rooms.iter().find(|r| r.id == rid))
+        .map(Room::full_name)
+}
@@ -1,17 +1,32 @@
 //! HTTP handlers for the calendar
 
+use std::io::Read;
+
+use chrono::{Duration, Local, NaiveDate};
 use diesel::prelude::*;
-use diesel::{delete, insert_into, update};
-use rocket::http::Status;
+use diesel::{delete, insert_into, replace_into, update};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use rocket::data::Data;
+use rocket::http::{ContentType, Status};
 use rocket::request::Form;
+use rocket::response::content::Content;
 use rocket::response::Redirect;
 
 use rocket_contrib::json::Json;
 
 use crate::attend::code::attendance_code;
 use crate::guards::*;
+use crate::rooms::bookings::{conflicts_any, Booked};
+use crate::rooms::handlers::{get_room, room_options};
+use crate::showcase::handlers::get_showcase;
 
+use super::feeds::*;
+use super::ics::write_calendar;
+use super::import::*;
 use super::models::*;
+use super::recurrence::Recurrence;
+use super::series::*;
 use super::templates::*;
 use crate::templates::FormError;
 use crate::ObservDbConn;
@@ -22,60 +37,203 @@ use crate::ObservDbConn;
 /// or a plain HTML list if JS is off.
 #[get("/calendar")]
 pub fn calendar(conn: ObservDbConn, l: MaybeLoggedIn) -> CalendarTemplate {
-    use crate::schema::events::dsl::*;
-
+    let user = l.user();
     CalendarTemplate {
-        logged_in: l.user(),
-        events: events
-            .order(start.asc())
-            .load(&conn.0)
-            .expect("Failed to get events"),
+        feed_token: user.as_ref().and_then(|u| feed_token(&*conn, u.id)),
+        logged_in: user,
+        events: all_entries(&*conn),
     }
 }
 
 /// GET handler for `/calendar.json`
 ///
-/// JSON endpoint that returns the calendar events as a single JSON array.
-#[get("/calendar.json")]
-pub fn calendar_json(conn: ObservDbConn) -> Json<Vec<Event>> {
-    use crate::schema::events::dsl::*;
+/// JSON endpoint that returns the calendar events from `start` until `end`
+/// as a single JSON array, with repeating events expanded. Only the dates
+/// are read, so times and time zones are ignored. Defaults to the month
+/// before through four months after today, and at most a year is
+/// returned.
+#[get("/calendar.json?<start>&<end>")]
+pub fn calendar_json(
+    conn: ObservDbConn,
+    start: Option<String>,
+    end: Option<String>,
+) -> Json<Vec<CalendarEntry>> {
+    let date =
+        |d: Option<String>| d.and_then(|d| NaiveDate::parse_from_str(d.get(..10)?, "%F").ok());
+    let today = Local::today().naive_local();
+    let from = date(start).unwrap_or(today - Duration::days(30));
+    let until = date(end)
+        .unwrap_or(today + Duration::days(120))
+        .min(from + Duration::days(366));
+    Json(expand(
+        &*conn,
+        from.and_hms(0, 0, 0),
+        until.and_hms(0, 0, 0),
+    ))
+}
 
-    Json(
-        events
-            .order(start.asc())
-            .load(&*conn)
-            .expect("Failed to get events"),
+/// GET handler for `/calendar.ics`
+///
+/// iCalendar feed of every event for calendar apps.
+#[get("/calendar.ics")]
+pub fn calendar_ics(conn: ObservDbConn) -> Content<String> {
+    Content(
+        ContentType::new("text", "calendar"),
+        write_calendar("RCOS", &event_entries(&*conn)),
     )
 }
 
+/// GET handler for `/calendar/feed/<token>`
+///
+/// A user's private iCalendar feed with the meetings of their groups and
+/// the events they are going to. The token is the secret so there is no
+/// login.
+#[get("/calendar/feed/<token>", rank = 2)]
+pub fn calendar_feed(conn: ObservDbConn, token: String) -> Option<Content<String>> {
+    let uid: i32 = {
+        use crate::schema::calendar_tokens::dsl;
+        dsl::calendar_tokens
+            .filter(dsl::token.eq(token))
+            .select(dsl::user_id)
+            .first(&*conn)
+            .optional()
+            .expect("Failed to get calendar token from database")?
+    };
+    Some(Content(
+        ContentType::new("text", "calendar"),
+        write_calendar("RCOS", &user_entries(&*conn, uid)),
+    ))
+}
+
+/// POST handler for `/calendar/feed`
+///
+/// Makes a new private feed URL for the logged in user. The old URL stops
+/// working.
+#[post("/calendar/feed")]
+pub fn calendar_feed_post(conn: ObservDbConn, l: UserGuard) -> Redirect {
+    use crate::schema::calendar_tokens::dsl::*;
+    replace_into(calendar_tokens)
+        .values(&NewCalendarToken {
+            user_id: l.0.id,
+            token: gen_token(),
+        })
+        .execute(&*conn)
+        .expect("Failed to add calendar token to database");
+    Redirect::to("/calendar")
+}
+
+/// DELETE handler for `/calendar/feed`
+///
+/// Turns off the logged in user's private feed.
+#[delete("/calendar/feed")]
+pub fn calendar_feed_delete(conn: ObservDbConn, l: UserGuard) -> Redirect {
+    use crate::schema::calendar_tokens::dsl::*;
+    delete(calendar_tokens.filter(user_id.eq(l.0.id)))
+        .execute(&*conn)
+        .expect("Failed to delete calendar token from database");
+    Redirect::to("/calendar")
+}
+
 /// GET handler for `/calendar/<eid>`
 ///
-/// A single calendar event's page with information on the event.
-#[get("/calendar/<eid>")]
-pub fn event(conn: ObservDbConn, l: MaybeLoggedIn, eid: i32) -> Option<EventTemplate> {
-    use crate::schema::events::dsl::*;
+/// A single calendar event's page with information on the event. A
+/// repeating event's page lists its upcoming times. With `on`, redirects
+/// to the time of the repeating event on that day, making it if it
+/// doesn't have an event yet.
+#[get("/calendar/<eid>?<on>")]
+pub fn event(
+    conn: ObservDbConn,
+    l: MaybeLoggedIn,
+    eid: i32,
+    on: Option<String>,
+) -> Option<Result<EventTemplate, Redirect>> {
+    let e = get_event(&*conn, eid)?;
+    if let Some(on) = on {
+        let day = NaiveDate::parse_from_str(&on, "%F").ok()?;
+        let tid = make_time(&*conn, &e, day).expect("Failed to insert event into database")?;
+        return Some(Err(Redirect::to(format!("/calendar/{}", tid))));
+    }
 
-    Some(EventTemplate {
-        logged_in: l.user(),
-        event: events
+    let rsvps: Vec<EventRsvp> = EventRsvp::belonging_to(&e)
+        .load(&*conn)
+        .expect("Failed to get RSVPs from database");
+    let now = Local::now().naive_local();
+    let user = l.user();
+    Some(Ok(EventTemplate {
+        going: user
+            .as_ref()
+            .map(|u| rsvps.iter().any(|r| r.user_id == u.id))
+            .unwrap_or(false),
+        rsvp_count: rsvps.len(),
+        logged_in: user,
+        has_showcase: get_showcase(&*conn, e.id).is_some(),
+        room: e.room_id.and_then(|rid| get_room(&*conn, rid)),
+        series: e.series_id.and_then(|sid| get_event(&*conn, sid)),
+        upcoming: if e.is_series() {
+            series_times(&*conn, &e)
+                .into_iter()
+                .filter(|t| t.event.end > now)
+                .take(10)
+                .collect()
+        } else {
+            Vec::new()
+        },
+        event: e,
+    }))
+}
+
+/// POST handler for `/calendar/<eid>/rsvp`
+///
+/// The logged in user is going to the event, which adds it to their
+/// private feed.
+#[post("/calendar/<eid>/rsvp")]
+pub fn event_rsvp_post(conn: ObservDbConn, l: UserGuard, eid: i32) -> Option<Redirect> {
+    {
+        use crate::schema::events::dsl::*;
+        events
             .find(eid)
-            .first(&*conn)
+            .select(id)
+            .first::<i32>(&*conn)
             .optional()
-            .expect("Failed to get event")?,
-    })
+            .expect("Failed to get event from database")?;
+    }
+    use crate::schema::event_rsvps::dsl::*;
+    replace_into(event_rsvps)
+        .values(&NewEventRsvp {
+            event_id: eid,
+            user_id: l.0.id,
+        })
+        .execute(&*conn)
+        .expect("Failed to add RSVP to database");
+    Some(Redirect::to(format!("/calendar/{}", eid)))
+}
+
+/// DELETE handler for `/calendar/<eid>/rsvp`
+///
+/// The logged in user isn't going to the event anymore.
+#[delete("/calendar/<eid>/rsvp")]
+pub fn event_rsvp_delete(conn: ObservDbConn, l: UserGuard, eid: i32) -> Redirect {
+    use crate::schema::event_rsvps::dsl::*;
+    delete(event_rsvps.filter(event_id.eq(eid).and(user_id.eq(l.0.id))))
+        .execute(&*conn)
+        .expect("Failed to delete RSVP from database");
+    Redirect::to(format!("/calendar/{}", eid))
 }
 
 /// GET handler for `/calendar/<eid>/edit`
 ///
-/// The page to edit a calendar event.
+/// The page to edit a calendar event. For a time of a repeating event,
+/// `scope` is whether only this time, this time and the following ones,
+/// or every time is changed.
 ///
 /// Restricted to Admins and the event owner.
-#[get("/calendar/<eid>/edit?<e>")]
+#[get("/calendar/<eid>/edit?<e>&<scope>")]
 pub fn event_edit(
     conn: ObservDbConn,
     l: UserGuard,
     eid: i32,
     e: Option<FormError>,
+    scope: Option<EditScope>,
 ) -> Result<EditEventTemplate, Status> {
     let l = l.0;
 
@@ -89,23 +247,37 @@ pub fn event_edit(
         .expect("Failed to get event code");
 
     if l.tier > 1 || l.id == host_id {
+        let ev: Event = if let Some(ev) = events
+            .find(eid)
+            .first(&*conn)
+            .optional()
+            .expect("Failed to get event from database")
+        {
+            ev
+        } else {
+            // Return early
+            return Err(Status::NotFound);
+        };
+        let series = ev.series_id.and_then(|sid| get_event(&*conn, sid));
+        let scope = scope.unwrap_or(EditScope::This);
+        // The repeat fields are the repeating event's when changing more
+        // than one time of it
+        let repeats = match &series {
+            Some(s) if scope != EditScope::This => Some(s.clone()),
+            Some(_) => None,
+            None => Some(ev.clone()),
+        };
         Ok(EditEventTemplate {
             logged_in: Some(l),
-            event: if let Some(e) = events
-                .find(eid)
-                .first(&*conn)
-                .optional()
-                .expect("Failed to get event from database")
-            {
-                e
-            } else {
-                // Return early
-                return Err(Status::NotFound);
-            },
+            rooms: room_options(&*conn, ev.room_id),
+            event: ev,
             all_users: users
                 .load(&*conn)
                 .expect("Failed to get users from database"),
             error: e,
+            series,
+            scope,
+            repeats,
         })
     } else {
         Err(Status::Unauthorized)
@@ -114,58 +286,137 @@ pub fn event_edit(
 
 /// PUT handler for `/calendar/<eid>`
 ///
-/// Changes the calendar event. For use with `editevent`.
+/// Changes the calendar event. For use with `editevent`. Fails if the
+/// event's room is booked for something else at the time.
+///
+/// For a time of a repeating event, `scope` is what is changed:
+/// - `this` changes only this time
+/// - `following` splits the repeating event so that this time starts a
+///   new one with the changes
+/// - `all` changes the repeating event, moving every time by as much as
+///   this one moved
+///
+/// Times that were changed on their own keep their changes. Giving a
+/// single event an RRULE makes it the first time of a new repeating event.
 ///
 /// Restricted to Admins and the event owner.
-#[put("/calendar/<eid>", data = "<editevent>")]
+#[put("/calendar/<eid>?<scope>", data = "<editevent>")]
 pub fn event_edit_put(
     conn: ObservDbConn,
     l: UserGuard,
     eid: i32,
+    scope: Option<EditScope>,
     editevent: Form<NewEvent>,
 ) -> Result<Redirect, Status> {
     let l = l.0;
 
     use crate::schema::events::dsl::*;
+    let scope = scope.unwrap_or(EditScope::This);
+    let retry = |err: FormError| -> Result<Redirect, Status> {
+        Ok(Redirect::to(format!(
+            "/calendar/{}/edit?e={}&scope={}",
+            eid, err, scope
+        )))
+    };
     let mut editevent = editevent.into_inner();
-    if editevent.check_times().is_err() {
-        return Ok(Redirect::to(format!(
-            "/calendar/{}/edit?e={}",
-            eid,
-            FormError::InvalidDate
-        )));
+    let (begins, ends) = match editevent.times() {
+        Ok(t) => t,
+        Err(_) => return retry(FormError::InvalidDate),
+    };
+    if editevent.parse_repeat().is_err() {
+        return retry(FormError::InvalidRepeat);
     }
-    let (atcode, host_id): (String, i32) = events
-        .find(eid)
-        .select((code, hosted_by))
-        .first(&*conn)
-        .expect("Failed to get event code");
-    editevent.code = atcode;
+    let old = get_event(&*conn, eid).ok_or(Status::NotFound)?;
+    if !(l.tier > 1 || l.id == old.hosted_by) {
+        return Err(Status::Unauthorized);
+    }
+    editevent.code = old.code.clone();
+    editevent.import_uid = old.import_uid.clone();
 
-    if l.tier > 1 || l.id == host_id {
-        update(events.find(eid))
-            .set(&editevent)
-            .execute(&*conn)
-            .expect("Failed to update event in database");
+    let series = old.series_id.and_then(|sid| get_event(&*conn, sid));
+    let result = match (&series, scope) {
+        (None, _) if old.is_series() => {
+            if editevent.rrule.is_none() {
+                return retry(FormError::InvalidRepeat);
+            }
+            if room_booked(&*conn, &editevent, Some(Booked::Series(eid))) {
+                return retry(FormError::RoomBooked);
+            }
+            update_series(&*conn, &old, &editevent)
+        }
+        (None, _) => {
+            if room_booked(&*conn, &editevent, Some(Booked::Event(eid))) {
+                return retry(FormError::RoomBooked);
+            }
+            if editevent.rrule.is_some() {
+                start_series(&*conn, eid, &editevent)
+            } else {
+                update(events.find(eid))
+                    .set(&editevent)
+                    .execute(&*conn)
+                    .map(|_| ())
+            }
+        }
+        (Some(_), EditScope::This) => {
+            editevent.rrule = None;
+            editevent.exdates = String::from("[]");
+            if room_booked(&*conn, &editevent, Some(Booked::Event(eid))) {
+                return retry(FormError::RoomBooked);
+            }
+            update(events.find(eid))
+                .set(&editevent)
+                .execute(&*conn)
+                .map(|_| ())
+        }
+        (Some(s), _) => {
+            if editevent.rrule.is_none() {
+                return retry(FormError::InvalidRepeat);
+            }
+            let day = old.occurrence.unwrap_or_else(|| old.start.date());
+            let first = s.occurrences(s.start.date(), day).first() == Some(&day);
+            if scope == EditScope::All || first {
+                // Move the first time by as much as this one moved
+                let moved = s.start + (begins - old.start);
+                editevent.start = moved.format("%F %T").to_string();
+                editevent.end = (moved + (ends - begins)).format("%F %T").to_string();
+                editevent.code = s.code.clone();
+                editevent.import_uid = s.import_uid.clone();
+            }
+            if room_booked(&*conn, &editevent, Some(Booked::Series(s.id))) {
+                return retry(FormError::RoomBooked);
+            }
+            if scope == EditScope::All || first {
+                update_series(&*conn, s, &editevent)
+            } else {
+                split_series(&*conn, s, day, &editevent).map(|_| ())
+            }
+        }
+    };
+    result.expect("Failed to update event in database");
 
-        Ok(Redirect::to("/calendar"))
-    } else {
-        Err(Status::Unauthorized)
-    }
+    Ok(Redirect::to("/calendar"))
 }
 
 /// DELETE handler for `/calendar/<eid>
 ///
-/// Deletes an event from the calendar and database.
+/// Deletes an event from the calendar and database along with its
+/// showcase, RSVPs, and attendance. Deleting a time of a repeating event
+/// skips that day, and deleting a repeating event deletes every time of
+/// it.
 ///
 /// Restricted to Admins.
 #[delete("/calendar/<eid>")]
-pub fn event_delete(conn: ObservDbConn, _l: AdminGuard, eid: i32) -> Redirect {
-    use crate::schema::events::dsl::*;
-    delete(events.find(eid))
-        .execute(&*conn)
-        .expect("Failed to delete event from database");
-    Redirect::to("/calendar")
+pub fn event_delete(conn: ObservDbConn, _l: AdminGuard, eid: i32) -> Option<Redirect> {
+    let e = get_event(&*conn, eid)?;
+    let result = if e.is_series() {
+        delete_series(&*conn, eid)
+    } else if e.series_id.is_some() {
+        skip_time(&*conn, &e)
+    } else {
+        delete_event(&*conn, eid)
+    };
+    result.expect("Failed to delete event from database");
+    Some(Redirect::to("/calendar"))
 }
 
 /// GET handler for `/calendar/new`
@@ -181,13 +432,15 @@ pub fn event_new(conn: ObservDbConn, admin: AdminGuard, e: Option<FormError>) ->
         all_users: users
             .load(&*conn)
             .expect("Failed to get users from database"),
+        rooms: room_options(&*conn, None),
         error: e,
     }
 }
 
 /// POST handler for `/calendar/new`
 ///
-/// Creates the new calendar event. For use with `newevent`.
+/// Creates the new calendar event. For use with `newevent`. Fails if the
+/// event's room is booked for something else at the time.
 ///
 /// Restricted to Admins.
 #[post("/calendar/new", data = "<newevent>")]
@@ -202,7 +455,14 @@ pub fn event_new_post(
     if newevent.check_times().is_err() {
         return Redirect::to(format!("/calendar/new?e={}", FormError::InvalidDate));
     }
+    if newevent.parse_repeat().is_err() {
+        return Redirect::to(format!("/calendar/new?e={}", FormError::InvalidRepeat));
+    }
+    if room_booked(&*conn, &newevent, None) {
+        return Redirect::to(format!("/calendar/new?e={}", FormError::RoomBooked));
+    }
     newevent.code = attendance_code(&*conn);
+    newevent.import_uid = None;
 
     insert_into(events)
         .values(&newevent)
@@ -211,3 +471,178 @@ pub fn event_new_post(
 
     Redirect::to("/calendar")
 }
+
+/// GET handler for `/calendar/import`
+///
+/// The page to upload an iCalendar file to import.
+///
+/// Restricted to Admins.
+#[get("/calendar/import?<e>")]
+pub fn calendar_import(l: AdminGuard, e: Option<FormError>) -> ImportTemplate {
+    ImportTemplate {
+        logged_in: Some(l.0),
+        error: e,
+    }
+}
+
+/// POST handler for `/calendar/import`
+///
+/// The body is the iCalendar file. It is kept until it is imported so
+/// that it can be previewed first.
+///
+/// Restricted to Admins.
+#[post("/calendar/import", data = "<data>")]
+pub fn calendar_import_post(conn: ObservDbConn, l: AdminGuard, data: Data) -> Redirect {
+    use crate::schema::event_imports::dsl::*;
+
+    // Read one byte past the limit to tell if the file is too big
+    let mut bytes = Vec::new();
+    let read = data
+        .open()
+        .take(MAX_IMPORT + 1)
+        .read_to_end(&mut bytes)
+        .is_ok();
+    let text = match String::from_utf8(bytes) {
+        Ok(t) if read && t.len() as u64 <= MAX_IMPORT && t.contains("BEGIN:VCALENDAR") => t,
+        _ => return Redirect::to(format!("/calendar/import?e={}", FormError::InvalidCalendar)),
+    };
+
+    let iid = conn
+        .transaction::<_, diesel::result::Error, _>(|| {
+            insert_into(event_imports)
+                .values(&NewEventImport {
+                    uploaded_by: l.0.id,
+                    ics: text,
+                })
+                .execute(&*conn)?;
+            event_imports
+                .select(id)
+                .order(id.desc())
+                .first::<i32>(&*conn)
+        })
+        .expect("Failed to add import to database");
+    Redirect::to(format!("/calendar/import/{}", iid))
+}
+
+/// GET handler for `/calendar/import/<iid>`
+///
+/// Preview of the events an uploaded calendar would add and change, and
+/// of the entries that couldn't be read.
+///
+/// Restricted to Admins.
+#[get("/calendar/import/<iid>", rank = 2)]
+pub fn calendar_import_preview(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    iid: i32,
+) -> Option<ImportPreviewTemplate> {
+    let import = get_import(&*conn, iid)?;
+    let (rows, errors) = plan_import(&*conn, &import.ics, l.0.id);
+    Some(ImportPreviewTemplate {
+        logged_in: Some(l.0),
+        import,
+        rows,
+        errors,
+    })
+}
+
+/// POST handler for `/calendar/import/<iid>`
+///
+/// Adds and updates the events of an uploaded calendar, then forgets the
+/// file.
+///
+/// Restricted to Admins.
+#[post("/calendar/import/<iid>", rank = 2)]
+pub fn calendar_import_apply(conn: ObservDbConn, l: AdminGuard, iid: i32) -> Option<Redirect> {
+    use crate::schema::event_imports::dsl::*;
+
+    let import = get_import(&*conn, iid)?;
+    let (rows, _) = plan_import(&*conn, &import.ics, l.0.id);
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        apply_import(&*conn, rows)?;
+        delete(event_imports.find(iid)).execute(&*conn)?;
+        Ok(())
+    })
+    .expect("Failed to import events into database");
+    Some(Redirect::to("/calendar"))
+}
+
+/// DELETE handler for `/calendar/import/<iid>`
+///
+/// Forgets an uploaded calendar without importing it.
+///
+/// Restricted to Admins.
+#[delete("/calendar/import/<iid>", rank = 2)]
+pub fn calendar_import_delete(conn: ObservDbConn, _l: AdminGuard, iid: i32) -> Redirect {
+    use crate::schema::event_imports::dsl::*;
+    delete(event_imports.find(iid))
+        .execute(&*conn)
+        .expect("Failed to delete import from database");
+    Redirect::to("/calendar")
+}
+
+//# Helper Functions
+
+fn get_event(conn: &SqliteConnection, eid: i32) -> Option<Event> {
+    use crate::schema::events::dsl::*;
+    events
+        .find(eid)
+        .first(conn)
+        .optional()
+        .expect("Failed to get event from database")
+}
+
+fn get_import(conn: &SqliteConnection, iid: i32) -> Option<EventImport> {
+    use crate::schema::event_imports::dsl::*;
+    event_imports
+        .find(iid)
+        .first(conn)
+        .optional()
+        .expect("Failed to get import from database")
+}
+
+/// Is the event's room booked for something else at the time?
+///
+/// For a repeating event every upcoming time is checked. `own` is what is
+/// being edited so that it doesn't conflict with itself.
+fn room_booked(conn: &SqliteConnection, event: &NewEvent, own: Option<Booked>) -> bool {
+    let (rid, (begins, ends)) = match (event.room_id, event.times()) {
+        (Some(rid), Ok(times)) => (rid, times),
+        _ => return false,
+    };
+    let times = match event.rrule.as_ref().and_then(|r| Recurrence::parse(r)) {
+        Some(rule) => {
+            let skip: Vec<NaiveDate> = serde_json::from_str(&event.exdates).unwrap_or_default();
+            let today = Local::today().naive_local();
+            rule.times(
+                begins,
+                (today + Duration::days(EXPAND_DAYS)).and_hms(0, 0, 0),
+            )
+            .into_iter()
+            .filter(|t| t.date() >= today && !skip.contains(&t.date()))
+            .map(|t| (t, t + (ends - begins)))
+            .collect()
+        }
+        None => vec![(begins, ends)],
+    };
+    !conflicts_any(conn, rid, &times, own).is_empty()
+}
+
+/// The token of the user's private feed, if they turned it on
+fn feed_token(conn: &SqliteConnection, uid: i32) -> Option<String> {
+    use crate::schema::calendar_tokens::dsl::*;
+    calendar_tokens
+        .filter(user_id.eq(uid))
+        .select(token)
+        .first(conn)
+        .optional()
+        .expect("Failed to get calendar token from database")
+}
+
+/// Generate a secret feed token
+///
+/// Tokens are long enough that they can't be guessed, unlike attendance
+/// codes.
+fn gen_token() -> String {
+    thread_rng().sample_iter(&Alphanumeric).take(32).collect()
+}
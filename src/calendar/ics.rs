@@ -0,0 +1,394 @@
+//! iCalendar files
+//!
+//! Writes events and meetings as [RFC 5545](https://tools.ietf.org/html/rfc5545)
+//! calendars that calendar apps can subscribe to. Times are stored in
+//! local time so they are written in UTC. Each entry has a UID that stays
+//! the same when it is edited so that apps update it instead of adding it
+//! again.
+//!
+//! Also reads the entries of uploaded calendars for `calendar::import`.
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use super::recurrence::Recurrence;
+
+/// Domain of the site, used for UIDs and links
+pub const DOMAIN: &str = "rcos.io";
+
+/// An entry in a calendar
+#[derive(Debug, PartialEq, Clone)]
+pub struct IcsEvent {
+    /// Unique ID of the entry, `@DOMAIN` is added to it
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    /// Path of the entry's page
+    pub url: String,
+    /// Cancelled entries are kept so that apps remove them
+    pub cancelled: bool,
+}
+
+/// Write a calendar named `name` with the entries
+pub fn write_calendar(name: &str, entries: &[IcsEvent]) -> String {
+    let stamp = utc(Local::now().naive_local());
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//RCOS//Observatory//EN"),
+        String::from("CALSCALE:GREGORIAN"),
+        String::from("METHOD:PUBLISH"),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+    for e in entries {
+        lines.push(String::from("BEGIN:VEVENT"));
+        lines.push(format!("UID:{}@{}", e.uid, DOMAIN));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART:{}", utc(e.start)));
+        lines.push(format!("DTEND:{}", utc(e.end)));
+        lines.push(format!("SUMMARY:{}", escape(&e.summary)));
+        if let Some(d) = &e.description {
+            lines.push(format!("DESCRIPTION:{}", escape(d)));
+        }
+        if let Some(l) = &e.location {
+            lines.push(format!("LOCATION:{}", escape(l)));
+        }
+        lines.push(format!("URL:https://{}{}", DOMAIN, e.url));
+        if e.cancelled {
+            lines.push(String::from("STATUS:CANCELLED"));
+        }
+        lines.push(String::from("END:VEVENT"));
+    }
+    lines.push(String::from("END:VCALENDAR"));
+
+    lines.iter().map(|l| fold(l)).collect()
+}
+
+/// Write a local time as a UTC date-time
+///
+/// Times skipped by daylight saving time are written without a time zone
+/// so that apps read them as local times.
+fn utc(t: NaiveDateTime) -> String {
+    match Local.from_local_datetime(&t).earliest() {
+        Some(local) => local
+            .with_timezone(&Utc)
+            .format("%Y%m%dT%H%M%SZ")
+            .to_string(),
+        None => t.format("%Y%m%dT%H%M%S").to_string(),
+    }
+}
+
+/// Escape a text value
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Fold a line so that no line is longer than 75 bytes, ending it with
+/// CRLF
+pub fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 2);
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+/// An entry read from an uploaded calendar
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParsedEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    /// RRULE of a repeating entry
+    pub rrule: Option<String>,
+    /// Days a repeating entry is skipped
+    pub exdates: Vec<NaiveDate>,
+}
+
+/// Read the entries of a calendar
+///
+/// Entries that can't be read are left out and described in the errors
+/// that are returned. Times with a `TZID` are read as local times since
+/// the calendars we import are in the same time zone as the site. Changes
+/// to one time of a repeating entry (`RECURRENCE-ID`) aren't supported.
+pub fn parse_calendar(text: &str) -> (Vec<ParsedEvent>, Vec<String>) {
+    let mut parsed = Vec::new();
+    let mut errors = Vec::new();
+    // Properties of the entry being read and how many components deep
+    // in the entry the line is, like alarms
+    let mut entry: Option<Vec<Property>> = None;
+    let mut depth = 0;
+    let mut n = 0;
+
+    for line in unfold(text) {
+        let prop = match Property::parse(&line) {
+            Some(p) => p,
+            None => continue,
+        };
+        let props = match entry.as_mut() {
+            Some(props) => props,
+            None => {
+                if prop.name == "BEGIN" && prop.value.eq_ignore_ascii_case("VEVENT") {
+                    n += 1;
+                    entry = Some(Vec::new());
+                }
+                continue;
+            }
+        };
+        match prop.name.as_str() {
+            "BEGIN" => depth += 1,
+            "END" if depth > 0 => depth -= 1,
+            "END" => {
+                let props = entry.take().unwrap_or_default();
+                match read_event(&props) {
+                    Ok(e) => parsed.push(e),
+                    Err(why) => {
+                        let title = props
+                            .iter()
+                            .find(|p| p.name == "SUMMARY")
+                            .map(|p| format!(" ({})", unescape(&p.value)))
+                            .unwrap_or_default();
+                        errors.push(format!("Entry {}{}: {}", n, title, why));
+                    }
+                }
+            }
+            _ if depth == 0 => props.push(prop),
+            _ => {}
+        }
+    }
+    if entry.is_some() {
+        errors.push(format!("Entry {}: the file ends before the entry does", n));
+    }
+
+    (parsed, errors)
+}
+
+/// Undo `escape`
+pub fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// A content line split into its name, parameters, and value
+#[derive(Debug, PartialEq, Clone)]
+struct Property {
+    /// Uppercase name of the property
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Property> {
+        // The value starts after the first colon that isn't quoted
+        let mut quoted = false;
+        let (split, _) = line.char_indices().find(|(_, c)| {
+            if *c == '"' {
+                quoted = !quoted;
+            }
+            !quoted && *c == ':'
+        })?;
+        let mut parts = line[..split].split(';');
+        let name = parts.next()?.trim().to_uppercase();
+        let params = parts
+            .filter_map(|p| {
+                let mut kv = p.splitn(2, '=');
+                Some((
+                    kv.next()?.trim().to_uppercase(),
+                    kv.next()?.trim_matches('"').to_string(),
+                ))
+            })
+            .collect();
+        Some(Property {
+            name,
+            params,
+            value: line[split + 1..].to_string(),
+        })
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Join folded lines back together
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&line[1..]);
+            }
+        } else if !line.trim().is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+fn read_event(props: &[Property]) -> Result<ParsedEvent, &'static str> {
+    let get = |name: &str| props.iter().find(|p| p.name == name);
+    let text = |name: &str| {
+        get(name)
+            .map(|p| unescape(&p.value).trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let uid = text("UID").ok_or("it has no UID")?;
+    let summary = text("SUMMARY").ok_or("it has no title")?;
+    if text("STATUS").map(|s| s.eq_ignore_ascii_case("CANCELLED")) == Some(true) {
+        return Err("it is cancelled");
+    }
+    if get("RECURRENCE-ID").is_some() {
+        return Err("it changes one time of a repeating entry, which can't be imported");
+    }
+    if get("RDATE").is_some() {
+        return Err("it repeats on listed days, which can't be imported");
+    }
+    let rrule = match get("RRULE") {
+        Some(p) => Some(
+            Recurrence::parse(&p.value)
+                .ok_or("its repeat rule can't be read")?
+                .to_string(),
+        ),
+        None => None,
+    };
+
+    let (start, all_day) = read_time(get("DTSTART").ok_or("it has no start time")?)
+        .ok_or("its start time can't be read")?;
+    let end = match (get("DTEND"), get("DURATION")) {
+        (Some(p), _) => read_time(p).ok_or("its end time can't be read")?.0,
+        (None, Some(p)) => start + read_duration(&p.value).ok_or("its duration can't be read")?,
+        (None, None) if all_day => start + Duration::days(1),
+        (None, None) => start,
+    };
+    if end < start {
+        return Err("it ends before it starts");
+    }
+    let mut exdates = Vec::new();
+    for p in props.iter().filter(|p| p.name == "EXDATE") {
+        for value in p.value.split(',') {
+            let day = Property {
+                value: value.to_string(),
+                ..p.clone()
+            };
+            exdates.push(
+                read_time(&day)
+                    .ok_or("a skipped day can't be read")?
+                    .0
+                    .date(),
+            );
+        }
+    }
+    exdates.sort();
+    exdates.dedup();
+
+    Ok(ParsedEvent {
+        uid,
+        summary,
+        description: text("DESCRIPTION"),
+        location: text("LOCATION"),
+        start,
+        end,
+        rrule,
+        exdates,
+    })
+}
+
+/// Read a date or date-time as a local time, and whether it was a date
+fn read_time(p: &Property) -> Option<(NaiveDateTime, bool)> {
+    let value = p.value.trim();
+    if p.param("VALUE") == Some("DATE") || value.len() == 8 {
+        let day = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((day.and_hms(0, 0, 0), true));
+    }
+    if value.ends_with('Z') {
+        let t = NaiveDateTime::parse_from_str(&value[..value.len() - 1], "%Y%m%dT%H%M%S").ok()?;
+        return Some((
+            Utc.from_utc_datetime(&t)
+                .with_timezone(&Local)
+                .naive_local(),
+            false,
+        ));
+    }
+    let t = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((t, false))
+}
+
+/// Read a duration like `PT1H30M`
+fn read_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, value) = if value.starts_with('-') {
+        (true, &value[1..])
+    } else {
+        (false, value.trim_start_matches('+'))
+    };
+    if !value.starts_with('P') {
+        return None;
+    }
+
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut time = false;
+    for c in value[1..].chars() {
+        match c {
+            'T' => time = true,
+            '0'..='9' => number.push(c),
+            _ => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total = total
+                    + match (c, time) {
+                        ('W', false) => Duration::weeks(n),
+                        ('D', false) => Duration::days(n),
+                        ('H', true) => Duration::hours(n),
+                        ('M', true) => Duration::minutes(n),
+                        ('S', true) => Duration::seconds(n),
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    Some(if negative { -total } else { total })
+}
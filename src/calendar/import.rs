@@ -0,0 +1,136 @@
+//! Importing events from iCalendar files
+//!
+//! An uploaded calendar is kept in `event_imports` so that what it would
+//! change can be previewed first. Imported events keep the UID of their
+//! entry so importing a newer version of the same calendar updates the
+//! events instead of adding them again. Events that were edited keep
+//! their host, color, room, and attendance code. Repeating entries become
+//! repeating events, see `calendar::series`.
+
+use diesel::prelude::*;
+use diesel::{insert_into, update};
+
+use super::ics::{parse_calendar, ParsedEvent};
+use super::models::{Event, NewEvent};
+use super::series::{start_series, update_series};
+use crate::attend::code::attendance_code;
+
+/// Largest calendar that can be uploaded in bytes
+pub const MAX_IMPORT: u64 = 1024 * 1024;
+
+/// An entry of an uploaded calendar and what importing it would do
+pub struct ImportRow {
+    pub event: NewEvent,
+    /// The event the entry was imported as before, if any
+    pub existing: Option<Event>,
+    /// Is the entry different from the event it was imported as?
+    pub changed: bool,
+}
+
+/// What importing the calendar would do, along with the entries that
+/// couldn't be read
+///
+/// New events are hosted by `host`.
+pub fn plan_import(conn: &SqliteConnection, ics: &str, host: i32) -> (Vec<ImportRow>, Vec<String>) {
+    let (parsed, mut errors) = parse_calendar(ics);
+
+    // Only the first entry with a UID is imported
+    let mut entries: Vec<ParsedEvent> = Vec::new();
+    for p in parsed {
+        if entries.iter().any(|e| e.uid == p.uid) {
+            errors.push(format!(
+                "{}: another entry has the same UID so it was left out",
+                p.summary
+            ));
+        } else {
+            entries.push(p);
+        }
+    }
+
+    let existing: Vec<Event> = {
+        use crate::schema::events::dsl::*;
+        events
+            .filter(import_uid.eq_any(entries.iter().map(|e| Some(e.uid.clone()))))
+            .load(conn)
+            .expect("Failed to get events from database")
+    };
+
+    let rows = entries
+        .into_iter()
+        .map(|p| {
+            let old = existing
+                .iter()
+                .find(|e| e.import_uid.as_ref() == Some(&p.uid))
+                .cloned();
+            let changed = old
+                .as_ref()
+                .map(|e| {
+                    e.title != p.summary
+                        || e.start != p.start
+                        || e.end != p.end
+                        || e.description != p.description
+                        || e.location != p.location
+                        || e.rrule != p.rrule
+                        || e.skip_list() != p.exdates
+                })
+                .unwrap_or(true);
+            let event = NewEvent {
+                title: p.summary,
+                start: p.start.format("%F %T").to_string(),
+                end: p.end.format("%F %T").to_string(),
+                description: p.description,
+                hosted_by: old.as_ref().map(|e| e.hosted_by).unwrap_or(host),
+                location: p.location,
+                code: old.as_ref().map(|e| e.code.clone()).unwrap_or_default(),
+                color: old.as_ref().and_then(|e| e.color.clone()),
+                room_id: old.as_ref().and_then(|e| e.room_id),
+                import_uid: Some(p.uid),
+                rrule: p.rrule,
+                exdates: serde_json::to_string(&p.exdates).unwrap(),
+            };
+            ImportRow {
+                event,
+                existing: old,
+                changed,
+            }
+        })
+        .collect();
+
+    (rows, errors)
+}
+
+/// Add the new events and update the changed ones
+///
+/// New events get their own attendance code. Changed repeating events
+/// change every time that wasn't changed on its own. Returns how many
+/// events were added and how many were updated.
+pub fn apply_import(conn: &SqliteConnection, rows: Vec<ImportRow>) -> QueryResult<(usize, usize)> {
+    use crate::schema::events::dsl::*;
+
+    conn.transaction(|| {
+        let mut added = 0;
+        let mut updated = 0;
+        for row in rows {
+            match row.existing {
+                None => {
+                    let mut new = row.event;
+                    new.code = attendance_code(conn);
+                    insert_into(events).values(&new).execute(conn)?;
+                    added += 1;
+                }
+                Some(old) if row.changed => {
+                    if old.is_series() {
+                        update_series(conn, &old, &row.event)?;
+                    } else if row.event.rrule.is_some() {
+                        start_series(conn, old.id, &row.event)?;
+                    } else {
+                        update(events.find(old.id)).set(&row.event).execute(conn)?;
+                    }
+                    updated += 1;
+                }
+                Some(_) => {}
+            }
+        }
+        Ok((added, updated))
+    })
+}
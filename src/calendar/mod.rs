@@ -3,14 +3,36 @@
 //! After many years of using horrible external calendar and spreadsheets
 //! we now have proper built-in calendar.
 //!
+//! Events can repeat by an RRULE, each time they happen gets its own
+//! attendance code. See `series`.
+//!
+//! Events, group meetings, and schedules can be added to calendar apps
+//! through iCalendar feeds. Every event is in `/calendar.ics`, each group
+//! has a feed, and users can turn on a private feed of their groups and
+//! the events they RSVP to. Admins can also import events from an
+//! iCalendar file.
+//!
 //! ## Routes
 //! - `/calendar`
 //! - `/calendar.json`
+//! - `/calendar.ics`
+//! - `/calendar/feed`
+//! - `/calendar/feed/<token>`
+//! - `/calendar/import`
+//! - `/calendar/import/<iid>`
 //! - `/calendar/new`
 //! - `/calendar/<eid>`
 //! - `/calendar/<eid>/edit`
+//! - `/calendar/<eid>/rsvp`
+//!
+//! Showcases attached to events are in the `showcase` module.
 
+pub mod feeds;
 pub mod handlers;
+pub mod ics;
+pub mod import;
 pub mod models;
+pub mod recurrence;
+pub mod series;
 
 mod templates;
@@ -1,9 +1,14 @@
 //! Models for the calendar
 //!
 //! Calendar events are stored in the `calendar` table where each row
-//! is an event.
-use chrono::naive::NaiveDateTime;
+//! is an event. A repeating event is a row with an RRULE, see
+//! `calendar::series`.
+use chrono::naive::{NaiveDate, NaiveDateTime};
+use rocket::http::RawStr;
+use rocket::request::FromFormValue;
+use std::fmt;
 
+use super::recurrence::Recurrence;
 use crate::models::Attendable;
 use crate::schema::*;
 
@@ -30,6 +35,67 @@ pub struct Event {
     pub code: String,
     /// Optional color to display the event on the calendar
     pub color: Option<String>,
+    /// ID of the room the event is in
+    pub room_id: Option<i32>,
+    /// UID of the iCalendar entry the event was imported from
+    pub import_uid: Option<String>,
+    /// RRULE of a repeating event, empty for events that happen once
+    pub rrule: Option<String>,
+    /// Days a repeating event is skipped as a JSON array
+    pub exdates: String,
+    /// ID of the repeating event this is one time of
+    pub series_id: Option<i32>,
+    /// Day of the repeating event this time was made for
+    pub occurrence: Option<NaiveDate>,
+}
+
+impl Event {
+    /// Is this a repeating event?
+    ///
+    /// Repeating events aren't attended, each time they happen is.
+    pub fn is_series(&self) -> bool {
+        self.rrule.is_some()
+    }
+
+    /// The parsed RRULE of a repeating event
+    pub fn recurrence(&self) -> Option<Recurrence> {
+        self.rrule.as_ref().and_then(|r| Recurrence::parse(r))
+    }
+
+    /// How the event repeats in words, empty if it doesn't
+    pub fn repeat_text(&self) -> String {
+        self.recurrence().map(|r| r.describe()).unwrap_or_default()
+    }
+
+    /// The parsed days the event is skipped
+    pub fn skip_list(&self) -> Vec<NaiveDate> {
+        serde_json::from_str(&self.exdates).unwrap_or_default()
+    }
+
+    /// The skipped days in the format used by the form
+    pub fn skip_text(&self) -> String {
+        self.skip_list()
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// The days from `from` through `until` a repeating event happens
+    ///
+    /// Skipped days aren't included. Empty if the event doesn't repeat.
+    pub fn occurrences(&self, from: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+        let rule = match self.recurrence() {
+            Some(r) => r,
+            None => return Vec::new(),
+        };
+        let skip = self.skip_list();
+        rule.times(self.start, until.and_hms(23, 59, 59))
+            .into_iter()
+            .map(|t| t.date())
+            .filter(|d| *d >= from && !skip.contains(d))
+            .collect()
+    }
 }
 
 // Implement the Attendable trait for an Event.
@@ -53,13 +119,14 @@ impl Attendable for Event {
         true
     }
     fn url(&self) -> String {
-        format!("/e/{}", self.id)
+        format!("/calendar/{}", self.id)
     }
 }
 
 /// Used to create a new event in the database
 #[derive(Debug, Default, Clone, FromForm, Insertable, AsChangeset)]
 #[table_name = "events"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct NewEvent {
     /// Title of the event
     pub title: String,
@@ -77,17 +144,166 @@ pub struct NewEvent {
     pub code: String,
     /// Optional color to display the event on the calendar
     pub color: Option<String>,
+    /// ID of the room the event is in, empty if it isn't in a room
+    pub room_id: Option<i32>,
+    /// UID of the iCalendar entry the event was imported from, never
+    /// taken from the form
+    pub import_uid: Option<String>,
+    /// RRULE of a repeating event, empty if it happens once
+    pub rrule: Option<String>,
+    /// Days a repeating event is skipped, separated by commas in the form
+    /// and stored as a JSON array, see `parse_repeat`
+    pub exdates: String,
 }
 
 impl NewEvent {
     /// Verifies that the start and end times are valid
     pub fn check_times(&self) -> Result<(), chrono::ParseError> {
-        NaiveDateTime::parse_from_str(&self.start, "%F %R")
-            .or(NaiveDateTime::parse_from_str(&self.start, "%F %T"))
-            .and(
-                NaiveDateTime::parse_from_str(&self.end, "%F %R")
-                    .or(NaiveDateTime::parse_from_str(&self.end, "%F %T")),
-            )
-            .and(Ok(()))
+        self.times().and(Ok(()))
+    }
+
+    /// The parsed start and end times
+    pub fn times(&self) -> Result<(NaiveDateTime, NaiveDateTime), chrono::ParseError> {
+        let start = NaiveDateTime::parse_from_str(&self.start, "%F %R")
+            .or(NaiveDateTime::parse_from_str(&self.start, "%F %T"))?;
+        let end = NaiveDateTime::parse_from_str(&self.end, "%F %R")
+            .or(NaiveDateTime::parse_from_str(&self.end, "%F %T"))?;
+        Ok((start, end))
+    }
+
+    /// Check the RRULE and skipped days from the form and put them in the
+    /// form they are stored in
+    ///
+    /// Fails if the rule or a day can't be read.
+    pub fn parse_repeat(&mut self) -> Result<(), ()> {
+        let rule = match self.rrule.as_ref().map(|r| r.trim()) {
+            Some(r) if !r.is_empty() => Some(Recurrence::parse(r).ok_or(())?.to_string()),
+            _ => None,
+        };
+        let mut skip = self
+            .exdates
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|d| !d.is_empty())
+            .map(|d| NaiveDate::parse_from_str(d, "%F").map_err(|_| ()))
+            .collect::<Result<Vec<NaiveDate>, ()>>()?;
+        skip.sort();
+        skip.dedup();
+
+        self.rrule = rule;
+        self.exdates = serde_json::to_string(&skip).unwrap();
+        Ok(())
+    }
+}
+
+/// One time of a repeating event with an event of its own
+///
+/// Used to add and update the times, see `calendar::series`.
+#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[table_name = "events"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NewOccurrence {
+    pub title: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub description: Option<String>,
+    pub hosted_by: i32,
+    pub location: Option<String>,
+    pub code: String,
+    pub color: Option<String>,
+    pub room_id: Option<i32>,
+    pub series_id: Option<i32>,
+    pub occurrence: Option<NaiveDate>,
+}
+
+/// What changing one time of a repeating event changes
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EditScope {
+    /// Only this time
+    This,
+    /// This time and the ones after it
+    Following,
+    /// Every time
+    All,
+}
+
+impl EditScope {
+    /// Name of the scope in URLs
+    pub fn name(&self) -> &'static str {
+        match self {
+            EditScope::This => "this",
+            EditScope::Following => "following",
+            EditScope::All => "all",
+        }
     }
 }
+
+impl fmt::Display for EditScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl<'v> FromFormValue<'v> for EditScope {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<EditScope, &'v RawStr> {
+        match form_value.as_str() {
+            "this" => Ok(EditScope::This),
+            "following" => Ok(EditScope::Following),
+            "all" => Ok(EditScope::All),
+            _ => Err(form_value),
+        }
+    }
+}
+
+/// A user planning to go to an event
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations)]
+#[belongs_to(Event)]
+#[table_name = "event_rsvps"]
+pub struct EventRsvp {
+    pub id: i32,
+    pub event_id: i32,
+    pub user_id: i32,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "event_rsvps"]
+pub struct NewEventRsvp {
+    pub event_id: i32,
+    pub user_id: i32,
+}
+
+/// Secret token in the URL of a user's private calendar feed
+///
+/// Making a new token revokes the old one.
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable)]
+pub struct CalendarToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub token: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "calendar_tokens"]
+pub struct NewCalendarToken {
+    pub user_id: i32,
+    pub token: String,
+}
+
+/// An uploaded iCalendar file waiting to be imported, see
+/// `calendar::import`
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable)]
+pub struct EventImport {
+    pub id: i32,
+    pub uploaded_by: i32,
+    pub ics: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "event_imports"]
+pub struct NewEventImport {
+    pub uploaded_by: i32,
+    pub ics: String,
+}
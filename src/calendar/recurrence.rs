@@ -0,0 +1,436 @@
+//! Recurrence rules of repeating events
+//!
+//! Reads the part of [RFC 5545](https://tools.ietf.org/html/rfc5545#section-3.3.10)
+//! recurrence rules that events use: a `FREQ` of `DAILY`, `WEEKLY`,
+//! `MONTHLY`, or `YEARLY` with `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY`,
+//! `BYMONTHDAY`, and `BYMONTH`. Every time happens at the time of day of
+//! the first one, so the day is enough to tell the times apart.
+
+use std::fmt;
+
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+
+use crate::models::WEEKDAYS;
+
+/// How often a rule repeats
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A recurrence rule like `FREQ=WEEKLY;BYDAY=TU,TH;COUNT=10`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Recurrence {
+    pub freq: Freq,
+    /// Repeats every this many days, weeks, months, or years
+    pub interval: u32,
+    /// How many times it happens, counting the first time
+    pub count: Option<u32>,
+    /// Last time it can happen
+    pub until: Option<NaiveDateTime>,
+    /// Days of the week, with which one in the month like the 2 of `2TU`
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    /// Days of the month, negative ones count from the end
+    pub by_month_day: Vec<i32>,
+    /// Months of the year, 1 is January
+    pub by_month: Vec<u32>,
+}
+
+/// Weekdays as they are written in rules, starting with Monday
+const DAY_CODES: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+/// Names of the months starting with January
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+impl Recurrence {
+    /// Parse a rule, with or without `RRULE:` in front
+    ///
+    /// Fails on parts that aren't supported so that a rule is never
+    /// expanded differently than calendar apps would.
+    pub fn parse(rule: &str) -> Option<Recurrence> {
+        let rule = rule.trim();
+        let rule = match rule.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &rule[6..],
+            _ => rule,
+        };
+
+        let mut freq = None;
+        let mut r = Recurrence {
+            freq: Freq::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+        };
+        for part in rule.split(';').filter(|p| !p.trim().is_empty()) {
+            let mut kv = part.splitn(2, '=');
+            let key = kv.next()?.trim().to_uppercase();
+            let value = kv.next()?.trim().to_uppercase();
+            match key.as_str() {
+                "FREQ" => {
+                    freq = Some(match value.as_str() {
+                        "DAILY" => Freq::Daily,
+                        "WEEKLY" => Freq::Weekly,
+                        "MONTHLY" => Freq::Monthly,
+                        "YEARLY" => Freq::Yearly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => r.interval = value.parse().ok().filter(|i| *i > 0)?,
+                "COUNT" => r.count = Some(value.parse().ok().filter(|c| *c > 0)?),
+                "UNTIL" => r.until = Some(read_until(&value)?),
+                "BYDAY" => r.by_day = value.split(',').map(read_day).collect::<Option<_>>()?,
+                "BYMONTHDAY" => {
+                    r.by_month_day = value
+                        .split(',')
+                        .map(|d| {
+                            d.trim()
+                                .parse()
+                                .ok()
+                                .filter(|d: &i32| *d != 0 && d.abs() <= 31)
+                        })
+                        .collect::<Option<_>>()?
+                }
+                "BYMONTH" => {
+                    r.by_month = value
+                        .split(',')
+                        .map(|m| m.trim().parse().ok().filter(|m| *m >= 1 && *m <= 12))
+                        .collect::<Option<_>>()?
+                }
+                // Weeks always start on Monday
+                "WKST" if value == "MO" => {}
+                _ => return None,
+            }
+        }
+        r.freq = freq?;
+
+        let ordinals = r.by_day.iter().any(|(n, _)| n.is_some());
+        let unsupported = match r.freq {
+            _ if r.count.is_some() && r.until.is_some() => true,
+            Freq::Daily | Freq::Weekly if ordinals => true,
+            Freq::Weekly => !r.by_month_day.is_empty(),
+            Freq::Yearly => !r.by_day.is_empty() && r.by_month.is_empty(),
+            _ => false,
+        };
+        if unsupported {
+            None
+        } else {
+            Some(r)
+        }
+    }
+
+    /// The times from `first`, the first time, through `last` in order
+    ///
+    /// The first time is always included even if the rule doesn't pick
+    /// it, like calendar apps do.
+    pub fn times(&self, first: NaiveDateTime, last: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let last = self.until.map(|u| u.min(last)).unwrap_or(last);
+        let mut times = Vec::new();
+        if first > last {
+            return times;
+        }
+        times.push(first);
+
+        let mut n = 0;
+        while let Some((begins, days)) = self.period(first.date(), n) {
+            if begins > last.date() {
+                break;
+            }
+            for day in days {
+                let t = day.and_time(first.time());
+                if t <= first {
+                    continue;
+                }
+                if t > last || self.count.map(|c| times.len() >= c as usize) == Some(true) {
+                    return times;
+                }
+                times.push(t);
+            }
+            n += i64::from(self.interval);
+        }
+        times
+    }
+
+    /// The rule cut off so that the last time is before `day`
+    pub fn ending_before(&self, day: NaiveDate) -> Recurrence {
+        Recurrence {
+            count: None,
+            until: Some(day.pred().and_hms(23, 59, 59)),
+            ..self.clone()
+        }
+    }
+
+    /// The rule in words like "Every 2 weeks on Tuesday, Thursday"
+    pub fn describe(&self) -> String {
+        let unit = match self.freq {
+            Freq::Daily => "day",
+            Freq::Weekly => "week",
+            Freq::Monthly => "month",
+            Freq::Yearly => "year",
+        };
+        let mut text = if self.interval == 1 {
+            format!("Every {}", unit)
+        } else {
+            format!("Every {} {}s", self.interval, unit)
+        };
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|(n, wd)| {
+                    let name = WEEKDAYS[wd.num_days_from_monday() as usize];
+                    match n {
+                        Some(n) => format!("the {} {}", ordinal(*n), name),
+                        None => name.to_string(),
+                    }
+                })
+                .collect();
+            text += &format!(" on {}", days.join(", "));
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(|d| ordinal(*d)).collect();
+            text += &format!(" on the {}", days.join(", "));
+        }
+        if !self.by_month.is_empty() {
+            let months: Vec<&str> = self
+                .by_month
+                .iter()
+                .map(|m| MONTHS[*m as usize - 1])
+                .collect();
+            text += &format!(" in {}", months.join(", "));
+        }
+        match (self.count, self.until) {
+            (Some(1), _) => text += ", once",
+            (Some(c), _) => text += &format!(", {} times", c),
+            (_, Some(u)) => text += &format!(" until {}", u.date()),
+            _ => {}
+        }
+        text
+    }
+
+    /// The days the rule picks in the `n`th day, week, month, or year
+    /// since `first`, along with the day the period begins
+    fn period(&self, first: NaiveDate, n: i64) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+        match self.freq {
+            Freq::Daily => {
+                let day = first.checked_add_signed(Duration::days(n))?;
+                let picked = self.month_picked(day)
+                    && self
+                        .days_in(day.year(), day.month(), day.day())
+                        .contains(&day);
+                Some((day, if picked { vec![day] } else { Vec::new() }))
+            }
+            Freq::Weekly => {
+                let monday = first
+                    .checked_sub_signed(Duration::days(
+                        first.weekday().num_days_from_monday().into(),
+                    ))?
+                    .checked_add_signed(Duration::weeks(n))?;
+                let weekdays: Vec<Weekday> = if self.by_day.is_empty() {
+                    vec![first.weekday()]
+                } else {
+                    self.by_day.iter().map(|(_, wd)| *wd).collect()
+                };
+                let mut days: Vec<NaiveDate> = weekdays
+                    .iter()
+                    .map(|wd| monday + Duration::days(wd.num_days_from_monday().into()))
+                    .filter(|d| self.month_picked(*d))
+                    .collect();
+                days.sort();
+                days.dedup();
+                Some((monday, days))
+            }
+            Freq::Monthly => {
+                let months = i64::from(first.year()) * 12 + i64::from(first.month0()) + n;
+                let (year, month) = ((months / 12) as i32, (months % 12) as u32 + 1);
+                let begins = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let days = if self.month_picked(begins) {
+                    self.days_in(year, month, first.day())
+                } else {
+                    Vec::new()
+                };
+                Some((begins, days))
+            }
+            Freq::Yearly => {
+                let year = first.year().checked_add(n as i32)?;
+                let begins = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                let mut months = if self.by_month.is_empty() {
+                    vec![first.month()]
+                } else {
+                    self.by_month.clone()
+                };
+                months.sort();
+                months.dedup();
+                let days = months
+                    .iter()
+                    .flat_map(|m| self.days_in(year, *m, first.day()))
+                    .collect();
+                Some((begins, days))
+            }
+        }
+    }
+
+    fn month_picked(&self, day: NaiveDate) -> bool {
+        self.by_month.is_empty() || self.by_month.contains(&day.month())
+    }
+
+    /// The days of a month the rule picks, or the day of the month of the
+    /// first time if it doesn't pick days
+    fn days_in(&self, year: i32, month: u32, default: u32) -> Vec<NaiveDate> {
+        if self.by_day.is_empty() && self.by_month_day.is_empty() {
+            return NaiveDate::from_ymd_opt(year, month, default)
+                .into_iter()
+                .collect();
+        }
+        let days: Vec<NaiveDate> = (1..=31)
+            .filter_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+            .collect();
+        let len = days.len() as i32;
+        days.into_iter()
+            .filter(|day| {
+                let d = day.day() as i32;
+                let by_month_day = self.by_month_day.is_empty()
+                    || self
+                        .by_month_day
+                        .iter()
+                        .any(|md| *md == d || *md == d - len - 1);
+                let by_day = self.by_day.is_empty()
+                    || self.by_day.iter().any(|(n, wd)| {
+                        *wd == day.weekday()
+                            && match n {
+                                None => true,
+                                Some(n) if *n > 0 => (d - 1) / 7 + 1 == *n,
+                                Some(n) => -((len - d) / 7 + 1) == *n,
+                            }
+                    });
+                by_month_day && by_day
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Recurrence {
+    /// Write the rule the way it is stored
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let freq = match self.freq {
+            Freq::Daily => "DAILY",
+            Freq::Weekly => "WEEKLY",
+            Freq::Monthly => "MONTHLY",
+            Freq::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", freq)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(c) = self.count {
+            write!(f, ";COUNT={}", c)?;
+        }
+        if let Some(u) = self.until {
+            write!(f, ";UNTIL={}", u.format("%Y%m%dT%H%M%S"))?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self
+                .by_day
+                .iter()
+                .map(|(n, wd)| {
+                    let code = DAY_CODES[wd.num_days_from_monday() as usize];
+                    match n {
+                        Some(n) => format!("{}{}", n, code),
+                        None => code.to_string(),
+                    }
+                })
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(|d| d.to_string()).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if !self.by_month.is_empty() {
+            let months: Vec<String> = self.by_month.iter().map(|m| m.to_string()).collect();
+            write!(f, ";BYMONTH={}", months.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+//# Helper Functions
+
+/// Read a day like `TU`, `2TU`, or `-1FR`
+fn read_day(text: &str) -> Option<(Option<i32>, Weekday)> {
+    let text = text.trim();
+    let split = text.len().checked_sub(2)?;
+    let (n, code) = (text.get(..split)?, text.get(split..)?);
+    let wd = match DAY_CODES.iter().position(|c| *c == code)? {
+        0 => Weekday::Mon,
+        1 => Weekday::Tue,
+        2 => Weekday::Wed,
+        3 => Weekday::Thu,
+        4 => Weekday::Fri,
+        5 => Weekday::Sat,
+        _ => Weekday::Sun,
+    };
+    if n.is_empty() {
+        return Some((None, wd));
+    }
+    let n: i32 = n.trim_start_matches('+').parse().ok()?;
+    if n == 0 || n.abs() > 5 {
+        return None;
+    }
+    Some((Some(n), wd))
+}
+
+/// Read the end of a rule as a local time
+///
+/// A date means the end of that day.
+fn read_until(value: &str) -> Option<NaiveDateTime> {
+    if value.len() == 8 {
+        let day = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(day.and_hms(23, 59, 59));
+    }
+    if value.ends_with('Z') {
+        let t = NaiveDateTime::parse_from_str(&value[..value.len() - 1], "%Y%m%dT%H%M%S").ok()?;
+        return Some(
+            Utc.from_utc_datetime(&t)
+                .with_timezone(&Local)
+                .naive_local(),
+        );
+    }
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()
+}
+
+/// A number like "2nd", or "last" for -1
+fn ordinal(n: i32) -> String {
+    match n {
+        -1 => String::from("last"),
+        n if n < 0 => format!("{} to last", ordinal(-n)),
+        n => {
+            let suffix = match (n % 10, n % 100) {
+                (_, 11..=13) => "th",
+                (1, _) => "st",
+                (2, _) => "nd",
+                (3, _) => "rd",
+                _ => "th",
+            };
+            format!("{}{}", n, suffix)
+        }
+    }
+}
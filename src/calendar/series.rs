@@ -0,0 +1,410 @@
+//! Repeating events
+//!
+//! A repeating event is stored once, as its first time along with the
+//! RRULE it repeats by (see `calendar::recurrence`). The times it happens
+//! are expanded from the rule when the calendar is shown, up to
+//! `EXPAND_DAYS` ahead. A time gets an event of its own, with its own
+//! attendance code and attendance, once it is coming up, is opened, or is
+//! changed on its own. Like meetings generated from group schedules, those
+//! events remember the day they were made for in `occurrence` so that the
+//! day is never expanded again. Days in `exdates` are skipped.
+
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel::{delete, insert_into, select, update};
+
+use super::models::*;
+use crate::attend::code::attendance_code;
+use crate::showcase::handlers::delete_showcase;
+
+/// How many days past today repeating events are expanded
+pub const EXPAND_DAYS: i64 = 366;
+
+/// A time on the calendar
+///
+/// Times of repeating events without an event of their own yet have the
+/// ID of the repeating event.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct CalendarEntry {
+    #[serde(flatten)]
+    pub event: Event,
+    /// Page of the time
+    pub url: String,
+}
+
+/// Everything on the calendar that overlaps `from` until `until`, in order
+pub fn expand(
+    conn: &SqliteConnection,
+    from: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Vec<CalendarEntry> {
+    entries(conn, Some((from, until)))
+}
+
+/// Everything on the calendar, in order
+///
+/// Repeating events are expanded from their first time.
+pub fn all_entries(conn: &SqliteConnection) -> Vec<CalendarEntry> {
+    entries(conn, None)
+}
+
+/// Every time of a repeating event, made or not
+pub fn series_times(conn: &SqliteConnection, s: &Event) -> Vec<CalendarEntry> {
+    let made: Vec<Event> = {
+        use crate::schema::events::dsl::*;
+        events
+            .filter(series_id.eq(s.id))
+            .load(conn)
+            .expect("Failed to get events from database")
+    };
+    let mut times: Vec<CalendarEntry> = made
+        .into_iter()
+        .chain(unmade_times(conn, s, None))
+        .map(entry)
+        .collect();
+    times.sort_by_key(|t| t.event.start);
+    times
+}
+
+/// The times of a repeating event that don't have an event of their own
+/// yet
+///
+/// Only the times that overlap the window are included, or every time
+/// from the first if there isn't one.
+pub fn unmade_times(
+    conn: &SqliteConnection,
+    s: &Event,
+    window: Option<(NaiveDateTime, NaiveDateTime)>,
+) -> Vec<Event> {
+    let length = s.end - s.start;
+    let last = Local::today().naive_local() + Duration::days(EXPAND_DAYS);
+    let (from, until) = match window {
+        Some((from, until)) => (
+            from.checked_sub_signed(length).unwrap_or(from).date(),
+            until.date().min(last),
+        ),
+        None => (s.start.date(), last),
+    };
+    let made: Vec<Option<NaiveDate>> = {
+        use crate::schema::events::dsl::*;
+        events
+            .filter(series_id.eq(s.id))
+            .select(occurrence)
+            .load(conn)
+            .expect("Failed to get events from database")
+    };
+
+    s.occurrences(from, until)
+        .into_iter()
+        .filter(|day| !made.contains(&Some(*day)))
+        .map(|day| {
+            let begins = day.and_time(s.start.time());
+            Event {
+                start: begins,
+                end: begins + length,
+                rrule: None,
+                exdates: String::from("[]"),
+                series_id: Some(s.id),
+                occurrence: Some(day),
+                ..s.clone()
+            }
+        })
+        .filter(|e| window.map(|(from, until)| e.start < until && from < e.end) != Some(false))
+        .collect()
+}
+
+/// The event of the time of a repeating event on `day`, made if it
+/// doesn't exist yet
+///
+/// `None` if the event doesn't happen that day.
+pub fn make_time(conn: &SqliteConnection, s: &Event, day: NaiveDate) -> QueryResult<Option<i32>> {
+    use crate::schema::events::dsl::*;
+
+    let made = events
+        .filter(series_id.eq(s.id).and(occurrence.eq(day)))
+        .select(id)
+        .first(conn)
+        .optional()?;
+    if made.is_some() || !s.occurrences(day, day).contains(&day) {
+        return Ok(made);
+    }
+    insert_into(events)
+        .values(&time_of(s, day, attendance_code(conn)))
+        .execute(conn)?;
+    events
+        .filter(series_id.eq(s.id).and(occurrence.eq(day)))
+        .select(id)
+        .first(conn)
+        .optional()
+}
+
+/// Make the times of every repeating event up to `ahead` days from now
+/// so that hosts have the attendance codes ahead of time
+pub fn generate_all(conn: &SqliteConnection, ahead: i64) {
+    use crate::schema::events::dsl::*;
+
+    let series: Vec<Event> = events
+        .filter(rrule.is_not_null())
+        .load(conn)
+        .expect("Failed to get events from database");
+    let today = Local::today().naive_local();
+    for s in series.iter() {
+        for day in s.occurrences(today, today + Duration::days(ahead)) {
+            make_time(conn, s, day).expect("Failed to insert event into database");
+        }
+    }
+}
+
+/// Add a repeating event, returning its ID
+pub fn add_series(conn: &SqliteConnection, new: &NewEvent) -> QueryResult<i32> {
+    use crate::schema::events::dsl::*;
+
+    conn.transaction(|| {
+        insert_into(events).values(new).execute(conn)?;
+        events.select(id).order(id.desc()).first(conn)
+    })
+}
+
+/// Make a single event repeat with `new`
+///
+/// The event becomes the first time of the repeating event so that its
+/// attendance, RSVPs, and showcase are kept.
+pub fn start_series(conn: &SqliteConnection, eid: i32, new: &NewEvent) -> QueryResult<()> {
+    use crate::schema::events::dsl::*;
+
+    conn.transaction(|| {
+        // The repeating event takes over the UID it was imported with
+        let code_of_first: String = events.find(eid).select(code).first(conn)?;
+        update(events.find(eid))
+            .set(import_uid.eq(None::<String>))
+            .execute(conn)?;
+        let mut s = new.clone();
+        s.code = attendance_code(conn);
+        let sid = add_series(conn, &s)?;
+        let series: Event = events.find(sid).first(conn)?;
+
+        update(events.find(eid))
+            .set(&time_of(&series, series.start.date(), code_of_first))
+            .execute(conn)?;
+        Ok(())
+    })
+}
+
+/// Change every time of a repeating event
+///
+/// Times that weren't changed on their own are changed too. If the
+/// repeating event moves to another day, every time moves with it.
+pub fn update_series(conn: &SqliteConnection, old: &Event, new: &NewEvent) -> QueryResult<()> {
+    use crate::schema::events::dsl::*;
+
+    conn.transaction(|| {
+        update(events.find(old.id)).set(new).execute(conn)?;
+        let (begins, _) = new.times().expect("Event times should be checked first");
+        let times = events.filter(series_id.eq(old.id)).load(conn)?;
+        carry_over(conn, old, old.id, times, begins.date() - old.start.date())
+    })
+}
+
+/// Change the time of a repeating event on `day` and the ones after it
+///
+/// The repeating event is split in two, the old one ends before `day` and
+/// a new one starts with `new`. Returns the ID of the new one.
+pub fn split_series(
+    conn: &SqliteConnection,
+    old: &Event,
+    day: NaiveDate,
+    new: &NewEvent,
+) -> QueryResult<i32> {
+    use crate::schema::events::dsl::*;
+
+    conn.transaction(|| {
+        if let Some(rule) = old.recurrence() {
+            let before: Vec<NaiveDate> = old.skip_list().into_iter().filter(|d| *d < day).collect();
+            update(events.find(old.id))
+                .set((
+                    rrule.eq(rule.ending_before(day).to_string()),
+                    exdates.eq(serde_json::to_string(&before).unwrap()),
+                ))
+                .execute(conn)?;
+        }
+
+        let mut s = new.clone();
+        s.code = attendance_code(conn);
+        s.import_uid = None;
+        let sid = add_series(conn, &s)?;
+
+        let (begins, _) = new.times().expect("Event times should be checked first");
+        let times = events
+            .filter(series_id.eq(old.id).and(occurrence.ge(day)))
+            .load(conn)?;
+        carry_over(conn, old, sid, times, begins.date() - day)?;
+        Ok(sid)
+    })
+}
+
+/// Delete a time of a repeating event so that it is skipped
+pub fn skip_time(conn: &SqliteConnection, t: &Event) -> QueryResult<()> {
+    use crate::schema::events::dsl::*;
+
+    conn.transaction(|| {
+        if let (Some(sid), Some(day)) = (t.series_id, t.occurrence) {
+            let s: Option<Event> = events.find(sid).first(conn).optional()?;
+            if let Some(s) = s {
+                let mut skip = s.skip_list();
+                skip.push(day);
+                skip.sort();
+                skip.dedup();
+                update(events.find(sid))
+                    .set(exdates.eq(serde_json::to_string(&skip).unwrap()))
+                    .execute(conn)?;
+            }
+        }
+        delete_event(conn, t.id)
+    })
+}
+
+/// Delete a repeating event along with every time of it
+pub fn delete_series(conn: &SqliteConnection, sid: i32) -> QueryResult<()> {
+    use crate::schema::events::dsl::*;
+
+    conn.transaction(|| {
+        let times: Vec<i32> = events.filter(series_id.eq(sid)).select(id).load(conn)?;
+        for eid in times {
+            delete_event(conn, eid)?;
+        }
+        delete_event(conn, sid)
+    })
+}
+
+/// Delete an event along with its showcase, RSVPs, and attendance
+pub fn delete_event(conn: &SqliteConnection, eid: i32) -> QueryResult<()> {
+    conn.transaction(|| {
+        delete_showcase(conn, eid)?;
+        {
+            use crate::schema::event_rsvps::dsl::*;
+            delete(event_rsvps.filter(event_id.eq(eid))).execute(conn)?;
+        }
+        {
+            use crate::schema::attendances::dsl::*;
+            delete(attendances.filter(event_id.eq(eid))).execute(conn)?;
+        }
+        use crate::schema::events::dsl::*;
+        delete(events.find(eid)).execute(conn)?;
+        Ok(())
+    })
+}
+
+//# Helper Functions
+
+fn entries(
+    conn: &SqliteConnection,
+    window: Option<(NaiveDateTime, NaiveDateTime)>,
+) -> Vec<CalendarEntry> {
+    use crate::schema::events::dsl::*;
+
+    let mut single = events.filter(rrule.is_null()).into_boxed();
+    let mut series = events.filter(rrule.is_not_null()).into_boxed();
+    if let Some((from, until)) = window {
+        single = single.filter(start.lt(until).and(end.gt(from)));
+        series = series.filter(start.lt(until));
+    }
+    let single: Vec<Event> = single
+        .load(conn)
+        .expect("Failed to get events from database");
+    let series: Vec<Event> = series
+        .load(conn)
+        .expect("Failed to get events from database");
+
+    let mut entries: Vec<CalendarEntry> = single.into_iter().map(entry).collect();
+    for s in series.iter() {
+        entries.extend(unmade_times(conn, s, window).into_iter().map(entry));
+    }
+    entries.sort_by_key(|e| e.event.start);
+    entries
+}
+
+fn entry(e: Event) -> CalendarEntry {
+    let url = match (e.series_id, e.occurrence) {
+        (Some(sid), Some(day)) if sid == e.id => format!("/calendar/{}?on={}", sid, day),
+        _ => format!("/calendar/{}", e.id),
+    };
+    CalendarEntry { event: e, url }
+}
+
+/// The time of a repeating event on `day` as the repeating event has it
+fn time_of(s: &Event, day: NaiveDate, atcode: String) -> NewOccurrence {
+    let begins = day.and_time(s.start.time());
+    NewOccurrence {
+        title: s.title.clone(),
+        start: begins,
+        end: begins + (s.end - s.start),
+        description: s.description.clone(),
+        hosted_by: s.hosted_by,
+        location: s.location.clone(),
+        code: atcode,
+        color: s.color.clone(),
+        room_id: s.room_id,
+        series_id: Some(s.id),
+        occurrence: Some(day),
+    }
+}
+
+/// Is the time still the way the repeating event has it?
+fn follows(s: &Event, t: &Event) -> bool {
+    t.occurrence.map(|day| day.and_time(s.start.time())) == Some(t.start)
+        && t.end - t.start == s.end - s.start
+        && t.title == s.title
+        && t.description == s.description
+        && t.hosted_by == s.hosted_by
+        && t.location == s.location
+        && t.color == s.color
+        && t.room_id == s.room_id
+}
+
+/// Move times of the repeating event `old` to the repeating event `sid`,
+/// `shift` days later
+///
+/// Times that weren't changed on their own take on the changes. They are
+/// deleted if the repeating event doesn't happen that day anymore and
+/// nobody attended.
+fn carry_over(
+    conn: &SqliteConnection,
+    old: &Event,
+    sid: i32,
+    mut times: Vec<Event>,
+    shift: Duration,
+) -> QueryResult<()> {
+    use crate::schema::events::dsl::*;
+
+    let s: Event = events.find(sid).first(conn)?;
+    // Move the times in the direction of the shift first so that two
+    // times never have the same day
+    times.sort_by_key(|t| t.occurrence);
+    if shift > Duration::zero() {
+        times.reverse();
+    }
+
+    for t in times {
+        let day = match t.occurrence {
+            Some(d) => d + shift,
+            None => continue,
+        };
+        let attended: bool = {
+            use crate::schema::attendances::dsl::*;
+            select(exists(attendances.filter(event_id.eq(t.id)))).get_result(conn)?
+        };
+        if follows(old, &t) && s.occurrences(day, day).contains(&day) {
+            update(events.find(t.id))
+                .set(&time_of(&s, day, t.code.clone()))
+                .execute(conn)?;
+        } else if follows(old, &t) && !attended {
+            delete_event(conn, t.id)?;
+        } else {
+            update(events.find(t.id))
+                .set((series_id.eq(sid), occurrence.eq(day)))
+                .execute(conn)?;
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,12 @@
 //! HTML templates for the Calendar
 
+use super::import::ImportRow;
 use super::models::*;
+use super::series::CalendarEntry;
 #[allow(unused_imports)]
 use crate::templates::{filters, FormError, OptUser};
 
-use crate::models::User;
+use crate::models::{Room, RoomOption, User};
 
 /// Calendar page template
 ///
@@ -16,7 +18,9 @@ use crate::models::User;
 #[template(path = "calendar/calendar.html")]
 pub struct CalendarTemplate {
     pub logged_in: OptUser,
-    pub events: Vec<Event>,
+    pub events: Vec<CalendarEntry>,
+    /// Token of the logged in user's private feed, if they turned it on
+    pub feed_token: Option<String>,
 }
 
 /// Event page template
@@ -29,6 +33,18 @@ pub struct CalendarTemplate {
 pub struct EventTemplate {
     pub logged_in: OptUser,
     pub event: Event,
+    /// Does the event have a showcase?
+    pub has_showcase: bool,
+    /// The room the event is in, if any
+    pub room: Option<Room>,
+    /// Is the logged in user going?
+    pub going: bool,
+    /// How many users are going
+    pub rsvp_count: usize,
+    /// The repeating event this is a time of, if any
+    pub series: Option<Event>,
+    /// The next times of a repeating event
+    pub upcoming: Vec<CalendarEntry>,
 }
 
 /// Template for creating a new Event
@@ -41,6 +57,7 @@ pub struct EventTemplate {
 pub struct NewEventTemplate {
     pub logged_in: OptUser,
     pub all_users: Vec<User>,
+    pub rooms: Vec<RoomOption>,
     pub error: Option<FormError>,
 }
 
@@ -55,5 +72,36 @@ pub struct EditEventTemplate {
     pub logged_in: OptUser,
     pub event: Event,
     pub all_users: Vec<User>,
+    pub rooms: Vec<RoomOption>,
     pub error: Option<FormError>,
+    /// The repeating event this is a time of, if any
+    pub series: Option<Event>,
+    /// What changing a time of a repeating event changes
+    pub scope: EditScope,
+    /// The event to fill in the RRULE and skipped days from, if they can
+    /// be changed
+    pub repeats: Option<Event>,
+}
+
+/// Template to upload a calendar to import
+///
+/// HTML File: `calendar/import.html`
+#[derive(Template)]
+#[template(path = "calendar/import.html")]
+pub struct ImportTemplate {
+    pub logged_in: OptUser,
+    pub error: Option<FormError>,
+}
+
+/// Preview of importing an uploaded calendar
+///
+/// HTML File: `calendar/import-preview.html`
+#[derive(Template)]
+#[template(path = "calendar/import-preview.html")]
+pub struct ImportPreviewTemplate {
+    pub logged_in: OptUser,
+    pub import: EventImport,
+    pub rows: Vec<ImportRow>,
+    /// Entries that couldn't be read and why
+    pub errors: Vec<String>,
 }
@@ -0,0 +1,501 @@
+//! HTTP handlers for evaluations
+
+use diesel::prelude::*;
+use diesel::{delete, insert_into, replace_into, update};
+use rocket::http::{ContentType, Status};
+use rocket::request::Form;
+use rocket::response::content::Content;
+use rocket::response::Redirect;
+
+use crate::groups::handlers::group_users;
+use crate::guards::*;
+use crate::models::{Group, GroupRole, User};
+use crate::semester::Semester;
+use crate::templates::FormError;
+use crate::ObservDbConn;
+
+use super::models::*;
+use super::templates::*;
+
+/// GET handler for `/evaluations`
+///
+/// Coordinators see every form, everyone else sees the open forms they
+/// can fill out.
+#[get("/evaluations")]
+pub fn eval_forms(conn: ObservDbConn, l: UserGuard) -> EvalFormsListTemplate {
+    use crate::schema::eval_forms::dsl::*;
+
+    let all: Vec<EvalForm> = eval_forms
+        .order(created_at.desc())
+        .load(&*conn)
+        .expect("Failed to get evaluation forms from database");
+
+    EvalFormsListTemplate {
+        forms: all
+            .into_iter()
+            .filter(|f| l.0.tier > 1 || (f.open && (l.0.tier > 0 || f.allow_self)))
+            .collect(),
+        logged_in: Some(l.0),
+    }
+}
+
+/// GET handler for `/evaluations/new`
+#[get("/evaluations/new")]
+pub fn eval_form_new(l: AdminGuard) -> NewEvalFormTemplate {
+    NewEvalFormTemplate {
+        logged_in: Some(l.0),
+        semester: Semester::current(),
+    }
+}
+
+/// POST handler for `/evaluations/new`
+#[post("/evaluations/new", data = "<form>")]
+pub fn eval_form_new_post(conn: ObservDbConn, l: AdminGuard, form: Form<EvalFormForm>) -> Redirect {
+    use crate::schema::eval_forms::dsl::*;
+
+    insert_into(eval_forms)
+        .values(&new_eval_form(form.into_inner(), l.0.id))
+        .execute(&*conn)
+        .expect("Failed to insert evaluation form into database");
+
+    let fid: i32 = eval_forms
+        .select(id)
+        .order(id.desc())
+        .first(&*conn)
+        .expect("Failed to get evaluation form from database");
+
+    Redirect::to(format!("/evaluations/{}", fid))
+}
+
+/// GET handler for `/evaluations/<fid>`
+///
+/// Coordinators see every student and mentors see the students in their
+/// groups along with how many evaluations they have.
+#[get("/evaluations/<fid>")]
+pub fn eval_form(conn: ObservDbConn, l: UserGuard, fid: i32) -> Option<EvalFormTemplate> {
+    let form = get_form(&*conn, fid)?;
+
+    let students: Vec<User> = if l.0.tier > 1 {
+        use crate::schema::users::dsl::*;
+        users
+            .filter(tier.eq(0).and(former.eq(false)))
+            .order(real_name.asc())
+            .load(&*conn)
+            .expect("Failed to get users from database")
+    } else if l.0.tier > 0 {
+        mentor_students(&*conn, &l.0)
+    } else {
+        Vec::new()
+    };
+
+    let evals: Vec<Evaluation> = Evaluation::belonging_to(&form)
+        .load(&*conn)
+        .expect("Failed to get evaluations from database");
+    let questions = form.question_list();
+
+    let rows = students
+        .into_iter()
+        .map(|s| {
+            let theirs: Vec<&Evaluation> = evals
+                .iter()
+                .filter(|e| e.student_id == s.id && !e.is_self())
+                .collect();
+            EvalRow {
+                evaluated: theirs.iter().any(|e| e.evaluator_id == l.0.id),
+                count: theirs.len(),
+                self_evaluated: evals.iter().any(|e| e.student_id == s.id && e.is_self()),
+                score: average(theirs.iter().filter_map(|e| e.score(&questions))).map(percent),
+                student: s,
+            }
+        })
+        .collect();
+
+    Some(EvalFormTemplate {
+        logged_in: Some(l.0),
+        form,
+        rows,
+    })
+}
+
+/// GET handler for `/evaluations/<fid>/edit`
+#[get("/evaluations/<fid>/edit")]
+pub fn eval_form_edit(conn: ObservDbConn, l: AdminGuard, fid: i32) -> Option<EditEvalFormTemplate> {
+    Some(EditEvalFormTemplate {
+        logged_in: Some(l.0),
+        form: get_form(&*conn, fid)?,
+    })
+}
+
+/// PUT handler for `/evaluations/<fid>`
+///
+/// Changing the questions of a form that already has evaluations will
+/// mix up their answers, so coordinators should only reword them.
+#[put("/evaluations/<fid>", data = "<form>")]
+pub fn eval_form_edit_put(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    fid: i32,
+    form: Form<EvalFormForm>,
+) -> Redirect {
+    use crate::schema::eval_forms::dsl::*;
+
+    let creator: i32 = eval_forms
+        .find(fid)
+        .select(created_by)
+        .first(&*conn)
+        .expect("Failed to get evaluation form from database");
+
+    update(eval_forms.find(fid))
+        .set(&new_eval_form(form.into_inner(), creator))
+        .execute(&*conn)
+        .expect("Failed to update evaluation form in database");
+
+    Redirect::to(format!("/evaluations/{}", fid))
+}
+
+/// DELETE handler for `/evaluations/<fid>`
+///
+/// Deletes the form and all evaluations made with it.
+#[delete("/evaluations/<fid>")]
+pub fn eval_form_delete(conn: ObservDbConn, _l: AdminGuard, fid: i32) -> Redirect {
+    {
+        use crate::schema::evaluations::dsl::*;
+        delete(evaluations.filter(form_id.eq(fid)))
+            .execute(&*conn)
+            .expect("Failed to delete evaluations from database");
+    }
+    use crate::schema::eval_forms::dsl::*;
+    delete(eval_forms.find(fid))
+        .execute(&*conn)
+        .expect("Failed to delete evaluation form from database");
+    Redirect::to("/evaluations")
+}
+
+/// GET handler for `/evaluations/<fid>/students/<uid>`
+///
+/// The form to evaluate a student along with the evaluations of the
+/// student that the logged in user is allowed to see.
+#[get("/evaluations/<fid>/students/<uid>?<e>")]
+pub fn evaluate(
+    conn: ObservDbConn,
+    l: UserGuard,
+    fid: i32,
+    uid: i32,
+    e: Option<FormError>,
+) -> Result<EvaluateTemplate, Status> {
+    let form = get_form(&*conn, fid).ok_or(Status::NotFound)?;
+    let student: User = {
+        use crate::schema::users::dsl::*;
+        users
+            .find(uid)
+            .first(&*conn)
+            .optional()
+            .expect("Failed to get user from database")
+            .ok_or(Status::NotFound)?
+    };
+
+    let can_eval = can_evaluate(&*conn, &form, &l.0, &student);
+    let can_see = can_view(&*conn, &form, &l.0, &student);
+    if !can_eval && !can_see {
+        return Err(Status::Unauthorized);
+    }
+
+    let evals: Vec<Evaluation> = {
+        use crate::schema::evaluations::dsl::*;
+        Evaluation::belonging_to(&form)
+            .filter(student_id.eq(uid))
+            .order(submitted_at.asc())
+            .load(&*conn)
+            .expect("Failed to get evaluations from database")
+    };
+    let questions = form.question_list();
+
+    let own = evals
+        .iter()
+        .find(|ev| ev.evaluator_id == l.0.id)
+        .map(|ev| ev.answer_list())
+        .unwrap_or_default();
+
+    let received = if can_see {
+        evals
+            .iter()
+            // Students only see what others wrote about them
+            .filter(|ev| l.0.tier > 0 || !ev.is_self())
+            .map(|ev| EvaluationEntry {
+                evaluator: get_user(&*conn, ev.evaluator_id),
+                answers: pair_answers(&questions, &ev.answer_list()),
+                score: ev.score(&questions).map(percent),
+                evaluation: ev.clone(),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(EvaluateTemplate {
+        logged_in: Some(l.0),
+        answers: pair_answers(&questions, &own),
+        can_evaluate: can_eval,
+        received,
+        form,
+        student,
+        error: e,
+    })
+}
+
+/// POST handler for `/evaluations/<fid>/students/<uid>`
+///
+/// Submitting again replaces the previous evaluation by the same user.
+#[post("/evaluations/<fid>/students/<uid>", data = "<form>")]
+pub fn evaluate_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    fid: i32,
+    uid: i32,
+    form: Form<AnswersForm>,
+) -> Result<Redirect, Status> {
+    let eform = get_form(&*conn, fid).ok_or(Status::NotFound)?;
+    let student: User = {
+        use crate::schema::users::dsl::*;
+        users
+            .find(uid)
+            .first(&*conn)
+            .optional()
+            .expect("Failed to get user from database")
+            .ok_or(Status::NotFound)?
+    };
+
+    if !can_evaluate(&*conn, &eform, &l.0, &student) {
+        return Err(Status::Unauthorized);
+    }
+
+    let questions = eform.question_list();
+    let mut answers = form.into_inner().0;
+    answers.resize(questions.len(), String::new());
+
+    // Ratings must be within the scale
+    let valid = questions.iter().zip(answers.iter()).all(|(q, a)| {
+        !q.is_rating()
+            || a.parse::<i32>()
+                .map(|r| r >= 1 && r <= q.max())
+                .unwrap_or(false)
+    });
+    if !valid {
+        return Ok(Redirect::to(format!(
+            "/evaluations/{}/students/{}?e={}",
+            fid,
+            uid,
+            FormError::Other
+        )));
+    }
+
+    use crate::schema::evaluations::dsl::*;
+    replace_into(evaluations)
+        .values(&NewEvaluation {
+            form_id: fid,
+            student_id: uid,
+            evaluator_id: l.0.id,
+            answers: serde_json::to_string(&answers).expect("Failed to serialize answers"),
+        })
+        .execute(&*conn)
+        .expect("Failed to insert evaluation into database");
+
+    Ok(Redirect::to(format!("/evaluations/{}", fid)))
+}
+
+/// GET handler for `/evaluations/<fid>/export.csv`
+///
+/// Every evaluation made with the form as CSV with one row per evaluation
+/// and one column per question.
+#[get("/evaluations/<fid>/export.csv")]
+pub fn eval_form_export(conn: ObservDbConn, _l: AdminGuard, fid: i32) -> Option<Content<String>> {
+    let form = get_form(&*conn, fid)?;
+    let questions = form.question_list();
+
+    let mut out = vec![
+        "student",
+        "handle",
+        "evaluator",
+        "self",
+        "submitted_at",
+        "score",
+    ]
+    .into_iter()
+    .map(String::from)
+    .chain(questions.iter().map(|q| q.prompt().to_string()))
+    .map(|f| csv_field(&f))
+    .collect::<Vec<String>>()
+    .join(",");
+    out.push('\n');
+
+    let evals: Vec<Evaluation> = Evaluation::belonging_to(&form)
+        .load(&*conn)
+        .expect("Failed to get evaluations from database");
+
+    for ev in evals {
+        let student = get_user(&*conn, ev.student_id);
+        let evaluator = get_user(&*conn, ev.evaluator_id);
+        let row = vec![
+            student.real_name,
+            student.handle,
+            evaluator.real_name,
+            ev.is_self().to_string(),
+            ev.submitted_at.to_string(),
+            ev.score(&questions)
+                .map(|s| format!("{:.2}", s))
+                .unwrap_or_default(),
+        ]
+        .into_iter()
+        .chain(ev.answer_list())
+        .map(|f| csv_field(&f))
+        .collect::<Vec<String>>()
+        .join(",");
+        out.push_str(&row);
+        out.push('\n');
+    }
+
+    Some(Content(ContentType::CSV, out))
+}
+
+//# Helper Functions
+
+fn get_form(conn: &SqliteConnection, fid: i32) -> Option<EvalForm> {
+    use crate::schema::eval_forms::dsl::*;
+    eval_forms
+        .find(fid)
+        .first(conn)
+        .optional()
+        .expect("Failed to get evaluation form from database")
+}
+
+fn get_user(conn: &SqliteConnection, uid: i32) -> User {
+    use crate::schema::users::dsl::*;
+    users
+        .find(uid)
+        .first(conn)
+        .expect("Failed to get user from database")
+}
+
+fn new_eval_form(form: EvalFormForm, creator: i32) -> NewEvalForm {
+    NewEvalForm {
+        title: form.title,
+        description: form.description,
+        questions: serde_json::to_string(&parse_questions(&form.questions))
+            .expect("Failed to serialize questions"),
+        semester: form.semester.id(),
+        allow_self: form.allow_self,
+        visible_to_student: form.visible_to_student,
+        graded: form.graded,
+        open: form.open,
+        created_by: creator,
+    }
+}
+
+fn pair_answers(questions: &[Question], answers: &[String]) -> Vec<QuestionAnswer> {
+    questions
+        .iter()
+        .enumerate()
+        .map(|(i, q)| QuestionAnswer {
+            index: i,
+            question: q.clone(),
+            answer: answers.get(i).cloned().unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn average<I: Iterator<Item = f64>>(scores: I) -> Option<f64> {
+    let (sum, n) = scores.fold((0.0, 0), |(s, n), x| (s + x, n + 1));
+    if n > 0 {
+        Some(sum / f64::from(n))
+    } else {
+        None
+    }
+}
+
+fn percent(score: f64) -> u32 {
+    (score * 100.0).round() as u32
+}
+
+/// Quote a field for CSV if it needs it
+fn csv_field(f: &str) -> String {
+    if f.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", f.replace('"', "\"\""))
+    } else {
+        f.to_string()
+    }
+}
+
+/// The students in all of the groups a mentor leads or is a mentor of
+///
+/// Assistants don't evaluate students.
+pub fn mentor_students(conn: &SqliteConnection, mentor: &User) -> Vec<User> {
+    let mentored: Vec<i32> = {
+        use crate::schema::group_mentors::dsl::*;
+        group_mentors
+            .filter(user_id.eq(mentor.id))
+            .filter(role.eq(GroupRole::Mentor.as_str()))
+            .select(group_id)
+            .load(conn)
+            .expect("Failed to get mentors from database")
+    };
+
+    use crate::schema::groups::dsl::*;
+    let owned: Vec<Group> = groups
+        .filter(owner_id.eq(mentor.id).or(id.eq_any(mentored)))
+        .load(conn)
+        .expect("Failed to get groups from database");
+
+    let mut students: Vec<User> = owned
+        .iter()
+        .flat_map(|g| group_users(conn, g))
+        .filter(|u| u.id != mentor.id)
+        .collect();
+    students.sort_by_key(|u| u.id);
+    students.dedup_by_key(|u| u.id);
+    students
+}
+
+/// Can the user fill out the form for the student?
+///
+/// Coordinators can evaluate anyone, mentors can evaluate the students in
+/// their groups, and students can evaluate themselves if the form allows.
+fn can_evaluate(conn: &SqliteConnection, form: &EvalForm, user: &User, student: &User) -> bool {
+    user.tier > 1
+        || (form.open
+            && ((user.id == student.id && form.allow_self)
+                || (user.tier > 0 && mentor_students(conn, user).contains(student))))
+}
+
+/// Can the user see the evaluations of the student?
+fn can_view(conn: &SqliteConnection, form: &EvalForm, user: &User, student: &User) -> bool {
+    user.tier > 1
+        || (user.id == student.id && form.visible_to_student)
+        || (user.tier > 0 && mentor_students(conn, user).contains(student))
+}
+
+/// The average score of a student's evaluations in a semester
+///
+/// Only forms that count toward the grade are included, and
+/// self-evaluations are left out. The score is a percentage.
+pub fn evaluation_score(conn: &SqliteConnection, user: &User, sem: &Semester) -> Option<u32> {
+    let forms: Vec<EvalForm> = {
+        use crate::schema::eval_forms::dsl::*;
+        eval_forms
+            .filter(graded.eq(true).and(semester.eq(sem.id())))
+            .load(conn)
+            .expect("Failed to get evaluation forms from database")
+    };
+
+    let mut scores = Vec::new();
+    for f in forms {
+        use crate::schema::evaluations::dsl::*;
+        let questions = f.question_list();
+        let evals: Vec<Evaluation> = Evaluation::belonging_to(&f)
+            .filter(student_id.eq(user.id).and(evaluator_id.ne(user.id)))
+            .load(conn)
+            .expect("Failed to get evaluations from database");
+        scores.extend(evals.iter().filter_map(|e| e.score(&questions)));
+    }
+
+    average(scores.into_iter()).map(percent)
+}
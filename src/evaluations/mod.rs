@@ -0,0 +1,19 @@
+//! Mentor evaluations
+//!
+//! Coordinators create evaluation forms with rating and free text
+//! questions. Mentors fill one out for each student in their groups and
+//! students can evaluate themselves if the form allows it. Forms can be
+//! exported as CSV and their ratings can count toward the grade.
+//!
+//! ## Routes
+//! - `/evaluations`
+//! - `/evaluations/new`
+//! - `/evaluations/<fid>`
+//! - `/evaluations/<fid>/edit`
+//! - `/evaluations/<fid>/export.csv`
+//! - `/evaluations/<fid>/students/<uid>`
+
+pub mod handlers;
+pub mod models;
+
+mod templates;
@@ -0,0 +1,247 @@
+//! Models for evaluations
+//!
+//! Coordinators create evaluation forms in `eval_forms`. Each filled out
+//! form is stored in `evaluations` with one row per form, student, and
+//! evaluator. Questions and answers are stored as JSON arrays.
+
+use chrono::NaiveDateTime;
+
+use crate::schema::*;
+use crate::semester::Semester;
+
+/// A single question on an evaluation form
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Question {
+    /// A rating from 1 up to `max`
+    Rating { prompt: String, max: i32 },
+    /// A free text answer
+    Text { prompt: String },
+}
+
+impl Question {
+    pub fn prompt(&self) -> &str {
+        match self {
+            Question::Rating { prompt, .. } => prompt,
+            Question::Text { prompt } => prompt,
+        }
+    }
+
+    pub fn is_rating(&self) -> bool {
+        match self {
+            Question::Rating { .. } => true,
+            Question::Text { .. } => false,
+        }
+    }
+
+    /// The highest rating, or 0 for text questions
+    pub fn max(&self) -> i32 {
+        match self {
+            Question::Rating { max, .. } => *max,
+            Question::Text { .. } => 0,
+        }
+    }
+}
+
+/// Parse questions from the text format used in the form
+///
+/// There is one question per line. Lines starting with `rating:` are
+/// ratings from 1 to 5, or `rating 10:` for a different scale. Lines
+/// starting with `text:` or without a prefix are free text.
+pub fn parse_questions(text: &str) -> Vec<Question> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .take(MAX_QUESTIONS)
+        .map(|l| {
+            let lower = l.to_lowercase();
+            if lower.starts_with("rating") {
+                if let Some(i) = l.find(':') {
+                    let max = l[6..i].trim().parse::<i32>().unwrap_or(5).max(2);
+                    return Question::Rating {
+                        prompt: l[i + 1..].trim().to_string(),
+                        max,
+                    };
+                }
+            } else if lower.starts_with("text:") {
+                return Question::Text {
+                    prompt: l[5..].trim().to_string(),
+                };
+            }
+            Question::Text {
+                prompt: l.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// An evaluation form
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Serialize)]
+pub struct EvalForm {
+    /// ID of the form
+    pub id: i32,
+    /// Title of the form such as "Midterm Evaluation"
+    pub title: String,
+    /// Instructions as Markdown
+    pub description: String,
+    /// The questions as a JSON array
+    pub questions: String,
+    /// Semester the form is for, like `2019-fall`
+    pub semester: String,
+    /// Can students evaluate themselves?
+    pub allow_self: bool,
+    /// Can students see the evaluations of themselves?
+    pub visible_to_student: bool,
+    /// Does the form count toward the grade?
+    pub graded: bool,
+    /// Is the form accepting evaluations?
+    pub open: bool,
+    /// ID of the coordinator who created the form
+    pub created_by: i32,
+    pub created_at: NaiveDateTime,
+}
+
+impl EvalForm {
+    /// The parsed questions of the form
+    pub fn question_list(&self) -> Vec<Question> {
+        serde_json::from_str(&self.questions).unwrap_or_default()
+    }
+
+    /// The questions in the text format used by the form
+    pub fn questions_text(&self) -> String {
+        self.question_list()
+            .iter()
+            .map(|q| match q {
+                Question::Rating { prompt, max } if *max == 5 => format!("rating: {}", prompt),
+                Question::Rating { prompt, max } => format!("rating {}: {}", max, prompt),
+                Question::Text { prompt } => format!("text: {}", prompt),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Used to create or edit an evaluation form
+#[derive(Debug, Default, Clone, Insertable, AsChangeset)]
+#[table_name = "eval_forms"]
+pub struct NewEvalForm {
+    pub title: String,
+    pub description: String,
+    pub questions: String,
+    pub semester: String,
+    pub allow_self: bool,
+    pub visible_to_student: bool,
+    pub graded: bool,
+    pub open: bool,
+    pub created_by: i32,
+}
+
+/// The evaluation form editor
+///
+/// Questions are in the text format read by `parse_questions`.
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct EvalFormForm {
+    pub title: String,
+    pub description: String,
+    pub questions: String,
+    pub semester: Semester,
+    pub allow_self: bool,
+    pub visible_to_student: bool,
+    pub graded: bool,
+    pub open: bool,
+}
+
+/// A filled out evaluation form
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(EvalForm, foreign_key = "form_id")]
+pub struct Evaluation {
+    /// ID of the evaluation
+    pub id: i32,
+    /// ID of the form that was filled out
+    pub form_id: i32,
+    /// ID of the student being evaluated
+    pub student_id: i32,
+    /// ID of the user who filled out the form
+    pub evaluator_id: i32,
+    /// The answers as a JSON array
+    pub answers: String,
+    /// When the evaluation was last submitted
+    pub submitted_at: NaiveDateTime,
+}
+
+impl Evaluation {
+    /// The parsed answers in the same order as the questions
+    pub fn answer_list(&self) -> Vec<String> {
+        serde_json::from_str(&self.answers).unwrap_or_default()
+    }
+
+    /// Is this a self-evaluation?
+    pub fn is_self(&self) -> bool {
+        self.student_id == self.evaluator_id
+    }
+
+    /// The ratings given as a fraction of the highest possible ratings
+    ///
+    /// Returns `None` if the form has no rating questions.
+    pub fn score(&self, questions: &[Question]) -> Option<f64> {
+        let answers = self.answer_list();
+        let (got, max) = questions
+            .iter()
+            .zip(answers.iter())
+            .filter(|(q, _)| q.is_rating())
+            .fold((0, 0), |(g, m), (q, a)| {
+                let r = a.parse::<i32>().unwrap_or(0).max(0).min(q.max());
+                (g + r, m + q.max())
+            });
+        if max > 0 {
+            Some(f64::from(got) / f64::from(max))
+        } else {
+            None
+        }
+    }
+}
+
+/// Used to submit an evaluation
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "evaluations"]
+pub struct NewEvaluation {
+    pub form_id: i32,
+    pub student_id: i32,
+    pub evaluator_id: i32,
+    pub answers: String,
+}
+
+/// The most questions a form can have
+pub const MAX_QUESTIONS: usize = 100;
+
+/// The answers submitted from an evaluation form
+///
+/// Each answer is a field named `q` followed by the index of the question
+/// such as `q0` or `q3`. The fields depend on the form so they can't be
+/// derived.
+#[derive(Debug, Default, Clone)]
+pub struct AnswersForm(pub Vec<String>);
+
+impl<'f> rocket::request::FromForm<'f> for AnswersForm {
+    type Error = ();
+
+    fn from_form(items: &mut rocket::request::FormItems<'f>, _strict: bool) -> Result<Self, ()> {
+        let mut answers = Vec::new();
+        for item in items {
+            let (k, v) = item.key_value_decoded();
+            if !k.starts_with('q') {
+                continue;
+            }
+            let i: usize = k[1..].parse().map_err(|_| ())?;
+            // No form has anywhere near this many questions
+            if i >= MAX_QUESTIONS {
+                return Err(());
+            }
+            if i >= answers.len() {
+                answers.resize(i + 1, String::new());
+            }
+            answers[i] = v;
+        }
+        Ok(AnswersForm(answers))
+    }
+}
@@ -0,0 +1,94 @@
+use super::models::*;
+#[allow(unused_imports)]
+use crate::templates::{filters, FormError, OptUser};
+
+use crate::models::User;
+use crate::semester::Semester;
+
+/// A question and the answer given to it
+pub struct QuestionAnswer {
+    /// Index of the question, used for the field name
+    pub index: usize,
+    pub question: Question,
+    pub answer: String,
+}
+
+/// An evaluation along with who filled it out
+pub struct EvaluationEntry {
+    pub evaluation: Evaluation,
+    pub evaluator: User,
+    pub answers: Vec<QuestionAnswer>,
+    /// Score as a percentage
+    pub score: Option<u32>,
+}
+
+/// How far along a student's evaluations are
+pub struct EvalRow {
+    pub student: User,
+    /// Has the logged in user evaluated the student?
+    pub evaluated: bool,
+    /// Has the student evaluated themselves?
+    pub self_evaluated: bool,
+    /// Number of evaluations by others
+    pub count: usize,
+    /// Average score as a percentage
+    pub score: Option<u32>,
+}
+
+/// List of evaluation forms
+///
+/// HTML File: `evaluations/forms-list.html`
+#[derive(Template)]
+#[template(path = "evaluations/forms-list.html")]
+pub struct EvalFormsListTemplate {
+    pub logged_in: OptUser,
+    pub forms: Vec<EvalForm>,
+}
+
+/// An evaluation form and the students to evaluate with it
+///
+/// HTML File: `evaluations/form.html`
+#[derive(Template)]
+#[template(path = "evaluations/form.html")]
+pub struct EvalFormTemplate {
+    pub logged_in: OptUser,
+    pub form: EvalForm,
+    pub rows: Vec<EvalRow>,
+}
+
+/// Create an evaluation form
+///
+/// HTML File: `evaluations/new-form.html`
+#[derive(Template)]
+#[template(path = "evaluations/new-form.html")]
+pub struct NewEvalFormTemplate {
+    pub logged_in: OptUser,
+    pub semester: Semester,
+}
+
+/// Edit an evaluation form
+///
+/// HTML File: `evaluations/edit-form.html`
+#[derive(Template)]
+#[template(path = "evaluations/edit-form.html")]
+pub struct EditEvalFormTemplate {
+    pub logged_in: OptUser,
+    pub form: EvalForm,
+}
+
+/// Evaluate a student
+///
+/// HTML File: `evaluations/evaluate.html`
+#[derive(Template)]
+#[template(path = "evaluations/evaluate.html")]
+pub struct EvaluateTemplate {
+    pub logged_in: OptUser,
+    pub form: EvalForm,
+    pub student: User,
+    pub can_evaluate: bool,
+    /// The logged in user's previous answers, if any
+    pub answers: Vec<QuestionAnswer>,
+    /// Evaluations of the student the user is allowed to see
+    pub received: Vec<EvaluationEntry>,
+    pub error: Option<FormError>,
+}
@@ -0,0 +1,12 @@
+//! Static site export
+//!
+//! Renders the public parts of Observatory, the project showcase, project
+//! pages, news, and calendar, into a directory of plain HTML files along
+//! with a JSON dump of the same data. The result can be published to any
+//! static host without running the app publicly.
+//!
+//! Run with `observatory export <dir>`. Pages link with absolute paths so
+//! the directory must be served from the root of a domain.
+
+pub mod site;
+mod templates;
@@ -0,0 +1,316 @@
+//! Writing the static site
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use askama::Template;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use rocket::fairing::Fairing;
+use rocket::Rocket;
+
+use crate::calendar::series::{all_entries, CalendarEntry};
+use crate::fairings::DatabaseCreate;
+use crate::handlers::{Embed, BLACKLIST};
+use crate::models::{EventRsvp, NewsStory};
+use crate::projects::handlers::{project_member_entries, project_repos, search_projects};
+use crate::projects::templates::ProjectListing;
+use crate::repos::handlers::project_syncs;
+use crate::rooms::handlers::all_rooms;
+use crate::uploads::models::{full_name, thumb_name};
+use crate::uploads::storage::UploadStore;
+
+use super::templates::*;
+
+/// A project as published in `data.json`
+#[derive(Debug, Serialize)]
+pub struct PublicProject {
+    pub id: i32,
+    pub name: String,
+    pub description: String,
+    pub homepage: Option<String>,
+    pub active: bool,
+    pub extrn: bool,
+    pub repos: Vec<String>,
+    pub tags: Vec<String>,
+    pub languages: Vec<String>,
+    pub logo: Option<String>,
+    pub members: Vec<PublicMember>,
+}
+
+/// A project member as published, without contact details
+#[derive(Debug, Serialize)]
+pub struct PublicMember {
+    pub real_name: String,
+    pub handle: String,
+    pub role: String,
+}
+
+/// An event as published, without the attendance code
+///
+/// Times of repeating events that don't have an event of their own yet
+/// have the ID of the repeating event.
+#[derive(Debug, Serialize)]
+pub struct PublicEvent {
+    pub id: i32,
+    /// Page of the event on the exported site
+    pub url: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub color: Option<String>,
+}
+
+impl From<&CalendarEntry> for PublicEvent {
+    fn from(entry: &CalendarEntry) -> Self {
+        let e = &entry.event;
+        PublicEvent {
+            id: e.id,
+            url: entry.url.clone(),
+            start: e.start,
+            end: e.end,
+            title: e.title.clone(),
+            description: e.description.clone(),
+            location: e.location.clone(),
+            color: e.color.clone(),
+        }
+    }
+}
+
+/// Everything published, written to `data.json`
+#[derive(Debug, Serialize)]
+pub struct SiteData {
+    pub generated_at: NaiveDateTime,
+    pub projects: Vec<PublicProject>,
+    pub news: Vec<NewsStory>,
+    pub events: Vec<PublicEvent>,
+}
+
+/// Render the public site into `dir`
+///
+/// Only approved projects that aren't archived are published. Existing
+/// files in `dir` are overwritten but nothing is deleted.
+pub fn export(rocket: &Rocket, dir: &Path) -> io::Result<()> {
+    // Make sure the database is up to date first
+    DatabaseCreate.on_launch(rocket);
+
+    let conn_url = rocket
+        .config()
+        .get_table("databases")
+        .unwrap()
+        .get("sqlite_observ")
+        .unwrap()
+        .get("url")
+        .unwrap()
+        .as_str()
+        .unwrap();
+    let conn =
+        SqliteConnection::establish(conn_url).expect("Failed to connect to database in export");
+
+    let listings = search_projects(&conn, &Default::default());
+    let stories: Vec<NewsStory> = {
+        use crate::schema::news::dsl::*;
+        news.order(happened_at.desc())
+            .load(&conn)
+            .expect("Failed to get news from database")
+    };
+    // Repeating events are expanded like on the calendar. Times without an
+    // event of their own yet can't be opened with `?on=` on a static site
+    // so they get a page for their day.
+    let rooms = all_rooms(&conn);
+    let events: Vec<CalendarEntry> = all_entries(&conn)
+        .into_iter()
+        .map(|mut entry| {
+            let e = &mut entry.event;
+            entry.url = match (e.series_id, e.occurrence) {
+                (Some(sid), Some(day)) if sid == e.id => format!("/calendar/{}/{}", sid, day),
+                _ => format!("/calendar/{}", e.id),
+            };
+            // Room pages aren't exported so events name their room instead
+            if let Some(r) = e.room_id.and_then(|rid| rooms.iter().find(|r| r.id == rid)) {
+                e.location = Some(r.full_name());
+            }
+            entry
+        })
+        .collect();
+
+    write_page(
+        dir,
+        "",
+        ExportIndexTemplate {
+            projects: listings.clone(),
+            stories: stories.iter().take(5).cloned().collect(),
+        },
+    )?;
+    write_page(
+        dir,
+        "projects",
+        ExportProjectsTemplate {
+            projects: listings.clone(),
+        },
+    )?;
+
+    let mut projects = Vec::new();
+    for listing in listings.iter() {
+        let p = &listing.project;
+        let members = project_member_entries(&conn, p);
+        projects.push(PublicProject {
+            id: p.id,
+            name: p.name.clone(),
+            description: p.description.clone(),
+            homepage: p.homepage.clone(),
+            active: p.active,
+            extrn: p.extrn,
+            repos: project_repos(p),
+            tags: listing.tags.clone(),
+            languages: listing.languages.clone(),
+            logo: p.logo.clone(),
+            members: members
+                .iter()
+                .map(|m| PublicMember {
+                    real_name: m.user.real_name.clone(),
+                    handle: m.user.handle.clone(),
+                    role: m.role.to_string(),
+                })
+                .collect(),
+        });
+        write_page(
+            dir,
+            &format!("projects/{}", p.id),
+            ExportProjectTemplate {
+                project: p.clone(),
+                syncs: project_syncs(&conn, p),
+                tags: listing.tags.clone(),
+                languages: listing.languages.clone(),
+                members,
+            },
+        )?;
+    }
+
+    write_page(
+        dir,
+        "news",
+        ExportNewsTemplate {
+            logged_in: None,
+            stories: stories.clone(),
+        },
+    )?;
+    for story in stories.iter() {
+        write_page(
+            dir,
+            &format!("news/{}", story.id),
+            ExportNewsStoryTemplate {
+                logged_in: None,
+                story: story.clone(),
+            },
+        )?;
+    }
+
+    write_page(
+        dir,
+        "calendar",
+        ExportCalendarTemplate {
+            logged_in: None,
+            events: events.clone(),
+            feed_token: None,
+        },
+    )?;
+    for entry in events.iter() {
+        let rsvps: Vec<EventRsvp> = EventRsvp::belonging_to(&entry.event)
+            .load(&conn)
+            .expect("Failed to get RSVPs from database");
+        write_page(
+            dir,
+            &entry.url[1..],
+            ExportEventTemplate {
+                logged_in: None,
+                event: entry.event.clone(),
+                room: None,
+                going: false,
+                rsvp_count: rsvps.len(),
+                series: None,
+                upcoming: Vec::new(),
+            },
+        )?;
+    }
+
+    let public_events: Vec<PublicEvent> = events.iter().map(PublicEvent::from).collect();
+    // The calendar page loads its events from here
+    fs::write(
+        dir.join("calendar.json"),
+        serde_json::to_string(&public_events).unwrap(),
+    )?;
+
+    copy_static(dir)?;
+    if let Some(store) = rocket.state::<UploadStore>() {
+        copy_uploads(dir, store, &listings, &stories)?;
+    }
+
+    let data = SiteData {
+        generated_at: chrono::offset::Local::now().naive_local(),
+        projects,
+        news: stories,
+        events: public_events,
+    };
+    fs::write(
+        dir.join("data.json"),
+        serde_json::to_string_pretty(&data).unwrap(),
+    )?;
+
+    Ok(())
+}
+
+//# Helper Functions
+
+/// Render a page to `<path>/index.html` so it is served at `/<path>`
+fn write_page<T: Template>(dir: &Path, path: &str, page: T) -> io::Result<()> {
+    let folder = dir.join(path);
+    fs::create_dir_all(&folder)?;
+    let html = page
+        .render()
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+    fs::write(folder.join("index.html"), html)
+}
+
+/// Copy the embedded static files to `static/`
+fn copy_static(dir: &Path) -> io::Result<()> {
+    for file in Embed::iter() {
+        if BLACKLIST.contains(&&*file) {
+            continue;
+        }
+        let path = dir.join("static").join(&*file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, Embed::get(&file).unwrap())?;
+    }
+    Ok(())
+}
+
+/// Copy the uploads used by published pages to `uploads/`
+fn copy_uploads(
+    dir: &Path,
+    store: &UploadStore,
+    listings: &[ProjectListing],
+    stories: &[NewsStory],
+) -> io::Result<()> {
+    let folder = dir.join("uploads");
+    fs::create_dir_all(&folder)?;
+
+    let keys = listings
+        .iter()
+        .filter_map(|l| l.project.logo.as_ref())
+        .chain(stories.iter().filter_map(|s| s.image.as_ref()));
+    for key in keys {
+        for name in [full_name(key), thumb_name(key)].iter() {
+            match store.0.get(name) {
+                Ok(bytes) => fs::write(folder.join(name), bytes)?,
+                Err(e) => eprintln!("\tFailed to read upload {}: {}", name, e),
+            }
+        }
+    }
+    Ok(())
+}
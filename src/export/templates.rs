@@ -0,0 +1,75 @@
+//! HTML templates for the static export
+//!
+//! These reuse the regular page templates where they have nothing that
+//! needs a login, only swapping the header and dropping the tools. Pages
+//! with forms or member links have their own trimmed down templates.
+
+use crate::calendar::series::CalendarEntry;
+use crate::models::{Event, NewsStory, Project, RepoSync, Room};
+use crate::projects::templates::{MemberEntry, ProjectListing};
+#[allow(unused_imports)]
+use crate::templates::{filters, OptUser};
+
+#[derive(Template)]
+#[template(path = "export/index.html")]
+pub struct ExportIndexTemplate {
+    pub projects: Vec<ProjectListing>,
+    pub stories: Vec<NewsStory>,
+}
+
+#[derive(Template)]
+#[template(path = "export/projects.html")]
+pub struct ExportProjectsTemplate {
+    pub projects: Vec<ProjectListing>,
+}
+
+#[derive(Template)]
+#[template(path = "export/project.html")]
+pub struct ExportProjectTemplate {
+    pub project: Project,
+    pub syncs: Vec<RepoSync>,
+    pub tags: Vec<String>,
+    pub languages: Vec<String>,
+    pub members: Vec<MemberEntry>,
+}
+
+/// Always logged out, as are the rest that extend regular templates
+#[derive(Template)]
+#[template(path = "export/news.html")]
+pub struct ExportNewsTemplate {
+    pub logged_in: OptUser,
+    pub stories: Vec<NewsStory>,
+}
+
+#[derive(Template)]
+#[template(path = "export/newsstory.html")]
+pub struct ExportNewsStoryTemplate {
+    pub logged_in: OptUser,
+    pub story: NewsStory,
+}
+
+#[derive(Template)]
+#[template(path = "export/calendar.html")]
+pub struct ExportCalendarTemplate {
+    pub logged_in: OptUser,
+    pub events: Vec<CalendarEntry>,
+    /// Always `None`, private feeds need a login
+    pub feed_token: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "export/event.html")]
+pub struct ExportEventTemplate {
+    pub logged_in: OptUser,
+    pub event: Event,
+    /// Always `None` since room pages aren't exported, the event's
+    /// location names its room instead
+    pub room: Option<Room>,
+    /// Always `false`, no one is logged in
+    pub going: bool,
+    pub rsvp_count: usize,
+    /// Always `None` and empty, repeating events are exported as their
+    /// times
+    pub series: Option<Event>,
+    pub upcoming: Vec<CalendarEntry>,
+}
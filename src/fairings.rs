@@ -124,6 +124,242 @@ impl Fairing for AdminCheck {
     }
 }
 
+/// Sync project repositories in the background
+///
+/// Spawns a worker thread at launch that periodically syncs the repositories
+/// of every active project into the local cache.
+/// The interval in seconds can be set with `sync_interval` in `Rocket.toml`
+/// and defaults to one hour.
+pub struct SyncRepos;
+
+impl Fairing for SyncRepos {
+    fn info(&self) -> Info {
+        Info {
+            name: "Background Repository Sync",
+            kind: Kind::Launch,
+        }
+    }
+
+    fn on_launch(&self, rocket: &Rocket) {
+        // Get the database url from the config
+        let conn_url = String::from(
+            rocket
+                .config()
+                .get_table("databases")
+                .unwrap()
+                .get("sqlite_observ")
+                .unwrap()
+                .get("url")
+                .unwrap()
+                .as_str()
+                .unwrap(),
+        );
+
+        let interval = rocket.config().get_int("sync_interval").unwrap_or(3600) as u64;
+
+        use crate::repos::github::GitHub;
+        use crate::repos::sync::sync_all;
+        use diesel::prelude::*;
+        use std::panic::{self, AssertUnwindSafe};
+        use std::thread;
+        use std::time::Duration;
+
+        thread::spawn(move || {
+            let gh = GitHub::new();
+            loop {
+                match SqliteConnection::establish(&conn_url) {
+                    // Don't let a panic in one pass take down the worker
+                    Ok(conn) => {
+                        if panic::catch_unwind(AssertUnwindSafe(|| sync_all(&conn, &gh))).is_err() {
+                            eprintln!("\tRepository sync failed, retrying next interval");
+                        }
+                    }
+                    Err(e) => eprintln!("\tFailed to connect to database in SyncRepos: {}", e),
+                }
+                thread::sleep(Duration::from_secs(interval));
+            }
+        });
+    }
+}
+
+/// Check project repository URLs at launch
+///
+/// Normalizes any repository URLs the migrations couldn't, see `RepoUrl`,
+/// and reports the ones that can't be parsed or are listed on more than one
+/// project so that a coordinator can fix them by hand.
+pub struct RepoCheck;
+
+impl Fairing for RepoCheck {
+    fn info(&self) -> Info {
+        Info {
+            name: "Repository URL Check",
+            kind: Kind::Launch,
+        }
+    }
+
+    fn on_launch(&self, rocket: &Rocket) {
+        // Get the database url from the config
+        let conn_url = rocket
+            .config()
+            .get_table("databases")
+            .unwrap()
+            .get("sqlite_observ")
+            .unwrap()
+            .get("url")
+            .unwrap()
+            .as_str()
+            .unwrap();
+
+        use crate::repos::url::RepoUrl;
+        use crate::schema::projects::dsl::*;
+        use diesel::prelude::*;
+        use diesel::update;
+
+        let conn = SqliteConnection::establish(conn_url)
+            .expect("Failed to connect to database in RepoCheck");
+
+        let all: Vec<(i32, String)> = projects
+            .select((id, repos))
+            .load(&conn)
+            .expect("Failed to get projects from database");
+
+        let mut seen: Vec<(String, i32)> = Vec::new();
+        for (pid, list) in all {
+            let urls: Vec<String> = match serde_json::from_str(&list) {
+                Ok(u) => u,
+                Err(_) => {
+                    eprintln!("\tProject {} has an unparseable repository list", pid);
+                    continue;
+                }
+            };
+
+            let mut fixed = Vec::new();
+            for u in urls.iter() {
+                match u.parse::<RepoUrl>() {
+                    Ok(r) => fixed.push(r.to_string()),
+                    Err(e) => {
+                        eprintln!("\tProject {} has an invalid repository {}: {}", pid, u, e);
+                        fixed.push(u.clone());
+                    }
+                }
+            }
+
+            for u in fixed.iter() {
+                if let Some((_, other)) = seen.iter().find(|(s, o)| s == u && *o != pid) {
+                    eprintln!(
+                        "\tRepository {} is listed on projects {} and {}",
+                        u, other, pid
+                    );
+                }
+                seen.push((u.clone(), pid));
+            }
+
+            if fixed != urls {
+                update(projects.find(pid))
+                    .set(repos.eq(serde_json::to_string(&fixed).unwrap()))
+                    .execute(&conn)
+                    .expect("Failed to update project in database");
+            }
+        }
+    }
+}
+
+/// Generate meetings from group schedules and the times of repeating
+/// events
+///
+/// At attach this reads how many days ahead to generate meetings from
+/// `schedule_ahead` in `Rocket.toml`, then at launch starts a background
+/// thread that generates them once a day.
+pub struct MeetingSchedules;
+
+impl Fairing for MeetingSchedules {
+    fn info(&self) -> Info {
+        Info {
+            name: "Scheduled Meetings",
+            kind: Kind::Attach | Kind::Launch,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> std::result::Result<Rocket, Rocket> {
+        use crate::groups::schedule::{ScheduleAhead, DEFAULT_AHEAD};
+
+        let ahead = rocket
+            .config()
+            .get_int("schedule_ahead")
+            .unwrap_or(DEFAULT_AHEAD);
+        Ok(rocket.manage(ScheduleAhead(ahead)))
+    }
+
+    fn on_launch(&self, rocket: &Rocket) {
+        // Get the database url from the config
+        let conn_url = String::from(
+            rocket
+                .config()
+                .get_table("databases")
+                .unwrap()
+                .get("sqlite_observ")
+                .unwrap()
+                .get("url")
+                .unwrap()
+                .as_str()
+                .unwrap(),
+        );
+
+        use crate::calendar::series;
+        use crate::groups::schedule::{generate_all, ScheduleAhead};
+        use diesel::prelude::*;
+        use std::panic::{self, AssertUnwindSafe};
+        use std::thread;
+        use std::time::Duration;
+
+        let ahead = rocket.state::<ScheduleAhead>().map(|a| a.0).unwrap_or(0);
+        thread::spawn(move || loop {
+            match SqliteConnection::establish(&conn_url) {
+                // Don't let a panic in one pass take down the worker
+                Ok(conn) => {
+                    let generate = || {
+                        generate_all(&conn, ahead);
+                        series::generate_all(&conn, ahead);
+                    };
+                    if panic::catch_unwind(AssertUnwindSafe(generate)).is_err() {
+                        eprintln!("\tGenerating scheduled meetings failed, retrying tomorrow");
+                    }
+                }
+                Err(e) => eprintln!("\tFailed to connect to database in MeetingSchedules: {}", e),
+            }
+            thread::sleep(Duration::from_secs(24 * 60 * 60));
+        });
+    }
+}
+
+/// Set up storage for uploaded images at attach
+///
+/// Uploads are kept in the directory set with `upload_dir` in `Rocket.toml`,
+/// which defaults to `uploads` next to the binary.
+pub struct Uploads;
+
+impl Fairing for Uploads {
+    fn info(&self) -> Info {
+        Info {
+            name: "Upload Storage",
+            kind: Kind::Attach,
+        }
+    }
+
+    fn on_attach(&self, rocket: Rocket) -> std::result::Result<Rocket, Rocket> {
+        use crate::uploads::storage::{LocalStorage, UploadStore};
+
+        let dir = String::from(rocket.config().get_str("upload_dir").unwrap_or("uploads"));
+        match LocalStorage::new(&dir) {
+            Ok(s) => Ok(rocket.manage(UploadStore(Box::new(s)))),
+            Err(e) => {
+                eprintln!("\tFailed to create upload directory {}: {}", dir, e);
+                Err(rocket)
+            }
+        }
+    }
+}
+
 /// Check for the config file at attach
 ///
 /// If there is no config file specified Rocket is going to fallback to defaults.
@@ -0,0 +1,371 @@
+//! HTTP handlers for final grades
+
+use chrono::Local;
+use diesel::prelude::*;
+use diesel::{insert_into, update};
+use rocket::http::Status;
+use rocket::request::Form;
+use rocket::response::Redirect;
+
+use crate::guards::*;
+use crate::models::{GradeSummary, User};
+use crate::semester::Semester;
+use crate::templates::FormError;
+use crate::users::handlers::grade_summary;
+use crate::ObservDbConn;
+
+use super::models::*;
+use super::templates::*;
+
+/// GET handler for `/grades`
+///
+/// The coordinator review queue. Unsubmitted grades come first, sorted by
+/// how close they are to the pass/fail boundary since those need the most
+/// attention.
+#[get("/grades?<semester>")]
+pub fn grades(conn: ObservDbConn, l: AdminGuard, semester: Option<Semester>) -> GradesTemplate {
+    let sem = semester.unwrap_or_default();
+
+    let students: Vec<User> = {
+        use crate::schema::users::dsl::*;
+        users
+            .filter(tier.eq(0).and(former.eq(false)))
+            .load(&*conn)
+            .expect("Failed to get users from database")
+    };
+
+    let mut rows: Vec<GradeRow> = students
+        .into_iter()
+        .map(|s| GradeRow {
+            grade: current_grade(&*conn, &s, &sem),
+            student: s,
+        })
+        .collect();
+    rows.sort_by_key(|r| (r.grade.submitted, r.grade.distance()));
+
+    GradesTemplate {
+        logged_in: Some(l.0),
+        semester: sem,
+        threshold: PASS_THRESHOLD,
+        rows,
+    }
+}
+
+/// GET handler for `/grades/<uid>`
+///
+/// A student's grade along with everything it was computed from and the
+/// history of changes to it.
+#[get("/grades/<uid>?<semester>&<e>")]
+pub fn grade(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    uid: i32,
+    semester: Option<Semester>,
+    e: Option<FormError>,
+) -> Option<GradeTemplate> {
+    let sem = semester.unwrap_or_default();
+    let student: User = {
+        use crate::schema::users::dsl::*;
+        users
+            .find(uid)
+            .first(&*conn)
+            .optional()
+            .expect("Failed to get user from database")?
+    };
+
+    let g = current_grade(&*conn, &student, &sem);
+    let audit = {
+        use crate::schema::grade_audit::dsl::*;
+        GradeAudit::belonging_to(&g)
+            .order(created_at.desc())
+            .load::<GradeAudit>(&*conn)
+            .expect("Failed to get grade audit log from database")
+            .into_iter()
+            .map(|a| AuditEntry {
+                by: get_user(&*conn, a.user_id),
+                audit: a,
+            })
+            .collect()
+    };
+
+    Some(GradeTemplate {
+        can_edit: !g.submitted,
+        logged_in: Some(l.0),
+        summary: grade_summary(&*conn, &student, &sem),
+        student,
+        grade: g,
+        audit,
+        threshold: PASS_THRESHOLD,
+        error: e,
+    })
+}
+
+/// POST handler for `/grades/<uid>/override`
+///
+/// Sets or clears the override. A reason is required to set one.
+///
+/// Once a grade is submitted it has to be reopened before it can be
+/// changed.
+#[post("/grades/<uid>/override", data = "<form>")]
+pub fn grade_override_post(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    uid: i32,
+    form: Form<OverrideForm>,
+) -> Result<Redirect, Status> {
+    let form = form.into_inner();
+    let student = get_user(&*conn, uid);
+    let g = refresh_grade(&*conn, &student, &form.semester);
+
+    if g.submitted {
+        return Err(Status::Unauthorized);
+    }
+
+    let why = form.reason.trim().to_string();
+    if let Some(v) = form.value {
+        if v < 0 || v > 100 || why.is_empty() {
+            return Ok(Redirect::to(format!(
+                "{}&e={}",
+                grade_url(uid, &form.semester),
+                FormError::Other
+            )));
+        }
+    }
+
+    {
+        use crate::schema::grades::dsl::*;
+        update(grades.find(g.id))
+            .set((
+                override_value.eq(form.value),
+                reason.eq(if form.value.is_some() {
+                    Some(why.clone())
+                } else {
+                    None
+                }),
+                overridden_by.eq(form.value.map(|_| l.0.id)),
+                overridden_at.eq(form.value.map(|_| Local::now().naive_local())),
+            ))
+            .execute(&*conn)
+            .expect("Failed to update grade in database");
+    }
+
+    log_change(
+        &*conn,
+        &g,
+        &l.0,
+        if form.value.is_some() {
+            "override"
+        } else {
+            "clear override"
+        },
+        Some(form.value.unwrap_or(g.computed)),
+        if why.is_empty() { None } else { Some(why) },
+    );
+
+    Ok(Redirect::to(grade_url(uid, &form.semester)))
+}
+
+/// POST handler for `/grades/<uid>/submit`
+///
+/// Signs off on the grade and locks it.
+#[post("/grades/<uid>/submit", data = "<form>")]
+pub fn grade_submit_post(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    uid: i32,
+    form: Form<SemesterForm>,
+) -> Redirect {
+    let sem = form.into_inner().semester;
+    let g = refresh_grade(&*conn, &get_user(&*conn, uid), &sem);
+
+    if !g.submitted {
+        use crate::schema::grades::dsl::*;
+        update(grades.find(g.id))
+            .set((
+                submitted.eq(true),
+                submitted_by.eq(Some(l.0.id)),
+                submitted_at.eq(Some(Local::now().naive_local())),
+            ))
+            .execute(&*conn)
+            .expect("Failed to update grade in database");
+        log_change(&*conn, &g, &l.0, "submit", Some(g.value()), None);
+    }
+
+    Redirect::to(grade_url(uid, &sem))
+}
+
+/// POST handler for `/grades/<uid>/reopen`
+///
+/// Unlocks a submitted grade so that it is computed again.
+#[post("/grades/<uid>/reopen", data = "<form>")]
+pub fn grade_reopen_post(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    uid: i32,
+    form: Form<SemesterForm>,
+) -> Redirect {
+    let sem = form.into_inner().semester;
+    let g = refresh_grade(&*conn, &get_user(&*conn, uid), &sem);
+
+    if g.submitted {
+        use crate::schema::grades::dsl::*;
+        update(grades.find(g.id))
+            .set((
+                submitted.eq(false),
+                submitted_by.eq(None::<i32>),
+                submitted_at.eq(None::<chrono::NaiveDateTime>),
+            ))
+            .execute(&*conn)
+            .expect("Failed to update grade in database");
+        log_change(&*conn, &g, &l.0, "reopen", Some(g.value()), None);
+    }
+
+    Redirect::to(grade_url(uid, &sem))
+}
+
+//# Helper Functions
+
+fn get_user(conn: &SqliteConnection, uid: i32) -> User {
+    use crate::schema::users::dsl::*;
+    users
+        .find(uid)
+        .first(conn)
+        .expect("Failed to get user from database")
+}
+
+fn grade_url(uid: i32, sem: &Semester) -> String {
+    format!("/grades/{}?semester={}", uid, sem.id())
+}
+
+fn log_change(
+    conn: &SqliteConnection,
+    g: &Grade,
+    by: &User,
+    what: &str,
+    new: Option<i32>,
+    why: Option<String>,
+) {
+    use crate::schema::grade_audit::dsl::*;
+    insert_into(grade_audit)
+        .values(&NewGradeAudit {
+            grade_id: g.id,
+            user_id: by.id,
+            action: what.to_string(),
+            old_value: Some(g.value()),
+            new_value: new,
+            reason: why,
+        })
+        .execute(conn)
+        .expect("Failed to insert grade audit log into database");
+}
+
+/// Compute a grade out of 100 from a grade summary
+///
+/// The grade is the average of the fraction of meetings attended, the
+/// fraction of weeks with a status update, and the evaluation score.
+/// Parts with nothing to measure yet are left out.
+pub fn computed_grade(summary: &GradeSummary) -> i32 {
+    let mut parts = Vec::new();
+    if summary.needed_attendances > 0 {
+        parts.push(ratio(summary.attendances.len(), summary.needed_attendances));
+    }
+    if summary.updates_needed > 0 {
+        parts.push(ratio(summary.updates_posted, summary.updates_needed));
+    }
+    if let Some(s) = summary.evaluation_score {
+        parts.push(f64::from(s.min(100)) / 100.0);
+    }
+
+    if parts.is_empty() {
+        100
+    } else {
+        (parts.iter().sum::<f64>() / parts.len() as f64 * 100.0).round() as i32
+    }
+}
+
+fn ratio(have: usize, need: usize) -> f64 {
+    (have as f64 / need as f64).min(1.0)
+}
+
+/// Get a student's grade for a semester with an up to date computed value
+///
+/// This doesn't write anything, a grade that hasn't been saved yet has an
+/// ID of 0. Submitted grades are locked so they are returned as they are.
+pub fn current_grade(conn: &SqliteConnection, user: &User, sem: &Semester) -> Grade {
+    use crate::schema::grades::dsl::*;
+
+    let existing: Option<Grade> = grades
+        .filter(user_id.eq(user.id).and(semester.eq(sem.id())))
+        .first(conn)
+        .optional()
+        .expect("Failed to get grade from database");
+
+    match existing {
+        Some(ref g) if g.submitted => return g.clone(),
+        _ => (),
+    }
+
+    let value = computed_grade(&grade_summary(conn, user, sem));
+    match existing {
+        Some(g) => Grade {
+            computed: value,
+            ..g
+        },
+        None => Grade {
+            id: 0,
+            user_id: user.id,
+            semester: sem.id(),
+            computed: value,
+            override_value: None,
+            reason: None,
+            overridden_by: None,
+            overridden_at: None,
+            submitted: false,
+            submitted_by: None,
+            submitted_at: None,
+        },
+    }
+}
+
+/// Get a student's grade for a semester, saving the computed value
+///
+/// Used before changing a grade so the change applies to a saved row.
+pub fn refresh_grade(conn: &SqliteConnection, user: &User, sem: &Semester) -> Grade {
+    use crate::schema::grades::dsl::*;
+
+    let g = current_grade(conn, user, sem);
+    if g.id == 0 {
+        insert_into(grades)
+            .values(&NewGrade {
+                user_id: user.id,
+                semester: sem.id(),
+                computed: g.computed,
+            })
+            .execute(conn)
+            .expect("Failed to insert grade into database");
+        grades
+            .filter(user_id.eq(user.id).and(semester.eq(sem.id())))
+            .first(conn)
+            .expect("Failed to get grade from database")
+    } else {
+        if !g.submitted {
+            update(grades.find(g.id))
+                .set(computed.eq(g.computed))
+                .execute(conn)
+                .expect("Failed to update grade in database");
+        }
+        g
+    }
+}
+
+/// A student's submitted grade for a semester, if there is one
+pub fn submitted_grade(conn: &SqliteConnection, user: &User, sem: &Semester) -> Option<i32> {
+    use crate::schema::grades::dsl::*;
+    grades
+        .filter(user_id.eq(user.id).and(semester.eq(sem.id())))
+        .filter(submitted.eq(true))
+        .first::<Grade>(conn)
+        .optional()
+        .expect("Failed to get grade from database")
+        .map(|g| g.value())
+}
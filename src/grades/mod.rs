@@ -0,0 +1,18 @@
+//! Final grades
+//!
+//! A grade is computed for each student from their grade summary, then
+//! coordinators review it, override it if needed, and submit it. Submitted
+//! grades are locked and only admins can change them. Every change is
+//! kept in an audit log.
+//!
+//! ## Routes
+//! - `/grades`
+//! - `/grades/<uid>`
+//! - `/grades/<uid>/override`
+//! - `/grades/<uid>/submit`
+//! - `/grades/<uid>/reopen`
+
+pub mod handlers;
+pub mod models;
+
+mod templates;
@@ -0,0 +1,115 @@
+//! Models for final grades
+//!
+//! There is one row in `grades` per student per semester, saved the first
+//! time a coordinator acts on the grade. Pages compute the grade as they
+//! show it; the saved value is refreshed on each change until the grade
+//! is submitted, after which the row is locked. Every change is recorded in `grade_audit`.
+
+use chrono::NaiveDateTime;
+
+use crate::models::User;
+use crate::schema::*;
+use crate::semester::Semester;
+
+/// A student's grade for a semester
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(User)]
+pub struct Grade {
+    /// ID of the grade
+    pub id: i32,
+    /// ID of the student
+    pub user_id: i32,
+    /// Semester of the grade, like `2019-fall`
+    pub semester: String,
+    /// Grade computed from the grade summary
+    pub computed: i32,
+    /// Grade set by a coordinator which replaces the computed one
+    pub override_value: Option<i32>,
+    /// Why the grade was overridden
+    pub reason: Option<String>,
+    /// ID of the coordinator who overrode the grade
+    pub overridden_by: Option<i32>,
+    pub overridden_at: Option<NaiveDateTime>,
+    /// Has the grade been signed off on?
+    pub submitted: bool,
+    /// ID of the coordinator who submitted the grade
+    pub submitted_by: Option<i32>,
+    pub submitted_at: Option<NaiveDateTime>,
+}
+
+impl Grade {
+    /// The grade that counts, the override if there is one
+    pub fn value(&self) -> i32 {
+        self.override_value.unwrap_or(self.computed)
+    }
+
+    /// Is the grade passing?
+    pub fn passing(&self) -> bool {
+        self.value() >= PASS_THRESHOLD
+    }
+
+    /// How far the grade is from the pass/fail boundary
+    pub fn distance(&self) -> i32 {
+        (self.value() - PASS_THRESHOLD).abs()
+    }
+}
+
+/// The lowest passing grade
+pub const PASS_THRESHOLD: i32 = 70;
+
+/// Used to start tracking a grade
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "grades"]
+pub struct NewGrade {
+    pub user_id: i32,
+    pub semester: String,
+    pub computed: i32,
+}
+
+/// A single change to a grade
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Grade)]
+#[table_name = "grade_audit"]
+pub struct GradeAudit {
+    pub id: i32,
+    /// ID of the grade that changed
+    pub grade_id: i32,
+    /// ID of the user who made the change
+    pub user_id: i32,
+    /// What was done, such as `override` or `submit`
+    pub action: String,
+    /// The final grade before the change
+    pub old_value: Option<i32>,
+    /// The final grade after the change
+    pub new_value: Option<i32>,
+    pub reason: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Used to record a change to a grade
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "grade_audit"]
+pub struct NewGradeAudit {
+    pub grade_id: i32,
+    pub user_id: i32,
+    pub action: String,
+    pub old_value: Option<i32>,
+    pub new_value: Option<i32>,
+    pub reason: Option<String>,
+}
+
+/// The grade override form
+///
+/// Leaving the value empty clears the override.
+#[derive(Debug, Clone, FromForm)]
+pub struct OverrideForm {
+    pub semester: Semester,
+    pub value: Option<i32>,
+    pub reason: String,
+}
+
+/// Form used to submit or reopen a grade
+#[derive(Debug, Clone, FromForm)]
+pub struct SemesterForm {
+    pub semester: Semester,
+}
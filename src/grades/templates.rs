@@ -0,0 +1,47 @@
+use super::models::*;
+#[allow(unused_imports)]
+use crate::templates::{filters, FormError, OptUser};
+
+use crate::models::{GradeSummary, User};
+use crate::semester::Semester;
+
+/// A student and their grade
+pub struct GradeRow {
+    pub student: User,
+    pub grade: Grade,
+}
+
+/// A change to a grade along with who made it
+pub struct AuditEntry {
+    pub audit: GradeAudit,
+    pub by: User,
+}
+
+/// The grade review queue
+///
+/// HTML File: `grades/grades.html`
+#[derive(Template)]
+#[template(path = "grades/grades.html")]
+pub struct GradesTemplate {
+    pub logged_in: OptUser,
+    pub semester: Semester,
+    pub threshold: i32,
+    pub rows: Vec<GradeRow>,
+}
+
+/// A single student's grade
+///
+/// HTML File: `grades/grade.html`
+#[derive(Template)]
+#[template(path = "grades/grade.html")]
+pub struct GradeTemplate {
+    pub logged_in: OptUser,
+    pub student: User,
+    pub grade: Grade,
+    pub summary: GradeSummary,
+    pub audit: Vec<AuditEntry>,
+    pub threshold: i32,
+    /// Can the grade be changed without reopening it?
+    pub can_edit: bool,
+    pub error: Option<FormError>,
+}
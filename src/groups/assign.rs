@@ -0,0 +1,174 @@
+//! Balancing students across small groups
+//!
+//! Students are put into teams, one per project so that project teams
+//! stay together, and everyone without a project is a team of one. The
+//! biggest teams are placed first, each into the group with the fewest
+//! students that still has room. Groups whose mentor asked for a project
+//! get its team if they have room, and ties go to the group most of the
+//! team is already in so that as few students as possible move.
+
+use std::collections::HashMap;
+
+/// A group students can be assigned to
+#[derive(Debug, PartialEq, Clone)]
+pub struct Slot {
+    pub group_id: i32,
+    /// Most students the group can take
+    pub capacity: Option<usize>,
+    /// Projects the group's mentor asked for
+    pub preferred: Vec<i32>,
+}
+
+/// Students that should be in the same group
+#[derive(Debug, PartialEq, Clone)]
+pub struct Team {
+    /// The project the team works on, if any
+    pub project_id: Option<i32>,
+    /// IDs of the students
+    pub members: Vec<i32>,
+}
+
+/// Put students into teams
+///
+/// `projects` is each project's ID and the IDs of its members. Students on
+/// more than one project are on the team of the biggest one. Without
+/// `keep_teams` every student is on their own, but still counts as on
+/// their biggest project for preferences.
+pub fn build_teams(students: &[i32], projects: &[(i32, Vec<i32>)], keep_teams: bool) -> Vec<Team> {
+    let mut projects: Vec<(i32, Vec<i32>)> = projects
+        .iter()
+        .map(|(p, ms)| {
+            let ms: Vec<i32> = ms
+                .iter()
+                .filter(|m| students.contains(m))
+                .cloned()
+                .collect();
+            (*p, ms)
+        })
+        .collect();
+    projects.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+
+    let mut placed: Vec<i32> = Vec::new();
+    let mut teams: Vec<Team> = Vec::new();
+    for (p, ms) in projects {
+        let ms: Vec<i32> = ms.into_iter().filter(|m| !placed.contains(m)).collect();
+        if ms.is_empty() {
+            continue;
+        }
+        placed.extend(ms.iter());
+        if keep_teams {
+            teams.push(Team {
+                project_id: Some(p),
+                members: ms,
+            });
+        } else {
+            teams.extend(ms.into_iter().map(|m| Team {
+                project_id: Some(p),
+                members: vec![m],
+            }));
+        }
+    }
+    teams.extend(
+        students
+            .iter()
+            .filter(|s| !placed.contains(s))
+            .map(|s| Team {
+                project_id: None,
+                members: vec![*s],
+            }),
+    );
+    teams
+}
+
+/// Assign every team to a group
+///
+/// `current` is the groups students are in now as `(user, group)`. Returns
+/// the group of each student as `(user, group)` ordered by user. Groups
+/// only go over capacity if no group has room for a team.
+pub fn assign(
+    slots: &[Slot],
+    teams: &[Team],
+    current: &[(i32, i32)],
+    use_prefs: bool,
+) -> Vec<(i32, i32)> {
+    if slots.is_empty() {
+        return Vec::new();
+    }
+
+    let mut teams: Vec<&Team> = teams.iter().collect();
+    teams.sort_by(|a, b| b.members.len().cmp(&a.members.len()));
+
+    let mut load: HashMap<i32, usize> = slots.iter().map(|s| (s.group_id, 0)).collect();
+    let mut assigned: Vec<(i32, i32)> = Vec::new();
+    for team in teams {
+        let size = team.members.len();
+        let fits: Vec<&Slot> = slots
+            .iter()
+            .filter(|s| {
+                s.capacity
+                    .map(|c| load[&s.group_id] + size <= c)
+                    .unwrap_or(true)
+            })
+            .collect();
+        let mut pool: Vec<&Slot> = if fits.is_empty() {
+            slots.iter().collect()
+        } else {
+            fits
+        };
+        if use_prefs {
+            if let Some(p) = team.project_id {
+                let preferred: Vec<&Slot> = pool
+                    .iter()
+                    .filter(|s| s.preferred.contains(&p))
+                    .cloned()
+                    .collect();
+                if !preferred.is_empty() {
+                    pool = preferred;
+                }
+            }
+        }
+
+        // Fewest students first, then where most of the team already is
+        let already = |g: i32| {
+            current
+                .iter()
+                .filter(|(u, cg)| *cg == g && team.members.contains(u))
+                .count()
+        };
+        let best = pool
+            .iter()
+            .min_by_key(|s| {
+                (
+                    load[&s.group_id],
+                    std::cmp::Reverse(already(s.group_id)),
+                    s.group_id,
+                )
+            })
+            .unwrap()
+            .group_id;
+
+        *load.get_mut(&best).unwrap() += size;
+        assigned.extend(team.members.iter().map(|m| (*m, best)));
+    }
+    assigned.sort();
+    assigned
+}
+
+/// The changes needed to go from the current groups to the assignment
+///
+/// Returns the `(user, group)` relations to add and to remove. Only the
+/// assigned students are moved, everyone else such as the mentors stays
+/// where they are.
+pub fn diff(current: &[(i32, i32)], assigned: &[(i32, i32)]) -> (Vec<(i32, i32)>, Vec<(i32, i32)>) {
+    let adds = assigned
+        .iter()
+        .filter(|a| !current.contains(a))
+        .cloned()
+        .collect();
+    let removes = current
+        .iter()
+        .filter(|(u, g)| assigned.iter().any(|(au, ag)| au == u && ag != g))
+        .cloned()
+        .collect();
+    (adds, removes)
+}
@@ -1,15 +1,29 @@
+use chrono::{Local, NaiveDateTime};
+use diesel::dsl::exists;
 use diesel::prelude::*;
-use diesel::{delete, insert_into, update};
-use rocket::http::Status;
+use diesel::{delete, insert_into, replace_into, select, update};
+use rocket::http::{ContentType, Status};
 use rocket::request::Form;
+use rocket::response::content::Content;
 use rocket::response::Redirect;
+use rocket::State;
 use rocket_contrib::json::Json;
 
 use crate::attend::code::attendance_code;
+use crate::calendar::feeds::group_entries;
+use crate::calendar::ics::write_calendar;
 use crate::guards::*;
+use crate::models::{Project, ProjectStatus, RelationProjectUser};
+use crate::rooms::bookings::{conflicts, schedule_conflicts, Booked, MEETING_MINUTES};
+use crate::rooms::handlers::{get_room, room_options};
+use crate::semester::Semester;
+use crate::templates::FormError;
+use crate::updates::handlers::missing_updates;
 use crate::ObservDbConn;
 
+use super::assign::*;
 use super::models::*;
+use super::schedule::*;
 use super::templates::*;
 
 #[get("/groups/<gid>")]
@@ -22,37 +36,134 @@ pub fn group(conn: ObservDbConn, l: UserGuard, gid: i32) -> Option<GroupTemplate
         .optional()
         .expect("Failed to get groups from database")?;
 
-    let m: Vec<Meeting> = Meeting::belonging_to(&g)
+    let m: Vec<Meeting> = {
+        use crate::schema::meetings::dsl::*;
+        Meeting::belonging_to(&g)
+            .order(happened_at.asc())
+            .load(&*conn)
+            .expect("Failed to get meetings from database")
+    };
+    let sched: Vec<ScheduleRow> = GroupSchedule::belonging_to(&g)
+        .load::<GroupSchedule>(&*conn)
+        .expect("Failed to get schedules from database")
+        .into_iter()
+        .map(|s| ScheduleRow {
+            room: s.room_id.and_then(|rid| get_room(&*conn, rid)),
+            schedule: s,
+        })
+        .collect();
+    let preferred: Vec<Project> = {
+        use crate::schema::projects::dsl::{name, projects};
+        GroupProjectPref::belonging_to(&g)
+            .inner_join(projects)
+            .order(name.asc())
+            .load::<(GroupProjectPref, Project)>(&*conn)
+            .expect("Failed to get preferences from database")
+            .into_iter()
+            .map(|(_, p)| p)
+            .collect()
+    };
+    let can_manage = can_manage_group(&*conn, &g, &l.0);
+    let role = group_role(&*conn, &g, &l.0);
+    let options: Vec<Project> = if can_manage {
+        use crate::schema::projects::dsl::*;
+        projects
+            .filter(active.eq(true).and(archived.eq(false)))
+            .order(name.asc())
+            .load::<Project>(&*conn)
+            .expect("Failed to get projects from database")
+            .into_iter()
+            .filter(|p| !preferred.contains(p))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let us = group_users(&*conn, &g);
+    let missing = us
+        .iter()
+        .filter(|u| !missing_updates(&*conn, u).is_empty())
+        .cloned()
+        .collect();
+
+    let all: Vec<Group> = groups
         .load(&*conn)
-        .expect("Failed to get project's repos from database");
+        .expect("Failed to get groups from database");
+    let parent = g
+        .parent_id
+        .and_then(|pid| all.iter().find(|p| p.id == pid).cloned());
+    let children: Vec<GroupRollup> = all
+        .iter()
+        .filter(|c| c.parent_id == Some(g.id) && c.id != g.id)
+        .filter(|c| g.archived || !c.archived)
+        .map(|c| group_rollup(&*conn, c, &all))
+        .collect();
+    let rollup = if l.0.tier > 1 && !children.is_empty() {
+        Some(group_rollup(&*conn, &g, &all))
+    } else {
+        None
+    };
+
+    let mentors = mentor_rows(&*conn, &g);
+    let can_change_mentors = !g.archived && (l.0.tier > 1 || l.0.id == g.owner_id);
+    let mentor_options: Vec<User> = if can_change_mentors {
+        use crate::schema::users::dsl::*;
+        users
+            .order(real_name.asc())
+            .load::<User>(&*conn)
+            .expect("Failed to get users from database")
+            .into_iter()
+            .filter(|u| !mentors.iter().any(|m| m.user.id == u.id))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let is_mentor = l.0.tier > 0 || role.is_some();
+    let past = if is_mentor {
+        past_members(&*conn, &g)
+    } else {
+        Vec::new()
+    };
 
     Some(GroupTemplate {
+        is_mentor,
         logged_in: Some(l.0),
-        users: group_users(&*conn, &g),
+        users: us,
+        past_members: past,
+        missing_updates: missing,
         group: g,
         meetings: m,
+        schedules: sched,
+        preferred,
+        projects: options,
+        parent,
+        children,
+        rollup,
+        mentors,
+        can_change_mentors,
+        mentor_options,
+        can_manage,
     })
 }
 
-#[get("/groups")]
-pub fn groups(conn: ObservDbConn, l: MentorGuard) -> GroupsListTemplate {
-    use crate::schema::groups::dsl::*;
+#[get("/groups?<archived>")]
+pub fn groups(conn: ObservDbConn, l: MentorGuard, archived: Option<bool>) -> GroupsListTemplate {
+    let show_archived = archived.unwrap_or(false);
     GroupsListTemplate {
         logged_in: Some(l.0),
-        groups: groups
-            .load(&*conn)
-            .expect("Failed to get groups from database"),
+        groups: group_rows(list_groups(&*conn, show_archived)),
+        archived: show_archived,
     }
 }
 
-#[get("/groups.json")]
-pub fn groups_json(conn: ObservDbConn, _l: MentorGuard) -> Json<Vec<Group>> {
-    use crate::schema::groups::dsl::*;
-    Json(
-        groups
-            .load(&*conn)
-            .expect("Failed to get groups from database"),
-    )
+#[get("/groups.json?<archived>")]
+pub fn groups_json(
+    conn: ObservDbConn,
+    _l: MentorGuard,
+    archived: Option<bool>,
+) -> Json<Vec<Group>> {
+    Json(list_groups(&*conn, archived.unwrap_or(false)))
 }
 
 #[get("/groups/new")]
@@ -63,6 +174,13 @@ pub fn group_new(conn: ObservDbConn, l: AdminGuard) -> NewGroupTemplate {
         all_users: users
             .load(&*conn)
             .expect("Failed to get users from database"),
+        parents: list_groups(&*conn, false)
+            .into_iter()
+            .map(|g| GroupOption {
+                group: g,
+                picked: false,
+            })
+            .collect(),
     }
 }
 
@@ -88,12 +206,156 @@ pub fn group_new_post(conn: ObservDbConn, _l: AdminGuard, newgroup: Form<NewGrou
         .values(&NewRelationGroupUser {
             group_id: gid,
             user_id: newgroup.owner_id,
+            joined_at: Some(Local::now().naive_local()),
         })
         .execute(&*conn)
         .expect("Failed to insert relation into database");
     Redirect::to("/groups")
 }
 
+/// GET handler for `/groups/assign`
+///
+/// Proposes an assignment of the active students to the picked groups
+/// and shows who would move. Nothing changes until it's applied.
+///
+/// Restricted to Admins.
+#[get("/groups/assign?<q..>")]
+pub fn group_assign(conn: ObservDbConn, l: AdminGuard, q: Form<AssignForm>) -> AssignTemplate {
+    let q = q.into_inner();
+    let all_groups = list_groups(&*conn, false);
+    let picked: Vec<Group> = all_groups
+        .iter()
+        .filter(|g| q.groups.contains(&g.id))
+        .cloned()
+        .collect();
+
+    let (assigned, current) = propose(&*conn, &picked, &q);
+    let students: Vec<User> = {
+        use crate::schema::users::dsl::*;
+        users
+            .filter(id.eq_any(assigned.iter().map(|(u, _)| *u).collect::<Vec<i32>>()))
+            .order(real_name.asc())
+            .load(&*conn)
+            .expect("Failed to get users from database")
+    };
+    let group_of = |uid: i32| assigned.iter().find(|(u, _)| *u == uid).map(|(_, g)| *g);
+
+    let proposal = picked
+        .iter()
+        .map(|g| {
+            let members: Vec<User> = students
+                .iter()
+                .filter(|s| group_of(s.id) == Some(g.id))
+                .cloned()
+                .collect();
+            ProposedGroup {
+                over: g
+                    .capacity
+                    .map(|c| members.len() > c as usize)
+                    .unwrap_or(false),
+                group: g.clone(),
+                members,
+            }
+        })
+        .collect();
+    let moves = students
+        .iter()
+        .filter_map(|s| {
+            let to = group_of(s.id)?;
+            let from: Vec<Group> = picked
+                .iter()
+                .filter(|g| current.contains(&(s.id, g.id)))
+                .cloned()
+                .collect();
+            if from.len() == 1 && from[0].id == to {
+                return None;
+            }
+            Some(Move {
+                user: s.clone(),
+                from,
+                to: picked.iter().find(|g| g.id == to)?.clone(),
+            })
+        })
+        .collect();
+
+    AssignTemplate {
+        logged_in: Some(l.0),
+        groups: all_groups
+            .into_iter()
+            .map(|g| GroupOption {
+                picked: q.groups.contains(&g.id),
+                group: g,
+            })
+            .collect(),
+        keep_teams: q.keep_teams,
+        prefs: q.prefs,
+        proposal,
+        moves,
+        assignment: assigned
+            .iter()
+            .map(|(u, g)| format!("{}:{}", u, g))
+            .collect::<Vec<String>>()
+            .join(","),
+    }
+}
+
+/// POST handler for `/groups/assign`
+///
+/// Applies the reviewed assignment. Students are removed from the picked
+/// groups they were moved out of and added to their new group all at
+/// once, nobody else's groups change.
+///
+/// Restricted to Admins.
+#[post("/groups/assign", data = "<form>")]
+pub fn group_assign_post(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    form: Form<AssignForm>,
+) -> Result<Redirect, Status> {
+    let form = form.into_inner();
+    let assigned = form.pairs().map_err(|_| Status::BadRequest)?;
+    let picked: Vec<i32> = list_groups(&*conn, false)
+        .iter()
+        .map(|g| g.id)
+        .filter(|g| form.groups.contains(g))
+        .collect();
+    if assigned.iter().any(|(_, g)| !picked.contains(g)) {
+        return Err(Status::BadRequest);
+    }
+
+    let current = current_members(&*conn, &picked);
+    let (adds, removes) = diff(&current, &assigned);
+    let now = Local::now().naive_local();
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        use crate::schema::relation_group_user::dsl::*;
+        for (uid, gid) in removes.iter() {
+            update(
+                relation_group_user
+                    .filter(group_id.eq(gid).and(user_id.eq(uid)))
+                    .filter(left_at.is_null()),
+            )
+            .set(left_at.eq(now))
+            .execute(&*conn)?;
+        }
+        insert_into(relation_group_user)
+            .values(
+                &adds
+                    .iter()
+                    .map(|(uid, gid)| NewRelationGroupUser {
+                        group_id: *gid,
+                        user_id: *uid,
+                        joined_at: Some(now),
+                    })
+                    .collect::<Vec<NewRelationGroupUser>>(),
+            )
+            .execute(&*conn)?;
+        Ok(())
+    })
+    .expect("Failed to update group members in database");
+
+    Ok(Redirect::to("/groups"))
+}
+
 #[get("/groups/<gid>/meetings")]
 pub fn meetings(gid: i32) -> Redirect {
     Redirect::to(format!("/groups/{}", gid))
@@ -110,13 +372,45 @@ pub fn meetings_json(conn: ObservDbConn, _l: MentorGuard, gid: i32) -> Json<Vec<
     )
 }
 
+/// GET handler for `/groups/<gid>/calendar.ics`
+///
+/// iCalendar feed of the group's meetings and upcoming scheduled days.
+/// Attendance codes aren't in the feed so it doesn't need a login.
+#[get("/groups/<gid>/calendar.ics")]
+pub fn group_calendar_ics(conn: ObservDbConn, gid: i32) -> Option<Content<String>> {
+    use crate::schema::groups::dsl::*;
+
+    let g: Group = groups
+        .find(gid)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get group from database")?;
+    Some(Content(
+        ContentType::new("text", "calendar"),
+        write_calendar(&g.name, &group_entries(&*conn, &g)),
+    ))
+}
+
 #[post("/groups/<gid>/meetings/new", data = "<newmeeting>")]
 pub fn meeting_new_post(
     conn: ObservDbConn,
     _l: MentorGuard,
     gid: i32,
     newmeeting: Form<NewMeeting>,
-) -> Redirect {
+) -> Result<Redirect, Status> {
+    let g: Group = {
+        use crate::schema::groups::dsl::*;
+        groups
+            .find(gid)
+            .first(&*conn)
+            .expect("Failed to get group from database")
+    };
+
+    // Archived groups are read-only
+    if g.archived {
+        return Err(Status::Conflict);
+    }
+
     use crate::schema::meetings::dsl::*;
 
     let mut newmeeting = newmeeting.into_inner();
@@ -128,7 +422,504 @@ pub fn meeting_new_post(
         .execute(&*conn)
         .expect("Failed to insert meeting into database");
 
-    Redirect::to(format!("/groups/{}", newmeeting.group_id))
+    Ok(Redirect::to(format!("/groups/{}", newmeeting.group_id)))
+}
+
+/// POST handler for `/groups/<gid>/meetings/<mid>/cancel`
+///
+/// Cancelled meetings can't be attended and don't count toward grades.
+/// The meeting is kept so that the schedule doesn't generate it again.
+#[post("/groups/<gid>/meetings/<mid>/cancel")]
+pub fn meeting_cancel_post(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    mid: i32,
+) -> Result<Redirect, Status> {
+    set_meeting_cancelled(&*conn, &l.0, gid, mid, true)
+}
+
+/// POST handler for `/groups/<gid>/meetings/<mid>/restore`
+///
+/// Undoes cancelling a meeting.
+#[post("/groups/<gid>/meetings/<mid>/restore")]
+pub fn meeting_restore_post(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    mid: i32,
+) -> Result<Redirect, Status> {
+    set_meeting_cancelled(&*conn, &l.0, gid, mid, false)
+}
+
+/// GET handler for `/groups/<gid>/meetings/<mid>`
+///
+/// The meeting's agenda and notes. Mentors and the group leader also see
+/// the attendance code and who attended.
+#[get("/groups/<gid>/meetings/<mid>")]
+pub fn meeting(
+    conn: ObservDbConn,
+    l: UserGuard,
+    gid: i32,
+    mid: i32,
+) -> Result<MeetingTemplate, Status> {
+    let g: Group = {
+        use crate::schema::groups::dsl::*;
+        groups
+            .find(gid)
+            .first(&*conn)
+            .optional()
+            .expect("Failed to get group from database")
+            .ok_or(Status::NotFound)?
+    };
+    let m = get_meeting(&*conn, gid, mid).ok_or(Status::NotFound)?;
+    let room = m.room_id.and_then(|rid| get_room(&*conn, rid));
+
+    // Where the meeting is unless it's somewhere else than usual
+    let place = m
+        .location
+        .clone()
+        .or_else(|| {
+            use crate::schema::group_schedules::dsl::*;
+            m.schedule_id.and_then(|sid| {
+                group_schedules
+                    .find(sid)
+                    .select(location)
+                    .first::<Option<String>>(&*conn)
+                    .optional()
+                    .expect("Failed to get schedule from database")
+                    .and_then(|l| l)
+            })
+        })
+        .or_else(|| g.location.clone());
+
+    let can_manage = !g.archived && can_manage_group(&*conn, &g, &l.0);
+    let is_mentor = l.0.tier > 0 || group_role(&*conn, &g, &l.0).is_some();
+    let attendees = if is_mentor {
+        use crate::schema::attendances::dsl::*;
+        use crate::schema::users::dsl::{real_name, users};
+        attendances
+            .inner_join(users)
+            .filter(meeting_id.eq(mid))
+            .order(real_name.asc())
+            .select(crate::schema::users::all_columns)
+            .load(&*conn)
+            .expect("Failed to get attendances from database")
+    } else {
+        Vec::new()
+    };
+
+    Ok(MeetingTemplate {
+        logged_in: Some(l.0),
+        group: g,
+        meeting: m,
+        room,
+        location: place,
+        can_manage,
+        is_mentor,
+        attendees,
+    })
+}
+
+/// GET handler for `/groups/<gid>/meetings/<mid>/edit`
+#[get("/groups/<gid>/meetings/<mid>/edit?<e>")]
+pub fn meeting_edit(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    mid: i32,
+    e: Option<FormError>,
+) -> Result<EditMeetingTemplate, Status> {
+    let g = manageable_group(&*conn, &l.0, gid)?;
+    let m = get_meeting(&*conn, gid, mid).ok_or(Status::NotFound)?;
+    Ok(EditMeetingTemplate {
+        logged_in: Some(l.0),
+        group: g,
+        rooms: room_options(&*conn, m.room_id),
+        meeting: m,
+        error: e,
+    })
+}
+
+/// PUT handler for `/groups/<gid>/meetings/<mid>`
+///
+/// Changing the time moves only this meeting, the rest of the schedule
+/// is unchanged. Fails if the room is booked for something else at the
+/// new time.
+#[put("/groups/<gid>/meetings/<mid>", data = "<form>")]
+pub fn meeting_edit_put(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    mid: i32,
+    form: Form<MeetingForm>,
+) -> Result<Redirect, Status> {
+    manageable_group(&*conn, &l.0, gid)?;
+    let m = get_meeting(&*conn, gid, mid).ok_or(Status::NotFound)?;
+
+    let edit = match form.parse() {
+        Ok(edit) => edit,
+        Err(_) => {
+            return Ok(Redirect::to(format!(
+                "/groups/{}/meetings/{}/edit?e={}",
+                gid,
+                mid,
+                FormError::InvalidDate
+            )))
+        }
+    };
+    // Cancelled meetings don't book their room
+    if let (Some(rid), false) = (edit.room_id, m.cancelled) {
+        let end = edit.happened_at + chrono::Duration::minutes(MEETING_MINUTES);
+        let own = Some(Booked::Meeting(mid));
+        if !conflicts(&*conn, rid, edit.happened_at, end, own).is_empty() {
+            return Ok(Redirect::to(format!(
+                "/groups/{}/meetings/{}/edit?e={}",
+                gid,
+                mid,
+                FormError::RoomBooked
+            )));
+        }
+    }
+
+    use crate::schema::meetings::dsl::*;
+    update(meetings.find(mid))
+        .set(&edit)
+        .execute(&*conn)
+        .expect("Failed to update meeting in database");
+    Ok(Redirect::to(format!("/groups/{}/meetings/{}", gid, mid)))
+}
+
+/// DELETE handler for `/groups/<gid>/meetings/<mid>`
+///
+/// Deletes the meeting along with its attendance. Attendance counts
+/// toward grades, so only Admins can delete a meeting someone attended,
+/// everyone else should cancel it instead.
+#[delete("/groups/<gid>/meetings/<mid>")]
+pub fn meeting_delete(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    mid: i32,
+) -> Result<Redirect, Status> {
+    manageable_group(&*conn, &l.0, gid)?;
+    get_meeting(&*conn, gid, mid).ok_or(Status::NotFound)?;
+
+    let attended: i64 = {
+        use crate::schema::attendances::dsl::*;
+        attendances
+            .filter(meeting_id.eq(mid))
+            .count()
+            .get_result(&*conn)
+            .expect("Failed to count attendances")
+    };
+    if attended > 0 && l.0.tier < 2 {
+        return Err(Status::Conflict);
+    }
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        {
+            use crate::schema::attendances::dsl::*;
+            delete(attendances.filter(meeting_id.eq(mid))).execute(&*conn)?;
+        }
+        use crate::schema::meetings::dsl::*;
+        delete(meetings.find(mid)).execute(&*conn)?;
+        Ok(())
+    })
+    .expect("Failed to delete meeting from database");
+
+    Ok(Redirect::to(format!("/groups/{}", gid)))
+}
+
+/// GET handler for `/groups/<gid>/schedules/new`
+#[get("/groups/<gid>/schedules/new?<e>")]
+pub fn schedule_new(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    e: Option<FormError>,
+) -> Result<ScheduleTemplate, Status> {
+    let g = manageable_group(&*conn, &l.0, gid)?;
+    Ok(ScheduleTemplate {
+        logged_in: Some(l.0),
+        group: g,
+        schedule: None,
+        weekdays: weekday_options(),
+        rooms: room_options(&*conn, None),
+        error: e,
+    })
+}
+
+/// POST handler for `/groups/<gid>/schedules/new`
+///
+/// Creates the schedule and generates its upcoming meetings right away.
+/// Fails if the room is booked for something else on one of the days.
+#[post("/groups/<gid>/schedules/new", data = "<form>")]
+pub fn schedule_new_post(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    form: Form<ScheduleForm>,
+    ahead: State<ScheduleAhead>,
+) -> Result<Redirect, Status> {
+    let g = manageable_group(&*conn, &l.0, gid)?;
+    let new = match form.parse(gid) {
+        Ok(n) => n,
+        Err(_) => {
+            return Ok(Redirect::to(format!(
+                "/groups/{}/schedules/new?e={}",
+                gid,
+                FormError::InvalidDate
+            )))
+        }
+    };
+    if !schedule_conflicts(&*conn, &new, None).is_empty() {
+        return Ok(Redirect::to(format!(
+            "/groups/{}/schedules/new?e={}",
+            gid,
+            FormError::RoomBooked
+        )));
+    }
+
+    use crate::schema::group_schedules::dsl::*;
+    insert_into(group_schedules)
+        .values(&new)
+        .execute(&*conn)
+        .expect("Failed to insert schedule into database");
+    let sched: GroupSchedule = group_schedules
+        .filter(group_id.eq(gid))
+        .order(id.desc())
+        .first(&*conn)
+        .expect("Failed to get schedule from database");
+    generate_meetings(&*conn, &sched, &g, ahead.0);
+
+    Ok(Redirect::to(format!("/groups/{}", gid)))
+}
+
+/// GET handler for `/groups/<gid>/schedules/<sid>/edit`
+#[get("/groups/<gid>/schedules/<sid>/edit?<e>")]
+pub fn schedule_edit(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    sid: i32,
+    e: Option<FormError>,
+) -> Result<ScheduleTemplate, Status> {
+    let g = manageable_group(&*conn, &l.0, gid)?;
+    let sched = get_schedule(&*conn, gid, sid).ok_or(Status::NotFound)?;
+    Ok(ScheduleTemplate {
+        logged_in: Some(l.0),
+        group: g,
+        rooms: room_options(&*conn, sched.room_id),
+        schedule: Some(sched),
+        weekdays: weekday_options(),
+        error: e,
+    })
+}
+
+/// PUT handler for `/groups/<gid>/schedules/<sid>`
+///
+/// Upcoming meetings nobody attended yet are generated again for the new
+/// schedule. Meetings that already happened are unchanged. Fails if the
+/// room is booked for something else on one of the days.
+#[put("/groups/<gid>/schedules/<sid>", data = "<form>")]
+pub fn schedule_edit_put(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    sid: i32,
+    form: Form<ScheduleForm>,
+    ahead: State<ScheduleAhead>,
+) -> Result<Redirect, Status> {
+    let g = manageable_group(&*conn, &l.0, gid)?;
+    let old = get_schedule(&*conn, gid, sid).ok_or(Status::NotFound)?;
+    let new = match form.parse(gid) {
+        Ok(n) => n,
+        Err(_) => {
+            return Ok(Redirect::to(format!(
+                "/groups/{}/schedules/{}/edit?e={}",
+                gid,
+                sid,
+                FormError::InvalidDate
+            )))
+        }
+    };
+    if !schedule_conflicts(&*conn, &new, Some(sid)).is_empty() {
+        return Ok(Redirect::to(format!(
+            "/groups/{}/schedules/{}/edit?e={}",
+            gid,
+            sid,
+            FormError::RoomBooked
+        )));
+    }
+
+    let today = Local::today().naive_local();
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        clear_upcoming(&*conn, &old, today)?;
+        use crate::schema::group_schedules::dsl::*;
+        update(group_schedules.find(sid))
+            .set(&new)
+            .execute(&*conn)?;
+        Ok(())
+    })
+    .expect("Failed to update schedule in database");
+
+    let sched = get_schedule(&*conn, gid, sid).ok_or(Status::NotFound)?;
+    generate_meetings(&*conn, &sched, &g, ahead.0);
+
+    Ok(Redirect::to(format!("/groups/{}", gid)))
+}
+
+/// DELETE handler for `/groups/<gid>/schedules/<sid>`
+///
+/// Stops generating meetings. Upcoming meetings nobody attended are
+/// deleted and the rest are kept.
+#[delete("/groups/<gid>/schedules/<sid>")]
+pub fn schedule_delete(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    sid: i32,
+) -> Result<Redirect, Status> {
+    manageable_group(&*conn, &l.0, gid)?;
+    let sched = get_schedule(&*conn, gid, sid).ok_or(Status::NotFound)?;
+
+    let today = Local::today().naive_local();
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        clear_upcoming(&*conn, &sched, today)?;
+        {
+            use crate::schema::meetings::dsl::*;
+            update(meetings.filter(schedule_id.eq(sid)))
+                .set(schedule_id.eq(None::<i32>))
+                .execute(&*conn)?;
+        }
+        use crate::schema::group_schedules::dsl::*;
+        delete(group_schedules.find(sid)).execute(&*conn)?;
+        Ok(())
+    })
+    .expect("Failed to delete schedule from database");
+
+    Ok(Redirect::to(format!("/groups/{}", gid)))
+}
+
+/// POST handler for `/groups/<gid>/mentors`
+///
+/// Adds a mentor to the group or changes their role. Mentors are also
+/// added as members so the group is on their dashboard.
+///
+/// Restricted to Coordinators and the group's leader.
+#[post("/groups/<gid>/mentors", data = "<form>")]
+pub fn group_mentor_post(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    form: Form<MentorForm>,
+) -> Result<Redirect, Status> {
+    let g = manageable_group(&*conn, &l.0, gid)?;
+    if !(l.0.tier > 1 || l.0.id == g.owner_id) {
+        return Err(Status::Unauthorized);
+    }
+    // The leader is changed by editing the group
+    if form.role == GroupRole::Leader || form.user_id == g.owner_id {
+        return Err(Status::BadRequest);
+    }
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        {
+            use crate::schema::group_mentors::dsl::*;
+            replace_into(group_mentors)
+                .values(&NewGroupMentor {
+                    group_id: gid,
+                    user_id: form.user_id,
+                    role: form.role.as_str().to_string(),
+                })
+                .execute(&*conn)?;
+        }
+        use crate::schema::relation_group_user::dsl::*;
+        let member: bool = select(exists(
+            relation_group_user
+                .filter(group_id.eq(gid).and(user_id.eq(form.user_id)))
+                .filter(left_at.is_null()),
+        ))
+        .get_result(&*conn)?;
+        if !member {
+            insert_into(relation_group_user)
+                .values(&NewRelationGroupUser {
+                    group_id: gid,
+                    user_id: form.user_id,
+                    joined_at: Some(Local::now().naive_local()),
+                })
+                .execute(&*conn)?;
+        }
+        Ok(())
+    })
+    .expect("Failed to add mentor to group in database");
+
+    Ok(Redirect::to(format!("/groups/{}", gid)))
+}
+
+/// DELETE handler for `/groups/<gid>/mentors/<uid>`
+///
+/// The mentor stays a member of the group.
+///
+/// Restricted to Coordinators and the group's leader.
+#[delete("/groups/<gid>/mentors/<uid>")]
+pub fn group_mentor_delete(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    uid: i32,
+) -> Result<Redirect, Status> {
+    use crate::schema::group_mentors::dsl::*;
+
+    let g = manageable_group(&*conn, &l.0, gid)?;
+    if !(l.0.tier > 1 || l.0.id == g.owner_id) {
+        return Err(Status::Unauthorized);
+    }
+    delete(group_mentors.filter(group_id.eq(gid).and(user_id.eq(uid))))
+        .execute(&*conn)
+        .expect("Failed to remove mentor from group in database");
+    Ok(Redirect::to(format!("/groups/{}", gid)))
+}
+
+/// POST handler for `/groups/<gid>/preferences`
+///
+/// Asks for a project to be in the group when students are assigned.
+#[post("/groups/<gid>/preferences", data = "<form>")]
+pub fn group_pref_post(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    form: Form<PrefForm>,
+) -> Result<Redirect, Status> {
+    use crate::schema::group_project_prefs::dsl::*;
+
+    manageable_group(&*conn, &l.0, gid)?;
+    replace_into(group_project_prefs)
+        .values(&NewGroupProjectPref {
+            group_id: gid,
+            project_id: form.project_id,
+        })
+        .execute(&*conn)
+        .expect("Failed to insert preference into database");
+    Ok(Redirect::to(format!("/groups/{}", gid)))
+}
+
+/// DELETE handler for `/groups/<gid>/preferences/<pid>`
+#[delete("/groups/<gid>/preferences/<pid>")]
+pub fn group_pref_delete(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+    pid: i32,
+) -> Result<Redirect, Status> {
+    use crate::schema::group_project_prefs::dsl::*;
+
+    manageable_group(&*conn, &l.0, gid)?;
+    delete(group_project_prefs.filter(group_id.eq(gid).and(project_id.eq(pid))))
+        .execute(&*conn)
+        .expect("Failed to delete preference from database");
+    Ok(Redirect::to(format!("/groups/{}", gid)))
 }
 
 #[get("/groups/<gid>/members/add")]
@@ -144,12 +935,17 @@ pub fn group_user_add(
         .find(gid)
         .first(&*conn)
         .expect("Failed to get group from database");
+
+    // Archived groups are read-only
+    if g.archived {
+        return Err(Status::Conflict);
+    }
     let all_users: Vec<User> = users
         .load(&*conn)
         .expect("Failed to get users from database");
     let gu = group_users(&*conn, &g);
 
-    if l.0.tier > 1 || g.owner_id == l.0.id {
+    if can_manage_group(&*conn, &g, &l.0) {
         Ok(AddUserTemplate {
             logged_in: Some(l.0),
             group: g,
@@ -183,7 +979,12 @@ pub fn group_user_add_post(
         .first(&*conn)
         .expect("Failed to get group from database");
 
-    if l.0.tier > 1 || g.owner_id == l.0.id {
+    // Archived groups are read-only
+    if g.archived {
+        return Err(Status::Conflict);
+    }
+
+    if can_manage_group(&*conn, &g, &l.0) {
         use crate::schema::relation_group_user::dsl::*;
 
         if let Some(uid) = form.into_inner().uid {
@@ -191,6 +992,7 @@ pub fn group_user_add_post(
                 .values(&NewRelationGroupUser {
                     group_id: g.id,
                     user_id: uid,
+                    joined_at: Some(Local::now().naive_local()),
                 })
                 .execute(&*conn)
                 .expect("Failed to insert new relation into database");
@@ -217,11 +1019,23 @@ pub fn group_user_delete(
         .first(&*conn)
         .expect("Failed to get group from database");
 
-    if l.0.tier > 1 || g.owner_id == l.0.id {
+    // Archived groups are read-only
+    if g.archived {
+        return Err(Status::Conflict);
+    }
+
+    if can_manage_group(&*conn, &g, &l.0) {
+        // The membership is kept so that meetings before they left still
+        // count
         use crate::schema::relation_group_user::dsl::*;
-        delete(relation_group_user.filter(group_id.eq(g.id).and(user_id.eq(uid))))
-            .execute(&*conn)
-            .expect("Failed to removed user from group in database");
+        update(
+            relation_group_user
+                .filter(group_id.eq(g.id).and(user_id.eq(uid)))
+                .filter(left_at.is_null()),
+        )
+        .set(left_at.eq(Local::now().naive_local()))
+        .execute(&*conn)
+        .expect("Failed to removed user from group in database");
         Ok(Redirect::to(format!("/groups/{}", gid)))
     } else {
         Err(Status::Unauthorized)
@@ -242,9 +1056,15 @@ pub fn group_edit(
         .first(&*conn)
         .expect("Failed to get group from database");
 
-    if l.0.tier > 1 || g.owner_id == l.0.id {
+    // Archived groups are read-only
+    if g.archived {
+        return Err(Status::Conflict);
+    }
+
+    if can_manage_group(&*conn, &g, &l.0) {
         Ok(EditGroupTemplate {
             logged_in: Some(l.0),
+            parents: parent_options(&*conn, &g),
             group: g,
             all_users: users
                 .load(&*conn)
@@ -271,9 +1091,23 @@ pub fn group_edit_put(
         .first(&*conn)
         .expect("Failed to get group from database");
 
-    if l.0.tier > 1 || g.owner_id == l.0.id {
+    // Archived groups are read-only
+    if g.archived {
+        return Err(Status::Conflict);
+    }
+
+    if can_manage_group(&*conn, &g, &l.0) {
+        // Only Coordinators can change who leads the group and where it is
         if !(l.0.tier > 1) {
-            editgroup.owner_id = l.0.id;
+            editgroup.owner_id = g.owner_id;
+            editgroup.parent_id = g.parent_id;
+            editgroup.join_on_signup = g.join_on_signup;
+        }
+        // A group can't be part of itself or a group under it
+        if let Some(pid) = editgroup.parent_id {
+            if !parent_options(&*conn, &g).iter().any(|o| o.group.id == pid) {
+                return Err(Status::BadRequest);
+            }
         }
         update(groups.find(gid))
             .set(&editgroup)
@@ -285,22 +1119,546 @@ pub fn group_edit_put(
     }
 }
 
+#[post("/groups/<gid>/archive")]
+pub fn group_archive_post(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+) -> Result<Redirect, Status> {
+    set_group_archived(&*conn, &l.0, gid, true)
+}
+
+#[post("/groups/<gid>/unarchive")]
+pub fn group_unarchive_post(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    gid: i32,
+) -> Result<Redirect, Status> {
+    set_group_archived(&*conn, &l.0, gid, false)
+}
+
+/// Confirmation page listing everything deleting the group would lose
+#[get("/groups/<gid>/delete")]
+pub fn group_delete_confirm(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    gid: i32,
+) -> Result<DeleteGroupTemplate, Status> {
+    let g: Group = {
+        use crate::schema::groups::dsl::*;
+        groups
+            .find(gid)
+            .first(&*conn)
+            .optional()
+            .expect("Failed to get group from database")
+            .ok_or(Status::NotFound)?
+    };
+
+    let meeting_ids = group_meeting_ids(&*conn, gid);
+    let attended: i64 = {
+        use crate::schema::attendances::dsl::*;
+        attendances
+            .filter(meeting_id.eq_any(meeting_ids.iter().map(|m| Some(*m))))
+            .count()
+            .get_result(&*conn)
+            .expect("Failed to count attendances")
+    };
+
+    Ok(DeleteGroupTemplate {
+        logged_in: Some(l.0),
+        losses: vec![
+            format!("{} members", group_users(&*conn, &g).len()),
+            format!("{} meetings", meeting_ids.len()),
+            format!(
+                "{} meeting schedules",
+                GroupSchedule::belonging_to(&g)
+                    .count()
+                    .get_result::<i64>(&*conn)
+                    .expect("Failed to count schedules")
+            ),
+            format!("{} attendance records, which count toward grades", attended),
+            format!(
+                "{} mentors",
+                GroupMentor::belonging_to(&g)
+                    .count()
+                    .get_result::<i64>(&*conn)
+                    .expect("Failed to count mentors")
+            ),
+        ],
+        group: g,
+    })
+}
+
+/// Permanently deletes the group along with its meetings, their attendance,
+/// and its schedules
+///
+/// Groups that were part of it are moved to the top.
+///
+/// Only admins can do this, everyone else should archive the group instead.
 #[delete("/groups/<gid>")]
-pub fn group_delete(conn: ObservDbConn, _l: AdminGuard, gid: i32) -> Redirect {
+pub fn group_delete(conn: ObservDbConn, _l: AdminGuard, gid: i32) -> Result<Redirect, Status> {
+    let meeting_ids = group_meeting_ids(&*conn, gid);
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        {
+            use crate::schema::attendances::dsl::*;
+            delete(attendances.filter(meeting_id.eq_any(meeting_ids.iter().map(|m| Some(*m)))))
+                .execute(&*conn)?;
+        }
+        {
+            use crate::schema::meetings::dsl::*;
+            delete(meetings.filter(group_id.eq(gid))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::group_schedules::dsl::*;
+            delete(group_schedules.filter(group_id.eq(gid))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::group_project_prefs::dsl::*;
+            delete(group_project_prefs.filter(group_id.eq(gid))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::group_mentors::dsl::*;
+            delete(group_mentors.filter(group_id.eq(gid))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::relation_group_user::dsl::*;
+            delete(relation_group_user.filter(group_id.eq(gid))).execute(&*conn)?;
+        }
+        use crate::schema::groups::dsl::*;
+        update(groups.filter(parent_id.eq(gid)))
+            .set(parent_id.eq(None::<i32>))
+            .execute(&*conn)?;
+        delete(groups.find(gid)).execute(&*conn)?;
+        Ok(())
+    })
+    .expect("Failed to delete group from database");
+
+    Ok(Redirect::to("/groups"))
+}
+
+fn set_group_archived(
+    conn: &SqliteConnection,
+    u: &User,
+    gid: i32,
+    value: bool,
+) -> Result<Redirect, Status> {
+    use crate::schema::groups::dsl::*;
+
+    let g: Group = groups
+        .find(gid)
+        .first(conn)
+        .optional()
+        .expect("Failed to get group from database")
+        .ok_or(Status::NotFound)?;
+
+    if !can_manage_group(conn, &g, u) {
+        return Err(Status::Unauthorized);
+    }
+
+    update(groups.find(gid))
+        .set(archived.eq(value))
+        .execute(conn)
+        .expect("Failed to update group in database");
+    Ok(Redirect::to(format!("/groups/{}", gid)))
+}
+
+fn set_meeting_cancelled(
+    conn: &SqliteConnection,
+    u: &User,
+    gid: i32,
+    mid: i32,
+    value: bool,
+) -> Result<Redirect, Status> {
+    manageable_group(conn, u, gid)?;
+
+    use crate::schema::meetings::dsl::*;
+    update(meetings.filter(id.eq(mid).and(group_id.eq(gid))))
+        .set(cancelled.eq(value))
+        .execute(conn)
+        .expect("Failed to update meeting in database");
+    Ok(Redirect::to(format!("/groups/{}/meetings/{}", gid, mid)))
+}
+
+/// The role of the user in the group, if they help run it
+pub fn group_role(conn: &SqliteConnection, g: &Group, u: &User) -> Option<GroupRole> {
+    if u.id == g.owner_id {
+        return Some(GroupRole::Leader);
+    }
+    use crate::schema::group_mentors::dsl::*;
+    group_mentors
+        .filter(group_id.eq(g.id).and(user_id.eq(u.id)))
+        .select(role)
+        .first::<String>(conn)
+        .optional()
+        .expect("Failed to get mentors from database")
+        .and_then(|r| r.parse().ok())
+}
+
+/// Can the user change the group, its meetings, and its members?
+///
+/// Coordinators can change every group.
+pub fn can_manage_group(conn: &SqliteConnection, g: &Group, u: &User) -> bool {
+    u.tier > 1
+        || group_role(conn, g, u)
+            .map(GroupRole::can_manage)
+            .unwrap_or(false)
+}
+
+/// The leader and mentors of the group, leader first
+fn mentor_rows(conn: &SqliteConnection, g: &Group) -> Vec<MentorRow> {
+    use crate::schema::users::dsl::{real_name, users};
+
+    let leader: Option<User> = users
+        .find(g.owner_id)
+        .first(conn)
+        .optional()
+        .expect("Failed to get user from database");
+    let others: Vec<(GroupMentor, User)> = GroupMentor::belonging_to(g)
+        .inner_join(users)
+        .order(real_name.asc())
+        .load(conn)
+        .expect("Failed to get mentors from database");
+
+    leader
+        .map(|u| MentorRow {
+            user: u,
+            role: GroupRole::Leader,
+        })
+        .into_iter()
+        .chain(others.into_iter().map(|(m, u)| MentorRow {
+            role: m.role.parse().unwrap_or(GroupRole::Assistant),
+            user: u,
+        }))
+        .filter(|r| r.role == GroupRole::Leader || r.user.id != g.owner_id)
+        .collect()
+}
+
+/// Add a new user to the groups new users join when they sign up
+///
+/// Returns how many groups the user was added to.
+pub fn join_default_groups(conn: &SqliteConnection, uid: i32) -> QueryResult<usize> {
+    let now = Local::now().naive_local();
+    let gids: Vec<i32> = {
+        use crate::schema::groups::dsl::*;
+        groups
+            .filter(join_on_signup.eq(true).and(archived.eq(false)))
+            .select(id)
+            .load(conn)?
+    };
+    let relations: Vec<NewRelationGroupUser> = gids
+        .into_iter()
+        .map(|gid| NewRelationGroupUser {
+            group_id: gid,
+            user_id: uid,
+            joined_at: Some(now),
+        })
+        .collect();
+
     use crate::schema::relation_group_user::dsl::*;
-    delete(relation_group_user.filter(group_id.eq(gid)))
-        .execute(&*conn)
-        .expect("Failed to delete relations from database");
+    insert_into(relation_group_user)
+        .values(&relations)
+        .execute(conn)
+}
+
+/// How many meetings the user had to attend
+///
+/// Only meetings in the semester that already happened, weren't
+/// cancelled, and were while the user was a member of the group count.
+pub fn required_meetings(conn: &SqliteConnection, uid: i32, sem: &Semester) -> usize {
+    let now = Local::now().naive_local();
+    let (from, until) = (sem.start().and_hms(0, 0, 0), sem.end().and_hms(0, 0, 0));
+    let relations: Vec<RelationGroupUser> = {
+        use crate::schema::relation_group_user::dsl::*;
+        relation_group_user
+            .filter(user_id.eq(uid))
+            .load(conn)
+            .expect("Failed to get relations from database")
+    };
+    let held: Vec<(i32, NaiveDateTime)> = {
+        use crate::schema::meetings::dsl::*;
+        meetings
+            .filter(group_id.eq_any(relations.iter().map(|r| r.group_id).collect::<Vec<i32>>()))
+            .filter(happened_at.ge(from).and(happened_at.lt(until)))
+            .filter(cancelled.eq(false).and(happened_at.le(now)))
+            .select((group_id, happened_at))
+            .load(conn)
+            .expect("Failed to get meetings from database")
+    };
+    held.iter()
+        .filter(|(gid, at)| !members_at(&relations, *gid, *at).is_empty())
+        .count()
+}
+
+/// Membership and attendance of the group and every group under it
+///
+/// `all` is every group. Meetings only count for the users who were
+/// members of the meeting's group when it happened.
+fn group_rollup(conn: &SqliteConnection, g: &Group, all: &[Group]) -> GroupRollup {
+    let now = Local::now().naive_local();
+    let gids = group_subtree(g.id, all);
+    let relations: Vec<RelationGroupUser> = {
+        use crate::schema::relation_group_user::dsl::*;
+        relation_group_user
+            .filter(group_id.eq_any(gids.clone()))
+            .load(conn)
+            .expect("Failed to get relations from database")
+    };
+    let held: Vec<(i32, i32, NaiveDateTime)> = {
+        use crate::schema::meetings::dsl::*;
+        meetings
+            .filter(group_id.eq_any(gids.clone()))
+            .filter(cancelled.eq(false).and(happened_at.le(now)))
+            .select((id, group_id, happened_at))
+            .load(conn)
+            .expect("Failed to get meetings from database")
+    };
+    let attended: Vec<(i32, Option<i32>)> = {
+        use crate::schema::attendances::dsl::*;
+        attendances
+            .filter(meeting_id.eq_any(held.iter().map(|(m, _, _)| Some(*m))))
+            .select((user_id, meeting_id))
+            .load(conn)
+            .expect("Failed to get attendances from database")
+    };
+
+    let mut users: Vec<i32> = relations
+        .iter()
+        .filter(|r| r.left_at.is_none())
+        .map(|r| r.user_id)
+        .collect();
+    users.sort();
+    users.dedup();
+    let expected: Vec<(i32, Vec<i32>)> = held
+        .iter()
+        .map(|(m, mg, at)| (*m, members_at(&relations, *mg, *at)))
+        .collect();
+    let attendances = attended
+        .iter()
+        .filter(|(u, mid)| {
+            expected
+                .iter()
+                .find(|(m, _)| Some(*m) == *mid)
+                .map(|(_, uids)| uids.contains(u))
+                .unwrap_or(false)
+        })
+        .count();
+
+    GroupRollup {
+        group: g.clone(),
+        members: users.len(),
+        meetings: held.len(),
+        attendances,
+        possible: expected.iter().map(|(_, uids)| uids.len()).sum(),
+    }
+}
+
+/// Groups the group can be part of with its parent picked
+///
+/// Groups under it are left out so that groups can't loop.
+fn parent_options(conn: &SqliteConnection, g: &Group) -> Vec<GroupOption> {
     use crate::schema::groups::dsl::*;
-    delete(groups.find(gid))
-        .execute(&*conn)
-        .expect("Failed to delete group from database");
-    Redirect::to("/groups")
+    let all: Vec<Group> = groups
+        .order(name.asc())
+        .load(conn)
+        .expect("Failed to get groups from database");
+    let under = group_subtree(g.id, &all);
+    all.into_iter()
+        .filter(|p| !under.contains(&p.id) && (!p.archived || Some(p.id) == g.parent_id))
+        .map(|p| GroupOption {
+            picked: Some(p.id) == g.parent_id,
+            group: p,
+        })
+        .collect()
+}
+
+/// Order the groups so that the groups in a group come right after it
+fn group_rows(list: Vec<Group>) -> Vec<GroupRow> {
+    fn add(rows: &mut Vec<GroupRow>, list: &[Group], g: &Group, depth: usize) {
+        if rows.iter().any(|r| r.group.id == g.id) {
+            return;
+        }
+        rows.push(GroupRow {
+            group: g.clone(),
+            depth,
+        });
+        for c in list.iter().filter(|c| c.parent_id == Some(g.id)) {
+            add(rows, list, c, depth + 1);
+        }
+    }
+
+    let mut rows = Vec::new();
+    // Groups whose parent isn't listed are at the top
+    for g in list.iter() {
+        if g.parent_id
+            .map(|p| !list.iter().any(|o| o.id == p))
+            .unwrap_or(true)
+        {
+            add(&mut rows, &list, g, 0);
+        }
+    }
+    for g in list.iter() {
+        add(&mut rows, &list, g, 0);
+    }
+    rows
+}
+
+/// The group if it isn't archived and the user can manage it
+fn manageable_group(conn: &SqliteConnection, u: &User, gid: i32) -> Result<Group, Status> {
+    use crate::schema::groups::dsl::*;
+
+    let g: Group = groups
+        .find(gid)
+        .first(conn)
+        .optional()
+        .expect("Failed to get group from database")
+        .ok_or(Status::NotFound)?;
+
+    // Archived groups are read-only
+    if g.archived {
+        return Err(Status::Conflict);
+    }
+    if can_manage_group(conn, &g, u) {
+        Ok(g)
+    } else {
+        Err(Status::Unauthorized)
+    }
+}
+
+/// Propose an assignment of the active students to the groups
+///
+/// Returns the assignment and the current members of the groups, both as
+/// `(user, group)`.
+fn propose(
+    conn: &SqliteConnection,
+    picked: &[Group],
+    q: &AssignForm,
+) -> (Vec<(i32, i32)>, Vec<(i32, i32)>) {
+    let gids: Vec<i32> = picked.iter().map(|g| g.id).collect();
+    let current = current_members(conn, &gids);
+    if picked.is_empty() {
+        return (Vec::new(), current);
+    }
+
+    // Mentors and coordinators run the groups rather than being in them
+    let students: Vec<i32> = {
+        use crate::schema::users::dsl::*;
+        users
+            .filter(active.eq(true).and(former.eq(false)).and(tier.eq(0)))
+            .select(id)
+            .load(conn)
+            .expect("Failed to get users from database")
+    };
+    let teams: Vec<(i32, Vec<i32>)> = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .filter(active.eq(true).and(archived.eq(false)))
+            .filter(status.eq(ProjectStatus::Approved.as_str()))
+            .load::<Project>(conn)
+            .expect("Failed to get projects from database")
+            .iter()
+            .map(|p| {
+                use crate::schema::relation_project_user::dsl::*;
+                let members = RelationProjectUser::belonging_to(p)
+                    .filter(left_at.is_null())
+                    .select(user_id)
+                    .load(conn)
+                    .expect("Failed to get relations from database");
+                (p.id, members)
+            })
+            .collect()
+    };
+    let prefs: Vec<GroupProjectPref> = {
+        use crate::schema::group_project_prefs::dsl::*;
+        group_project_prefs
+            .filter(group_id.eq_any(gids))
+            .load(conn)
+            .expect("Failed to get preferences from database")
+    };
+
+    let slots: Vec<Slot> = picked
+        .iter()
+        .map(|g| Slot {
+            group_id: g.id,
+            capacity: g.capacity.map(|c| c.max(0) as usize),
+            preferred: prefs
+                .iter()
+                .filter(|p| p.group_id == g.id)
+                .map(|p| p.project_id)
+                .collect(),
+        })
+        .collect();
+    let teams = build_teams(&students, &teams, q.keep_teams);
+    (assign(&slots, &teams, &current, q.prefs), current)
+}
+
+/// The members of the groups as `(user, group)`
+fn current_members(conn: &SqliteConnection, gids: &[i32]) -> Vec<(i32, i32)> {
+    use crate::schema::relation_group_user::dsl::*;
+    relation_group_user
+        .filter(group_id.eq_any(gids.to_vec()))
+        .filter(left_at.is_null())
+        .select((user_id, group_id))
+        .load(conn)
+        .expect("Failed to get relations from database")
+}
+
+fn get_meeting(conn: &SqliteConnection, gid: i32, mid: i32) -> Option<Meeting> {
+    use crate::schema::meetings::dsl::*;
+    meetings
+        .filter(id.eq(mid).and(group_id.eq(gid)))
+        .first(conn)
+        .optional()
+        .expect("Failed to get meeting from database")
+}
+
+fn get_schedule(conn: &SqliteConnection, gid: i32, sid: i32) -> Option<GroupSchedule> {
+    use crate::schema::group_schedules::dsl::*;
+    group_schedules
+        .filter(id.eq(sid).and(group_id.eq(gid)))
+        .first(conn)
+        .optional()
+        .expect("Failed to get schedule from database")
+}
+
+/// The groups that are or aren't archived, by name
+fn list_groups(conn: &SqliteConnection, show_archived: bool) -> Vec<Group> {
+    use crate::schema::groups::dsl::*;
+    groups
+        .filter(archived.eq(show_archived))
+        .order(name.asc())
+        .load(conn)
+        .expect("Failed to get groups from database")
+}
+
+fn group_meeting_ids(conn: &SqliteConnection, gid: i32) -> Vec<i32> {
+    use crate::schema::meetings::dsl::*;
+    meetings
+        .filter(group_id.eq(gid))
+        .select(id)
+        .load(conn)
+        .expect("Failed to get meetings from database")
+}
+
+/// Users who left the group, most recent first
+fn past_members(conn: &SqliteConnection, g: &Group) -> Vec<PastMember> {
+    use crate::schema::relation_group_user::dsl::left_at;
+    use crate::schema::users::dsl::users;
+    RelationGroupUser::belonging_to(g)
+        .inner_join(users)
+        .filter(left_at.is_not_null())
+        .order(left_at.desc())
+        .load::<(RelationGroupUser, User)>(conn)
+        .expect("Failed to get relations from database")
+        .into_iter()
+        .map(|(relation, user)| PastMember { user, relation })
+        .collect()
 }
 
 use crate::models::User;
-fn group_users(conn: &SqliteConnection, group: &Group) -> Vec<User> {
+pub fn group_users(conn: &SqliteConnection, group: &Group) -> Vec<User> {
+    use crate::schema::relation_group_user::dsl::left_at;
     RelationGroupUser::belonging_to(group)
+        .filter(left_at.is_null())
         .load::<RelationGroupUser>(conn)
         .expect("Failed to get relations from database")
         .iter()
@@ -7,15 +7,37 @@
 //! - `/groups`
 //! - `/groups.json`
 //! - `/groups/new`
+//! - `/groups/assign`
 //! - `/groups/<gid>`
+//! - `/groups/<gid>/calendar.ics`
 //! - `/groups/<gid>/add`
 //! - `/groups/<gid>/remove/<uid>`
 //! - `/groups/<gid>/edit`
+//! - `/groups/<gid>/mentors`
+//! - `/groups/<gid>/mentors/<uid>`
+//! - `/groups/<gid>/preferences`
+//! - `/groups/<gid>/preferences/<pid>`
+//! - `/groups/<gid>/archive`
+//! - `/groups/<gid>/unarchive`
+//! - `/groups/<gid>/delete`
 //! - `/groups/<gid>/meetings
 //! - `/groups/<gid>/meetings.json
 //! - `/groups/<gid>/meetings/new
+//! - `/groups/<gid>/meetings/<mid>`
+//! - `/groups/<gid>/meetings/<mid>/edit`
+//! - `/groups/<gid>/meetings/<mid>/cancel`
+//! - `/groups/<gid>/meetings/<mid>/restore`
+//! - `/groups/<gid>/schedules/new`
+//! - `/groups/<gid>/schedules/<sid>`
+//! - `/groups/<gid>/schedules/<sid>/edit`
+//!
+//! Groups that meet every week can have a schedule that meetings are
+//! generated from ahead of time, see `schedule`. Coordinators can assign
+//! students to groups all at once, see `assign`.
 
+pub mod assign;
 pub mod handlers;
 pub mod models;
+pub mod schedule;
 
 mod templates;
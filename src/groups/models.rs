@@ -1,9 +1,14 @@
 //!
 
-use chrono::NaiveDateTime;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use rocket::http::RawStr;
+use rocket::request::FromFormValue;
 
 use crate::models::Attendable;
-use crate::models::User;
+use crate::models::{Project, User};
 use crate::schema::*;
 
 #[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Serialize)]
@@ -12,14 +17,47 @@ pub struct Group {
     pub name: String,
     pub owner_id: i32,
     pub location: Option<String>,
+    /// Archived groups are read-only and not listed
+    pub archived: bool,
+    /// Most students the group can take, see `groups::assign`
+    pub capacity: Option<i32>,
+    /// ID of the group this group is part of
+    pub parent_id: Option<i32>,
+    /// Are new users added to the group when they sign up?
+    pub join_on_signup: bool,
+}
+
+/// IDs of the group and every group under it
+///
+/// `all` is every group. Parents that loop back around are only
+/// followed once.
+pub fn group_subtree(gid: i32, all: &[Group]) -> Vec<i32> {
+    let mut ids = vec![gid];
+    let mut i = 0;
+    while i < ids.len() {
+        let parent = ids[i];
+        for g in all {
+            if g.parent_id == Some(parent) && !ids.contains(&g.id) {
+                ids.push(g.id);
+            }
+        }
+        i += 1;
+    }
+    ids
 }
 
 #[derive(Debug, Default, Clone, FromForm, Insertable, AsChangeset)]
 #[table_name = "groups"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct NewGroup {
     pub name: String,
     pub owner_id: i32,
     pub location: Option<String>,
+    /// Most students the group can take, empty for no limit
+    pub capacity: Option<i32>,
+    /// ID of the group this group is part of, empty for none
+    pub parent_id: Option<i32>,
+    pub join_on_signup: bool,
 }
 
 #[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
@@ -30,6 +68,31 @@ pub struct Meeting {
     pub code: String,
     pub group_id: i32,
     pub hosted_by: i32,
+    /// ID of the schedule the meeting was generated from
+    pub schedule_id: Option<i32>,
+    /// Day the schedule generated the meeting for, kept when rescheduled
+    pub occurrence: Option<NaiveDate>,
+    /// Cancelled meetings can't be attended and don't count toward grades
+    pub cancelled: bool,
+    /// What the meeting is about
+    pub title: Option<String>,
+    /// Plan for the meeting as Markdown
+    pub agenda: Option<String>,
+    /// What was discussed as Markdown
+    pub notes: Option<String>,
+    /// Where the meeting is if not where the group usually meets
+    pub location: Option<String>,
+    /// ID of the room the meeting is in
+    pub room_id: Option<i32>,
+}
+
+impl Meeting {
+    /// The title, or "Meeting" if it doesn't have one
+    pub fn title_or_default(&self) -> String {
+        self.title
+            .clone()
+            .unwrap_or_else(|| String::from("Meeting"))
+    }
 }
 
 impl Attendable for Meeting {
@@ -37,7 +100,7 @@ impl Attendable for Meeting {
         self.id
     }
     fn name(&self) -> String {
-        format!("Meeting at: {}", self.happened_at)
+        self.title_or_default()
     }
     fn time(&self) -> NaiveDateTime {
         self.happened_at
@@ -58,8 +121,11 @@ impl Attendable for Meeting {
     fn is_event(&self) -> bool {
         false
     }
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
     fn url(&self) -> String {
-        format!("/h/{}", self.group_id)
+        format!("/groups/{}/meetings/{}", self.group_id, self.id)
     }
 }
 
@@ -70,6 +136,395 @@ pub struct NewMeeting {
     pub group_id: i32,
 }
 
+/// Used to insert a meeting generated from a schedule
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "meetings"]
+pub struct NewScheduledMeeting {
+    pub happened_at: NaiveDateTime,
+    pub code: String,
+    pub group_id: i32,
+    pub hosted_by: i32,
+    pub schedule_id: Option<i32>,
+    pub occurrence: Option<NaiveDate>,
+    pub room_id: Option<i32>,
+}
+
+/// The meeting editor
+///
+/// Empty text fields are cleared.
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct MeetingForm {
+    pub title: Option<String>,
+    /// The time like `2020-03-12 16:00`, changing it reschedules the meeting
+    pub happened_at: String,
+    pub location: Option<String>,
+    /// ID of the room, empty if it isn't in a room
+    pub room_id: Option<i32>,
+    pub agenda: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Used to save the meeting editor
+#[derive(Debug, Clone, AsChangeset)]
+#[table_name = "meetings"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct EditMeeting {
+    pub title: Option<String>,
+    pub happened_at: NaiveDateTime,
+    pub location: Option<String>,
+    pub room_id: Option<i32>,
+    pub agenda: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl MeetingForm {
+    /// Parse the form into the changes to the meeting
+    ///
+    /// Fails if the time can't be parsed.
+    pub fn parse(&self) -> Result<EditMeeting, chrono::ParseError> {
+        let at = self.happened_at.trim();
+        let happened_at = NaiveDateTime::parse_from_str(at, "%F %R")
+            .or_else(|_| NaiveDateTime::parse_from_str(at, "%F %T"))?;
+        let text = |t: &Option<String>| {
+            t.as_ref()
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+        };
+        Ok(EditMeeting {
+            title: text(&self.title),
+            happened_at,
+            location: text(&self.location),
+            room_id: self.room_id,
+            agenda: text(&self.agenda),
+            notes: text(&self.notes),
+        })
+    }
+}
+
+/// When a group meets every week
+///
+/// Meetings are generated from the schedule ahead of time, see
+/// `groups::schedule`.
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Group)]
+pub struct GroupSchedule {
+    pub id: i32,
+    /// ID of the group that meets
+    pub group_id: i32,
+    /// Day of the week, 0 is Monday
+    pub weekday: i32,
+    /// Time of day the meetings start
+    pub start_time: NaiveTime,
+    /// Where the group meets
+    pub location: Option<String>,
+    /// No meetings before this day
+    pub starts_on: NaiveDate,
+    /// No meetings after this day
+    pub ends_on: NaiveDate,
+    /// Days without a meeting as a JSON array
+    pub skip_dates: String,
+    /// ID of the room the group meets in
+    pub room_id: Option<i32>,
+}
+
+/// Names of the days of the week starting with Monday
+pub const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+impl GroupSchedule {
+    /// The parsed days without a meeting
+    pub fn skip_list(&self) -> Vec<NaiveDate> {
+        serde_json::from_str(&self.skip_dates).unwrap_or_default()
+    }
+
+    /// The skipped days in the format used by the form
+    pub fn skip_text(&self) -> String {
+        self.skip_list()
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Name of the day of the week like "Tuesday"
+    pub fn weekday_name(&self) -> &'static str {
+        WEEKDAYS.get(self.weekday as usize).unwrap_or(&"Unknown")
+    }
+
+    /// The days from `from` through `until` the group meets
+    ///
+    /// Only days within the term that aren't skipped are included.
+    pub fn occurrences(&self, from: NaiveDate, until: NaiveDate) -> Vec<NaiveDate> {
+        let skip = self.skip_list();
+        let until = until.min(self.ends_on);
+        let mut day = from.max(self.starts_on);
+        // Move forward to the first meeting day
+        while day.weekday().num_days_from_monday() as i32 != self.weekday && day <= until {
+            day = day.succ();
+        }
+
+        let mut days = Vec::new();
+        while day <= until {
+            if !skip.contains(&day) {
+                days.push(day);
+            }
+            day += Duration::weeks(1);
+        }
+        days
+    }
+}
+
+/// Used to create or edit a schedule
+#[derive(Debug, Clone, Insertable, AsChangeset)]
+#[table_name = "group_schedules"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NewGroupSchedule {
+    pub group_id: i32,
+    pub weekday: i32,
+    pub start_time: NaiveTime,
+    pub location: Option<String>,
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+    pub skip_dates: String,
+    pub room_id: Option<i32>,
+}
+
+/// The schedule editor
+///
+/// Dates are like `2020-01-13` and the time is like `16:00`. Skipped
+/// days are separated by commas or whitespace.
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct ScheduleForm {
+    pub weekday: i32,
+    pub start_time: String,
+    pub location: Option<String>,
+    pub starts_on: String,
+    pub ends_on: String,
+    pub skip_dates: String,
+    /// ID of the room, empty if the group doesn't meet in a room
+    pub room_id: Option<i32>,
+}
+
+impl ScheduleForm {
+    /// Parse the form into a schedule for the group
+    ///
+    /// Fails if a date or the time can't be parsed, the weekday is out of
+    /// range, or the term ends before it starts.
+    pub fn parse(&self, gid: i32) -> Result<NewGroupSchedule, ()> {
+        let date = |d: &str| NaiveDate::parse_from_str(d.trim(), "%F").map_err(|_| ());
+        let starts_on = date(&self.starts_on)?;
+        let ends_on = date(&self.ends_on)?;
+        let start_time = NaiveTime::parse_from_str(self.start_time.trim(), "%R")
+            .or_else(|_| NaiveTime::parse_from_str(self.start_time.trim(), "%T"))
+            .map_err(|_| ())?;
+        let mut skip = self
+            .skip_dates
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|d| !d.is_empty())
+            .map(date)
+            .collect::<Result<Vec<NaiveDate>, ()>>()?;
+        skip.sort();
+        skip.dedup();
+
+        if self.weekday < 0 || self.weekday > 6 || ends_on < starts_on {
+            return Err(());
+        }
+        Ok(NewGroupSchedule {
+            group_id: gid,
+            weekday: self.weekday,
+            start_time,
+            location: self
+                .location
+                .as_ref()
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty()),
+            starts_on,
+            ends_on,
+            skip_dates: serde_json::to_string(&skip).unwrap(),
+            room_id: self.room_id,
+        })
+    }
+}
+
+/// A project a group's mentor would like to have in their group
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations)]
+#[belongs_to(Group)]
+#[belongs_to(Project)]
+pub struct GroupProjectPref {
+    pub id: i32,
+    pub group_id: i32,
+    pub project_id: i32,
+}
+
+/// Used to add a project to a group's preferences
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "group_project_prefs"]
+pub struct NewGroupProjectPref {
+    pub group_id: i32,
+    pub project_id: i32,
+}
+
+/// The form to add a project to a group's preferences
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct PrefForm {
+    pub project_id: i32,
+}
+
+/// The group assignment options and, when applying, the assignment
+///
+/// Each group to assign students to is a field named `g` so they can't
+/// be derived. `assignment` is the reviewed assignment as a comma
+/// separated list of `user:group`.
+#[derive(Debug, Default, Clone)]
+pub struct AssignForm {
+    /// IDs of the groups to assign students to
+    pub groups: Vec<i32>,
+    /// Keep project teams in the same group
+    pub keep_teams: bool,
+    /// Give groups the projects their mentor asked for
+    pub prefs: bool,
+    pub assignment: Option<String>,
+}
+
+impl AssignForm {
+    /// The parsed assignment as `(user, group)`
+    pub fn pairs(&self) -> Result<Vec<(i32, i32)>, ()> {
+        self.assignment
+            .as_ref()
+            .map(|a| a.as_str())
+            .unwrap_or("")
+            .split(',')
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| {
+                let mut parts = p.trim().splitn(2, ':');
+                let u = parts.next().and_then(|u| u.parse().ok()).ok_or(())?;
+                let g = parts.next().and_then(|g| g.parse().ok()).ok_or(())?;
+                Ok((u, g))
+            })
+            .collect()
+    }
+}
+
+impl<'f> rocket::request::FromForm<'f> for AssignForm {
+    type Error = ();
+
+    fn from_form(items: &mut rocket::request::FormItems<'f>, _strict: bool) -> Result<Self, ()> {
+        let mut form = AssignForm::default();
+        for item in items {
+            let (k, v) = item.key_value_decoded();
+            match k.as_str() {
+                "g" => form.groups.push(v.parse().map_err(|_| ())?),
+                "keep_teams" => form.keep_teams = v == "on" || v == "true",
+                "prefs" => form.prefs = v == "on" || v == "true",
+                "assignment" => form.assignment = Some(v),
+                _ => {}
+            }
+        }
+        form.groups.sort();
+        form.groups.dedup();
+        Ok(form)
+    }
+}
+
+/// A mentor helping run a group along with its leader
+#[derive(Debug, PartialEq, Clone, Queryable, Associations, Identifiable)]
+#[belongs_to(Group)]
+#[belongs_to(User)]
+pub struct GroupMentor {
+    pub id: i32,
+    pub group_id: i32,
+    pub user_id: i32,
+    /// Role of the mentor in the group, see `GroupRole`
+    pub role: String,
+}
+
+/// Used to add a mentor to a group or change their role
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "group_mentors"]
+pub struct NewGroupMentor {
+    pub group_id: i32,
+    pub user_id: i32,
+    pub role: String,
+}
+
+/// The form to add a mentor to a group
+#[derive(Debug, Clone, FromForm)]
+pub struct MentorForm {
+    pub user_id: i32,
+    pub role: GroupRole,
+}
+
+/// Role of someone running a group
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GroupRole {
+    /// Runs the group, this is the group's owner
+    Leader,
+    /// Helps run the group and can do everything the leader can
+    Mentor,
+    /// Helps at meetings and can see attendance but can't change the group
+    Assistant,
+}
+
+impl GroupRole {
+    /// The value stored in the `role` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GroupRole::Leader => "leader",
+            GroupRole::Mentor => "mentor",
+            GroupRole::Assistant => "assistant",
+        }
+    }
+
+    /// Can this role change the group, its meetings, and its members?
+    pub fn can_manage(self) -> bool {
+        self != GroupRole::Assistant
+    }
+}
+
+// Converts to a human readable string like "Assistant"
+impl fmt::Display for GroupRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                GroupRole::Leader => "Leader",
+                GroupRole::Mentor => "Mentor",
+                GroupRole::Assistant => "Assistant",
+            }
+        )
+    }
+}
+
+// Converts from the value stored in the `role` column
+impl FromStr for GroupRole {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "leader" => Ok(GroupRole::Leader),
+            "mentor" => Ok(GroupRole::Mentor),
+            "assistant" => Ok(GroupRole::Assistant),
+            _ => Err(()),
+        }
+    }
+}
+
+impl<'v> FromFormValue<'v> for GroupRole {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<GroupRole, &'v RawStr> {
+        form_value.parse().map_err(|_| form_value)
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Queryable, Associations, Identifiable)]
 #[belongs_to(Group)]
 #[belongs_to(User)]
@@ -78,11 +533,40 @@ pub struct RelationGroupUser {
     pub id: i32,
     pub group_id: i32,
     pub user_id: i32,
+    /// When the user joined, `None` if it was before this was kept
+    pub joined_at: Option<NaiveDateTime>,
+    /// When the user left, `None` while they are still a member
+    pub left_at: Option<NaiveDateTime>,
+}
+
+impl RelationGroupUser {
+    /// Was the user a member of the group at `t`?
+    ///
+    /// Members from before join dates were kept were members all along.
+    pub fn member_at(&self, t: NaiveDateTime) -> bool {
+        self.joined_at.map(|j| j <= t).unwrap_or(true)
+            && self.left_at.map(|l| t < l).unwrap_or(true)
+    }
+}
+
+/// IDs of the users who were members of the group at `t`
+pub fn members_at(relations: &[RelationGroupUser], gid: i32, t: NaiveDateTime) -> Vec<i32> {
+    let mut uids: Vec<i32> = relations
+        .iter()
+        .filter(|r| r.group_id == gid && r.member_at(t))
+        .map(|r| r.user_id)
+        .collect();
+    uids.sort();
+    uids.dedup();
+    uids
 }
 
+/// Adds a user to a group, removing them sets `left_at` instead so that
+/// the membership is kept
 #[derive(Debug, Default, Clone, Insertable)]
 #[table_name = "relation_group_user"]
 pub struct NewRelationGroupUser {
     pub group_id: i32,
     pub user_id: i32,
+    pub joined_at: Option<NaiveDateTime>,
 }
@@ -0,0 +1,116 @@
+//! Generating meetings from group schedules
+//!
+//! Meetings are generated a couple weeks ahead so that mentors have the
+//! attendance code before class starts. Each generated meeting remembers
+//! the day it was generated for in `occurrence`, so a meeting that was
+//! moved to another day or cancelled is never generated again.
+
+use chrono::{Duration, Local, NaiveDate};
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel::{delete, insert_into, select};
+
+use crate::attend::code::attendance_code;
+use crate::models::{Group, GroupSchedule, NewScheduledMeeting};
+
+/// How many days ahead meetings are generated by default
+pub const DEFAULT_AHEAD: i64 = 14;
+
+/// How many days ahead meetings are generated
+///
+/// Set with `schedule_ahead` in `Rocket.toml` and managed by the
+/// `MeetingSchedules` fairing.
+pub struct ScheduleAhead(pub i64);
+
+/// Generate the meetings of every schedule up to `ahead` days from now
+///
+/// Schedules of archived groups are skipped.
+pub fn generate_all(conn: &SqliteConnection, ahead: i64) {
+    use crate::schema::group_schedules::dsl::*;
+    use crate::schema::groups::dsl::{archived, groups};
+
+    let schedules: Vec<(GroupSchedule, Group)> = group_schedules
+        .inner_join(groups)
+        .filter(archived.eq(false))
+        .load(conn)
+        .expect("Failed to get schedules from database");
+    for (s, g) in schedules.iter() {
+        generate_meetings(conn, s, g, ahead);
+    }
+}
+
+/// Generate the meetings of a schedule from today up to `ahead` days from
+/// now that don't exist yet
+///
+/// Returns how many meetings were generated.
+pub fn generate_meetings(
+    conn: &SqliteConnection,
+    sched: &GroupSchedule,
+    group: &Group,
+    ahead: i64,
+) -> usize {
+    use crate::schema::meetings::dsl::*;
+
+    let today = Local::today().naive_local();
+    let mut generated = 0;
+    for day in sched.occurrences(today, today + Duration::days(ahead)) {
+        let made: bool = select(exists(
+            meetings.filter(schedule_id.eq(sched.id).and(occurrence.eq(day))),
+        ))
+        .get_result(conn)
+        .expect("Failed to get meetings from database");
+        if made {
+            continue;
+        }
+
+        insert_into(meetings)
+            .values(&NewScheduledMeeting {
+                happened_at: day.and_time(sched.start_time),
+                code: attendance_code(conn),
+                group_id: group.id,
+                hosted_by: group.owner_id,
+                schedule_id: Some(sched.id),
+                occurrence: Some(day),
+                room_id: sched.room_id,
+            })
+            .execute(conn)
+            .expect("Failed to insert meeting into database");
+        generated += 1;
+    }
+    generated
+}
+
+/// Delete the meetings of a schedule after `after` that nobody attended
+///
+/// Used before a schedule is changed or deleted so that the meetings are
+/// generated again for the new days. Meetings that were moved or
+/// cancelled by hand are kept.
+pub fn clear_upcoming(
+    conn: &SqliteConnection,
+    sched: &GroupSchedule,
+    after: NaiveDate,
+) -> QueryResult<usize> {
+    use crate::schema::meetings::dsl::*;
+
+    let upcoming: Vec<(i32, Option<NaiveDate>, chrono::NaiveDateTime)> = meetings
+        .filter(schedule_id.eq(sched.id).and(cancelled.eq(false)))
+        .filter(occurrence.gt(after))
+        .select((id, occurrence, happened_at))
+        .load(conn)?;
+
+    let mut cleared = 0;
+    for (mid, day, at) in upcoming {
+        // Skip meetings moved to another time
+        if day.map(|d| d.and_time(sched.start_time)) != Some(at) {
+            continue;
+        }
+        let attended: bool = {
+            use crate::schema::attendances::dsl::*;
+            select(exists(attendances.filter(meeting_id.eq(mid)))).get_result(conn)?
+        };
+        if !attended {
+            cleared += delete(meetings.find(mid)).execute(conn)?;
+        }
+    }
+    Ok(cleared)
+}
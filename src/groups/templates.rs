@@ -2,9 +2,9 @@
 
 use super::models::*;
 #[allow(unused_imports)]
-use crate::templates::{filters, OptUser};
+use crate::templates::{filters, FormError, OptUser};
 
-use crate::models::User;
+use crate::models::{Project, Room, RoomOption, User};
 
 #[derive(Template)]
 #[template(path = "group/group.html")]
@@ -12,7 +12,134 @@ pub struct GroupTemplate {
     pub logged_in: OptUser,
     pub group: Group,
     pub users: Vec<User>,
+    /// Users who left the group, only shown to mentors
+    pub past_members: Vec<PastMember>,
     pub meetings: Vec<Meeting>,
+    pub schedules: Vec<ScheduleRow>,
+    /// Members with a project missing this week's status update
+    pub missing_updates: Vec<User>,
+    /// Projects the mentor asked for, see `group_assign`
+    pub preferred: Vec<Project>,
+    /// Active projects that can be asked for
+    pub projects: Vec<Project>,
+    /// The group this group is part of
+    pub parent: Option<Group>,
+    /// The groups directly under this group with their rollups
+    pub children: Vec<GroupRollup>,
+    /// Membership and attendance of the group and every group under it,
+    /// only shown to Coordinators
+    pub rollup: Option<GroupRollup>,
+    pub mentors: Vec<MentorRow>,
+    /// Can the logged in user add and remove mentors?
+    pub can_change_mentors: bool,
+    /// Users who can be added as mentors
+    pub mentor_options: Vec<User>,
+    /// Can the logged in user change the group?
+    pub can_manage: bool,
+    /// Is the logged in user a Mentor or running the group?
+    pub is_mentor: bool,
+}
+
+/// A user who left a group and when they were in it
+pub struct PastMember {
+    pub user: User,
+    pub relation: RelationGroupUser,
+}
+
+/// Someone running a group and their role
+pub struct MentorRow {
+    pub user: User,
+    pub role: GroupRole,
+}
+
+/// Membership and attendance of a group and every group under it
+pub struct GroupRollup {
+    pub group: Group,
+    /// Number of different users in the groups
+    pub members: usize,
+    /// Number of meetings that happened and weren't cancelled
+    pub meetings: usize,
+    pub attendances: usize,
+    /// Attendances if every member attended every meeting of their group
+    pub possible: usize,
+}
+
+impl GroupRollup {
+    /// Attendance as a percentage of the possible attendance
+    pub fn rate(&self) -> Option<usize> {
+        if self.possible == 0 {
+            None
+        } else {
+            Some(self.attendances * 100 / self.possible)
+        }
+    }
+}
+
+/// A schedule along with the room the group meets in
+pub struct ScheduleRow {
+    pub schedule: GroupSchedule,
+    pub room: Option<Room>,
+}
+
+/// A single meeting's page
+#[derive(Template)]
+#[template(path = "group/meeting.html")]
+pub struct MeetingTemplate {
+    pub logged_in: OptUser,
+    pub group: Group,
+    pub meeting: Meeting,
+    /// The room the meeting is in, if any
+    pub room: Option<Room>,
+    /// Where the meeting is, from the meeting, its schedule, or the group
+    pub location: Option<String>,
+    /// Can the logged in user edit the meeting?
+    pub can_manage: bool,
+    /// Is the logged in user a Mentor or running the group?
+    pub is_mentor: bool,
+    /// Who attended, only shown to Mentors and the group leader
+    pub attendees: Vec<User>,
+}
+
+#[derive(Template)]
+#[template(path = "group/edit-meeting.html")]
+pub struct EditMeetingTemplate {
+    pub logged_in: OptUser,
+    pub group: Group,
+    pub meeting: Meeting,
+    pub rooms: Vec<RoomOption>,
+    pub error: Option<FormError>,
+}
+
+/// A day of the week to pick in the schedule form
+pub struct WeekdayOption {
+    /// Value stored in `weekday`, 0 is Monday
+    pub value: i32,
+    pub name: &'static str,
+}
+
+/// The days of the week for the schedule form
+pub fn weekday_options() -> Vec<WeekdayOption> {
+    WEEKDAYS
+        .iter()
+        .enumerate()
+        .map(|(i, name)| WeekdayOption {
+            value: i as i32,
+            name: *name,
+        })
+        .collect()
+}
+
+/// Form to create or edit a group's schedule
+#[derive(Template)]
+#[template(path = "group/schedule.html")]
+pub struct ScheduleTemplate {
+    pub logged_in: OptUser,
+    pub group: Group,
+    /// The schedule being edited, if any
+    pub schedule: Option<GroupSchedule>,
+    pub weekdays: Vec<WeekdayOption>,
+    pub rooms: Vec<RoomOption>,
+    pub error: Option<FormError>,
 }
 
 #[derive(Template)]
@@ -20,6 +147,8 @@ pub struct GroupTemplate {
 pub struct NewGroupTemplate {
     pub logged_in: OptUser,
     pub all_users: Vec<User>,
+    /// Groups the new group can be part of
+    pub parents: Vec<GroupOption>,
 }
 
 #[derive(Template)]
@@ -28,13 +157,25 @@ pub struct EditGroupTemplate {
     pub logged_in: OptUser,
     pub group: Group,
     pub all_users: Vec<User>,
+    /// Groups the group can be part of, which aren't under it
+    pub parents: Vec<GroupOption>,
 }
 
 #[derive(Template)]
 #[template(path = "group/groups-list.html")]
 pub struct GroupsListTemplate {
     pub logged_in: OptUser,
-    pub groups: Vec<Group>,
+    /// The groups with each group's children right after it
+    pub groups: Vec<GroupRow>,
+    /// Are these the archived groups?
+    pub archived: bool,
+}
+
+/// A group in the groups list
+pub struct GroupRow {
+    pub group: Group,
+    /// How many groups up the top of the list the group is under
+    pub depth: usize,
 }
 
 #[derive(Template)]
@@ -44,3 +185,50 @@ pub struct AddUserTemplate {
     pub group: Group,
     pub all_users: Vec<User>,
 }
+
+/// Lists everything that will be lost before a group is deleted
+#[derive(Template)]
+#[template(path = "group/delete.html")]
+pub struct DeleteGroupTemplate {
+    pub logged_in: OptUser,
+    pub group: Group,
+    pub losses: Vec<String>,
+}
+
+/// A group that can be picked for assignment or as a parent
+pub struct GroupOption {
+    pub group: Group,
+    pub picked: bool,
+}
+
+/// The proposed members of a group
+pub struct ProposedGroup {
+    pub group: Group,
+    pub members: Vec<User>,
+    /// Does the group have more students than its capacity?
+    pub over: bool,
+}
+
+/// A student who would change groups
+pub struct Move {
+    pub user: User,
+    /// The picked groups the student is in now
+    pub from: Vec<Group>,
+    pub to: Group,
+}
+
+/// Group assignment preview
+///
+/// HTML File: `group/assign.html`
+#[derive(Template)]
+#[template(path = "group/assign.html")]
+pub struct AssignTemplate {
+    pub logged_in: OptUser,
+    pub groups: Vec<GroupOption>,
+    pub keep_teams: bool,
+    pub prefs: bool,
+    pub proposal: Vec<ProposedGroup>,
+    pub moves: Vec<Move>,
+    /// The proposal in the format read by `AssignForm`
+    pub assignment: String,
+}
@@ -11,16 +11,27 @@ use rocket::response::{Redirect, Response};
 use rocket::Request;
 
 use crate::guards::*;
+use crate::semester::Semester;
 use crate::templates::*;
 use crate::ObservDbConn;
 
 // Load all the sub-module's handlers
+pub use crate::activity::handlers::*;
 pub use crate::attend::handlers::*;
 pub use crate::auth::handlers::*;
 pub use crate::calendar::handlers::*;
+pub use crate::evaluations::handlers::*;
+pub use crate::grades::handlers::*;
 pub use crate::groups::handlers::*;
 pub use crate::news::handlers::*;
+pub use crate::notifications::handlers::*;
 pub use crate::projects::handlers::*;
+pub use crate::proposals::handlers::*;
+pub use crate::repos::handlers::*;
+pub use crate::rooms::handlers::*;
+pub use crate::showcase::handlers::*;
+pub use crate::updates::handlers::*;
+pub use crate::uploads::handlers::*;
 pub use crate::users::handlers::*;
 
 /// GET handler for `/`
@@ -55,11 +66,19 @@ pub fn big(l: MaybeLoggedIn, text: String) -> BigTemplate {
 /// GET handler for `/dashboard`
 ///
 /// The logged in user's dashboard showing their groups, projects, and attendance
-#[get("/dashboard")]
-pub fn dashboard(conn: ObservDbConn, l: UserGuard) -> DashboardTemplate {
+#[get("/dashboard?<semester>")]
+pub fn dashboard(
+    conn: ObservDbConn,
+    l: UserGuard,
+    semester: Option<Semester>,
+) -> DashboardTemplate {
+    use crate::notifications::handlers::unseen_count;
+    use crate::updates::handlers::missing_updates;
     use crate::users::handlers::{grade_summary, user_groups, user_projects};
     DashboardTemplate {
-        summary: grade_summary(&*conn, &l.0),
+        missing_updates: missing_updates(&*conn, &l.0),
+        notifications: unseen_count(&*conn, &l.0),
+        summary: grade_summary(&*conn, &l.0, &semester.unwrap_or_default()),
         projects: user_projects(&*conn, &l.0),
         groups: user_groups(&*conn, &l.0),
         logged_in: Some(l.0),
@@ -76,14 +95,14 @@ pub fn sitemap() -> SitemapTemplate {
 // For some reason it doesn't like having doc-comments on it.
 #[derive(RustEmbed)]
 #[folder = "static/"]
-struct Embed;
+pub struct Embed;
 
 /// Paths that will not be served over `/static`
 ///
 /// These files will not be served by the webserver.
 /// However they are still embedded into the binary so be careful.
 // Make sure to increment the length if you add something
-const BLACKLIST: [&str; 1] = ["README.md"];
+pub const BLACKLIST: [&str; 1] = ["README.md"];
 
 /// GET handler for static files
 ///
@@ -34,21 +34,33 @@ extern crate serde_derive;
 extern crate diesel_migrations;
 
 // Module files
+mod export;
 mod fairings;
 mod guards;
 mod handlers;
 mod schema;
+mod semester;
 mod templates;
 #[cfg(test)]
 mod tests;
 
 // Table Modules
+mod activity;
 mod attend;
 mod auth;
 mod calendar;
+mod evaluations;
+mod grades;
 mod groups;
 mod news;
+mod notifications;
 mod projects;
+mod proposals;
+mod repos;
+mod rooms;
+mod showcase;
+mod updates;
+mod uploads;
 mod users;
 
 /// The database connection
@@ -63,7 +75,9 @@ pub fn rocket(test_config: Option<rocket::Config>) -> rocket::Rocket {
     use handlers::*;
 
     // Load the fairings
-    use fairings::{AdminCheck, ConfigWrite, DatabaseCreate};
+    use fairings::{
+        AdminCheck, ConfigWrite, DatabaseCreate, MeetingSchedules, RepoCheck, SyncRepos, Uploads,
+    };
 
     let app = if test_config.is_some() {
         rocket::custom(test_config.unwrap())
@@ -76,6 +90,10 @@ pub fn rocket(test_config: Option<rocket::Config>) -> rocket::Rocket {
         // Attach fairings
         .attach(DatabaseCreate)
         .attach(AdminCheck)
+        .attach(RepoCheck)
+        .attach(SyncRepos)
+        .attach(MeetingSchedules)
+        .attach(Uploads)
         .attach(ObservDbConn::fairing())
         // Register Catchers
         .register(catchers![catch_401, catch_403, catch_404])
@@ -92,12 +110,46 @@ pub fn rocket(test_config: Option<rocket::Config>) -> rocket::Rocket {
                 // Calendar
                 calendar,
                 calendar_json,
+                calendar_ics,
+                calendar_feed,
+                calendar_feed_post,
+                calendar_feed_delete,
                 event,
+                event_rsvp_post,
+                event_rsvp_delete,
                 event_edit,
                 event_edit_put,
                 event_delete,
                 event_new,
                 event_new_post,
+                calendar_import,
+                calendar_import_post,
+                calendar_import_preview,
+                calendar_import_apply,
+                calendar_import_delete,
+                // Rooms
+                rooms,
+                room_new,
+                room_new_post,
+                room,
+                room_bookings_json,
+                room_edit,
+                room_edit_put,
+                room_delete,
+                // Showcases
+                showcase,
+                showcase_post,
+                showcase_open_post,
+                showcase_close_post,
+                showcase_entry_post,
+                showcase_entries_active_post,
+                showcase_entry_delete,
+                showcase_judge_add_post,
+                showcase_judge_delete,
+                showcase_vote_post,
+                showcase_judge,
+                showcase_score_post,
+                showcase_results,
                 // Sign Up and Log In
                 signup,
                 signup_post,
@@ -123,30 +175,99 @@ pub fn rocket(test_config: Option<rocket::Config>) -> rocket::Rocket {
                 project_new,
                 project_new_post,
                 project_delete,
+                project_delete_confirm,
+                project_archive_post,
+                project_unarchive_post,
                 project_edit,
                 project_edit_put,
+                project_tags_post,
                 project_join,
                 project_join_post,
+                project_request_accept_post,
+                project_request_decline_post,
                 project_members,
                 project_members_json,
                 project_member_add,
                 project_member_add_post,
                 project_member_delete,
+                project_member_role_post,
+                project_transfer_post,
+                project_resync,
+                // Activity
+                activity,
+                project_activity,
+                project_activity_atom,
+                // Proposals
+                proposals,
+                proposal,
+                proposal_edit_put,
+                proposal_review_post,
+                proposal_comment_post,
+                // Notifications
+                notifications,
+                notifications_seen_post,
+                // Status Updates
+                project_updates,
+                update_new,
+                update_new_post,
+                status_update,
+                update_edit,
+                update_edit_put,
+                update_comment_post,
+                update_review_post,
                 // Groups
                 group,
                 groups,
                 groups_json,
+                group_calendar_ics,
                 group_new,
                 group_new_post,
+                group_assign,
+                group_assign_post,
+                group_mentor_post,
+                group_mentor_delete,
+                group_pref_post,
+                group_pref_delete,
                 group_user_add,
                 group_user_add_post,
                 group_user_delete,
                 group_delete,
+                group_delete_confirm,
+                group_archive_post,
+                group_unarchive_post,
                 meetings,
                 meetings_json,
                 meeting_new_post,
+                meeting_cancel_post,
+                meeting_restore_post,
+                meeting,
+                meeting_edit,
+                meeting_edit_put,
+                meeting_delete,
+                schedule_new,
+                schedule_new_post,
+                schedule_edit,
+                schedule_edit_put,
+                schedule_delete,
                 group_edit,
                 group_edit_put,
+                // Evaluations
+                eval_forms,
+                eval_form_new,
+                eval_form_new_post,
+                eval_form,
+                eval_form_edit,
+                eval_form_edit_put,
+                eval_form_delete,
+                eval_form_export,
+                evaluate,
+                evaluate_post,
+                // Grades
+                grades,
+                grade,
+                grade_override_post,
+                grade_submit_post,
+                grade_reopen_post,
                 // News
                 news,
                 news_json,
@@ -158,6 +279,14 @@ pub fn rocket(test_config: Option<rocket::Config>) -> rocket::Rocket {
                 story_delete,
                 story_edit,
                 story_edit_put,
+                // Uploads
+                upload,
+                project_logo_post,
+                project_logo_delete,
+                user_avatar_post,
+                user_avatar_delete,
+                story_image_post,
+                story_image_delete,
             ],
         )
 }
@@ -167,7 +296,21 @@ pub fn rocket(test_config: Option<rocket::Config>) -> rocket::Rocket {
 /// This is the standard `main` function that acts as the start of the program.
 /// Here it loads Rocket, sets it up with the fairings and handlers,
 /// then launches the server.
+///
+/// Running `observatory export <dir>` instead writes the public pages to
+/// `<dir>` as a static site, see the `export` module.
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("export") {
+        let dir = args.get(2).map(String::as_str).unwrap_or("site");
+        if let Err(e) = export::site::export(&rocket(None), std::path::Path::new(dir)) {
+            eprintln!("\tFailed to export site to {}: {}", dir, e);
+            std::process::exit(1);
+        }
+        println!("\tExported site to {}", dir);
+        return;
+    }
+
     // Liftoff! Starts the webserver
     rocket(None).launch();
 }
@@ -181,11 +324,21 @@ pub mod models {
     use std::fmt::Debug;
 
     // Import then re-export all models
+    pub use crate::activity::models::*;
     pub use crate::attend::models::*;
     pub use crate::calendar::models::*;
+    pub use crate::evaluations::models::*;
+    pub use crate::grades::models::*;
     pub use crate::groups::models::*;
     pub use crate::news::models::*;
+    pub use crate::notifications::models::*;
     pub use crate::projects::models::*;
+    pub use crate::proposals::models::*;
+    pub use crate::repos::models::*;
+    pub use crate::rooms::models::*;
+    pub use crate::showcase::models::*;
+    pub use crate::updates::models::*;
+    pub use crate::uploads::models::*;
     pub use crate::users::models::*;
 
     /// Represents anything that can be attended such as meetings and events.
@@ -203,6 +356,10 @@ pub mod models {
             None
         }
         fn is_event(&self) -> bool;
+        /// Cancelled meetings can't be attended
+        fn is_cancelled(&self) -> bool {
+            false
+        }
         fn url(&self) -> String;
     }
 }
@@ -5,11 +5,14 @@ use diesel::{delete, insert_into, update};
 use rocket::http::{ContentType, Status};
 use rocket::request::Form;
 use rocket::response::{Redirect, Response};
+use rocket::State;
 
 use rocket_contrib::json::Json;
 
 use crate::guards::*;
 use crate::templates::FormError;
+use crate::uploads::handlers::release;
+use crate::uploads::storage::UploadStore;
 use crate::ObservDbConn;
 
 use super::models::*;
@@ -162,11 +165,26 @@ pub fn story_edit_put(
 }
 
 #[delete("/news/<nid>")]
-pub fn story_delete(conn: ObservDbConn, _l: AdminGuard, nid: i32) -> Redirect {
+pub fn story_delete(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    store: State<UploadStore>,
+    nid: i32,
+) -> Redirect {
     use crate::schema::news::dsl::*;
+    let old: Option<String> = news
+        .find(nid)
+        .select(image)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get news story from database")
+        .flatten();
     delete(news.find(nid))
         .execute(&*conn)
         .expect("Failed to delete news story from database");
+    if let Some(old) = old {
+        release(&*conn, &*store, &old);
+    }
     Redirect::to("/news")
 }
 
@@ -185,6 +203,7 @@ pub fn news_summary(conn: &SqliteConnection) -> (Vec<Event>, Vec<NewsStory>) {
             events
                 .order(start.desc())
                 .filter(start.gt(now))
+                .filter(rrule.is_null())
                 .limit(5)
                 .load(&*conn)
                 .expect("Failed to get news from database")
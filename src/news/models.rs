@@ -11,6 +11,8 @@ pub struct NewsStory {
     pub description: String,
     pub color: Option<String>,
     pub announcement: bool,
+    /// Key of the uploaded image, see `uploads`
+    pub image: Option<String>,
 }
 
 #[derive(Debug, Default, Clone, FromForm, Insertable, AsChangeset)]
@@ -0,0 +1,65 @@
+//! HTTP handlers for notifications
+
+use diesel::prelude::*;
+use diesel::{insert_into, update};
+use rocket::response::Redirect;
+
+use crate::guards::*;
+use crate::models::User;
+use crate::ObservDbConn;
+
+use super::models::*;
+use super::templates::*;
+
+/// GET handler for `/notifications`
+///
+/// The logged in user's notifications, newest first.
+#[get("/notifications")]
+pub fn notifications(conn: ObservDbConn, l: UserGuard) -> NotificationsTemplate {
+    use crate::schema::notifications::dsl::*;
+    NotificationsTemplate {
+        notifications: Notification::belonging_to(&l.0)
+            .order(created_at.desc())
+            .load(&*conn)
+            .expect("Failed to get notifications from database"),
+        logged_in: Some(l.0),
+    }
+}
+
+/// POST handler for `/notifications/seen`
+///
+/// Marks all of the logged in user's notifications as seen.
+#[post("/notifications/seen")]
+pub fn notifications_seen_post(conn: ObservDbConn, l: UserGuard) -> Redirect {
+    use crate::schema::notifications::dsl::*;
+    update(notifications.filter(user_id.eq(l.0.id)))
+        .set(seen.eq(true))
+        .execute(&*conn)
+        .expect("Failed to update notifications in database");
+    Redirect::to("/notifications")
+}
+
+//# Helper Functions
+
+/// Send a notification to a user
+pub fn notify(conn: &SqliteConnection, uid: i32, msg: &str, to: &str) {
+    use crate::schema::notifications::dsl::*;
+    insert_into(notifications)
+        .values(&NewNotification {
+            user_id: uid,
+            message: msg.to_string(),
+            link: to.to_string(),
+        })
+        .execute(conn)
+        .expect("Failed to insert notification into database");
+}
+
+/// How many notifications the user hasn't seen yet
+pub fn unseen_count(conn: &SqliteConnection, user: &User) -> usize {
+    use crate::schema::notifications::dsl::*;
+    notifications
+        .filter(user_id.eq(user.id).and(seen.eq(false)))
+        .count()
+        .get_result::<i64>(conn)
+        .expect("Failed to count notifications in database") as usize
+}
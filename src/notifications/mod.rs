@@ -0,0 +1,14 @@
+//! Notifications
+//!
+//! Short messages for a single user about something that happened, such
+//! as their project proposal being reviewed. Other modules send them with
+//! `handlers::notify`.
+//!
+//! ## Routes
+//! - `/notifications`
+//! - `/notifications/seen`
+
+pub mod handlers;
+pub mod models;
+
+mod templates;
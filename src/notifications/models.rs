@@ -0,0 +1,32 @@
+//! Models for notifications
+
+use chrono::NaiveDateTime;
+
+use crate::models::User;
+use crate::schema::*;
+
+/// A notification shown to a single user
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(User)]
+pub struct Notification {
+    /// ID of the notification
+    pub id: i32,
+    /// ID of the user being notified
+    pub user_id: i32,
+    /// The notification itself
+    pub message: String,
+    /// Where to go to see what it is about
+    pub link: String,
+    /// Has the user seen it?
+    pub seen: bool,
+    pub created_at: NaiveDateTime,
+}
+
+/// Used to send a notification
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "notifications"]
+pub struct NewNotification {
+    pub user_id: i32,
+    pub message: String,
+    pub link: String,
+}
@@ -0,0 +1,13 @@
+use super::models::*;
+#[allow(unused_imports)]
+use crate::templates::{filters, OptUser};
+
+/// The logged in user's notifications
+///
+/// HTML File: `notifications/notifications.html`
+#[derive(Template)]
+#[template(path = "notifications/notifications.html")]
+pub struct NotificationsTemplate {
+    pub logged_in: OptUser,
+    pub notifications: Vec<Notification>,
+}
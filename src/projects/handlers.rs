@@ -1,39 +1,73 @@
+use chrono::Local;
 use diesel::prelude::*;
-use diesel::{delete, insert_into, update};
+use diesel::{delete, insert_into, replace_into, update};
 use rocket::http::Status;
-use rocket::request::Form;
+use rocket::request::{Form, LenientForm};
 use rocket::response::Redirect;
+use rocket::State;
 
 use rocket_contrib::json::Json;
 use serde_json;
 
+use crate::activity::handlers::log_activity;
+use crate::activity::models::ActivityKind;
 use crate::guards::*;
+use crate::notifications::handlers::notify;
+use crate::proposals::models::{NewProjectProposal, ProposalForm};
+use crate::repos::handlers::project_syncs;
+use crate::repos::url::{normalize, RepoUrl};
+use crate::semester::Semester;
+use crate::showcase::handlers::delete_entries;
+use crate::templates::FormError;
+use crate::uploads::handlers::release;
+use crate::uploads::storage::UploadStore;
 use crate::ObservDbConn;
 
 use super::models::*;
 use super::templates::*;
 
-/// GET handler for `/projects?s`
-/// Project list page with an optional search string,
+/// GET handler for `/projects?s&active&external&language&tag&semester`
+/// Project list page with an optional search string and facets
 
-#[get("/projects?<s>")]
-pub fn projects(conn: ObservDbConn, l: MaybeLoggedIn, s: Option<String>) -> ProjectsListTemplate {
+#[get("/projects?<q..>")]
+pub fn projects(
+    conn: ObservDbConn,
+    l: MaybeLoggedIn,
+    q: LenientForm<ProjectQuery>,
+) -> ProjectsListTemplate {
+    let q = q.into_inner();
+    let found = search_projects(&*conn, &q);
     ProjectsListTemplate {
         logged_in: l.user(),
-        projects: filter_projects(&*conn, s),
+        languages: facets(found.iter().flat_map(|p| p.languages.iter())),
+        tags: facets(found.iter().flat_map(|p| p.tags.iter())),
+        projects: found,
+        term: q.s.unwrap_or_default(),
+        active: q.active.map(|a| a.to_string()).unwrap_or_default(),
+        external: q.external.map(|e| e.to_string()).unwrap_or_default(),
+        language: q.language.unwrap_or_default(),
+        tag: q.tag.unwrap_or_default(),
+        semester: q.semester.map(|s| s.id()).unwrap_or_default(),
+        archived: q.archived.unwrap_or(false),
     }
 }
 
-/// GET handler for `/projects?s`
-/// Return JSON object of the project with an optional search string
+/// GET handler for `/projects.json?s&active&external&language&tag&semester`
+/// Return JSON object of the projects with an optional search string and facets
 
-#[get("/projects.json?<s>")]
-pub fn projects_json(conn: ObservDbConn, s: Option<String>) -> Json<Vec<Project>> {
-    Json(filter_projects(&*conn, s))
+#[get("/projects.json?<q..>")]
+pub fn projects_json(conn: ObservDbConn, q: LenientForm<ProjectQuery>) -> Json<Vec<Project>> {
+    Json(
+        search_projects(&*conn, &*q)
+            .into_iter()
+            .map(|p| p.project)
+            .collect(),
+    )
 }
 
 /// GET handler for `/projects/id`
 /// Gets an indivual project from the data base by its ID and returns its template
+/// Projects that haven't been approved are only shown to their members and coordinators
 
 #[get("/projects/<n>")]
 pub fn project(conn: ObservDbConn, l: MaybeLoggedIn, n: i32) -> Option<ProjectTemplate> {
@@ -45,10 +79,47 @@ pub fn project(conn: ObservDbConn, l: MaybeLoggedIn, n: i32) -> Option<ProjectTe
         .optional()
         .expect("Failed to get project from database")?;
 
+    let members = project_member_entries(&*conn, &p);
+    let lu = l.user();
+    let role = lu
+        .as_ref()
+        .and_then(|u| members.iter().find(|m| m.user.id == u.id))
+        .map(|m| m.role);
+    if !p.approved() {
+        match &lu {
+            Some(u) if u.tier > 1 || role.is_some() => (),
+            _ => return None,
+        }
+    }
+
+    let can_manage = match &lu {
+        Some(u) => {
+            !p.archived && (u.tier > 1 || role.map(ProjectRole::can_manage).unwrap_or(false))
+        }
+        None => false,
+    };
+    let is_lead = match &lu {
+        Some(u) => !p.archived && (u.tier > 1 || u.id == p.owner_id),
+        None => false,
+    };
+
+    // Pending join requests are only shown to those who can accept them
+    let requests = match &lu {
+        Some(u) if !p.archived && (u.tier > 0 || can_manage) => join_requests(&*conn, &p),
+        _ => Vec::new(),
+    };
+
     Some(ProjectTemplate {
-        logged_in: l.user(),
+        logged_in: lu,
+        tags: project_tags(&*conn, &p),
+        languages: project_languages(&*conn, &p),
         repos: project_repos(&p),
-        users: project_users(&*conn, &p),
+        syncs: project_syncs(&*conn, &p),
+        is_member: role.is_some(),
+        members,
+        can_manage,
+        is_lead,
+        requests,
         project: p,
     })
 }
@@ -71,39 +142,46 @@ pub fn project_by_handle(conn: ObservDbConn, _l: MaybeLoggedIn, n: String) -> Op
 /// GET handler for `/projects/new`
 /// Returns the new project template
 
-#[get("/projects/new")]
-pub fn project_new(l: UserGuard) -> NewProjectTemplate {
+#[get("/projects/new?<e>")]
+pub fn project_new(l: UserGuard, e: Option<FormError>) -> NewProjectTemplate {
     NewProjectTemplate {
         logged_in: Some(l.0),
+        error: e,
     }
 }
 
 /// POST `/project/new`
-/// Accepts the data from the new project template form and creates the project in the data base
+/// Accepts the data from the new project template form and creates the project as a proposal.
+/// It isn't listed or joinable until a coordinator approves it.
 
-#[post("/projects/new", data = "<newproject>")]
+#[post("/projects/new", data = "<proposalform>")]
 pub fn project_new_post(
     conn: ObservDbConn,
     l: UserGuard,
-    newproject: Form<NewProject>,
+    proposalform: Form<ProposalForm>,
 ) -> Redirect {
-    let mut newproject = newproject.into_inner();
-    newproject.owner_id = l.0.id; // set owner to be the person who created the project
-
-    // handles the fact that projects can have multiple repos
-    newproject.repos = serde_json::to_string(
-        &serde_json::from_str::<Vec<String>>(&newproject.repos)
-            .unwrap()
-            .iter()
-            .filter(|s| !s.is_empty())
-            .collect::<Vec<&String>>(),
-    )
-    .unwrap();
+    let form = proposalform.into_inner();
+    let repo_list = match parse_repos(&*conn, &form.repos, None) {
+        Ok(r) => r,
+        Err(e) => return Redirect::to(format!("/projects/new?e={}", e)),
+    };
+    let newproject = NewProject {
+        name: form.name,
+        description: form.description,
+        homepage: form.homepage,
+        owner_id: l.0.id, // set owner to be the person who created the project
+        // handles the fact that projects can have multiple repos
+        repos: serde_json::to_string(&repo_list).unwrap(),
+        extrn: form.extrn,
+        join_policy: JoinPolicy::Open.as_str().to_string(),
+        max_size: None,
+        language: None,
+    };
 
     // inserts the project into the data base
     use crate::schema::projects::dsl::*;
     insert_into(projects)
-        .values(&newproject)
+        .values((&newproject, status.eq(ProjectStatus::Proposed.as_str())))
         .execute(&*conn)
         .expect("Failed to insert project into database");
 
@@ -113,45 +191,68 @@ pub fn project_new_post(
         .first(&*conn)
         .expect("Failed to get project from database");
 
+    {
+        use crate::schema::project_proposals::dsl::*;
+        insert_into(project_proposals)
+            .values(&NewProjectProposal {
+                project_id: p.id,
+                goals: form.goals,
+                stack: form.stack,
+                license: form.license,
+                team_size: form.team_size,
+            })
+            .execute(&*conn)
+            .expect("Failed to insert proposal into database");
+    }
+
     //creates the relation for the project owner
     use crate::schema::relation_project_user::dsl::*;
     insert_into(relation_project_user)
         .values(&NewRelationProjectUser {
             project_id: p.id,
             user_id: l.0.id,
+            role: ProjectRole::Lead.as_str().to_string(),
+            joined_at: Some(Local::now().naive_local()),
         })
         .execute(&*conn)
         .expect("Failed to add user to project");
-
-    Redirect::to(format!("/projects/{}", p.id))
+    log_activity(
+        &*conn,
+        p.id,
+        Some(l.0.id),
+        ActivityKind::Edit,
+        "proposed the project",
+        &format!("/projects/{}/proposal", p.id),
+    );
+
+    Redirect::to(format!("/projects/{}/proposal", p.id))
 }
 
 /// GET handler for `/projects/edit`
 /// Get the project template for editing
 
-#[get("/projects/<h>/edit")]
+#[get("/projects/<h>/edit?<e>")]
 pub fn project_edit(
     conn: ObservDbConn,
     l: UserGuard,
     h: i32,
+    e: Option<FormError>,
 ) -> Result<EditProjectTemplate, Status> {
     use crate::schema::projects::dsl::*;
-    use crate::schema::users::dsl::*;
 
     let p: Project = projects
         .find(h)
         .first(&*conn)
         .expect("Failed to get project from database");
 
-    //checks to see what tier logged in user is or if there the owner
-    if l.0.tier > 1 || p.owner_id == l.0.id {
+    //checks to see what tier logged in user is or if they can manage the project
+    if can_manage_project(&*conn, &l.0, &p) {
         Ok(EditProjectTemplate {
             logged_in: Some(l.0),
+            tags: project_tags(&*conn, &p).join(", "),
             repos: project_repos(&p),
             project: p,
-            all_users: users
-                .load(&*conn)
-                .expect("Failed to get users from database"),
+            error: e,
         })
     } else {
         Err(Status::Unauthorized)
@@ -171,55 +272,240 @@ pub fn project_edit_put(
     use crate::schema::projects::dsl::*;
 
     let mut editproject = editproject.into_inner();
-    editproject.repos = serde_json::to_string(
-        &serde_json::from_str::<Vec<String>>(&editproject.repos)
-            .unwrap()
-            .iter()
-            .filter(|s| !s.is_empty())
-            .collect::<Vec<&String>>(),
-    )
-    .unwrap();
+    if editproject.join_policy.parse::<JoinPolicy>().is_err() {
+        editproject.join_policy = JoinPolicy::Closed.as_str().to_string();
+    }
+    editproject.language = editproject
+        .language
+        .map(|lang| lang.trim().to_string())
+        .filter(|lang| !lang.is_empty());
 
     let p: Project = projects
         .find(h)
         .first(&*conn)
         .expect("Failed to get project from database");
 
-    //checks to see what tier logged in user is or if there the owner so no one outside the project messes with it
-    if l.0.tier > 1 || p.owner_id == l.0.id {
+    //checks to see what tier logged in user is or their role so no one outside the project messes with it
+    if can_manage_project(&*conn, &l.0, &p) {
+        // The lead can only be changed by transferring the project
+        editproject.owner_id = p.owner_id;
+        editproject.repos = match parse_repos(&*conn, &editproject.repos, Some(h)) {
+            Ok(r) => serde_json::to_string(&r).unwrap(),
+            Err(e) => return Ok(Redirect::to(format!("/projects/{}/edit?e={}", h, e))),
+        };
         update(projects.find(h))
             .set(&editproject)
             .execute(&*conn)
             .expect("Failed to update project in database");
+        log_activity(
+            &*conn,
+            h,
+            Some(l.0.id),
+            ActivityKind::Edit,
+            "edited the project",
+            &format!("/projects/{}", h),
+        );
         Ok(Redirect::to(format!("/projects/{}", h)))
     } else {
         Err(Status::Unauthorized)
     }
 }
 
+/// POST handler for `/projects/h/tags`
+/// Replaces the tags of the project
+
+#[post("/projects/<h>/tags", data = "<tagsform>")]
+pub fn project_tags_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    tagsform: Form<TagsForm>,
+) -> Result<Redirect, Status> {
+    let p: Project = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .find(h)
+            .first(&*conn)
+            .expect("Failed to get project from database")
+    };
+
+    if !can_manage_project(&*conn, &l.0, &p) {
+        return Err(Status::Unauthorized);
+    }
+
+    let new_tags: Vec<NewProjectTag> = parse_tags(&tagsform.tags)
+        .into_iter()
+        .map(|t| NewProjectTag {
+            project_id: h,
+            tag: t,
+        })
+        .collect();
+
+    use crate::schema::project_tags::dsl::*;
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        delete(project_tags.filter(project_id.eq(h))).execute(&*conn)?;
+        insert_into(project_tags)
+            .values(&new_tags)
+            .execute(&*conn)?;
+        Ok(())
+    })
+    .expect("Failed to update tags in database");
+    log_activity(
+        &*conn,
+        h,
+        Some(l.0.id),
+        ActivityKind::Edit,
+        "changed the tags",
+        &format!("/projects/{}", h),
+    );
+
+    Ok(Redirect::to(format!("/projects/{}", h)))
+}
+
+/// POST handler for `/projects/h/archive`
+/// Archives the project, making it read-only and hiding it from the project list
+/// Past members still see it on their profiles
+
+#[post("/projects/<h>/archive")]
+pub fn project_archive_post(conn: ObservDbConn, l: UserGuard, h: i32) -> Result<Redirect, Status> {
+    set_project_archived(&*conn, &l.0, h, true)
+}
+
+/// POST handler for `/projects/h/unarchive`
+/// Brings an archived project back
+
+#[post("/projects/<h>/unarchive")]
+pub fn project_unarchive_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+) -> Result<Redirect, Status> {
+    set_project_archived(&*conn, &l.0, h, false)
+}
+
+/// GET handler for `/projects/h/delete`
+/// Confirmation page listing everything that deleting the project would lose
+
+#[get("/projects/<h>/delete")]
+pub fn project_delete_confirm(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    h: i32,
+) -> Result<DeleteProjectTemplate, Status> {
+    let p: Project = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .find(h)
+            .first(&*conn)
+            .optional()
+            .expect("Failed to get project from database")
+            .ok_or(Status::NotFound)?
+    };
+
+    Ok(DeleteProjectTemplate {
+        logged_in: Some(l.0),
+        losses: project_losses(&*conn, &p),
+        project: p,
+    })
+}
+
 /// DELETE handler for `/projects/h`
-/// Deletes relation from all users tied to the project then deletes the project
+/// Permanently deletes the project along with its members, status updates,
+/// proposal, repository cache, showcase entries, and group preferences.
+/// Only admins can do this, everyone else should archive the project
+/// instead.
 
 #[delete("/projects/<h>")]
-pub fn project_delete(conn: ObservDbConn, l: UserGuard, h: i32) -> Result<Redirect, Status> {
-    use crate::schema::projects::dsl::*;
-    let p: Project = projects
-        .find(h)
-        .first(&*conn)
-        .expect("Failed to get project from database");
+pub fn project_delete(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    store: State<UploadStore>,
+    h: i32,
+) -> Result<Redirect, Status> {
+    let old: Option<String> = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .find(h)
+            .select(logo)
+            .first(&*conn)
+            .optional()
+            .expect("Failed to get project from database")
+            .flatten()
+    };
 
-    if l.0.tier > 1 || p.owner_id == l.0.id {
-        use crate::schema::relation_project_user::dsl::*;
-        delete(relation_project_user.filter(project_id.eq(h)))
-            .execute(&*conn)
-            .expect("Failed to delete relations from database");
-        delete(projects.find(h))
-            .execute(&*conn)
-            .expect("Failed to delete project from database");
-        Ok(Redirect::to("/projects"))
-    } else {
-        Err(Status::Unauthorized)
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        {
+            use crate::schema::status_update_comments::dsl::*;
+            use crate::schema::status_updates::dsl::{id, project_id, status_updates};
+            let uids = status_updates.filter(project_id.eq(h)).select(id);
+            delete(status_update_comments.filter(update_id.eq_any(uids))).execute(&*conn)?;
+            delete(status_updates.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::project_proposals::dsl::{id, project_id, project_proposals};
+            use crate::schema::proposal_comments::dsl::*;
+            let pids = project_proposals.filter(project_id.eq(h)).select(id);
+            delete(proposal_comments.filter(proposal_id.eq_any(pids))).execute(&*conn)?;
+            delete(project_proposals.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::join_requests::dsl::*;
+            delete(join_requests.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::project_tags::dsl::*;
+            delete(project_tags.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::project_activity::dsl::*;
+            delete(project_activity.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::repo_commits::dsl::*;
+            delete(repo_commits.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::repo_pulls::dsl::*;
+            delete(repo_pulls.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::repo_reviews::dsl::*;
+            delete(repo_reviews.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::repo_issues::dsl::*;
+            delete(repo_issues.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::repo_syncs::dsl::*;
+            delete(repo_syncs.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::showcase_entries::dsl::*;
+            let eids: Vec<i32> = showcase_entries
+                .filter(project_id.eq(h))
+                .select(id)
+                .load(&*conn)?;
+            delete_entries(&*conn, eids)?;
+        }
+        {
+            use crate::schema::group_project_prefs::dsl::*;
+            delete(group_project_prefs.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        {
+            use crate::schema::relation_project_user::dsl::*;
+            delete(relation_project_user.filter(project_id.eq(h))).execute(&*conn)?;
+        }
+        use crate::schema::projects::dsl::*;
+        delete(projects.find(h)).execute(&*conn)?;
+        Ok(())
+    })
+    .expect("Failed to delete project from database");
+    if let Some(old) = old {
+        release(&*conn, &*store, &old);
     }
+
+    Ok(Redirect::to("/projects"))
 }
 
 /// GET handler for `/projects/h/members`
@@ -247,11 +533,12 @@ pub fn project_members_json(conn: ObservDbConn, h: i32) -> Json<Vec<User>> {
 /// GET handler for `/projects/h/members/add`
 /// Returns the member add page
 
-#[get("/projects/<h>/members/add")]
+#[get("/projects/<h>/members/add?<e>")]
 pub fn project_member_add(
     conn: ObservDbConn,
     l: UserGuard,
     h: i32,
+    e: Option<FormError>,
 ) -> Result<AddUserTemplate, Status> {
     let p: Project = {
         use crate::schema::projects::dsl::*;
@@ -261,12 +548,17 @@ pub fn project_member_add(
             .expect("Failed to get project from database")
     };
 
+    // Archived projects are read-only
+    if p.archived {
+        return Err(Status::Conflict);
+    }
+
     let pu = project_users(&*conn, &p);
 
     use crate::schema::users::dsl::*;
 
-    //checks to see what tier your logged into
-    if l.0.tier > 0 || l.0.id == p.owner_id {
+    //checks to see what tier your logged into or your role in the project
+    if l.0.tier > 0 || can_manage_project(&*conn, &l.0, &p) {
         Ok(AddUserTemplate {
             logged_in: Some(l.0),
             project: p,
@@ -276,10 +568,11 @@ pub fn project_member_add(
                     .load(&*conn)
                     .expect("Failed to get users from database")
                     .iter()
-                    .filter(|&e| !pu.contains(e))
+                    .filter(|&u| !pu.contains(u))
                     .cloned()
                     .collect()
             },
+            error: e,
         })
     } else {
         Err(Status::Unauthorized)
@@ -311,16 +604,39 @@ pub fn project_member_add_post(
             .expect("Failed to get project from database")
     };
 
+    // Archived projects are read-only
+    if p.archived {
+        return Err(Status::Conflict);
+    }
+
     //checks to see if your the right tier so you cant jsut send what you want
-    if l.0.tier > 0 || l.0.id == p.owner_id {
+    if l.0.tier > 0 || (can_manage_project(&*conn, &l.0, &p) && p.policy() != JoinPolicy::Closed) {
+        if p.is_full(project_users(&*conn, &p).len()) {
+            return Ok(Redirect::to(format!(
+                "/projects/{}/members/add?e={}",
+                h,
+                FormError::ProjectFull
+            )));
+        }
+        let uid = userid.into_inner().uid;
         use crate::schema::relation_project_user::dsl::*;
         insert_into(relation_project_user)
             .values(&NewRelationProjectUser {
                 project_id: h,
-                user_id: userid.into_inner().uid,
+                user_id: uid,
+                role: ProjectRole::Member.as_str().to_string(),
+                joined_at: Some(Local::now().naive_local()),
             })
             .execute(&*conn)
             .expect("Failed to insert relation into database");
+        log_activity(
+            &*conn,
+            h,
+            Some(l.0.id),
+            ActivityKind::Member,
+            &format!("added {}", user_name(&*conn, uid)),
+            &format!("/users/{}", uid),
+        );
         Ok(Redirect::to(format!("/projects/{}", h)))
     } else {
         Err(Status::Unauthorized)
@@ -337,20 +653,47 @@ pub fn project_member_delete(
     h: i32,
     uid: i32,
 ) -> Result<Redirect, Status> {
-    let owner_id: i32 = {
+    let p: Project = {
         use crate::schema::projects::dsl::*;
         projects
             .find(h)
-            .select(owner_id)
             .first(&*conn)
             .expect("Failed to get project from database")
     };
 
-    if l.0.tier > 0 || l.0.id == owner_id {
+    // Archived projects are read-only
+    if p.archived {
+        return Err(Status::Conflict);
+    }
+
+    // The lead has to transfer the project before they can be removed
+    if uid == p.owner_id {
+        return Err(Status::Conflict);
+    }
+
+    if l.0.tier > 0 || can_manage_project(&*conn, &l.0, &p) {
         use crate::schema::relation_project_user::dsl::*;
-        delete(relation_project_user.filter(project_id.eq(h).and(user_id.eq(uid))))
-            .execute(&*conn)
-            .expect("Failed to delete relation from database");
+        update(
+            relation_project_user
+                .filter(project_id.eq(h).and(user_id.eq(uid)))
+                .filter(left_at.is_null()),
+        )
+        .set(left_at.eq(Local::now().naive_local()))
+        .execute(&*conn)
+        .expect("Failed to remove user from project in database");
+        let msg = if uid == l.0.id {
+            String::from("left the project")
+        } else {
+            format!("removed {}", user_name(&*conn, uid))
+        };
+        log_activity(
+            &*conn,
+            h,
+            Some(l.0.id),
+            ActivityKind::Member,
+            &msg,
+            &format!("/projects/{}", h),
+        );
         Ok(Redirect::to(format!("/projects/{}", h)))
     } else {
         Err(Status::Unauthorized)
@@ -360,69 +703,562 @@ pub fn project_member_delete(
 ///GET handler for `projects/h/members/join`
 /// Returns the join page for a particular project
 
-#[get("/projects/<h>/members/join")]
-pub fn project_join(conn: ObservDbConn, l: UserGuard, h: i32) -> JoinTemplate {
+#[get("/projects/<h>/members/join?<e>")]
+pub fn project_join(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    e: Option<FormError>,
+) -> JoinTemplate {
     use crate::schema::projects::dsl::*;
+    let p: Project = projects
+        .find(h)
+        .first(&*conn)
+        .expect("Failed to get project from database");
+
     JoinTemplate {
+        requested: join_requests(&*conn, &p)
+            .iter()
+            .any(|r| r.request.user_id == l.0.id),
+        full: p.is_full(project_users(&*conn, &p).len()),
         logged_in: Some(l.0),
-        project: projects
-            .find(h)
-            .first(&*conn)
-            .expect("Failed to get project from database"),
+        project: p,
+        error: e,
     }
 }
 
 ///POST handler for `projects/h/members/join`
-/// The User confirms they want to join the project user relation added to project database
+/// The User confirms they want to join the project
+/// If the project is open they are added right away, if it requires approval a join request is made
 
-#[post("/projects/<h>/members/join")]
-pub fn project_join_post(conn: ObservDbConn, l: UserGuard, h: i32) -> Result<Redirect, Status> {
+#[post("/projects/<h>/members/join", data = "<joinform>")]
+pub fn project_join_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    joinform: Form<JoinForm>,
+) -> Result<Redirect, Status> {
     use crate::schema::projects::dsl::*;
 
-    let a: bool = projects
-        .select(active)
+    let p: Project = projects
         .find(h)
         .first(&*conn)
         .expect("Failed to get project from database");
 
-    if a {
+    // Only approved projects can be joined
+    if !p.active || !p.approved() || p.archived {
+        return Err(Status::Conflict);
+    }
+
+    if p.is_full(project_users(&*conn, &p).len()) {
+        return Ok(Redirect::to(format!(
+            "/projects/{}/members/join?e={}",
+            h,
+            FormError::ProjectFull
+        )));
+    }
+
+    match p.policy() {
+        JoinPolicy::Open => {
+            use crate::schema::relation_project_user::dsl::*;
+            insert_into(relation_project_user)
+                .values(&NewRelationProjectUser {
+                    project_id: h,
+                    user_id: l.0.id,
+                    role: ProjectRole::Member.as_str().to_string(),
+                    joined_at: Some(Local::now().naive_local()),
+                })
+                .execute(&*conn)
+                .expect("Failed to add relation to database");
+            log_activity(
+                &*conn,
+                h,
+                Some(l.0.id),
+                ActivityKind::Member,
+                "joined the project",
+                &format!("/projects/{}", h),
+            );
+            Ok(Redirect::to(format!("/projects/{}", h)))
+        }
+        JoinPolicy::Approval => {
+            use crate::schema::join_requests::dsl::*;
+            replace_into(join_requests)
+                .values(&NewJoinRequest {
+                    project_id: h,
+                    user_id: l.0.id,
+                    message: joinform.into_inner().message,
+                })
+                .execute(&*conn)
+                .expect("Failed to insert join request into database");
+            notify(
+                &*conn,
+                p.owner_id,
+                &format!("{} asked to join {}", l.0.real_name, p.name),
+                &format!("/projects/{}", h),
+            );
+            Ok(Redirect::to(format!("/projects/{}/members/join", h)))
+        }
+        JoinPolicy::Closed | JoinPolicy::Invite => Err(Status::Conflict),
+    }
+}
+
+///POST handler for `projects/h/requests/rid/accept`
+/// Accepts a join request, adding the user to the project
+
+#[post("/projects/<h>/requests/<rid>/accept")]
+pub fn project_request_accept_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    rid: i32,
+) -> Result<Redirect, Status> {
+    let (p, r) = get_join_request(&*conn, h, rid).ok_or(Status::NotFound)?;
+
+    // Archived projects are read-only
+    if p.archived {
+        return Err(Status::Conflict);
+    }
+
+    if !(l.0.tier > 0 || can_manage_project(&*conn, &l.0, &p)) {
+        return Err(Status::Unauthorized);
+    }
+
+    if p.is_full(project_users(&*conn, &p).len()) {
+        return Ok(Redirect::to(format!(
+            "/projects/{}/members/add?e={}",
+            h,
+            FormError::ProjectFull
+        )));
+    }
+
+    {
         use crate::schema::relation_project_user::dsl::*;
         insert_into(relation_project_user)
             .values(&NewRelationProjectUser {
                 project_id: h,
-                user_id: l.0.id,
+                user_id: r.user_id,
+                role: ProjectRole::Member.as_str().to_string(),
+                joined_at: Some(Local::now().naive_local()),
             })
             .execute(&*conn)
             .expect("Failed to add relation to database");
-        Ok(Redirect::to(format!("/projects/{}", h)))
-    } else {
-        Err(Status::Conflict)
     }
+    delete_join_request(&*conn, rid);
+    log_activity(
+        &*conn,
+        h,
+        Some(l.0.id),
+        ActivityKind::Member,
+        &format!(
+            "accepted {}'s request to join",
+            user_name(&*conn, r.user_id)
+        ),
+        &format!("/users/{}", r.user_id),
+    );
+    notify(
+        &*conn,
+        r.user_id,
+        &format!("Your request to join {} was accepted", p.name),
+        &format!("/projects/{}", h),
+    );
+
+    Ok(Redirect::to(format!("/projects/{}", h)))
+}
+
+///POST handler for `projects/h/requests/rid/decline`
+/// Declines a join request
+
+#[post("/projects/<h>/requests/<rid>/decline")]
+pub fn project_request_decline_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    rid: i32,
+) -> Result<Redirect, Status> {
+    let (p, r) = get_join_request(&*conn, h, rid).ok_or(Status::NotFound)?;
+
+    // Archived projects are read-only
+    if p.archived {
+        return Err(Status::Conflict);
+    }
+
+    if !(l.0.tier > 0 || can_manage_project(&*conn, &l.0, &p)) {
+        return Err(Status::Unauthorized);
+    }
+
+    delete_join_request(&*conn, rid);
+    notify(
+        &*conn,
+        r.user_id,
+        &format!("Your request to join {} was declined", p.name),
+        &format!("/projects/{}", h),
+    );
+
+    Ok(Redirect::to(format!("/projects/{}", h)))
+}
+
+///Form struct for changing a member's role
+
+#[derive(FromForm)]
+pub struct RoleForm {
+    pub role: ProjectRole,
+}
+
+///POST handler for `projects/h/members/uid/role`
+/// Changes the role of a member, only the lead and coordinators can do this
+/// The lead role can only be given by transferring the project
+
+#[post("/projects/<h>/members/<uid>/role", data = "<roleform>")]
+pub fn project_member_role_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    uid: i32,
+    roleform: Form<RoleForm>,
+) -> Result<Redirect, Status> {
+    let p: Project = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .find(h)
+            .first(&*conn)
+            .expect("Failed to get project from database")
+    };
+
+    // Archived projects are read-only
+    if p.archived {
+        return Err(Status::Conflict);
+    }
+
+    if !(l.0.tier > 1 || l.0.id == p.owner_id) {
+        return Err(Status::Unauthorized);
+    }
+
+    let new_role = roleform.into_inner().role;
+    if new_role == ProjectRole::Lead || uid == p.owner_id {
+        return Err(Status::Conflict);
+    }
+
+    use crate::schema::relation_project_user::dsl::*;
+    let changed = update(
+        relation_project_user
+            .filter(project_id.eq(h).and(user_id.eq(uid)))
+            .filter(left_at.is_null()),
+    )
+    .set(role.eq(new_role.as_str()))
+    .execute(&*conn)
+    .expect("Failed to update relation in database");
+    if changed == 0 {
+        return Err(Status::NotFound);
+    }
+    log_activity(
+        &*conn,
+        h,
+        Some(l.0.id),
+        ActivityKind::Member,
+        &format!("made {} a {}", user_name(&*conn, uid), new_role.as_str()),
+        &format!("/users/{}", uid),
+    );
+
+    Ok(Redirect::to(format!("/projects/{}", h)))
+}
+
+///POST handler for `projects/h/transfer`
+/// Makes another member the lead and owner of the project
+/// The old lead stays on as a maintainer
+
+#[post("/projects/<h>/transfer", data = "<userid>")]
+pub fn project_transfer_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    userid: Form<UserId>,
+) -> Result<Redirect, Status> {
+    let p: Project = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .find(h)
+            .first(&*conn)
+            .expect("Failed to get project from database")
+    };
+
+    // Archived projects are read-only
+    if p.archived {
+        return Err(Status::Conflict);
+    }
+
+    if !(l.0.tier > 1 || l.0.id == p.owner_id) {
+        return Err(Status::Unauthorized);
+    }
+
+    let uid = userid.into_inner().uid;
+    if uid == p.owner_id {
+        return Ok(Redirect::to(format!("/projects/{}", h)));
+    }
+    // The new lead has to already be on the project
+    if project_role(&*conn, &p, uid).is_none() {
+        return Err(Status::Conflict);
+    }
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        {
+            use crate::schema::relation_project_user::dsl::*;
+            update(
+                relation_project_user
+                    .filter(project_id.eq(h).and(user_id.eq(p.owner_id)))
+                    .filter(left_at.is_null()),
+            )
+            .set(role.eq(ProjectRole::Maintainer.as_str()))
+            .execute(&*conn)?;
+            update(
+                relation_project_user
+                    .filter(project_id.eq(h).and(user_id.eq(uid)))
+                    .filter(left_at.is_null()),
+            )
+            .set(role.eq(ProjectRole::Lead.as_str()))
+            .execute(&*conn)?;
+        }
+        use crate::schema::projects::dsl::*;
+        update(projects.find(h))
+            .set(owner_id.eq(uid))
+            .execute(&*conn)?;
+        Ok(())
+    })
+    .expect("Failed to transfer project in database");
+    log_activity(
+        &*conn,
+        h,
+        Some(l.0.id),
+        ActivityKind::Member,
+        &format!("made {} the lead", user_name(&*conn, uid)),
+        &format!("/users/{}", uid),
+    );
+
+    notify(
+        &*conn,
+        uid,
+        &format!("You are now the lead of {}", p.name),
+        &format!("/projects/{}", h),
+    );
+
+    Ok(Redirect::to(format!("/projects/{}", h)))
 }
 
 //# Helper Functions
 
 pub fn project_repos(p: &Project) -> Vec<String> {
-    serde_json::from_str(&p.repos).unwrap()
+    serde_json::from_str(&p.repos).unwrap_or_default()
 }
 
-pub fn filter_projects(conn: &SqliteConnection, term: Option<String>) -> Vec<Project> {
+/// Parse the repositories from a project form
+///
+/// The form sends them as a JSON array. Empty entries are skipped and the
+/// rest are normalized, see `RepoUrl`. Repositories already listed on a
+/// project other than `except` are rejected.
+pub fn parse_repos(
+    conn: &SqliteConnection,
+    json: &str,
+    except: Option<i32>,
+) -> Result<Vec<String>, FormError> {
+    let mut list: Vec<String> = Vec::new();
+    for r in serde_json::from_str::<Vec<String>>(json).map_err(|_| FormError::InvalidRepo)? {
+        if r.trim().is_empty() {
+            continue;
+        }
+        let url = r
+            .parse::<RepoUrl>()
+            .map_err(|_| FormError::InvalidRepo)?
+            .to_string();
+        if !list.contains(&url) {
+            list.push(url);
+        }
+    }
+
     use crate::schema::projects::dsl::*;
+    let others: Vec<(i32, String)> = projects
+        .select((id, repos))
+        .load(conn)
+        .expect("Failed to get projects from database");
+    let taken = others
+        .iter()
+        .filter(|(pid, _)| Some(*pid) != except)
+        .flat_map(|(_, r)| serde_json::from_str::<Vec<String>>(r).unwrap_or_default())
+        .any(|r| list.contains(&normalize(&r)));
+    if taken {
+        return Err(FormError::RepoExists);
+    }
 
-    if let Some(term) = term {
-        let sterm = format!("%{}%", term);
-        let filter = name.like(&sterm);
-        projects.filter(filter).load(conn)
-    } else {
-        projects.load(conn)
+    Ok(list)
+}
+
+/// Search the approved projects
+///
+/// See `ProjectQuery` for how searches work. Results are sorted by name.
+pub fn search_projects(conn: &SqliteConnection, q: &ProjectQuery) -> Vec<ProjectListing> {
+    let found: Vec<Project> = {
+        use crate::schema::projects::dsl::*;
+        let mut query = projects
+            .filter(status.eq(ProjectStatus::Approved.as_str()))
+            .filter(archived.eq(q.archived.unwrap_or(false)))
+            .into_boxed();
+        if let Some(a) = q.active {
+            query = query.filter(active.eq(a));
+        }
+        if let Some(e) = q.external {
+            query = query.filter(extrn.eq(e));
+        }
+        query
+            .order(name.asc())
+            .load(conn)
+            .expect("Failed to get projects")
+    };
+
+    // Words that are semesters also match the projects worked on then
+    let words: Vec<(String, Option<Vec<i32>>)> =
+        q.s.as_ref()
+            .map(|t| {
+                t.split_whitespace()
+                    .map(|w| {
+                        let sem = w.parse::<Semester>().ok();
+                        (
+                            w.to_lowercase(),
+                            sem.map(|sem| semester_projects(conn, &sem)),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+    let language = q.language.as_ref().filter(|l| !l.is_empty());
+    let tag = q.tag.as_ref().filter(|t| !t.is_empty());
+    let semester = q.semester.map(|sem| semester_projects(conn, &sem));
+
+    found
+        .into_iter()
+        .filter(|p| semester.as_ref().map_or(true, |ids| ids.contains(&p.id)))
+        .map(|p| ProjectListing {
+            tags: project_tags(conn, &p),
+            languages: project_languages(conn, &p),
+            project: p,
+        })
+        .filter(|p| {
+            language.map_or(true, |l| {
+                p.languages.iter().any(|pl| pl.eq_ignore_ascii_case(l))
+            })
+        })
+        .filter(|p| tag.map_or(true, |t| p.tags.contains(t)))
+        .filter(|p| {
+            if words.is_empty() {
+                return true;
+            }
+            let members = project_users(conn, &p.project);
+            words.iter().all(|(w, sem)| {
+                p.project.name.to_lowercase().contains(w)
+                    || p.project.description.to_lowercase().contains(w)
+                    || p.tags.iter().any(|t| t.contains(w))
+                    || p.languages.iter().any(|l| l.to_lowercase().contains(w))
+                    || members.iter().any(|u| {
+                        u.real_name.to_lowercase().contains(w)
+                            || u.handle.to_lowercase().contains(w)
+                    })
+                    || sem
+                        .as_ref()
+                        .map_or(false, |ids| ids.contains(&p.project.id))
+            })
+        })
+        .collect()
+}
+
+/// The IDs of the projects worked on during a semester
+///
+/// A project counts if it has a status update or a commit in the semester.
+pub fn semester_projects(conn: &SqliteConnection, sem: &Semester) -> Vec<i32> {
+    let mut ids: Vec<i32> = {
+        use crate::schema::status_updates::dsl::*;
+        status_updates
+            .filter(week.ge(sem.start()).and(week.lt(sem.end())))
+            .select(project_id)
+            .distinct()
+            .load(conn)
+            .expect("Failed to get status updates from database")
+    };
+    {
+        use crate::schema::repo_commits::dsl::*;
+        ids.extend(
+            repo_commits
+                .filter(
+                    committed_at
+                        .ge(sem.start().and_hms(0, 0, 0))
+                        .and(committed_at.lt(sem.end().and_hms(0, 0, 0))),
+                )
+                .select(project_id)
+                .distinct()
+                .load::<i32>(conn)
+                .expect("Failed to get commits from database"),
+        );
+    }
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// The tags of a project in alphabetical order
+pub fn project_tags(conn: &SqliteConnection, p: &Project) -> Vec<String> {
+    use crate::schema::project_tags::dsl::*;
+    ProjectTag::belonging_to(p)
+        .select(tag)
+        .order(tag.asc())
+        .load(conn)
+        .expect("Failed to get tags from database")
+}
+
+/// The primary languages of a project
+///
+/// The language set on the project is used if there is one, otherwise
+/// the languages detected from its repositories.
+pub fn project_languages(conn: &SqliteConnection, p: &Project) -> Vec<String> {
+    if let Some(l) = &p.language {
+        return vec![l.clone()];
+    }
+    let mut langs: Vec<String> = project_syncs(conn, p)
+        .into_iter()
+        .filter_map(|s| s.language)
+        .collect();
+    langs.sort();
+    langs.dedup();
+    langs
+}
+
+/// Count how many times each value appears, most common first
+fn facets<'a>(values: impl Iterator<Item = &'a String>) -> Vec<Facet> {
+    let mut found: Vec<Facet> = Vec::new();
+    for v in values {
+        match found.iter_mut().find(|f| &f.value == v) {
+            Some(f) => f.count += 1,
+            None => found.push(Facet {
+                value: v.clone(),
+                count: 1,
+            }),
+        }
     }
-    .expect("Failed to get projects")
+    found.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    found
 }
 
 use crate::models::User;
 
+/// The real name of a user, for activity messages
+fn user_name(conn: &SqliteConnection, uid: i32) -> String {
+    use crate::schema::users::dsl::*;
+    users
+        .find(uid)
+        .select(real_name)
+        .first(conn)
+        .expect("Failed to get user from database")
+}
+
 pub fn project_users(conn: &SqliteConnection, project: &Project) -> Vec<User> {
+    use crate::schema::relation_project_user::dsl::left_at;
     RelationProjectUser::belonging_to(project)
+        .filter(left_at.is_null())
         .load::<RelationProjectUser>(conn)
         .expect("Failed to get relations from database")
         .iter()
@@ -436,70 +1272,202 @@ pub fn project_users(conn: &SqliteConnection, project: &Project) -> Vec<User> {
         .collect()
 }
 
-/// Get the commits in the project
-///
-/// This function calls to the GitHub API to get the commits.
-///
-/// If the project does not use GitHub for it's repo this returns `None`.
-/// Otherwise it returns a vector of JSON values with each repo having an
-/// entry.
+/// The members of a project along with their roles, lead first
+pub fn project_member_entries(conn: &SqliteConnection, project: &Project) -> Vec<MemberEntry> {
+    use crate::schema::relation_project_user::dsl::left_at;
+    let mut members: Vec<MemberEntry> = RelationProjectUser::belonging_to(project)
+        .filter(left_at.is_null())
+        .load::<RelationProjectUser>(conn)
+        .expect("Failed to get relations from database")
+        .into_iter()
+        .map(|r| MemberEntry {
+            user: {
+                use crate::schema::users::dsl::*;
+                users
+                    .find(r.user_id)
+                    .first(conn)
+                    .expect("Failed to get user from database")
+            },
+            role: r.role.parse().unwrap_or(ProjectRole::Member),
+        })
+        .collect();
+    members.sort_by_key(|m| m.role != ProjectRole::Lead);
+    members
+}
+
+/// The role of a user in a project, if they are a member
+pub fn project_role(conn: &SqliteConnection, p: &Project, uid: i32) -> Option<ProjectRole> {
+    use crate::schema::relation_project_user::dsl::*;
+    relation_project_user
+        .filter(project_id.eq(p.id).and(user_id.eq(uid)))
+        .filter(left_at.is_null())
+        .select(role)
+        .first::<String>(conn)
+        .optional()
+        .expect("Failed to get relation from database")
+        .map(|r| r.parse().unwrap_or(ProjectRole::Member))
+}
+
+/// Can the user edit the project and manage its members?
 ///
-/// TODO support other services like GitLab.
-pub fn project_commits(conn: &SqliteConnection, proj: &Project) -> Option<Vec<serde_json::Value>> {
-    // Get the repos from the DB
-    let mut repos: Vec<String> = {
-        use crate::schema::projects::dsl::*;
-        serde_json::from_str(
-            &projects
-                .find(proj.id)
-                .select(repos)
-                .first::<String>(conn)
-                .expect("Failed to get repos from the database"),
-        )
-        .unwrap()
-    };
+/// Coordinators can manage any project, otherwise it depends on the
+/// user's role in the project. Nobody can manage an archived project.
+pub fn can_manage_project(conn: &SqliteConnection, u: &User, p: &Project) -> bool {
+    !p.archived
+        && (u.tier > 1
+            || u.id == p.owner_id
+            || project_role(conn, p, u.id)
+                .map(ProjectRole::can_manage)
+                .unwrap_or(false))
+}
+
+fn set_project_archived(
+    conn: &SqliteConnection,
+    u: &User,
+    h: i32,
+    value: bool,
+) -> Result<Redirect, Status> {
+    use crate::schema::projects::dsl::*;
+
+    let p: Project = projects
+        .find(h)
+        .first(conn)
+        .optional()
+        .expect("Failed to get project from database")
+        .ok_or(Status::NotFound)?;
 
-    // No repos at all
-    if repos.is_empty() {
-        return None;
+    if !(u.tier > 1 || u.id == p.owner_id) {
+        return Err(Status::Unauthorized);
     }
 
-    // Use a regex to filter to only GitHub and convert to the API string
-    use regex::Regex;
-    let re = Regex::new(r"^(https?://)?github\.com/(\S+/\S+)/?$")
-        .expect("Failed to build regular expression");
-    repos = repos
-        .iter()
-        .filter(|s| re.is_match(&s))
-        .map(|s| {
-            String::from(re.replace(s, "https://api.github.com/repos/$2/commits?per_page=100"))
+    update(projects.find(h))
+        .set(archived.eq(value))
+        .execute(conn)
+        .expect("Failed to update project in database");
+    log_activity(
+        conn,
+        h,
+        Some(u.id),
+        ActivityKind::Edit,
+        if value {
+            "archived the project"
+        } else {
+            "unarchived the project"
+        },
+        &format!("/projects/{}", h),
+    );
+    Ok(Redirect::to(format!("/projects/{}", h)))
+}
+
+/// Describe everything that would be lost by deleting a project
+fn project_losses(conn: &SqliteConnection, p: &Project) -> Vec<String> {
+    let members = project_users(conn, p).len();
+    let updates: i64 = {
+        use crate::schema::status_updates::dsl::*;
+        status_updates
+            .filter(project_id.eq(p.id))
+            .count()
+            .get_result(conn)
+            .expect("Failed to count status updates")
+    };
+    let commits: i64 = {
+        use crate::schema::repo_commits::dsl::*;
+        repo_commits
+            .filter(project_id.eq(p.id))
+            .count()
+            .get_result(conn)
+            .expect("Failed to count commits")
+    };
+    let pulls: i64 = {
+        use crate::schema::repo_pulls::dsl::*;
+        repo_pulls
+            .filter(project_id.eq(p.id))
+            .count()
+            .get_result(conn)
+            .expect("Failed to count pull requests")
+    };
+    let issues: i64 = {
+        use crate::schema::repo_issues::dsl::*;
+        repo_issues
+            .filter(project_id.eq(p.id))
+            .count()
+            .get_result(conn)
+            .expect("Failed to count issues")
+    };
+    let entries: i64 = {
+        use crate::schema::showcase_entries::dsl::*;
+        showcase_entries
+            .filter(project_id.eq(p.id))
+            .count()
+            .get_result(conn)
+            .expect("Failed to count showcase entries")
+    };
+    let prefs: i64 = {
+        use crate::schema::group_project_prefs::dsl::*;
+        group_project_prefs
+            .filter(project_id.eq(p.id))
+            .count()
+            .get_result(conn)
+            .expect("Failed to count group preferences")
+    };
+
+    vec![
+        format!("{} members and their roles", members),
+        format!("{} status updates and their comments", updates),
+        String::from("The proposal and its review comments"),
+        format!("{} pending join requests", join_requests(conn, p).len()),
+        format!(
+            "{} cached commits, {} pull requests, and {} issues, which count toward grades",
+            commits, pulls, issues
+        ),
+        String::from("The activity feed"),
+        format!("{} showcase entries and their votes and scores", entries),
+        format!("{} small groups' preferences for the project", prefs),
+    ]
+}
+
+/// The pending join requests of a project, oldest first
+pub fn join_requests(conn: &SqliteConnection, p: &Project) -> Vec<JoinRequestEntry> {
+    use crate::schema::join_requests::dsl::*;
+    JoinRequest::belonging_to(p)
+        .order(created_at.asc())
+        .load::<JoinRequest>(conn)
+        .expect("Failed to get join requests from database")
+        .into_iter()
+        .map(|r| JoinRequestEntry {
+            user: {
+                use crate::schema::users::dsl::*;
+                users
+                    .find(r.user_id)
+                    .first(conn)
+                    .expect("Failed to get user from database")
+            },
+            request: r,
         })
-        .collect();
+        .collect()
+}
 
-    // If no GitHub repos
-    if repos.is_empty() {
-        return None;
-    }
+fn get_join_request(conn: &SqliteConnection, h: i32, rid: i32) -> Option<(Project, JoinRequest)> {
+    let r: JoinRequest = {
+        use crate::schema::join_requests::dsl::*;
+        join_requests
+            .find(rid)
+            .filter(project_id.eq(h))
+            .first(conn)
+            .optional()
+            .expect("Failed to get join request from database")?
+    };
+    use crate::schema::projects::dsl::*;
+    let p: Project = projects
+        .find(h)
+        .first(conn)
+        .expect("Failed to get project from database");
+    Some((p, r))
+}
 
-    // Get the commits and return them, filtering out errors
-    Some(
-        repos
-            .iter()
-            .filter_map(|s| {
-                let res = reqwest::get(s);
-                if res.is_ok() {
-                    if let Ok(json) = res
-                        .expect("Failed to get response from GitHub")
-                        .json::<serde_json::Value>()
-                    {
-                        Some(json)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
-            .collect(),
-    )
+fn delete_join_request(conn: &SqliteConnection, rid: i32) {
+    use crate::schema::join_requests::dsl::*;
+    delete(join_requests.find(rid))
+        .execute(conn)
+        .expect("Failed to delete join request from database");
 }
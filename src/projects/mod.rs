@@ -3,4 +3,4 @@
 pub mod handlers;
 pub mod models;
 
-mod templates;
+pub mod templates;
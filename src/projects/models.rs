@@ -1,6 +1,14 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use rocket::http::RawStr;
+use rocket::request::FromFormValue;
+
 use crate::schema::*;
 
 use crate::models::User;
+use crate::semester::Semester;
 
 /// Project template
 ///
@@ -22,6 +30,153 @@ pub struct Project {
     /// Link the Project repository
     pub repos: String,
     pub extrn: bool,
+    /// Where the project is in the proposal process, see `ProjectStatus`
+    pub status: String,
+    /// Who can join the project, see `JoinPolicy`
+    pub join_policy: String,
+    /// Most members the project can have
+    pub max_size: Option<i32>,
+    /// Primary language set by the project, see `project_languages`
+    pub language: Option<String>,
+    /// Archived projects are read-only and not listed
+    pub archived: bool,
+    /// Key of the uploaded logo, see `uploads`
+    pub logo: Option<String>,
+}
+
+impl Project {
+    /// Has the project's proposal been approved?
+    ///
+    /// Only approved projects are listed and can be joined.
+    pub fn approved(&self) -> bool {
+        self.status == ProjectStatus::Approved.as_str()
+    }
+
+    /// Who can join the project
+    ///
+    /// Unknown values are treated as closed.
+    pub fn policy(&self) -> JoinPolicy {
+        self.join_policy.parse().unwrap_or(JoinPolicy::Closed)
+    }
+
+    /// Does the project have as many members as it allows?
+    pub fn is_full(&self, members: usize) -> bool {
+        self.max_size
+            .map(|m| members >= m as usize)
+            .unwrap_or(false)
+    }
+
+    /// The status in a human readable form
+    pub fn status_label(&self) -> String {
+        self.status
+            .parse::<ProjectStatus>()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|_| self.status.clone())
+    }
+}
+
+/// Where a project is in the proposal process
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ProjectStatus {
+    /// Waiting for a coordinator to review it
+    Proposed,
+    /// A coordinator asked for changes before approving it
+    ChangesRequested,
+    /// Live and accepting members
+    Approved,
+    /// Will not be part of RCOS
+    Rejected,
+}
+
+impl ProjectStatus {
+    /// The value stored in the `status` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProjectStatus::Proposed => "proposed",
+            ProjectStatus::ChangesRequested => "changes_requested",
+            ProjectStatus::Approved => "approved",
+            ProjectStatus::Rejected => "rejected",
+        }
+    }
+}
+
+// Converts to a human readable string like "Changes Requested"
+impl fmt::Display for ProjectStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ProjectStatus::Proposed => "Proposed",
+                ProjectStatus::ChangesRequested => "Changes Requested",
+                ProjectStatus::Approved => "Approved",
+                ProjectStatus::Rejected => "Rejected",
+            }
+        )
+    }
+}
+
+// Converts from the value stored in the `status` column
+impl FromStr for ProjectStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "proposed" => Ok(ProjectStatus::Proposed),
+            "changes_requested" => Ok(ProjectStatus::ChangesRequested),
+            "approved" => Ok(ProjectStatus::Approved),
+            "rejected" => Ok(ProjectStatus::Rejected),
+            _ => Err(()),
+        }
+    }
+}
+
+impl<'v> FromFormValue<'v> for ProjectStatus {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<ProjectStatus, &'v RawStr> {
+        form_value.parse().map_err(|_| form_value)
+    }
+}
+
+/// Who can join a project
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum JoinPolicy {
+    /// Anyone can join
+    Open,
+    /// Anyone can ask to join and the owner or a mentor decides
+    Approval,
+    /// Nobody can join
+    Closed,
+    /// Only people added by the owner or a mentor can join
+    Invite,
+}
+
+impl JoinPolicy {
+    /// The value stored in the `join_policy` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JoinPolicy::Open => "open",
+            JoinPolicy::Approval => "approval",
+            JoinPolicy::Closed => "closed",
+            JoinPolicy::Invite => "invite",
+        }
+    }
+}
+
+// Converts from the value stored in the `join_policy` column
+impl FromStr for JoinPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(JoinPolicy::Open),
+            "approval" => Ok(JoinPolicy::Approval),
+            "closed" => Ok(JoinPolicy::Closed),
+            "invite" => Ok(JoinPolicy::Invite),
+            _ => Err(()),
+        }
+    }
 }
 
 /// New Project Template
@@ -29,6 +184,7 @@ pub struct Project {
 /// Represents the template when a new project is created
 #[derive(Debug, Default, Clone, FromForm, Insertable, AsChangeset)]
 #[table_name = "projects"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct NewProject {
     /// Name of the Project
     pub name: String,
@@ -41,6 +197,12 @@ pub struct NewProject {
     /// Link to the Project Repository
     pub repos: String,
     pub extrn: bool,
+    /// Who can join the project, see `JoinPolicy`
+    pub join_policy: String,
+    /// Most members the project can have, empty for no limit
+    pub max_size: Option<i32>,
+    /// Primary language, empty to use the ones detected from the repos
+    pub language: Option<String>,
 }
 
 /// Student Relation to the Project
@@ -57,14 +219,193 @@ pub struct RelationProjectUser {
     pub project_id: i32,
     /// The ID of the Indivual User
     pub user_id: i32,
+    /// Role of the user in the project, see `ProjectRole`
+    pub role: String,
+    /// When the user joined, `None` if it was before this was kept
+    pub joined_at: Option<NaiveDateTime>,
+    /// When the user left, `None` while they are still a member
+    pub left_at: Option<NaiveDateTime>,
+}
+
+/// Role of a member in a project
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ProjectRole {
+    /// Runs the project, this is the project's owner
+    Lead,
+    /// Helps run the project
+    Maintainer,
+    /// Works on the project
+    Member,
+    /// Mentor advising the project
+    Mentor,
+}
+
+impl ProjectRole {
+    /// The value stored in the `role` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProjectRole::Lead => "lead",
+            ProjectRole::Maintainer => "maintainer",
+            ProjectRole::Member => "member",
+            ProjectRole::Mentor => "mentor",
+        }
+    }
+
+    /// Can this role edit the project and manage its members?
+    pub fn can_manage(self) -> bool {
+        self != ProjectRole::Member
+    }
+}
+
+// Converts to a human readable string like "Maintainer"
+impl fmt::Display for ProjectRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ProjectRole::Lead => "Lead",
+                ProjectRole::Maintainer => "Maintainer",
+                ProjectRole::Member => "Member",
+                ProjectRole::Mentor => "Mentor",
+            }
+        )
+    }
+}
+
+// Converts from the value stored in the `role` column
+impl FromStr for ProjectRole {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lead" => Ok(ProjectRole::Lead),
+            "maintainer" => Ok(ProjectRole::Maintainer),
+            "member" => Ok(ProjectRole::Member),
+            "mentor" => Ok(ProjectRole::Mentor),
+            _ => Err(()),
+        }
+    }
+}
+
+impl<'v> FromFormValue<'v> for ProjectRole {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<ProjectRole, &'v RawStr> {
+        form_value.parse().map_err(|_| form_value)
+    }
 }
 
 /// Used to Tie a student to a project
-#[derive(Debug, Default, Clone, Insertable)]
+#[derive(Debug, Clone, Insertable)]
 #[table_name = "relation_project_user"]
 pub struct NewRelationProjectUser {
     /// ID of the Project
     pub project_id: i32,
     /// ID of the student being added to the project
     pub user_id: i32,
+    /// Role of the student in the project, see `ProjectRole`
+    pub role: String,
+    /// When the student joined the project
+    pub joined_at: Option<NaiveDateTime>,
+}
+
+/// A request to join a project that requires approval
+#[derive(Debug, PartialEq, Clone, Queryable, Associations, Identifiable, Serialize)]
+#[belongs_to(Project)]
+#[belongs_to(User)]
+pub struct JoinRequest {
+    /// ID of the request
+    pub id: i32,
+    /// ID of the project the user wants to join
+    pub project_id: i32,
+    /// ID of the user who wants to join
+    pub user_id: i32,
+    /// Message to the project owner
+    pub message: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Used to ask to join a project
+#[derive(Debug, Default, Clone, Insertable)]
+#[table_name = "join_requests"]
+pub struct NewJoinRequest {
+    pub project_id: i32,
+    pub user_id: i32,
+    pub message: String,
+}
+
+/// The join form
+///
+/// The message is only used when the project requires approval.
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct JoinForm {
+    pub message: String,
+}
+
+/// A tag on a project such as `web` or `machine-learning`
+#[derive(Debug, PartialEq, Clone, Queryable, Associations, Identifiable, Serialize)]
+#[belongs_to(Project)]
+pub struct ProjectTag {
+    pub id: i32,
+    /// ID of the project the tag is on
+    pub project_id: i32,
+    /// The tag, always lowercase
+    pub tag: String,
+}
+
+/// Used to tag a project
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "project_tags"]
+pub struct NewProjectTag {
+    pub project_id: i32,
+    pub tag: String,
+}
+
+/// The most tags a project can have
+pub const MAX_TAGS: usize = 20;
+
+/// Parse tags from a comma separated list
+///
+/// Tags are lowercased and spaces are replaced with dashes so that
+/// `Machine Learning` and `machine-learning` are the same tag.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for t in text.split(',') {
+        let t = t
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join("-")
+            .to_lowercase();
+        if !t.is_empty() && t.len() <= 32 && !tags.contains(&t) {
+            tags.push(t);
+        }
+    }
+    tags.truncate(MAX_TAGS);
+    tags
+}
+
+/// The tag editor form
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct TagsForm {
+    /// Comma separated tags
+    pub tags: String,
+}
+
+/// A search of the project list
+///
+/// Every word of `s` has to match the name, description, tags,
+/// languages, or members of a project, or be a semester like
+/// `2019-fall` the project was worked on in. The other fields narrow
+/// down the results.
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct ProjectQuery {
+    pub s: Option<String>,
+    pub active: Option<bool>,
+    pub external: Option<bool>,
+    pub language: Option<String>,
+    pub tag: Option<String>,
+    pub semester: Option<Semester>,
+    /// Show archived projects instead of current ones
+    pub archived: Option<bool>,
 }
@@ -1,8 +1,40 @@
 use super::models::*;
 #[allow(unused_imports)]
-use crate::templates::{filters, OptUser};
+use crate::templates::{filters, FormError, OptUser};
 
-use crate::models::User;
+use crate::models::{RepoSync, User};
+
+/// A join request along with the user who made it
+pub struct JoinRequestEntry {
+    pub request: JoinRequest,
+    pub user: User,
+}
+
+/// A project in the project list along with its tags and languages
+#[derive(Clone)]
+pub struct ProjectListing {
+    pub project: Project,
+    pub tags: Vec<String>,
+    pub languages: Vec<String>,
+}
+
+/// A value to narrow down a search by and how many results have it
+pub struct Facet {
+    pub value: String,
+    pub count: usize,
+}
+
+/// A member of a project along with their role
+pub struct MemberEntry {
+    pub user: User,
+    pub role: ProjectRole,
+}
+
+impl MemberEntry {
+    pub fn is_lead(&self) -> bool {
+        self.role == ProjectRole::Lead
+    }
+}
 
 /// Project page template
 ///
@@ -16,7 +48,18 @@ pub struct ProjectTemplate {
     pub logged_in: OptUser,
     pub project: Project,
     pub repos: Vec<String>,
-    pub users: Vec<User>,
+    pub syncs: Vec<RepoSync>,
+    pub tags: Vec<String>,
+    pub languages: Vec<String>,
+    pub members: Vec<MemberEntry>,
+    /// Is the logged in user on the project?
+    pub is_member: bool,
+    /// Can the logged in user edit the project and manage members?
+    pub can_manage: bool,
+    /// Can the logged in user change roles and transfer the project?
+    pub is_lead: bool,
+    /// Pending join requests, only for those who can accept them
+    pub requests: Vec<JoinRequestEntry>,
 }
 
 /// Project page template
@@ -29,6 +72,7 @@ pub struct ProjectTemplate {
 #[template(path = "project/new-project.html")]
 pub struct NewProjectTemplate {
     pub logged_in: OptUser,
+    pub error: Option<FormError>,
 }
 
 /// Project page template
@@ -42,7 +86,9 @@ pub struct EditProjectTemplate {
     pub logged_in: OptUser,
     pub project: Project,
     pub repos: Vec<String>,
-    pub all_users: Vec<User>,
+    /// The project's tags, comma separated
+    pub tags: String,
+    pub error: Option<FormError>,
 }
 
 /// List of the Projects
@@ -54,7 +100,19 @@ pub struct EditProjectTemplate {
 #[template(path = "project/projects-list.html")]
 pub struct ProjectsListTemplate {
     pub logged_in: OptUser,
-    pub projects: Vec<Project>,
+    pub projects: Vec<ProjectListing>,
+    /// Languages of the results
+    pub languages: Vec<Facet>,
+    /// Tags of the results
+    pub tags: Vec<Facet>,
+    // The current search, empty if not set
+    pub term: String,
+    pub active: String,
+    pub external: String,
+    pub language: String,
+    pub tag: String,
+    pub semester: String,
+    pub archived: bool,
 }
 
 /// Template shown when a student wants to join a project
@@ -67,6 +125,11 @@ pub struct ProjectsListTemplate {
 pub struct JoinTemplate {
     pub logged_in: OptUser,
     pub project: Project,
+    /// Has the user already asked to join?
+    pub requested: bool,
+    /// Is the team full?
+    pub full: bool,
+    pub error: Option<FormError>,
 }
 
 /// The Adduser Template
@@ -80,4 +143,18 @@ pub struct AddUserTemplate {
     pub logged_in: OptUser,
     pub project: Project,
     pub all_users: Vec<User>,
+    pub error: Option<FormError>,
+}
+
+/// Project deletion confirmation
+///
+/// HTML File: `project/delete.html`
+///
+/// Lists everything that will be lost before a project is deleted.
+#[derive(Template)]
+#[template(path = "project/delete.html")]
+pub struct DeleteProjectTemplate {
+    pub logged_in: OptUser,
+    pub project: Project,
+    pub losses: Vec<String>,
 }
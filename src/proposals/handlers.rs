@@ -0,0 +1,277 @@
+//! HTTP handlers for project proposals
+
+use chrono::Local;
+use diesel::prelude::*;
+use diesel::{insert_into, update};
+use rocket::http::Status;
+use rocket::request::Form;
+use rocket::response::Redirect;
+
+use crate::activity::handlers::log_activity;
+use crate::activity::models::ActivityKind;
+use crate::guards::*;
+use crate::models::{CommentForm, Project, ProjectStatus, User};
+use crate::notifications::handlers::notify;
+use crate::projects::handlers::project_users;
+use crate::ObservDbConn;
+
+use super::models::*;
+use super::templates::*;
+
+/// GET handler for `/proposals`
+///
+/// The coordinator review queue of proposals waiting on a decision,
+/// oldest first.
+#[get("/proposals")]
+pub fn proposals(conn: ObservDbConn, l: AdminGuard) -> ProposalsTemplate {
+    let pending: Vec<Project> = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .filter(status.eq_any(vec![
+                ProjectStatus::Proposed.as_str(),
+                ProjectStatus::ChangesRequested.as_str(),
+            ]))
+            .load(&*conn)
+            .expect("Failed to get projects from database")
+    };
+
+    let mut entries: Vec<ProposalEntry> = pending
+        .into_iter()
+        .filter_map(|p| {
+            Some(ProposalEntry {
+                proposal: project_proposal(&*conn, &p)?,
+                owner: get_user(&*conn, p.owner_id),
+                project: p,
+            })
+        })
+        .collect();
+    entries.sort_by_key(|e| e.proposal.submitted_at);
+
+    ProposalsTemplate {
+        logged_in: Some(l.0),
+        entries,
+    }
+}
+
+/// GET handler for `/projects/<h>/proposal`
+///
+/// Restricted to members of the project and Coordinators.
+#[get("/projects/<h>/proposal")]
+pub fn proposal(conn: ObservDbConn, l: UserGuard, h: i32) -> Result<ProposalTemplate, Status> {
+    let p = get_project(&*conn, h).ok_or(Status::NotFound)?;
+    let prop = project_proposal(&*conn, &p).ok_or(Status::NotFound)?;
+
+    if !(l.0.tier > 1 || project_users(&*conn, &p).contains(&l.0)) {
+        return Err(Status::Unauthorized);
+    }
+
+    let comments = {
+        use crate::schema::proposal_comments::dsl::*;
+        ProposalComment::belonging_to(&prop)
+            .order(created_at.asc())
+            .load::<ProposalComment>(&*conn)
+            .expect("Failed to get comments from database")
+            .into_iter()
+            .map(|c| ProposalCommentEntry {
+                author: get_user(&*conn, c.user_id),
+                comment: c,
+            })
+            .collect()
+    };
+
+    Ok(ProposalTemplate {
+        logged_in: Some(l.0),
+        owner: get_user(&*conn, p.owner_id),
+        project: p,
+        proposal: prop,
+        comments,
+    })
+}
+
+/// PUT handler for `/projects/<h>/proposal`
+///
+/// Revises the proposal. If changes were requested it goes back into the
+/// review queue.
+///
+/// Restricted to the owner of the project.
+#[put("/projects/<h>/proposal", data = "<form>")]
+pub fn proposal_edit_put(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    form: Form<ProposalEditForm>,
+) -> Result<Redirect, Status> {
+    let p = get_project(&*conn, h).ok_or(Status::NotFound)?;
+    let prop = project_proposal(&*conn, &p).ok_or(Status::NotFound)?;
+
+    if p.owner_id != l.0.id {
+        return Err(Status::Unauthorized);
+    }
+
+    {
+        use crate::schema::project_proposals::dsl::*;
+        update(project_proposals.find(prop.id))
+            .set(&form.into_inner())
+            .execute(&*conn)
+            .expect("Failed to update proposal in database");
+    }
+
+    if p.status == ProjectStatus::ChangesRequested.as_str() {
+        set_status(&*conn, &p, ProjectStatus::Proposed);
+        if let Some(r) = prop.reviewed_by {
+            notify(
+                &*conn,
+                r,
+                &format!("The proposal for {} was revised", p.name),
+                &proposal_url(h),
+            );
+        }
+    }
+
+    Ok(Redirect::to(proposal_url(h)))
+}
+
+/// POST handler for `/projects/<h>/proposal/review`
+///
+/// Approves, rejects, or requests changes to a proposal and lets the
+/// proposer know.
+#[post("/projects/<h>/proposal/review", data = "<form>")]
+pub fn proposal_review_post(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    h: i32,
+    form: Form<ReviewForm>,
+) -> Result<Redirect, Status> {
+    let form = form.into_inner();
+    let p = get_project(&*conn, h).ok_or(Status::NotFound)?;
+    let prop = project_proposal(&*conn, &p).ok_or(Status::NotFound)?;
+
+    if form.decision == ProjectStatus::Proposed {
+        return Err(Status::BadRequest);
+    }
+
+    set_status(&*conn, &p, form.decision);
+    {
+        use crate::schema::project_proposals::dsl::*;
+        update(project_proposals.find(prop.id))
+            .set((
+                reviewed_by.eq(Some(l.0.id)),
+                reviewed_at.eq(Some(Local::now().naive_local())),
+            ))
+            .execute(&*conn)
+            .expect("Failed to update proposal in database");
+    }
+    if !form.comment.trim().is_empty() {
+        add_comment(&*conn, &prop, &l.0, form.comment);
+    }
+    log_activity(
+        &*conn,
+        h,
+        Some(l.0.id),
+        ActivityKind::Edit,
+        &format!("reviewed the proposal: {}", form.decision),
+        &proposal_url(h),
+    );
+
+    notify(
+        &*conn,
+        p.owner_id,
+        &format!(
+            "Your proposal for {} was reviewed: {}",
+            p.name, form.decision
+        ),
+        &proposal_url(h),
+    );
+
+    Ok(Redirect::to("/proposals"))
+}
+
+/// POST handler for `/projects/<h>/proposal/comments`
+///
+/// Restricted to the owner of the project and Coordinators.
+#[post("/projects/<h>/proposal/comments", data = "<form>")]
+pub fn proposal_comment_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    form: Form<CommentForm>,
+) -> Result<Redirect, Status> {
+    let p = get_project(&*conn, h).ok_or(Status::NotFound)?;
+    let prop = project_proposal(&*conn, &p).ok_or(Status::NotFound)?;
+
+    if !(l.0.tier > 1 || p.owner_id == l.0.id) {
+        return Err(Status::Unauthorized);
+    }
+
+    add_comment(&*conn, &prop, &l.0, form.into_inner().body);
+
+    // Let the other side of the conversation know
+    let to = if l.0.id == p.owner_id {
+        prop.reviewed_by
+    } else {
+        Some(p.owner_id)
+    };
+    if let Some(to) = to {
+        notify(
+            &*conn,
+            to,
+            &format!("{} commented on the proposal for {}", l.0.real_name, p.name),
+            &proposal_url(h),
+        );
+    }
+
+    Ok(Redirect::to(proposal_url(h)))
+}
+
+//# Helper Functions
+
+fn proposal_url(h: i32) -> String {
+    format!("/projects/{}/proposal", h)
+}
+
+fn get_project(conn: &SqliteConnection, h: i32) -> Option<Project> {
+    use crate::schema::projects::dsl::*;
+    projects
+        .find(h)
+        .first(conn)
+        .optional()
+        .expect("Failed to get project from database")
+}
+
+fn get_user(conn: &SqliteConnection, uid: i32) -> User {
+    use crate::schema::users::dsl::*;
+    users
+        .find(uid)
+        .first(conn)
+        .expect("Failed to get user from database")
+}
+
+fn set_status(conn: &SqliteConnection, p: &Project, to: ProjectStatus) {
+    use crate::schema::projects::dsl::*;
+    update(projects.find(p.id))
+        .set(status.eq(to.as_str()))
+        .execute(conn)
+        .expect("Failed to update project in database");
+}
+
+fn add_comment(conn: &SqliteConnection, prop: &ProjectProposal, user: &User, text: String) {
+    use crate::schema::proposal_comments::dsl::*;
+    insert_into(proposal_comments)
+        .values(&NewProposalComment {
+            proposal_id: prop.id,
+            user_id: user.id,
+            body: text,
+        })
+        .execute(conn)
+        .expect("Failed to insert comment into database");
+}
+
+/// The proposal of a project, if it was proposed
+///
+/// Projects created before proposals existed don't have one.
+pub fn project_proposal(conn: &SqliteConnection, p: &Project) -> Option<ProjectProposal> {
+    ProjectProposal::belonging_to(p)
+        .first(conn)
+        .optional()
+        .expect("Failed to get proposal from database")
+}
@@ -0,0 +1,17 @@
+//! Project proposals
+//!
+//! New projects are proposed rather than created outright. Coordinators
+//! review proposals and approve them, reject them, or request changes,
+//! discussing them with the proposer in comments. Only approved projects
+//! are listed and can be joined.
+//!
+//! ## Routes
+//! - `/proposals`
+//! - `/projects/<h>/proposal`
+//! - `/projects/<h>/proposal/review`
+//! - `/projects/<h>/proposal/comments`
+
+pub mod handlers;
+pub mod models;
+
+mod templates;
@@ -0,0 +1,101 @@
+//! Models for project proposals
+//!
+//! New projects start out with a proposal stored in `project_proposals`
+//! and a status of `proposed`. Coordinators and the proposer discuss it in
+//! `proposal_comments` until it is approved or rejected.
+
+use chrono::NaiveDateTime;
+
+use crate::models::{Project, ProjectStatus, User};
+use crate::schema::*;
+
+/// The proposal for a project
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Project)]
+pub struct ProjectProposal {
+    /// ID of the proposal
+    pub id: i32,
+    /// ID of the project being proposed
+    pub project_id: i32,
+    /// What the project aims to do this semester
+    pub goals: String,
+    /// Languages, frameworks, and tools the project will use
+    pub stack: String,
+    /// License the code will be released under
+    pub license: String,
+    /// How many students the project is looking for
+    pub team_size: i32,
+    pub submitted_at: NaiveDateTime,
+    /// ID of the coordinator who last reviewed the proposal
+    pub reviewed_by: Option<i32>,
+    pub reviewed_at: Option<NaiveDateTime>,
+}
+
+/// Used to create a proposal
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "project_proposals"]
+pub struct NewProjectProposal {
+    pub project_id: i32,
+    pub goals: String,
+    pub stack: String,
+    pub license: String,
+    pub team_size: i32,
+}
+
+/// The form used to propose a new project
+///
+/// This is the new project form with the proposal questions added.
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct ProposalForm {
+    pub name: String,
+    pub description: String,
+    pub homepage: Option<String>,
+    pub repos: String,
+    pub extrn: bool,
+    pub goals: String,
+    pub stack: String,
+    pub license: String,
+    pub team_size: i32,
+}
+
+/// The form used to revise a proposal
+#[derive(Debug, Default, Clone, FromForm, AsChangeset)]
+#[table_name = "project_proposals"]
+pub struct ProposalEditForm {
+    pub goals: String,
+    pub stack: String,
+    pub license: String,
+    pub team_size: i32,
+}
+
+/// A coordinator's decision on a proposal
+#[derive(Debug, Clone, FromForm)]
+pub struct ReviewForm {
+    pub decision: ProjectStatus,
+    pub comment: String,
+}
+
+/// A comment on a proposal
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(ProjectProposal, foreign_key = "proposal_id")]
+#[belongs_to(User)]
+pub struct ProposalComment {
+    /// ID of the comment
+    pub id: i32,
+    /// ID of the proposal being commented on
+    pub proposal_id: i32,
+    /// ID of the user who wrote the comment
+    pub user_id: i32,
+    /// The comment as Markdown
+    pub body: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// Used to post a comment
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "proposal_comments"]
+pub struct NewProposalComment {
+    pub proposal_id: i32,
+    pub user_id: i32,
+    pub body: String,
+}
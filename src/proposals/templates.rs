@@ -0,0 +1,41 @@
+use super::models::*;
+#[allow(unused_imports)]
+use crate::templates::{filters, OptUser};
+
+use crate::models::{Project, User};
+
+/// A proposal along with its project and who proposed it
+pub struct ProposalEntry {
+    pub project: Project,
+    pub proposal: ProjectProposal,
+    pub owner: User,
+}
+
+/// A comment along with the user who left it
+pub struct ProposalCommentEntry {
+    pub comment: ProposalComment,
+    pub author: User,
+}
+
+/// The proposal review queue
+///
+/// HTML File: `proposals/proposals.html`
+#[derive(Template)]
+#[template(path = "proposals/proposals.html")]
+pub struct ProposalsTemplate {
+    pub logged_in: OptUser,
+    pub entries: Vec<ProposalEntry>,
+}
+
+/// A single proposal and the discussion on it
+///
+/// HTML File: `proposals/proposal.html`
+#[derive(Template)]
+#[template(path = "proposals/proposal.html")]
+pub struct ProposalTemplate {
+    pub logged_in: OptUser,
+    pub project: Project,
+    pub proposal: ProjectProposal,
+    pub owner: User,
+    pub comments: Vec<ProposalCommentEntry>,
+}
@@ -0,0 +1,199 @@
+//! A small client for the GitHub REST API
+//!
+//! Only the few endpoints needed by the sync worker are used, so rather
+//! than pulling in a full API library this wraps `reqwest` directly.
+
+use std::cell::Cell;
+use std::fmt;
+
+use chrono::Utc;
+use regex::Regex;
+use reqwest::header::{ACCEPT, AUTHORIZATION, ETAG, IF_NONE_MATCH, LINK, USER_AGENT};
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+
+/// Base URL of the GitHub API
+pub const API: &str = "https://api.github.com";
+
+/// Requests stop once fewer than this many are left before the rate
+/// limit resets
+pub const MIN_REMAINING: u32 = 100;
+
+/// A GitHub API client
+///
+/// If the `GITHUB_TOKEN` environment variable is set it is used to
+/// authenticate, which raises the rate limit considerably.
+pub struct GitHub {
+    client: Client,
+    token: Option<String>,
+    /// Requests left in the rate limit as of the last response
+    remaining: Cell<Option<u32>>,
+    /// When the rate limit resets, in seconds since the epoch
+    reset: Cell<i64>,
+}
+
+/// A single page of a response from the API
+pub struct Page {
+    /// The decoded JSON body
+    pub body: Value,
+    /// The ETag of the response, used for conditional requests
+    pub etag: Option<String>,
+    /// URL of the next page if the response is paginated
+    pub next: Option<String>,
+}
+
+impl Page {
+    /// The items of a paginated listing
+    pub fn items(&self) -> &[Value] {
+        self.body.as_array().map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+impl GitHub {
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            token: std::env::var("GITHUB_TOKEN").ok(),
+            remaining: Cell::new(None),
+            reset: Cell::new(0),
+        }
+    }
+
+    /// Is the rate limit nearly used up?
+    ///
+    /// Stays true until the limit resets.
+    pub fn rate_limited(&self) -> bool {
+        match self.remaining.get() {
+            Some(n) => n < MIN_REMAINING && Utc::now().timestamp() < self.reset.get(),
+            None => false,
+        }
+    }
+
+    /// Make a GET request to the API
+    ///
+    /// If an ETag is given the request is made conditional on it and
+    /// `Ok(None)` is returned when nothing has changed. Requests that
+    /// return `304 Not Modified` do not count against the rate limit.
+    ///
+    /// Fails without making the request if the rate limit is nearly used
+    /// up.
+    pub fn get(&self, url: &str, etag: Option<&str>) -> Result<Option<Page>, SyncError> {
+        if self.rate_limited() {
+            return Err(SyncError::RateLimited);
+        }
+
+        let mut req = self
+            .client
+            .get(url)
+            .header(USER_AGENT, "observatory")
+            .header(ACCEPT, "application/vnd.github.v3+json");
+        if let Some(t) = &self.token {
+            req = req.header(AUTHORIZATION, format!("token {}", t));
+        }
+        if let Some(e) = etag {
+            req = req.header(IF_NONE_MATCH, e);
+        }
+
+        let mut res = req.send()?;
+        let header = |name: &str| {
+            res.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<i64>().ok())
+        };
+        if let (Some(left), Some(reset)) =
+            (header("x-ratelimit-remaining"), header("x-ratelimit-reset"))
+        {
+            self.remaining.set(Some(left as u32));
+            self.reset.set(reset);
+        }
+        if res.status() == StatusCode::FORBIDDEN && self.rate_limited() {
+            return Err(SyncError::RateLimited);
+        }
+        if res.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !res.status().is_success() {
+            return Err(SyncError::Status(res.status().as_u16()));
+        }
+
+        let etag = res
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let next = res
+            .headers()
+            .get(LINK)
+            .and_then(|v| v.to_str().ok())
+            .and_then(next_link);
+
+        Ok(Some(Page {
+            body: res.json()?,
+            etag,
+            next,
+        }))
+    }
+}
+
+/// Get the `owner/name` path of a GitHub repository URL
+///
+/// Returns `None` if the URL is not a GitHub repository.
+pub fn api_path(repo: &str) -> Option<String> {
+    let re = Regex::new(r"^(https?://)?(www\.)?github\.com/([^/\s]+/[^/\s]+?)(\.git)?/?$")
+        .expect("Failed to build regular expression");
+    re.captures(repo)
+        .and_then(|c| c.get(3))
+        .map(|m| String::from(m.as_str()))
+}
+
+/// Find the `rel="next"` URL in a `Link` header
+fn next_link(header: &str) -> Option<String> {
+    header
+        .split(',')
+        .find(|l| l.contains("rel=\"next\""))
+        .and_then(|l| {
+            let start = l.find('<')? + 1;
+            let end = l.find('>')?;
+            Some(String::from(&l[start..end]))
+        })
+}
+
+/// Errors that can happen while syncing a repository
+#[derive(Debug)]
+pub enum SyncError {
+    /// The request could not be made or the body could not be decoded
+    Http(reqwest::Error),
+    /// The API responded with an unexpected status code
+    Status(u16),
+    /// The rate limit is nearly used up, see `GitHub::rate_limited`
+    RateLimited,
+    /// The cache could not be updated
+    DatabaseError(diesel::result::Error),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SyncError::Http(e) => write!(f, "Request failed: {}", e),
+            SyncError::Status(s) => write!(f, "GitHub responded with status {}", s),
+            SyncError::RateLimited => write!(
+                f,
+                "GitHub's rate limit was nearly used up, the rest is synced once it resets"
+            ),
+            SyncError::DatabaseError(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for SyncError {
+    fn from(e: reqwest::Error) -> Self {
+        SyncError::Http(e)
+    }
+}
+
+impl From<diesel::result::Error> for SyncError {
+    fn from(e: diesel::result::Error) -> Self {
+        SyncError::DatabaseError(e)
+    }
+}
@@ -0,0 +1,69 @@
+//! HTTP handlers for the repository cache
+
+use chrono::NaiveDateTime;
+use diesel::dsl::max;
+use diesel::prelude::*;
+use rocket::http::Status;
+use rocket::response::Redirect;
+
+use crate::guards::*;
+use crate::models::Project;
+use crate::projects::handlers::can_manage_project;
+use crate::ObservDbConn;
+
+use super::github::GitHub;
+use super::models::*;
+use super::sync::sync_project;
+
+/// POST handler for `/projects/<h>/resync`
+///
+/// Syncs the project's repositories right away instead of waiting for
+/// the background worker.
+///
+/// Restricted to Admins and those who can manage the project.
+#[post("/projects/<h>/resync")]
+pub fn project_resync(conn: ObservDbConn, l: UserGuard, h: i32) -> Result<Redirect, Status> {
+    use crate::schema::projects::dsl::*;
+
+    let p: Project = projects
+        .find(h)
+        .first(&*conn)
+        .expect("Failed to get project from database");
+
+    if can_manage_project(&*conn, &l.0, &p) {
+        sync_project(&*conn, &GitHub::new(), &p);
+        Ok(Redirect::to(format!("/projects/{}", h)))
+    } else {
+        Err(Status::Unauthorized)
+    }
+}
+
+//# Helper Functions
+
+/// The sync state of each of a project's repositories
+pub fn project_syncs(conn: &SqliteConnection, proj: &Project) -> Vec<RepoSync> {
+    RepoSync::belonging_to(proj)
+        .load(conn)
+        .expect("Failed to get sync state from database")
+}
+
+/// The cached commits of a project, newest first
+pub fn project_commits(conn: &SqliteConnection, proj: &Project) -> Vec<RepoCommit> {
+    use crate::schema::repo_commits::dsl::*;
+    RepoCommit::belonging_to(proj)
+        .order(committed_at.desc())
+        .load(conn)
+        .expect("Failed to get commits from database")
+}
+
+/// When any of the given projects was last synced
+///
+/// Returns `None` if none of them have ever been synced.
+pub fn last_synced(conn: &SqliteConnection, pids: &[i32]) -> Option<NaiveDateTime> {
+    use crate::schema::repo_syncs::dsl::*;
+    repo_syncs
+        .filter(project_id.eq_any(pids))
+        .select(max(synced_at))
+        .first::<Option<NaiveDateTime>>(conn)
+        .expect("Failed to get sync state from database")
+}
@@ -0,0 +1,148 @@
+//! Contribution metrics
+//!
+//! Commit counts alone are a poor measure of how much someone contributed
+//! to a project, so these also count pull requests, code reviews, and issue
+//! triage. Everything is computed from the repository cache.
+
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Text};
+
+use crate::models::{Project, User};
+use crate::semester::Semester;
+
+sql_function! {
+    /// SQL `lower()`, used to match GitHub logins case insensitively
+    fn lower(x: Nullable<Text>) -> Nullable<Text>;
+}
+
+/// A user's contributions to a single project over a semester
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct ContributionMetrics {
+    pub project_id: i32,
+    pub project_name: String,
+    /// Commits authored
+    pub commits: usize,
+    /// Pull requests opened
+    pub pulls_opened: usize,
+    /// Pull requests of theirs that were merged
+    pub pulls_merged: usize,
+    /// Reviews given on pull requests
+    pub reviews_given: usize,
+    /// Issues opened
+    pub issues_opened: usize,
+    /// Issues they closed
+    pub issues_closed: usize,
+    /// Lines added by their merged pull requests
+    pub lines_added: usize,
+    /// Lines removed by their merged pull requests
+    pub lines_removed: usize,
+}
+
+impl ContributionMetrics {
+    /// Add up the metrics of several projects
+    pub fn total(all: &[ContributionMetrics]) -> Self {
+        all.iter().fold(Self::default(), |mut t, m| {
+            t.commits += m.commits;
+            t.pulls_opened += m.pulls_opened;
+            t.pulls_merged += m.pulls_merged;
+            t.reviews_given += m.reviews_given;
+            t.issues_opened += m.issues_opened;
+            t.issues_closed += m.issues_closed;
+            t.lines_added += m.lines_added;
+            t.lines_removed += m.lines_removed;
+            t
+        })
+    }
+}
+
+/// Compute a user's contributions to a project during a semester
+///
+/// Contributions are matched to the user by their GitHub handle.
+pub fn contribution_metrics(
+    conn: &SqliteConnection,
+    user: &User,
+    proj: &Project,
+    sem: &Semester,
+) -> ContributionMetrics {
+    let (from, until) = bounds(sem);
+    let login = &user.handle;
+
+    let commits = {
+        use crate::schema::repo_commits::dsl::*;
+        repo_commits
+            .filter(project_id.eq(proj.id))
+            .filter(lower(author).eq(lower(login)))
+            .filter(committed_at.ge(from).and(committed_at.lt(until)))
+            .count()
+            .get_result::<i64>(conn)
+            .expect("Failed to count commits in database") as usize
+    };
+
+    let (pulls_opened, merged) = {
+        use crate::schema::repo_pulls::dsl::*;
+        let opened = repo_pulls
+            .filter(project_id.eq(proj.id))
+            .filter(lower(author).eq(lower(login)))
+            .filter(created_at.ge(from).and(created_at.lt(until)))
+            .count()
+            .get_result::<i64>(conn)
+            .expect("Failed to count pull requests in database") as usize;
+        let merged: Vec<(i32, i32)> = repo_pulls
+            .filter(project_id.eq(proj.id))
+            .filter(lower(author).eq(lower(login)))
+            .filter(merged_at.ge(from).and(merged_at.lt(until)))
+            .select((additions, deletions))
+            .load(conn)
+            .expect("Failed to get pull requests from database");
+        (opened, merged)
+    };
+
+    let reviews_given = {
+        use crate::schema::repo_reviews::dsl::*;
+        repo_reviews
+            .filter(project_id.eq(proj.id))
+            .filter(lower(author).eq(lower(login)))
+            .filter(submitted_at.ge(from).and(submitted_at.lt(until)))
+            .count()
+            .get_result::<i64>(conn)
+            .expect("Failed to count reviews in database") as usize
+    };
+
+    let (issues_opened, issues_closed) = {
+        use crate::schema::repo_issues::dsl::*;
+        let opened = repo_issues
+            .filter(project_id.eq(proj.id))
+            .filter(lower(author).eq(lower(login)))
+            .filter(created_at.ge(from).and(created_at.lt(until)))
+            .count()
+            .get_result::<i64>(conn)
+            .expect("Failed to count issues in database") as usize;
+        let closed = repo_issues
+            .filter(project_id.eq(proj.id))
+            .filter(lower(closed_by).eq(lower(login)))
+            .filter(closed_at.ge(from).and(closed_at.lt(until)))
+            .count()
+            .get_result::<i64>(conn)
+            .expect("Failed to count issues in database") as usize;
+        (opened, closed)
+    };
+
+    ContributionMetrics {
+        project_id: proj.id,
+        project_name: proj.name.clone(),
+        commits,
+        pulls_opened,
+        pulls_merged: merged.len(),
+        reviews_given,
+        issues_opened,
+        issues_closed,
+        lines_added: merged.iter().map(|(a, _)| *a as usize).sum(),
+        lines_removed: merged.iter().map(|(_, d)| *d as usize).sum(),
+    }
+}
+
+/// The start and end of a semester as times
+fn bounds(sem: &Semester) -> (NaiveDateTime, NaiveDateTime) {
+    (sem.start().and_hms(0, 0, 0), sem.end().and_hms(0, 0, 0))
+}
@@ -0,0 +1,18 @@
+//! Repository sync and cache
+//!
+//! Commits, pull requests, and issues from the repositories listed on
+//! projects are synced in the background and cached locally, along with
+//! each repository's README and metadata, so that pages like the user
+//! profile, dashboard, and project page never make requests to GitHub
+//! themselves. The cache is also used to compute each student's
+//! contribution metrics.
+//!
+//! ## Routes
+//! - `/projects/<h>/resync`
+
+pub mod github;
+pub mod handlers;
+pub mod metrics;
+pub mod models;
+pub mod sync;
+pub mod url;
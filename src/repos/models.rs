@@ -0,0 +1,218 @@
+//! Models for the repository cache
+//!
+//! Activity from the repositories listed on each project is synced in the
+//! background and stored in these tables so that pages never have to wait
+//! on the network.
+
+use chrono::NaiveDateTime;
+
+use crate::models::Project;
+use crate::schema::*;
+
+/// Sync state of a single repository
+///
+/// There is one of these for each repository listed on a project.
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Project)]
+pub struct RepoSync {
+    /// ID of the sync state
+    pub id: i32,
+    /// ID of the project the repository is listed on
+    pub project_id: i32,
+    /// URL of the repository
+    pub repo: String,
+    /// ETag of the first page of commits from the last sync
+    #[serde(skip)]
+    pub commits_etag: Option<String>,
+    /// ETag of the first page of pull requests from the last sync
+    #[serde(skip)]
+    pub pulls_etag: Option<String>,
+    /// Oldest commit listed by a sync that didn't get back to cached history
+    #[serde(skip)]
+    pub commits_cursor: Option<String>,
+    /// When the repository was last synced successfully
+    pub synced_at: Option<NaiveDateTime>,
+    /// Error from the last failed sync, if any
+    pub last_error: Option<String>,
+    /// Primary language of the repository as reported by the forge
+    pub language: Option<String>,
+    /// Description of the repository
+    pub description: Option<String>,
+    /// Name of the license such as "MIT License"
+    pub license: Option<String>,
+    pub stars: i32,
+    /// Open issues, GitHub counts pull requests as issues too
+    pub open_issues: i32,
+    /// The README as Markdown
+    #[serde(skip)]
+    pub readme: Option<String>,
+    /// Tag of the latest release
+    pub latest_release: Option<String>,
+}
+
+/// Used to start tracking a repository
+#[derive(Debug, Default, Clone, Insertable)]
+#[table_name = "repo_syncs"]
+pub struct NewRepoSync {
+    /// ID of the project the repository is listed on
+    pub project_id: i32,
+    /// URL of the repository
+    pub repo: String,
+}
+
+/// A cached commit
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Project)]
+pub struct RepoCommit {
+    /// ID of the commit in the cache
+    pub id: i32,
+    /// ID of the project the commit belongs to
+    pub project_id: i32,
+    /// URL of the repository the commit is in
+    pub repo: String,
+    /// The commit hash
+    pub sha: String,
+    /// Forge login of the author if it is linked to an account
+    pub author: Option<String>,
+    /// The commit message
+    pub message: String,
+    /// When the commit was authored
+    pub committed_at: NaiveDateTime,
+}
+
+/// Used to add a commit to the cache
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "repo_commits"]
+pub struct NewRepoCommit {
+    pub project_id: i32,
+    pub repo: String,
+    pub sha: String,
+    pub author: Option<String>,
+    pub message: String,
+    pub committed_at: NaiveDateTime,
+}
+
+/// A cached pull request
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Project)]
+pub struct RepoPull {
+    /// ID of the pull request in the cache
+    pub id: i32,
+    /// ID of the project the pull request belongs to
+    pub project_id: i32,
+    /// URL of the repository the pull request is in
+    pub repo: String,
+    /// The pull request number
+    pub number: i32,
+    /// Forge login of the user who opened it
+    pub author: Option<String>,
+    /// Title of the pull request
+    pub title: String,
+    /// Either `open` or `closed`
+    pub state: String,
+    /// Was the pull request merged?
+    pub merged: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub closed_at: Option<NaiveDateTime>,
+    pub merged_at: Option<NaiveDateTime>,
+    /// Lines added by the pull request
+    pub additions: i32,
+    /// Lines removed by the pull request
+    pub deletions: i32,
+}
+
+/// Used to add or replace a pull request in the cache
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "repo_pulls"]
+pub struct NewRepoPull {
+    pub project_id: i32,
+    pub repo: String,
+    pub number: i32,
+    pub author: Option<String>,
+    pub title: String,
+    pub state: String,
+    pub merged: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub closed_at: Option<NaiveDateTime>,
+    pub merged_at: Option<NaiveDateTime>,
+    pub additions: i32,
+    pub deletions: i32,
+}
+
+/// A cached review of a pull request
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Project)]
+pub struct RepoReview {
+    /// ID of the review in the cache
+    pub id: i32,
+    /// ID of the project the review belongs to
+    pub project_id: i32,
+    /// URL of the repository the review is in
+    pub repo: String,
+    /// ID of the review on the forge
+    pub review_id: i64,
+    /// Number of the pull request that was reviewed
+    pub pull_number: i32,
+    /// Forge login of the reviewer
+    pub author: Option<String>,
+    /// The review state such as `APPROVED` or `CHANGES_REQUESTED`
+    pub state: String,
+    /// When the review was submitted
+    pub submitted_at: NaiveDateTime,
+}
+
+/// Used to add or replace a review in the cache
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "repo_reviews"]
+pub struct NewRepoReview {
+    pub project_id: i32,
+    pub repo: String,
+    pub review_id: i64,
+    pub pull_number: i32,
+    pub author: Option<String>,
+    pub state: String,
+    pub submitted_at: NaiveDateTime,
+}
+
+/// A cached issue
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Project)]
+pub struct RepoIssue {
+    /// ID of the issue in the cache
+    pub id: i32,
+    /// ID of the project the issue belongs to
+    pub project_id: i32,
+    /// URL of the repository the issue is in
+    pub repo: String,
+    /// The issue number
+    pub number: i32,
+    /// Forge login of the user who opened it
+    pub author: Option<String>,
+    /// Title of the issue
+    pub title: String,
+    /// Either `open` or `closed`
+    pub state: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub closed_at: Option<NaiveDateTime>,
+    /// Forge login of the user who closed the issue
+    pub closed_by: Option<String>,
+}
+
+/// Used to add or replace an issue in the cache
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "repo_issues"]
+pub struct NewRepoIssue {
+    pub project_id: i32,
+    pub repo: String,
+    pub number: i32,
+    pub author: Option<String>,
+    pub title: String,
+    pub state: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub closed_at: Option<NaiveDateTime>,
+    pub closed_by: Option<String>,
+}
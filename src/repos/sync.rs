@@ -0,0 +1,641 @@
+//! Repository sync worker
+//!
+//! Pulls metadata, commits, pull requests, reviews, and issues for every
+//! active project into the local cache. This is run periodically in the background
+//! by the `SyncRepos` fairing and on demand when a project owner asks for it.
+//!
+//! Syncs are incremental: commits and pull requests use conditional
+//! requests with the ETag of the last sync and stop paging once they reach
+//! something already cached (a commit listing cut short is resumed from
+//! `commits_cursor` on the next sync), while issues are requested `since`
+//! the last successful sync. Pull requests and issues that haven't been
+//! updated since they were cached aren't fetched again. Once GitHub's rate
+//! limit is nearly used up the sync stops, the rest is synced next time.
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use diesel::prelude::*;
+use diesel::{delete, insert_into, replace_into, update};
+use serde_json::Value;
+
+use crate::activity::handlers::log_activity;
+use crate::activity::models::ActivityKind;
+use crate::models::Project;
+use crate::projects::handlers::project_repos;
+
+use super::github::*;
+use super::models::*;
+
+/// Maximum number of pages fetched from a single listing per sync
+const MAX_PAGES: usize = 10;
+
+/// Sync every active project that isn't archived
+pub fn sync_all(conn: &SqliteConnection, gh: &GitHub) {
+    use crate::schema::projects::dsl::*;
+    let all: Vec<Project> = projects
+        .filter(active.eq(true).and(archived.eq(false)))
+        .load(conn)
+        .expect("Failed to get projects from database");
+    for p in all.iter() {
+        if gh.rate_limited() {
+            break;
+        }
+        sync_project(conn, gh, p);
+    }
+}
+
+/// Sync all of the repositories of a project
+///
+/// Failures are recorded on the repository's `RepoSync` rather than
+/// returned so that one broken repository doesn't stop the others.
+pub fn sync_project(conn: &SqliteConnection, gh: &GitHub, proj: &Project) {
+    let repos = project_repos(proj);
+    prune(conn, proj, &repos).expect("Failed to prune repository cache");
+
+    for r in repos.iter() {
+        if gh.rate_limited() {
+            break;
+        }
+        let path = if let Some(path) = api_path(r) {
+            path
+        } else {
+            // Only GitHub is supported for now
+            continue;
+        };
+
+        let tracked = sync_state(conn, proj.id, r).expect("Failed to get sync state from database");
+
+        use crate::schema::repo_syncs::dsl::*;
+        let res = match sync_repo(conn, gh, &tracked, &path) {
+            Ok((ce, pe)) => update(repo_syncs.find(tracked.id))
+                .set((
+                    commits_etag.eq(ce),
+                    pulls_etag.eq(pe),
+                    synced_at.eq(Some(Local::now().naive_local())),
+                    last_error.eq(None::<String>),
+                ))
+                .execute(conn),
+            Err(e) => update(repo_syncs.find(tracked.id))
+                .set(last_error.eq(Some(e.to_string())))
+                .execute(conn),
+        };
+        res.expect("Failed to update sync state in database");
+    }
+}
+
+/// Sync a single repository, returning the new ETags
+fn sync_repo(
+    conn: &SqliteConnection,
+    gh: &GitHub,
+    tracked: &RepoSync,
+    path: &str,
+) -> Result<(Option<String>, Option<String>), SyncError> {
+    sync_metadata(conn, gh, tracked, path)?;
+    let ce = sync_commits(conn, gh, tracked, path)?;
+    let pe = sync_pulls(conn, gh, tracked, path)?;
+    sync_issues(conn, gh, tracked, path)?;
+    Ok((ce, pe))
+}
+
+/// Update what the forge says about the repository itself
+///
+/// This includes the README and latest release, either of which might
+/// not exist.
+fn sync_metadata(
+    conn: &SqliteConnection,
+    gh: &GitHub,
+    tracked: &RepoSync,
+    path: &str,
+) -> Result<(), SyncError> {
+    use crate::schema::repo_syncs::dsl::*;
+
+    let meta = match gh.get(&format!("{}/repos/{}", API, path), None)? {
+        Some(page) => page.body,
+        None => return Ok(()),
+    };
+    let readme_md = missing_ok(gh.get(&format!("{}/repos/{}/readme", API, path), None))?
+        .and_then(|page| decode_content(&page.body));
+    let release = missing_ok(gh.get(&format!("{}/repos/{}/releases/latest", API, path), None))?
+        .and_then(|page| page.body["tag_name"].as_str().map(String::from));
+
+    update(repo_syncs.find(tracked.id))
+        .set((
+            language.eq(meta["language"].as_str().map(String::from)),
+            description.eq(meta["description"].as_str().map(String::from)),
+            license.eq(meta["license"]["name"].as_str().map(String::from)),
+            stars.eq(meta["stargazers_count"].as_i64().unwrap_or_default() as i32),
+            open_issues.eq(meta["open_issues_count"].as_i64().unwrap_or_default() as i32),
+            readme.eq(readme_md),
+            latest_release.eq(release),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Cache new commits on the default branch
+///
+/// The listing stops at the first commit that is already cached. When it
+/// runs out of pages or fails before that, the oldest commit it got to is
+/// kept in `commits_cursor` and the next sync lists older history from
+/// there so the cache is left without gaps.
+fn sync_commits(
+    conn: &SqliteConnection,
+    gh: &GitHub,
+    tracked: &RepoSync,
+    path: &str,
+) -> Result<Option<String>, SyncError> {
+    let mut url = format!("{}/repos/{}/commits?per_page=100", API, path);
+    let mut etag = tracked.commits_etag.clone();
+    let mut cursor = tracked.commits_cursor.clone();
+    let mut added = 0;
+
+    for n in 0..MAX_PAGES {
+        let cond = if n == 0 {
+            tracked.commits_etag.as_ref().map(String::as_str)
+        } else {
+            None
+        };
+        let page = match gh.get(&url, cond)? {
+            Some(page) => page,
+            None => break,
+        };
+        if n == 0 {
+            etag = page.etag.clone();
+        }
+
+        let (new, caught_up) = cache_commits(conn, tracked, page.items(), true)?;
+        added += new;
+        let oldest = oldest_sha(page.items());
+        match page.next {
+            Some(next) if !caught_up => {
+                // Everything below this page is missing until a later
+                // page reaches the cache
+                set_cursor(conn, tracked, oldest)?;
+                url = next;
+            }
+            Some(_) => {
+                set_cursor(conn, tracked, cursor.clone())?;
+                break;
+            }
+            None => {
+                cursor = None;
+                set_cursor(conn, tracked, None)?;
+                break;
+            }
+        }
+    }
+
+    // Finish listing older history that an earlier sync didn't get to
+    if let Some(from) = cursor {
+        let mut url = format!("{}/repos/{}/commits?sha={}&per_page=100", API, path, from);
+        for _ in 0..MAX_PAGES {
+            let page = match gh.get(&url, None)? {
+                Some(page) => page,
+                None => break,
+            };
+            cache_commits(conn, tracked, page.items(), false)?;
+            let oldest = oldest_sha(page.items());
+            match page.next {
+                Some(next) => {
+                    set_cursor(conn, tracked, oldest)?;
+                    url = next;
+                }
+                None => {
+                    set_cursor(conn, tracked, None)?;
+                    break;
+                }
+            }
+        }
+    }
+
+    if added > 0 {
+        log_activity(
+            conn,
+            tracked.project_id,
+            None,
+            ActivityKind::Commit,
+            &format!(
+                "{} new commit{} in {}",
+                added,
+                if added == 1 { "" } else { "s" },
+                tracked.repo
+            ),
+            &format!("{}/commits", tracked.repo),
+        );
+    }
+
+    Ok(etag)
+}
+
+/// Cache the commits from a page of the commit listing
+///
+/// Returns how many commits were added. With `stop` set this stops at the
+/// first commit that is already cached and also returns whether it found
+/// one.
+fn cache_commits(
+    conn: &SqliteConnection,
+    tracked: &RepoSync,
+    commits: &[Value],
+    stop: bool,
+) -> QueryResult<(usize, bool)> {
+    use crate::schema::repo_commits::dsl::*;
+
+    let mut added = 0;
+    for c in commits {
+        let csha = c["sha"].as_str().unwrap_or_default();
+        let cached = repo_commits
+            .filter(repo.eq(&tracked.repo).and(sha.eq(csha)))
+            .count()
+            .get_result::<i64>(conn)?
+            > 0;
+        if cached {
+            if stop {
+                return Ok((added, true));
+            }
+            continue;
+        }
+
+        insert_into(repo_commits)
+            .values(&NewRepoCommit {
+                project_id: tracked.project_id,
+                repo: tracked.repo.clone(),
+                sha: String::from(csha),
+                author: login(&c["author"]),
+                message: String::from(c["commit"]["message"].as_str().unwrap_or_default()),
+                committed_at: parse_time(&c["commit"]["author"]["date"])
+                    .unwrap_or_else(|| Local::now().naive_local()),
+            })
+            .execute(conn)?;
+        added += 1;
+    }
+    Ok((added, false))
+}
+
+/// SHA of the last (oldest) commit on a page of the commit listing
+fn oldest_sha(commits: &[Value]) -> Option<String> {
+    commits
+        .last()
+        .and_then(|c| c["sha"].as_str())
+        .map(String::from)
+}
+
+/// Save where the next sync should continue listing older commits from
+fn set_cursor(conn: &SqliteConnection, tracked: &RepoSync, sha: Option<String>) -> QueryResult<()> {
+    use crate::schema::repo_syncs::dsl::*;
+    update(repo_syncs.find(tracked.id))
+        .set(commits_cursor.eq(sha))
+        .execute(conn)?;
+    Ok(())
+}
+
+fn sync_pulls(
+    conn: &SqliteConnection,
+    gh: &GitHub,
+    tracked: &RepoSync,
+    path: &str,
+) -> Result<Option<String>, SyncError> {
+    let mut url = format!(
+        "{}/repos/{}/pulls?state=all&sort=updated&direction=desc&per_page=100",
+        API, path
+    );
+    let mut etag = tracked.pulls_etag.clone();
+
+    for n in 0..MAX_PAGES {
+        let cond = if n == 0 {
+            tracked.pulls_etag.as_ref().map(String::as_str)
+        } else {
+            None
+        };
+        let page = match gh.get(&url, cond)? {
+            Some(page) => page,
+            None => break,
+        };
+        if n == 0 {
+            etag = page.etag.clone();
+        }
+
+        // Pull requests are sorted by when they were last updated so
+        // stop once we reach ones that haven't changed since the last sync
+        let mut caught_up = false;
+        for p in page.items() {
+            let updated =
+                parse_time(&p["updated_at"]).unwrap_or_else(|| Local::now().naive_local());
+            if tracked.synced_at.map_or(false, |s| updated < s) {
+                caught_up = true;
+                break;
+            }
+
+            sync_pull(conn, gh, tracked, path, p, updated)?;
+        }
+
+        match page.next {
+            Some(next) if !caught_up => url = next,
+            _ => break,
+        }
+    }
+
+    Ok(etag)
+}
+
+/// Cache a single pull request along with its size and reviews
+///
+/// The listing doesn't include how many lines were changed so the pull
+/// request itself has to be fetched too. Pull requests that haven't been
+/// updated since they were cached are skipped.
+fn sync_pull(
+    conn: &SqliteConnection,
+    gh: &GitHub,
+    tracked: &RepoSync,
+    path: &str,
+    p: &Value,
+    updated: NaiveDateTime,
+) -> Result<(), SyncError> {
+    let pnum = p["number"].as_i64().unwrap_or_default() as i32;
+    let merged_now = !p["merged_at"].is_null();
+
+    // Nothing to fetch if it hasn't changed since it was cached
+    let cached: Option<(bool, NaiveDateTime)> = {
+        use crate::schema::repo_pulls::dsl::*;
+        repo_pulls
+            .filter(repo.eq(&tracked.repo).and(number.eq(pnum)))
+            .select((merged, updated_at))
+            .first(conn)
+            .optional()?
+    };
+    if cached.map(|(_, u)| u) == Some(updated) {
+        return Ok(());
+    }
+
+    let detail = gh
+        .get(&format!("{}/repos/{}/pulls/{}", API, path, pnum), None)?
+        .map(|d| d.body)
+        .unwrap_or_default();
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        {
+            use crate::schema::repo_pulls::dsl::*;
+            replace_into(repo_pulls)
+                .values(&NewRepoPull {
+                    project_id: tracked.project_id,
+                    repo: tracked.repo.clone(),
+                    number: pnum,
+                    author: login(&p["user"]),
+                    title: String::from(p["title"].as_str().unwrap_or_default()),
+                    state: String::from(p["state"].as_str().unwrap_or_default()),
+                    merged: merged_now,
+                    created_at: parse_time(&p["created_at"]).unwrap_or(updated),
+                    updated_at: updated,
+                    closed_at: parse_time(&p["closed_at"]),
+                    merged_at: parse_time(&p["merged_at"]),
+                    additions: detail["additions"].as_i64().unwrap_or_default() as i32,
+                    deletions: detail["deletions"].as_i64().unwrap_or_default() as i32,
+                })
+                .execute(conn)?;
+        }
+
+        // Opening and merging are logged as activity, other changes aren't.
+        // The first sync of a repository would log its whole history so
+        // skip it.
+        if tracked.synced_at.is_none() {
+            return Ok(());
+        }
+        let before = cached.map(|(m, _)| m);
+        let who = login(&p["user"]).unwrap_or_else(|| String::from("Someone"));
+        let title_str = p["title"].as_str().unwrap_or_default();
+        let link_url = format!("{}/pull/{}", tracked.repo, pnum);
+        if before.is_none() {
+            log_activity(
+                conn,
+                tracked.project_id,
+                None,
+                ActivityKind::Pull,
+                &format!("{} opened pull request #{}: {}", who, pnum, title_str),
+                &link_url,
+            );
+        }
+        if merged_now && before != Some(true) {
+            log_activity(
+                conn,
+                tracked.project_id,
+                None,
+                ActivityKind::Pull,
+                &format!("Pull request #{} was merged: {}", pnum, title_str),
+                &link_url,
+            );
+        }
+        Ok(())
+    })?;
+
+    let mut url = format!("{}/repos/{}/pulls/{}/reviews?per_page=100", API, path, pnum);
+    for _ in 0..MAX_PAGES {
+        let page = match gh.get(&url, None)? {
+            Some(page) => page,
+            None => break,
+        };
+
+        use crate::schema::repo_reviews::dsl::*;
+        for r in page.items() {
+            // Reviews that are still pending have no submission time
+            let submitted = match parse_time(&r["submitted_at"]) {
+                Some(t) => t,
+                None => continue,
+            };
+            replace_into(repo_reviews)
+                .values(&NewRepoReview {
+                    project_id: tracked.project_id,
+                    repo: tracked.repo.clone(),
+                    review_id: r["id"].as_i64().unwrap_or_default(),
+                    pull_number: pnum,
+                    author: login(&r["user"]),
+                    state: String::from(r["state"].as_str().unwrap_or_default()),
+                    submitted_at: submitted,
+                })
+                .execute(conn)?;
+        }
+
+        match page.next {
+            Some(next) => url = next,
+            None => break,
+        }
+    }
+
+    Ok(())
+}
+
+fn sync_issues(
+    conn: &SqliteConnection,
+    gh: &GitHub,
+    tracked: &RepoSync,
+    path: &str,
+) -> Result<(), SyncError> {
+    use crate::schema::repo_issues::dsl::*;
+
+    let mut url = format!("{}/repos/{}/issues?state=all&per_page=100", API, path);
+    if let Some(s) = tracked.synced_at {
+        url += &format!("&since={}", to_utc(s).format("%Y-%m-%dT%H:%M:%SZ"));
+    }
+
+    for _ in 0..MAX_PAGES {
+        let page = match gh.get(&url, None)? {
+            Some(page) => page,
+            None => break,
+        };
+
+        // GitHub lists pull requests as issues too, those are handled
+        // separately so skip them here
+        for i in page
+            .items()
+            .iter()
+            .filter(|i| i.get("pull_request").is_none())
+        {
+            let updated =
+                parse_time(&i["updated_at"]).unwrap_or_else(|| Local::now().naive_local());
+            let inum = i["number"].as_i64().unwrap_or_default() as i32;
+            let closed = parse_time(&i["closed_at"]);
+
+            let cached: Option<(NaiveDateTime, Option<NaiveDateTime>, Option<String>)> =
+                repo_issues
+                    .filter(repo.eq(&tracked.repo).and(number.eq(inum)))
+                    .select((updated_at, closed_at, closed_by))
+                    .first(conn)
+                    .optional()?;
+            if cached.as_ref().map(|c| c.0) == Some(updated) {
+                continue;
+            }
+
+            // Who closed an issue is only included on the issue itself, so
+            // it is only fetched when the issue was closed since it was cached
+            let closer = match cached {
+                Some((_, c, Some(by))) if closed.is_some() && c == closed => Some(by),
+                _ if i["state"].as_str() == Some("closed") => gh
+                    .get(&format!("{}/repos/{}/issues/{}", API, path, inum), None)?
+                    .and_then(|d| login(&d.body["closed_by"])),
+                _ => None,
+            };
+
+            replace_into(repo_issues)
+                .values(&NewRepoIssue {
+                    project_id: tracked.project_id,
+                    repo: tracked.repo.clone(),
+                    number: inum,
+                    author: login(&i["user"]),
+                    title: String::from(i["title"].as_str().unwrap_or_default()),
+                    state: String::from(i["state"].as_str().unwrap_or_default()),
+                    created_at: parse_time(&i["created_at"]).unwrap_or(updated),
+                    updated_at: updated,
+                    closed_at: closed,
+                    closed_by: closer,
+                })
+                .execute(conn)?;
+        }
+
+        match page.next {
+            Some(next) => url = next,
+            None => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the sync state of a repository, creating it if needed
+fn sync_state(conn: &SqliteConnection, pid: i32, url: &str) -> QueryResult<RepoSync> {
+    use crate::schema::repo_syncs::dsl::*;
+
+    let existing = repo_syncs
+        .filter(project_id.eq(pid).and(repo.eq(url)))
+        .first(conn)
+        .optional()?;
+    if let Some(s) = existing {
+        return Ok(s);
+    }
+
+    insert_into(repo_syncs)
+        .values(&NewRepoSync {
+            project_id: pid,
+            repo: String::from(url),
+        })
+        .execute(conn)?;
+    repo_syncs
+        .filter(project_id.eq(pid).and(repo.eq(url)))
+        .first(conn)
+}
+
+/// Remove cached data for repositories no longer listed on the project
+fn prune(conn: &SqliteConnection, proj: &Project, listed: &[String]) -> QueryResult<()> {
+    {
+        use crate::schema::repo_syncs::dsl::*;
+        delete(repo_syncs.filter(project_id.eq(proj.id).and(repo.ne_all(listed)))).execute(conn)?;
+    }
+    {
+        use crate::schema::repo_commits::dsl::*;
+        delete(repo_commits.filter(project_id.eq(proj.id).and(repo.ne_all(listed))))
+            .execute(conn)?;
+    }
+    {
+        use crate::schema::repo_pulls::dsl::*;
+        delete(repo_pulls.filter(project_id.eq(proj.id).and(repo.ne_all(listed)))).execute(conn)?;
+    }
+    {
+        use crate::schema::repo_reviews::dsl::*;
+        delete(repo_reviews.filter(project_id.eq(proj.id).and(repo.ne_all(listed))))
+            .execute(conn)?;
+    }
+    {
+        use crate::schema::repo_issues::dsl::*;
+        delete(repo_issues.filter(project_id.eq(proj.id).and(repo.ne_all(listed))))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Treat a 404 from the API as there being nothing there
+fn missing_ok(res: Result<Option<Page>, SyncError>) -> Result<Option<Page>, SyncError> {
+    match res {
+        Err(SyncError::Status(404)) => Ok(None),
+        res => res,
+    }
+}
+
+/// The most of a README that is kept in the cache
+const MAX_README: usize = 64 * 1024;
+
+/// Decode the base64 `content` of a file from the contents API
+///
+/// Returns `None` if it isn't valid UTF-8. Very long files are cut off.
+pub fn decode_content(body: &Value) -> Option<String> {
+    let encoded: String = body["content"]
+        .as_str()?
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let mut text = String::from_utf8(base64::decode(&encoded).ok()?).ok()?;
+    if text.len() > MAX_README {
+        let mut end = MAX_README;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    Some(text)
+}
+
+/// The login of a GitHub user object, if there is one
+fn login(user: &Value) -> Option<String> {
+    user["login"].as_str().map(String::from)
+}
+
+/// Parse a GitHub timestamp into local time
+fn parse_time(v: &Value) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(v.as_str()?)
+        .ok()
+        .map(|t| t.with_timezone(&Local).naive_local())
+}
+
+/// Convert a local time from the database to UTC
+fn to_utc(t: NaiveDateTime) -> DateTime<Utc> {
+    Local
+        .from_local_datetime(&t)
+        .earliest()
+        .unwrap_or_else(Local::now)
+        .with_timezone(&Utc)
+}
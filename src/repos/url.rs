@@ -0,0 +1,122 @@
+//! Repository URLs
+//!
+//! Projects list their repositories as URLs typed in by students, so the
+//! same repository can show up as `http://github.com/rcos/observatory.git`
+//! or `github.com/rcos/observatory/`. Every URL is parsed into a `RepoUrl`
+//! before it is stored so that it is always written the same way.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A validated and normalized repository URL
+///
+/// Always written as `https://host/owner/repo`, without a trailing slash
+/// or `.git`. Hosts like GitLab allow nested groups so the path can have
+/// more than two parts.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RepoUrl {
+    /// The lowercase host such as `github.com`
+    pub host: String,
+    /// The path such as `rcos/observatory`
+    pub path: String,
+}
+
+/// Why a repository URL couldn't be parsed
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RepoUrlError {
+    /// The scheme wasn't `http` or `https`
+    Scheme,
+    /// The host was missing or not a domain name
+    Host,
+    /// The path wasn't an owner and repository
+    Path,
+}
+
+impl fmt::Display for RepoUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RepoUrlError::Scheme => "only http and https URLs are allowed",
+                RepoUrlError::Host => "the host is not a domain name",
+                RepoUrlError::Path => "the path is not an owner and repository",
+            }
+        )
+    }
+}
+
+impl FromStr for RepoUrl {
+    type Err = RepoUrlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let rest = if s.starts_with("https://") {
+            &s[8..]
+        } else if s.starts_with("http://") {
+            &s[7..]
+        } else if s.contains("://") {
+            return Err(RepoUrlError::Scheme);
+        } else {
+            s
+        };
+
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, ""),
+        };
+
+        let host = host.to_lowercase();
+        let host = if host.starts_with("www.") {
+            host[4..].to_string()
+        } else {
+            host
+        };
+        if !host.contains('.')
+            || host.starts_with('.')
+            || host.ends_with('.')
+            || !host
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+        {
+            return Err(RepoUrlError::Host);
+        }
+
+        let mut path = path.trim_end_matches('/');
+        if path.ends_with(".git") {
+            path = path[..path.len() - 4].trim_end_matches('/');
+        }
+        let parts: Vec<&str> = path.split('/').collect();
+        let valid_part = |p: &&str| {
+            !p.is_empty()
+                && *p != "."
+                && *p != ".."
+                && p.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        };
+        if parts.len() < 2 || !parts.iter().all(valid_part) {
+            return Err(RepoUrlError::Path);
+        }
+
+        Ok(RepoUrl {
+            host,
+            path: parts.join("/"),
+        })
+    }
+}
+
+impl fmt::Display for RepoUrl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "https://{}/{}", self.host, self.path)
+    }
+}
+
+/// Normalize a URL if it can be parsed, otherwise leave it as is
+///
+/// Used when comparing against URLs already in the database, which might
+/// predate validation.
+pub fn normalize(url: &str) -> String {
+    url.parse::<RepoUrl>()
+        .map(|r| r.to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
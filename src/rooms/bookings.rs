@@ -0,0 +1,253 @@
+//! Finding what a room is booked for
+//!
+//! A room is booked by events, by meetings, and by the upcoming days of
+//! group schedules that don't have a meeting generated yet. Meetings only
+//! have a start time so they are assumed to last `MEETING_MINUTES`.
+//! Cancelled meetings and schedules of archived groups don't book rooms.
+//! Repeating events book rooms for each time they happen.
+
+use chrono::{Duration, Local, NaiveDateTime};
+use diesel::prelude::*;
+
+use crate::calendar::series::unmade_times;
+use crate::models::{Attendable, Event, Group, GroupSchedule, Meeting, NewGroupSchedule};
+
+/// How long a meeting is assumed to take
+pub const MEETING_MINUTES: i64 = 120;
+
+/// What a room is booked for
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Booked {
+    Event(i32),
+    Meeting(i32),
+    Schedule(i32),
+    /// A repeating event, only used for what is being edited
+    Series(i32),
+}
+
+/// A time a room is booked
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Booking {
+    pub title: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    /// Page of the event, meeting, or group
+    pub url: String,
+    #[serde(skip)]
+    pub booked: Booked,
+    /// ID of the schedule a meeting was generated from
+    #[serde(skip)]
+    pub schedule_id: Option<i32>,
+    /// ID of the repeating event an event is a time of
+    #[serde(skip)]
+    pub series_id: Option<i32>,
+}
+
+impl Booking {
+    /// Is the booking for `own` or, if `own` is a schedule or repeating
+    /// event, one of its meetings or times?
+    ///
+    /// Used so that editing something doesn't conflict with itself.
+    pub fn is_part_of(&self, own: Option<Booked>) -> bool {
+        match own {
+            Some(o) if o == self.booked => true,
+            Some(Booked::Schedule(sid)) => self.schedule_id == Some(sid),
+            Some(Booked::Series(sid)) => self.series_id == Some(sid),
+            _ => false,
+        }
+    }
+
+    /// Does the booking overlap `start` until `end`?
+    pub fn overlaps(&self, start: NaiveDateTime, end: NaiveDateTime) -> bool {
+        self.start < end && start < self.end
+    }
+}
+
+/// Everything a room is booked for from `from` until `until`, in order
+pub fn bookings(
+    conn: &SqliteConnection,
+    rid: i32,
+    from: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Vec<Booking> {
+    let length = Duration::minutes(MEETING_MINUTES);
+    let mut booked: Vec<Booking> = Vec::new();
+
+    let (mut evs, series): (Vec<Event>, Vec<Event>) = {
+        use crate::schema::events::dsl::*;
+        events
+            .filter(room_id.eq(rid))
+            .filter(start.lt(until).and(end.gt(from)).or(rrule.is_not_null()))
+            .load::<Event>(conn)
+            .expect("Failed to get events from database")
+            .into_iter()
+            .partition(|e| !e.is_series())
+    };
+    for s in series.iter() {
+        evs.extend(unmade_times(conn, s, Some((from, until))));
+    }
+    booked.extend(evs.into_iter().map(|e| {
+        let url = match (e.series_id, e.occurrence) {
+            (Some(sid), Some(day)) if sid == e.id => format!("/calendar/{}?on={}", sid, day),
+            _ => e.url(),
+        };
+        Booking {
+            title: e.title.clone(),
+            start: e.start,
+            end: e.end,
+            url,
+            booked: Booked::Event(e.id),
+            schedule_id: None,
+            series_id: e.series_id,
+        }
+    }));
+
+    let ms: Vec<Meeting> = {
+        use crate::schema::meetings::dsl::*;
+        meetings
+            .filter(room_id.eq(rid).and(cancelled.eq(false)))
+            .filter(happened_at.lt(until).and(happened_at.gt(from - length)))
+            .load(conn)
+            .expect("Failed to get meetings from database")
+    };
+    let names: Vec<(i32, String)> = {
+        use crate::schema::groups::dsl::*;
+        groups
+            .filter(id.eq_any(ms.iter().map(|m| m.group_id).collect::<Vec<i32>>()))
+            .select((id, name))
+            .load(conn)
+            .expect("Failed to get groups from database")
+    };
+    booked.extend(ms.into_iter().map(|m| {
+        let group = names
+            .iter()
+            .find(|(gid, _)| *gid == m.group_id)
+            .map(|(_, n)| n.clone())
+            .unwrap_or_default();
+        Booking {
+            title: format!("{}: {}", group, m.title_or_default()),
+            start: m.happened_at,
+            end: m.happened_at + length,
+            url: m.url(),
+            booked: Booked::Meeting(m.id),
+            schedule_id: m.schedule_id,
+            series_id: None,
+        }
+    }));
+
+    let scheds: Vec<(GroupSchedule, Group)> = {
+        use crate::schema::group_schedules::dsl::*;
+        use crate::schema::groups::dsl::{archived, groups};
+        group_schedules
+            .inner_join(groups)
+            .filter(room_id.eq(rid).and(archived.eq(false)))
+            .load(conn)
+            .expect("Failed to get schedules from database")
+    };
+    for (s, g) in scheds {
+        let generated: Vec<Option<chrono::NaiveDate>> = {
+            use crate::schema::meetings::dsl::*;
+            meetings
+                .filter(schedule_id.eq(s.id))
+                .select(occurrence)
+                .load(conn)
+                .expect("Failed to get meetings from database")
+        };
+        for day in s.occurrences((from - length).date(), until.date()) {
+            let start = day.and_time(s.start_time);
+            if generated.contains(&Some(day)) || !(start < until && from < start + length) {
+                continue;
+            }
+            booked.push(Booking {
+                title: g.name.clone(),
+                start,
+                end: start + length,
+                url: format!("/groups/{}", g.id),
+                booked: Booked::Schedule(s.id),
+                schedule_id: Some(s.id),
+                series_id: None,
+            });
+        }
+    }
+
+    booked.sort_by(|a, b| a.start.cmp(&b.start));
+    booked
+}
+
+/// Bookings of a room that overlap `start` until `end`
+///
+/// `own` is what is being booked when it is edited so that it doesn't
+/// conflict with itself.
+pub fn conflicts(
+    conn: &SqliteConnection,
+    rid: i32,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    own: Option<Booked>,
+) -> Vec<Booking> {
+    bookings(conn, rid, start, end)
+        .into_iter()
+        .filter(|b| !b.is_part_of(own))
+        .collect()
+}
+
+/// Bookings of the schedule's room that overlap its upcoming meetings
+///
+/// `sid` is the ID of the schedule when it is edited. Days that already
+/// happened aren't checked.
+pub fn schedule_conflicts(
+    conn: &SqliteConnection,
+    new: &NewGroupSchedule,
+    sid: Option<i32>,
+) -> Vec<Booking> {
+    let rid = match new.room_id {
+        Some(rid) => rid,
+        None => return Vec::new(),
+    };
+    let sched = GroupSchedule {
+        id: sid.unwrap_or(0),
+        group_id: new.group_id,
+        weekday: new.weekday,
+        start_time: new.start_time,
+        location: new.location.clone(),
+        starts_on: new.starts_on,
+        ends_on: new.ends_on,
+        skip_dates: new.skip_dates.clone(),
+        room_id: new.room_id,
+    };
+    let today = Local::today().naive_local();
+    let from = today.max(sched.starts_on);
+    let times: Vec<(NaiveDateTime, NaiveDateTime)> = sched
+        .occurrences(from, sched.ends_on)
+        .into_iter()
+        .map(|day| {
+            let start = day.and_time(sched.start_time);
+            (start, start + Duration::minutes(MEETING_MINUTES))
+        })
+        .collect();
+    conflicts_any(conn, rid, &times, sid.map(Booked::Schedule))
+}
+
+/// Bookings of a room that overlap any of the times
+///
+/// `own` is what is being booked when it is edited so that it doesn't
+/// conflict with itself.
+pub fn conflicts_any(
+    conn: &SqliteConnection,
+    rid: i32,
+    times: &[(NaiveDateTime, NaiveDateTime)],
+    own: Option<Booked>,
+) -> Vec<Booking> {
+    let first = times.iter().map(|(start, _)| *start).min();
+    let last = times.iter().map(|(_, end)| *end).max();
+    let (first, last) = match (first, last) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Vec::new(),
+    };
+
+    bookings(conn, rid, first, last)
+        .into_iter()
+        .filter(|b| !b.is_part_of(own))
+        .filter(|b| times.iter().any(|(start, end)| b.overlaps(*start, *end)))
+        .collect()
+}
@@ -0,0 +1,214 @@
+//! HTTP handlers for rooms
+
+use chrono::{Duration, Local, NaiveDate};
+use diesel::prelude::*;
+use diesel::{delete, insert_into, update};
+use rocket::http::Status;
+use rocket::request::Form;
+use rocket::response::Redirect;
+
+use rocket_contrib::json::Json;
+
+use crate::guards::*;
+
+use super::bookings::*;
+use super::models::*;
+use super::templates::*;
+use crate::templates::FormError;
+use crate::ObservDbConn;
+
+/// GET handler for `/rooms`
+#[get("/rooms")]
+pub fn rooms(conn: ObservDbConn, l: UserGuard) -> RoomsTemplate {
+    RoomsTemplate {
+        logged_in: Some(l.0),
+        rooms: all_rooms(&*conn),
+    }
+}
+
+/// GET handler for `/rooms/new`
+///
+/// Restricted to Admins.
+#[get("/rooms/new?<e>")]
+pub fn room_new(l: AdminGuard, e: Option<FormError>) -> EditRoomTemplate {
+    EditRoomTemplate {
+        logged_in: Some(l.0),
+        room: None,
+        error: e,
+    }
+}
+
+/// POST handler for `/rooms/new`
+///
+/// Restricted to Admins.
+#[post("/rooms/new", data = "<newroom>")]
+pub fn room_new_post(conn: ObservDbConn, _l: AdminGuard, newroom: Form<NewRoom>) -> Redirect {
+    use crate::schema::rooms::dsl::*;
+
+    let newroom = newroom.into_inner().cleaned();
+    if newroom.name.is_empty() {
+        return Redirect::to(format!("/rooms/new?e={}", FormError::Other));
+    }
+    insert_into(rooms)
+        .values(&newroom)
+        .execute(&*conn)
+        .expect("Failed to insert room into database");
+    Redirect::to("/rooms")
+}
+
+/// GET handler for `/rooms/<rid>`
+///
+/// The room's calendar along with what it is booked for the next four
+/// weeks for when JS is off.
+#[get("/rooms/<rid>")]
+pub fn room(conn: ObservDbConn, l: UserGuard, rid: i32) -> Option<RoomTemplate> {
+    let r = get_room(&*conn, rid)?;
+    let now = Local::now().naive_local();
+    Some(RoomTemplate {
+        logged_in: Some(l.0),
+        upcoming: bookings(&*conn, r.id, now, now + Duration::weeks(4)),
+        room: r,
+    })
+}
+
+/// GET handler for `/rooms/<rid>/bookings.json`
+///
+/// What the room is booked for from `start` until `end` as a JSON array
+/// for FullCalendar. Only the dates are read, so times and time zones
+/// are ignored. Defaults to the month before through four months after
+/// today, and at most a year is returned.
+#[get("/rooms/<rid>/bookings.json?<start>&<end>")]
+pub fn room_bookings_json(
+    conn: ObservDbConn,
+    _l: UserGuard,
+    rid: i32,
+    start: Option<String>,
+    end: Option<String>,
+) -> Option<Json<Vec<Booking>>> {
+    let r = get_room(&*conn, rid)?;
+    let date =
+        |d: Option<String>| d.and_then(|d| NaiveDate::parse_from_str(d.get(..10)?, "%F").ok());
+    let today = Local::today().naive_local();
+    let from = date(start).unwrap_or(today - Duration::days(30));
+    let until = date(end)
+        .unwrap_or(today + Duration::days(120))
+        .min(from + Duration::days(366));
+    Some(Json(bookings(
+        &*conn,
+        r.id,
+        from.and_hms(0, 0, 0),
+        until.and_hms(0, 0, 0),
+    )))
+}
+
+/// GET handler for `/rooms/<rid>/edit`
+///
+/// Restricted to Admins.
+#[get("/rooms/<rid>/edit?<e>")]
+pub fn room_edit(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    rid: i32,
+    e: Option<FormError>,
+) -> Option<EditRoomTemplate> {
+    Some(EditRoomTemplate {
+        logged_in: Some(l.0),
+        room: Some(get_room(&*conn, rid)?),
+        error: e,
+    })
+}
+
+/// PUT handler for `/rooms/<rid>`
+///
+/// Restricted to Admins.
+#[put("/rooms/<rid>", data = "<editroom>")]
+pub fn room_edit_put(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    rid: i32,
+    editroom: Form<NewRoom>,
+) -> Result<Redirect, Status> {
+    use crate::schema::rooms::dsl::*;
+
+    get_room(&*conn, rid).ok_or(Status::NotFound)?;
+    let editroom = editroom.into_inner().cleaned();
+    if editroom.name.is_empty() {
+        return Ok(Redirect::to(format!(
+            "/rooms/{}/edit?e={}",
+            rid,
+            FormError::Other
+        )));
+    }
+    update(rooms.find(rid))
+        .set(&editroom)
+        .execute(&*conn)
+        .expect("Failed to update room in database");
+    Ok(Redirect::to(format!("/rooms/{}", rid)))
+}
+
+/// DELETE handler for `/rooms/<rid>`
+///
+/// Events, meetings, and schedules in the room are kept but are no
+/// longer in a room.
+///
+/// Restricted to Admins.
+#[delete("/rooms/<rid>")]
+pub fn room_delete(conn: ObservDbConn, _l: AdminGuard, rid: i32) -> Redirect {
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        {
+            use crate::schema::events::dsl::*;
+            update(events.filter(room_id.eq(rid)))
+                .set(room_id.eq(None::<i32>))
+                .execute(&*conn)?;
+        }
+        {
+            use crate::schema::meetings::dsl::*;
+            update(meetings.filter(room_id.eq(rid)))
+                .set(room_id.eq(None::<i32>))
+                .execute(&*conn)?;
+        }
+        {
+            use crate::schema::group_schedules::dsl::*;
+            update(group_schedules.filter(room_id.eq(rid)))
+                .set(room_id.eq(None::<i32>))
+                .execute(&*conn)?;
+        }
+        use crate::schema::rooms::dsl::*;
+        delete(rooms.find(rid)).execute(&*conn)?;
+        Ok(())
+    })
+    .expect("Failed to delete room from database");
+    Redirect::to("/rooms")
+}
+
+//# Helper Functions
+
+/// All the rooms ordered by building then name
+pub fn all_rooms(conn: &SqliteConnection) -> Vec<Room> {
+    use crate::schema::rooms::dsl::*;
+    rooms
+        .order((building.asc(), name.asc()))
+        .load(conn)
+        .expect("Failed to get rooms from database")
+}
+
+/// All the rooms to pick from in a form with `selected` picked
+pub fn room_options(conn: &SqliteConnection, selected: Option<i32>) -> Vec<RoomOption> {
+    all_rooms(conn)
+        .into_iter()
+        .map(|r| RoomOption {
+            selected: Some(r.id) == selected,
+            room: r,
+        })
+        .collect()
+}
+
+/// Get a room by its ID
+pub fn get_room(conn: &SqliteConnection, rid: i32) -> Option<Room> {
+    use crate::schema::rooms::dsl::*;
+    rooms
+        .find(rid)
+        .first(conn)
+        .optional()
+        .expect("Failed to get room from database")
+}
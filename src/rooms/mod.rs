@@ -0,0 +1,17 @@
+//! Rooms that events and meetings are booked into
+//!
+//! Events, meetings, and group schedules can each be in a room. Two
+//! bookings of the same room can't overlap, see `bookings`.
+//!
+//! ## Routes
+//! - `/rooms`
+//! - `/rooms/new`
+//! - `/rooms/<rid>`
+//! - `/rooms/<rid>/bookings.json`
+//! - `/rooms/<rid>/edit`
+
+pub mod bookings;
+pub mod handlers;
+pub mod models;
+
+mod templates;
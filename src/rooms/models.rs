@@ -0,0 +1,65 @@
+//! Models for rooms
+//!
+//! Rooms are stored in the `rooms` table. Events, meetings, and group
+//! schedules point to the room they are in with `room_id`.
+
+use crate::schema::*;
+
+/// A room on campus
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Serialize)]
+pub struct Room {
+    /// ID of the room
+    pub id: i32,
+    /// Name of the room like "DCC 330"
+    pub name: String,
+    /// Building the room is in
+    pub building: Option<String>,
+    /// Most people the room fits
+    pub capacity: Option<i32>,
+    /// Accessibility notes like where the elevator is
+    pub accessibility: Option<String>,
+}
+
+impl Room {
+    /// The name along with the building like "DCC 330, Darrin"
+    pub fn full_name(&self) -> String {
+        match &self.building {
+            Some(b) => format!("{}, {}", self.name, b),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Used to create or edit a room
+///
+/// Empty text fields are cleared.
+#[derive(Debug, Default, Clone, FromForm, Insertable, AsChangeset)]
+#[table_name = "rooms"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NewRoom {
+    pub name: String,
+    pub building: Option<String>,
+    /// Empty for no limit
+    pub capacity: Option<i32>,
+    pub accessibility: Option<String>,
+}
+
+impl NewRoom {
+    /// Trim the fields and clear the empty ones
+    pub fn cleaned(self) -> NewRoom {
+        let text = |t: Option<String>| t.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+        NewRoom {
+            name: self.name.trim().to_string(),
+            building: text(self.building),
+            capacity: self.capacity.filter(|c| *c > 0),
+            accessibility: text(self.accessibility),
+        }
+    }
+}
+
+/// A room to pick in a form
+pub struct RoomOption {
+    pub room: Room,
+    /// Is the room the one already picked?
+    pub selected: bool,
+}
@@ -0,0 +1,43 @@
+//! HTML templates for rooms
+
+use super::bookings::Booking;
+use super::models::*;
+#[allow(unused_imports)]
+use crate::templates::{filters, FormError, OptUser};
+
+/// Rooms list template
+///
+/// HTML File: `rooms/rooms.html`
+#[derive(Template)]
+#[template(path = "rooms/rooms.html")]
+pub struct RoomsTemplate {
+    pub logged_in: OptUser,
+    pub rooms: Vec<Room>,
+}
+
+/// Room page template
+///
+/// HTML File: `rooms/room.html`
+///
+/// Shows the room's calendar, or a list of its upcoming bookings if JS
+/// is off.
+#[derive(Template)]
+#[template(path = "rooms/room.html")]
+pub struct RoomTemplate {
+    pub logged_in: OptUser,
+    pub room: Room,
+    /// What the room is booked for the next four weeks
+    pub upcoming: Vec<Booking>,
+}
+
+/// Template to create or edit a room
+///
+/// HTML File: `rooms/edit-room.html`
+#[derive(Template)]
+#[template(path = "rooms/edit-room.html")]
+pub struct EditRoomTemplate {
+    pub logged_in: OptUser,
+    /// The room being edited, if any
+    pub room: Option<Room>,
+    pub error: Option<FormError>,
+}
@@ -8,6 +8,59 @@ table! {
     }
 }
 
+table! {
+    calendar_tokens (id) {
+        id -> Integer,
+        user_id -> Integer,
+        token -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    eval_forms (id) {
+        id -> Integer,
+        title -> Text,
+        description -> Text,
+        questions -> Text,
+        semester -> Text,
+        allow_self -> Bool,
+        visible_to_student -> Bool,
+        graded -> Bool,
+        open -> Bool,
+        created_by -> Integer,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    evaluations (id) {
+        id -> Integer,
+        form_id -> Integer,
+        student_id -> Integer,
+        evaluator_id -> Integer,
+        answers -> Text,
+        submitted_at -> Timestamp,
+    }
+}
+
+table! {
+    event_imports (id) {
+        id -> Integer,
+        uploaded_by -> Integer,
+        ics -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    event_rsvps (id) {
+        id -> Integer,
+        event_id -> Integer,
+        user_id -> Integer,
+    }
+}
+
 table! {
     events (id) {
         id -> Integer,
@@ -19,6 +72,72 @@ table! {
         location -> Nullable<Text>,
         code -> Text,
         color -> Nullable<Text>,
+        room_id -> Nullable<Integer>,
+        import_uid -> Nullable<Text>,
+        rrule -> Nullable<Text>,
+        exdates -> Text,
+        series_id -> Nullable<Integer>,
+        occurrence -> Nullable<Date>,
+    }
+}
+
+table! {
+    grade_audit (id) {
+        id -> Integer,
+        grade_id -> Integer,
+        user_id -> Integer,
+        action -> Text,
+        old_value -> Nullable<Integer>,
+        new_value -> Nullable<Integer>,
+        reason -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    grades (id) {
+        id -> Integer,
+        user_id -> Integer,
+        semester -> Text,
+        computed -> Integer,
+        override_value -> Nullable<Integer>,
+        reason -> Nullable<Text>,
+        overridden_by -> Nullable<Integer>,
+        overridden_at -> Nullable<Timestamp>,
+        submitted -> Bool,
+        submitted_by -> Nullable<Integer>,
+        submitted_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    group_mentors (id) {
+        id -> Integer,
+        group_id -> Integer,
+        user_id -> Integer,
+        role -> Text,
+    }
+}
+
+table! {
+    group_project_prefs (id) {
+        id -> Integer,
+        group_id -> Integer,
+        project_id -> Integer,
+    }
+}
+
+table! {
+    group_schedules (id) {
+        id -> Integer,
+        group_id -> Integer,
+        weekday -> Integer,
+        start_time -> Time,
+        location -> Nullable<Text>,
+        starts_on -> Date,
+        ends_on -> Date,
+        skip_dates -> Text,
+        room_id -> Nullable<Integer>,
     }
 }
 
@@ -28,6 +147,20 @@ table! {
         name -> Text,
         owner_id -> Integer,
         location -> Nullable<Text>,
+        archived -> Bool,
+        capacity -> Nullable<Integer>,
+        parent_id -> Nullable<Integer>,
+        join_on_signup -> Bool,
+    }
+}
+
+table! {
+    join_requests (id) {
+        id -> Integer,
+        project_id -> Integer,
+        user_id -> Integer,
+        message -> Text,
+        created_at -> Timestamp,
     }
 }
 
@@ -38,6 +171,14 @@ table! {
         code -> Text,
         group_id -> Integer,
         hosted_by -> Integer,
+        schedule_id -> Nullable<Integer>,
+        occurrence -> Nullable<Date>,
+        cancelled -> Bool,
+        title -> Nullable<Text>,
+        agenda -> Nullable<Text>,
+        notes -> Nullable<Text>,
+        location -> Nullable<Text>,
+        room_id -> Nullable<Integer>,
     }
 }
 
@@ -49,6 +190,52 @@ table! {
         description -> Text,
         color -> Nullable<Text>,
         announcement -> Bool,
+        image -> Nullable<Text>,
+    }
+}
+
+table! {
+    notifications (id) {
+        id -> Integer,
+        user_id -> Integer,
+        message -> Text,
+        link -> Text,
+        seen -> Bool,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    project_activity (id) {
+        id -> Integer,
+        project_id -> Integer,
+        user_id -> Nullable<Integer>,
+        kind -> Text,
+        message -> Text,
+        link -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    project_proposals (id) {
+        id -> Integer,
+        project_id -> Integer,
+        goals -> Text,
+        stack -> Text,
+        license -> Text,
+        team_size -> Integer,
+        submitted_at -> Timestamp,
+        reviewed_by -> Nullable<Integer>,
+        reviewed_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    project_tags (id) {
+        id -> Integer,
+        project_id -> Integer,
+        tag -> Text,
     }
 }
 
@@ -62,6 +249,22 @@ table! {
         active -> Bool,
         repos -> Text,
         extrn -> Bool,
+        status -> Text,
+        join_policy -> Text,
+        max_size -> Nullable<Integer>,
+        language -> Nullable<Text>,
+        archived -> Bool,
+        logo -> Nullable<Text>,
+    }
+}
+
+table! {
+    proposal_comments (id) {
+        id -> Integer,
+        proposal_id -> Integer,
+        user_id -> Integer,
+        body -> Text,
+        created_at -> Timestamp,
     }
 }
 
@@ -70,6 +273,8 @@ table! {
         id -> Integer,
         group_id -> Integer,
         user_id -> Integer,
+        joined_at -> Nullable<Timestamp>,
+        left_at -> Nullable<Timestamp>,
     }
 }
 
@@ -78,6 +283,183 @@ table! {
         id -> Integer,
         project_id -> Integer,
         user_id -> Integer,
+        role -> Text,
+        joined_at -> Nullable<Timestamp>,
+        left_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    repo_commits (id) {
+        id -> Integer,
+        project_id -> Integer,
+        repo -> Text,
+        sha -> Text,
+        author -> Nullable<Text>,
+        message -> Text,
+        committed_at -> Timestamp,
+    }
+}
+
+table! {
+    repo_issues (id) {
+        id -> Integer,
+        project_id -> Integer,
+        repo -> Text,
+        number -> Integer,
+        author -> Nullable<Text>,
+        title -> Text,
+        state -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        closed_at -> Nullable<Timestamp>,
+        closed_by -> Nullable<Text>,
+    }
+}
+
+table! {
+    repo_pulls (id) {
+        id -> Integer,
+        project_id -> Integer,
+        repo -> Text,
+        number -> Integer,
+        author -> Nullable<Text>,
+        title -> Text,
+        state -> Text,
+        merged -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        closed_at -> Nullable<Timestamp>,
+        merged_at -> Nullable<Timestamp>,
+        additions -> Integer,
+        deletions -> Integer,
+    }
+}
+
+table! {
+    repo_reviews (id) {
+        id -> Integer,
+        project_id -> Integer,
+        repo -> Text,
+        review_id -> BigInt,
+        pull_number -> Integer,
+        author -> Nullable<Text>,
+        state -> Text,
+        submitted_at -> Timestamp,
+    }
+}
+
+table! {
+    repo_syncs (id) {
+        id -> Integer,
+        project_id -> Integer,
+        repo -> Text,
+        commits_etag -> Nullable<Text>,
+        pulls_etag -> Nullable<Text>,
+        commits_cursor -> Nullable<Text>,
+        synced_at -> Nullable<Timestamp>,
+        last_error -> Nullable<Text>,
+        language -> Nullable<Text>,
+        description -> Nullable<Text>,
+        license -> Nullable<Text>,
+        stars -> Integer,
+        open_issues -> Integer,
+        readme -> Nullable<Text>,
+        latest_release -> Nullable<Text>,
+    }
+}
+
+table! {
+    rooms (id) {
+        id -> Integer,
+        name -> Text,
+        building -> Nullable<Text>,
+        capacity -> Nullable<Integer>,
+        accessibility -> Nullable<Text>,
+    }
+}
+
+table! {
+    showcase_entries (id) {
+        id -> Integer,
+        showcase_id -> Integer,
+        project_id -> Integer,
+    }
+}
+
+table! {
+    showcase_judges (id) {
+        id -> Integer,
+        showcase_id -> Integer,
+        user_id -> Integer,
+    }
+}
+
+table! {
+    showcase_scores (id) {
+        id -> Integer,
+        entry_id -> Integer,
+        judge_id -> Integer,
+        scores -> Text,
+        submitted_at -> Timestamp,
+    }
+}
+
+table! {
+    showcase_votes (id) {
+        id -> Integer,
+        showcase_id -> Integer,
+        entry_id -> Integer,
+        user_id -> Integer,
+        category -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    showcases (id) {
+        id -> Integer,
+        event_id -> Integer,
+        categories -> Text,
+        rubric -> Text,
+        voting_open -> Bool,
+    }
+}
+
+table! {
+    status_update_comments (id) {
+        id -> Integer,
+        update_id -> Integer,
+        user_id -> Integer,
+        body -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    status_updates (id) {
+        id -> Integer,
+        user_id -> Integer,
+        project_id -> Integer,
+        week -> Date,
+        body -> Text,
+        created_at -> Timestamp,
+        reviewed_by -> Nullable<Integer>,
+        reviewed_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    uploads (id) {
+        id -> Integer,
+        key -> Text,
+        kind -> Text,
+        content_type -> Text,
+        size -> Integer,
+        width -> Integer,
+        height -> Integer,
+        uploaded_by -> Integer,
+        created_at -> Timestamp,
     }
 }
 
@@ -96,25 +478,100 @@ table! {
         mmost -> Text,
         former -> Bool,
         extrn -> Bool,
+        avatar -> Nullable<Text>,
     }
 }
 
 joinable!(attendances -> events (event_id));
 joinable!(attendances -> meetings (meeting_id));
 joinable!(attendances -> users (user_id));
+joinable!(calendar_tokens -> users (user_id));
+joinable!(evaluations -> eval_forms (form_id));
+joinable!(event_imports -> users (uploaded_by));
+joinable!(event_rsvps -> events (event_id));
+joinable!(event_rsvps -> users (user_id));
+joinable!(events -> rooms (room_id));
+joinable!(grade_audit -> grades (grade_id));
+joinable!(group_mentors -> groups (group_id));
+joinable!(group_mentors -> users (user_id));
+joinable!(group_project_prefs -> groups (group_id));
+joinable!(group_project_prefs -> projects (project_id));
+joinable!(group_schedules -> groups (group_id));
+joinable!(group_schedules -> rooms (room_id));
+joinable!(grade_audit -> users (user_id));
+joinable!(join_requests -> projects (project_id));
+joinable!(meetings -> group_schedules (schedule_id));
+joinable!(meetings -> rooms (room_id));
+joinable!(join_requests -> users (user_id));
+joinable!(notifications -> users (user_id));
+joinable!(project_activity -> projects (project_id));
+joinable!(project_activity -> users (user_id));
+joinable!(project_proposals -> projects (project_id));
+joinable!(project_tags -> projects (project_id));
+joinable!(proposal_comments -> project_proposals (proposal_id));
+joinable!(proposal_comments -> users (user_id));
 joinable!(relation_group_user -> groups (group_id));
 joinable!(relation_group_user -> users (user_id));
 joinable!(relation_project_user -> projects (project_id));
 joinable!(relation_project_user -> users (user_id));
+joinable!(repo_commits -> projects (project_id));
+joinable!(repo_issues -> projects (project_id));
+joinable!(repo_pulls -> projects (project_id));
+joinable!(repo_reviews -> projects (project_id));
+joinable!(repo_syncs -> projects (project_id));
+joinable!(showcase_entries -> projects (project_id));
+joinable!(showcase_entries -> showcases (showcase_id));
+joinable!(showcase_judges -> showcases (showcase_id));
+joinable!(showcase_judges -> users (user_id));
+joinable!(showcase_scores -> showcase_entries (entry_id));
+joinable!(showcase_scores -> users (judge_id));
+joinable!(showcase_votes -> showcase_entries (entry_id));
+joinable!(showcase_votes -> showcases (showcase_id));
+joinable!(showcase_votes -> users (user_id));
+joinable!(showcases -> events (event_id));
+joinable!(status_update_comments -> status_updates (update_id));
+joinable!(status_update_comments -> users (user_id));
+joinable!(status_updates -> projects (project_id));
+joinable!(uploads -> users (uploaded_by));
 
 allow_tables_to_appear_in_same_query!(
     attendances,
+    calendar_tokens,
+    eval_forms,
+    evaluations,
+    event_imports,
+    event_rsvps,
     events,
+    grade_audit,
+    grades,
+    group_mentors,
+    group_project_prefs,
+    group_schedules,
     groups,
+    join_requests,
     meetings,
     news,
+    notifications,
+    project_activity,
+    project_proposals,
+    project_tags,
     projects,
+    proposal_comments,
     relation_group_user,
     relation_project_user,
+    repo_commits,
+    repo_issues,
+    repo_pulls,
+    repo_reviews,
+    repo_syncs,
+    rooms,
+    showcase_entries,
+    showcase_judges,
+    showcase_scores,
+    showcase_votes,
+    showcases,
+    status_update_comments,
+    status_updates,
+    uploads,
     users,
 );
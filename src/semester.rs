@@ -0,0 +1,185 @@
+//! Academic semesters
+//!
+//! RCOS runs on RPI's academic calendar so most things that are graded or
+//! summarized are grouped by semester. Semesters are derived from dates
+//! rather than stored:
+//!
+//! - Spring runs January through May
+//! - Summer runs June through August
+//! - Fall runs September through December
+//!
+//! In URLs and forms a semester is written like `2019-fall`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime};
+use rocket::http::RawStr;
+use rocket::request::FromFormValue;
+
+/// A term of the academic year
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize)]
+pub enum Term {
+    Spring,
+    Summer,
+    Fall,
+}
+
+/// A single semester such as Fall 2019
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize)]
+pub struct Semester {
+    pub year: i32,
+    pub term: Term,
+}
+
+impl Semester {
+    /// The semester a date falls in
+    pub fn of(date: NaiveDate) -> Self {
+        let term = match date.month() {
+            1..=5 => Term::Spring,
+            6..=8 => Term::Summer,
+            _ => Term::Fall,
+        };
+        Self {
+            year: date.year(),
+            term,
+        }
+    }
+
+    /// The semester it is right now
+    pub fn current() -> Self {
+        Self::of(Local::today().naive_local())
+    }
+
+    /// The first day of the semester
+    pub fn start(&self) -> NaiveDate {
+        let month = match self.term {
+            Term::Spring => 1,
+            Term::Summer => 6,
+            Term::Fall => 9,
+        };
+        NaiveDate::from_ymd(self.year, month, 1)
+    }
+
+    /// The first day after the semester ends
+    pub fn end(&self) -> NaiveDate {
+        self.next().start()
+    }
+
+    /// The Mondays of the weeks of the semester that have started so far
+    pub fn weeks_so_far(&self) -> Vec<NaiveDate> {
+        let today = Local::today().naive_local();
+        let mut weeks = Vec::new();
+        let mut w = week_of(self.start());
+        while w < self.end() && w <= today {
+            weeks.push(w);
+            w += Duration::weeks(1);
+        }
+        weeks
+    }
+
+    /// Is the time during this semester?
+    pub fn contains(&self, t: NaiveDateTime) -> bool {
+        t.date() >= self.start() && t.date() < self.end()
+    }
+
+    /// The semester before this one
+    pub fn prev(&self) -> Self {
+        match self.term {
+            Term::Spring => Self {
+                year: self.year - 1,
+                term: Term::Fall,
+            },
+            Term::Summer => Self {
+                year: self.year,
+                term: Term::Spring,
+            },
+            Term::Fall => Self {
+                year: self.year,
+                term: Term::Summer,
+            },
+        }
+    }
+
+    /// The semester after this one
+    pub fn next(&self) -> Self {
+        match self.term {
+            Term::Spring => Self {
+                year: self.year,
+                term: Term::Summer,
+            },
+            Term::Summer => Self {
+                year: self.year,
+                term: Term::Fall,
+            },
+            Term::Fall => Self {
+                year: self.year + 1,
+                term: Term::Spring,
+            },
+        }
+    }
+
+    /// The identifier used in URLs and forms, like `2019-fall`
+    pub fn id(&self) -> String {
+        format!(
+            "{}-{}",
+            self.year,
+            match self.term {
+                Term::Spring => "spring",
+                Term::Summer => "summer",
+                Term::Fall => "fall",
+            }
+        )
+    }
+}
+
+/// The Monday of the week a date falls in
+///
+/// Weeks are identified by their Monday wherever something is done weekly.
+pub fn week_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// The Monday of the current week
+pub fn this_week() -> NaiveDate {
+    week_of(Local::today().naive_local())
+}
+
+impl Default for Semester {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+// Converts to a human readable string like "Fall 2019"
+impl fmt::Display for Semester {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} {}", self.term, self.year)
+    }
+}
+
+// Converts from an identifier like "2019-fall"
+impl FromStr for Semester {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '-');
+        let year = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let term = match parts.next().ok_or(())?.to_lowercase().as_str() {
+            "spring" => Term::Spring,
+            "summer" => Term::Summer,
+            "fall" => Term::Fall,
+            _ => return Err(()),
+        };
+        Ok(Self { year, term })
+    }
+}
+
+// Lets a Semester be used as a query parameter
+impl<'v> FromFormValue<'v> for Semester {
+    type Error = &'v RawStr;
+
+    fn from_form_value(form_value: &'v RawStr) -> Result<Semester, &'v RawStr> {
+        form_value.parse().map_err(|_| form_value)
+    }
+}
@@ -0,0 +1,670 @@
+//! HTTP handlers for showcases
+
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel::{delete, insert_into, replace_into, select, update};
+use rocket::http::Status;
+use rocket::request::Form;
+use rocket::response::Redirect;
+
+use crate::evaluations::models::AnswersForm;
+use crate::guards::*;
+use crate::models::{Event, Project, ProjectStatus, User};
+use crate::projects::handlers::UserId;
+use crate::templates::FormError;
+use crate::ObservDbConn;
+
+use super::models::*;
+use super::templates::*;
+
+/// GET handler for `/calendar/<eid>/showcase`
+///
+/// The entries of the showcase and the voting form for attendees.
+/// Coordinators set up the showcase here if the event doesn't have one.
+#[get("/calendar/<eid>/showcase?<e>")]
+pub fn showcase(
+    conn: ObservDbConn,
+    l: MaybeLoggedIn,
+    eid: i32,
+    e: Option<FormError>,
+) -> Option<ShowcaseTemplate> {
+    let ev = get_event(&*conn, eid)?;
+    let l = l.user();
+    let s = get_showcase(&*conn, eid);
+    let entries = match &s {
+        Some(s) => entry_rows(&*conn, s),
+        None => Vec::new(),
+    };
+    let judges = match &s {
+        Some(s) => showcase_judges(&*conn, s),
+        None => Vec::new(),
+    };
+
+    let votes: Vec<ShowcaseVote> = match (&s, &l) {
+        (Some(s), Some(u)) => {
+            use crate::schema::showcase_votes::dsl::*;
+            ShowcaseVote::belonging_to(s)
+                .filter(user_id.eq(u.id))
+                .load(&*conn)
+                .expect("Failed to get votes from database")
+        }
+        _ => Vec::new(),
+    };
+    let categories = s
+        .as_ref()
+        .map(Showcase::category_list)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| CategoryVote {
+            voted: votes.iter().find(|v| v.category == c).map(|v| v.entry_id),
+            category: c,
+        })
+        .collect();
+
+    let is_admin = l.as_ref().map(|u| u.tier > 1).unwrap_or(false);
+    let projects = if is_admin {
+        use crate::schema::projects::dsl::*;
+        projects
+            .filter(active.eq(true).and(archived.eq(false)))
+            .order(name.asc())
+            .load::<Project>(&*conn)
+            .expect("Failed to get projects from database")
+            .into_iter()
+            .filter(|p| !entries.iter().any(|r| r.project.id == p.id))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let all_users = if is_admin {
+        use crate::schema::users::dsl::*;
+        users
+            .order(real_name.asc())
+            .load(&*conn)
+            .expect("Failed to get users from database")
+    } else {
+        Vec::new()
+    };
+
+    Some(ShowcaseTemplate {
+        attended: l
+            .as_ref()
+            .map(|u| attended(&*conn, eid, u.id))
+            .unwrap_or(false),
+        is_judge: l
+            .as_ref()
+            .map(|u| judges.iter().any(|j| j.id == u.id))
+            .unwrap_or(false),
+        logged_in: l,
+        event: ev,
+        showcase: s,
+        entries,
+        judges,
+        categories,
+        projects,
+        all_users,
+        error: e,
+    })
+}
+
+/// POST handler for `/calendar/<eid>/showcase`
+///
+/// Sets up the showcase for the event or edits its categories and rubric.
+///
+/// Restricted to Admins.
+#[post("/calendar/<eid>/showcase", data = "<form>")]
+pub fn showcase_post(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    eid: i32,
+    form: Form<ShowcaseForm>,
+) -> Result<Redirect, Status> {
+    get_event(&*conn, eid).ok_or(Status::NotFound)?;
+    let form = form.into_inner();
+    let new = NewShowcase {
+        event_id: eid,
+        categories: serde_json::to_string(&parse_categories(&form.categories)).unwrap(),
+        rubric: serde_json::to_string(&parse_rubric(&form.rubric)).unwrap(),
+    };
+
+    use crate::schema::showcases::dsl::*;
+    if get_showcase(&*conn, eid).is_some() {
+        update(showcases.filter(event_id.eq(eid)))
+            .set(&new)
+            .execute(&*conn)
+            .expect("Failed to update showcase in database");
+    } else {
+        insert_into(showcases)
+            .values(&new)
+            .execute(&*conn)
+            .expect("Failed to insert showcase into database");
+    }
+
+    Ok(Redirect::to(format!("/calendar/{}/showcase", eid)))
+}
+
+/// POST handler for `/calendar/<eid>/showcase/open`
+///
+/// Opens voting to the audience.
+///
+/// Restricted to Admins.
+#[post("/calendar/<eid>/showcase/open")]
+pub fn showcase_open_post(conn: ObservDbConn, _l: AdminGuard, eid: i32) -> Redirect {
+    set_voting(&*conn, eid, true);
+    Redirect::to(format!("/calendar/{}/showcase", eid))
+}
+
+/// POST handler for `/calendar/<eid>/showcase/close`
+///
+/// Closes voting, the votes so far are kept.
+///
+/// Restricted to Admins.
+#[post("/calendar/<eid>/showcase/close")]
+pub fn showcase_close_post(conn: ObservDbConn, _l: AdminGuard, eid: i32) -> Redirect {
+    set_voting(&*conn, eid, false);
+    Redirect::to(format!("/calendar/{}/showcase", eid))
+}
+
+/// POST handler for `/calendar/<eid>/showcase/entries`
+///
+/// Registers a project to present.
+///
+/// Restricted to Admins.
+#[post("/calendar/<eid>/showcase/entries", data = "<form>")]
+pub fn showcase_entry_post(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    eid: i32,
+    form: Form<EntryForm>,
+) -> Result<Redirect, Status> {
+    let s = get_showcase(&*conn, eid).ok_or(Status::NotFound)?;
+    add_entries(&*conn, &s, vec![form.project_id]);
+    Ok(Redirect::to(format!("/calendar/{}/showcase", eid)))
+}
+
+/// POST handler for `/calendar/<eid>/showcase/entries/active`
+///
+/// Registers every active, approved project that isn't entered yet.
+///
+/// Restricted to Admins.
+#[post("/calendar/<eid>/showcase/entries/active")]
+pub fn showcase_entries_active_post(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    eid: i32,
+) -> Result<Redirect, Status> {
+    let s = get_showcase(&*conn, eid).ok_or(Status::NotFound)?;
+    let active_ids: Vec<i32> = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .filter(status.eq(ProjectStatus::Approved.as_str()))
+            .filter(active.eq(true).and(archived.eq(false)))
+            .select(id)
+            .load(&*conn)
+            .expect("Failed to get projects from database")
+    };
+    add_entries(&*conn, &s, active_ids);
+    Ok(Redirect::to(format!("/calendar/{}/showcase", eid)))
+}
+
+/// DELETE handler for `/calendar/<eid>/showcase/entries/<entry>`
+///
+/// Removes an entry along with its votes and scores.
+///
+/// Restricted to Admins.
+#[delete("/calendar/<eid>/showcase/entries/<entry>")]
+pub fn showcase_entry_delete(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    eid: i32,
+    entry: i32,
+) -> Result<Redirect, Status> {
+    let s = get_showcase(&*conn, eid).ok_or(Status::NotFound)?;
+    let entry = get_entry(&*conn, &s, entry).ok_or(Status::NotFound)?;
+    delete_entries(&*conn, vec![entry.id]).expect("Failed to delete showcase entry from database");
+    Ok(Redirect::to(format!("/calendar/{}/showcase", eid)))
+}
+
+/// POST handler for `/calendar/<eid>/showcase/judges`
+///
+/// Restricted to Admins.
+#[post("/calendar/<eid>/showcase/judges", data = "<userid>")]
+pub fn showcase_judge_add_post(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    eid: i32,
+    userid: Form<UserId>,
+) -> Result<Redirect, Status> {
+    use crate::schema::showcase_judges::dsl::*;
+
+    let s = get_showcase(&*conn, eid).ok_or(Status::NotFound)?;
+    replace_into(showcase_judges)
+        .values(&NewShowcaseJudge {
+            showcase_id: s.id,
+            user_id: userid.uid,
+        })
+        .execute(&*conn)
+        .expect("Failed to insert judge into database");
+    Ok(Redirect::to(format!("/calendar/{}/showcase", eid)))
+}
+
+/// DELETE handler for `/calendar/<eid>/showcase/judges/<uid>`
+///
+/// Removes a judge, the scores they already gave are kept.
+///
+/// Restricted to Admins.
+#[delete("/calendar/<eid>/showcase/judges/<uid>")]
+pub fn showcase_judge_delete(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    eid: i32,
+    uid: i32,
+) -> Result<Redirect, Status> {
+    use crate::schema::showcase_judges::dsl::*;
+
+    let s = get_showcase(&*conn, eid).ok_or(Status::NotFound)?;
+    delete(showcase_judges.filter(showcase_id.eq(s.id).and(user_id.eq(uid))))
+        .execute(&*conn)
+        .expect("Failed to delete judge from database");
+    Ok(Redirect::to(format!("/calendar/{}/showcase", eid)))
+}
+
+/// POST handler for `/calendar/<eid>/showcase/votes`
+///
+/// Votes for an entry in a category. Each user gets one vote per
+/// category and it can't be changed.
+///
+/// Restricted to users who attended the event while voting is open.
+#[post("/calendar/<eid>/showcase/votes", data = "<form>")]
+pub fn showcase_vote_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    eid: i32,
+    form: Form<VoteForm>,
+) -> Result<Redirect, Status> {
+    let form = form.into_inner();
+    let s = get_showcase(&*conn, eid).ok_or(Status::NotFound)?;
+
+    if !s.voting_open {
+        return Err(Status::Conflict);
+    }
+    if !attended(&*conn, eid, l.0.id) {
+        return Err(Status::Unauthorized);
+    }
+    if !s.category_list().contains(&form.category) {
+        return Ok(Redirect::to(format!(
+            "/calendar/{}/showcase?e={}",
+            eid,
+            FormError::Other
+        )));
+    }
+    let entered: bool = {
+        use crate::schema::showcase_entries::dsl::*;
+        select(exists(
+            showcase_entries.filter(id.eq(form.entry_id).and(showcase_id.eq(s.id))),
+        ))
+        .get_result(&*conn)
+        .expect("Failed to get showcase entry from database")
+    };
+    if !entered {
+        return Err(Status::NotFound);
+    }
+
+    use crate::schema::showcase_votes::dsl::*;
+    let voted: bool = select(exists(
+        showcase_votes.filter(
+            showcase_id
+                .eq(s.id)
+                .and(user_id.eq(l.0.id))
+                .and(category.eq(&form.category)),
+        ),
+    ))
+    .get_result(&*conn)
+    .expect("Failed to get votes from database");
+    if voted {
+        return Err(Status::Conflict);
+    }
+
+    insert_into(showcase_votes)
+        .values(&NewShowcaseVote {
+            showcase_id: s.id,
+            entry_id: form.entry_id,
+            user_id: l.0.id,
+            category: form.category,
+        })
+        .execute(&*conn)
+        .expect("Failed to insert vote into database");
+
+    Ok(Redirect::to(format!("/calendar/{}/showcase", eid)))
+}
+
+/// GET handler for `/calendar/<eid>/showcase/judge`
+///
+/// The scoring form for every entry.
+///
+/// Restricted to the judges of the showcase.
+#[get("/calendar/<eid>/showcase/judge")]
+pub fn showcase_judge(conn: ObservDbConn, l: UserGuard, eid: i32) -> Result<JudgeTemplate, Status> {
+    let ev = get_event(&*conn, eid).ok_or(Status::NotFound)?;
+    let s = get_showcase(&*conn, eid).ok_or(Status::NotFound)?;
+    if !is_judge(&*conn, &s, l.0.id) {
+        return Err(Status::Unauthorized);
+    }
+
+    let criteria = s.criteria();
+    let given: Vec<ShowcaseScore> = {
+        use crate::schema::showcase_scores::dsl::*;
+        showcase_scores
+            .filter(judge_id.eq(l.0.id))
+            .load(&*conn)
+            .expect("Failed to get scores from database")
+    };
+    let entries = entry_rows(&*conn, &s)
+        .into_iter()
+        .map(|r| {
+            let score = given.iter().find(|g| g.entry_id == r.entry.id);
+            let list = score.map(ShowcaseScore::score_list).unwrap_or_default();
+            JudgeEntry {
+                scores: criteria
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| CriterionScore {
+                        index: i,
+                        criterion: c.clone(),
+                        score: list.get(i).map(i32::to_string).unwrap_or_default(),
+                    })
+                    .collect(),
+                scored: score.is_some(),
+                entry: r.entry,
+                project: r.project,
+            }
+        })
+        .collect();
+
+    Ok(JudgeTemplate {
+        logged_in: Some(l.0),
+        event: ev,
+        entries,
+    })
+}
+
+/// POST handler for `/calendar/<eid>/showcase/judge/<entry>`
+///
+/// Scores an entry, replacing the judge's previous scores for it.
+///
+/// Restricted to the judges of the showcase.
+#[post("/calendar/<eid>/showcase/judge/<entry>", data = "<form>")]
+pub fn showcase_score_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    eid: i32,
+    entry: i32,
+    form: Form<AnswersForm>,
+) -> Result<Redirect, Status> {
+    let s = get_showcase(&*conn, eid).ok_or(Status::NotFound)?;
+    if !is_judge(&*conn, &s, l.0.id) {
+        return Err(Status::Unauthorized);
+    }
+    let entry = get_entry(&*conn, &s, entry).ok_or(Status::NotFound)?;
+
+    let answers = form.into_inner().0;
+    let mut given: Vec<i32> = Vec::new();
+    for (i, c) in s.criteria().iter().enumerate() {
+        match answers.get(i).and_then(|a| a.trim().parse::<i32>().ok()) {
+            Some(v) if v >= 1 && v <= c.max => given.push(v),
+            _ => return Err(Status::BadRequest),
+        }
+    }
+
+    use crate::schema::showcase_scores::dsl::*;
+    replace_into(showcase_scores)
+        .values(&NewShowcaseScore {
+            entry_id: entry.id,
+            judge_id: l.0.id,
+            scores: serde_json::to_string(&given).unwrap(),
+        })
+        .execute(&*conn)
+        .expect("Failed to insert scores into database");
+
+    Ok(Redirect::to(format!("/calendar/{}/showcase/judge", eid)))
+}
+
+/// GET handler for `/calendar/<eid>/showcase/results`
+///
+/// The votes in each category and the average judge scores, meant to be
+/// put on the projector.
+///
+/// Restricted to Admins.
+#[get("/calendar/<eid>/showcase/results")]
+pub fn showcase_results(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    eid: i32,
+) -> Result<ResultsTemplate, Status> {
+    let ev = get_event(&*conn, eid).ok_or(Status::NotFound)?;
+    let s = get_showcase(&*conn, eid).ok_or(Status::NotFound)?;
+    let entries = entry_rows(&*conn, &s);
+
+    let votes: Vec<ShowcaseVote> = ShowcaseVote::belonging_to(&s)
+        .load(&*conn)
+        .expect("Failed to get votes from database");
+    let categories = s
+        .category_list()
+        .into_iter()
+        .map(|c| {
+            let mut rows: Vec<VoteRow> = entries
+                .iter()
+                .map(|r| VoteRow {
+                    project: r.project.clone(),
+                    votes: votes
+                        .iter()
+                        .filter(|v| v.category == c && v.entry_id == r.entry.id)
+                        .count(),
+                })
+                .collect();
+            rows.sort_by(|a, b| b.votes.cmp(&a.votes));
+            CategoryResult { category: c, rows }
+        })
+        .collect();
+
+    let criteria = s.criteria();
+    let max: i32 = criteria.iter().map(|c| c.max).sum();
+    let scores: Vec<ShowcaseScore> = {
+        use crate::schema::showcase_scores::dsl::*;
+        showcase_scores
+            .filter(entry_id.eq_any(entries.iter().map(|r| r.entry.id).collect::<Vec<i32>>()))
+            .load(&*conn)
+            .expect("Failed to get scores from database")
+    };
+    let mut judged: Vec<JudgeRow> = entries
+        .iter()
+        .map(|r| {
+            let totals: Vec<i32> = scores
+                .iter()
+                .filter(|g| g.entry_id == r.entry.id)
+                .map(|g| g.total(&criteria))
+                .collect();
+            JudgeRow {
+                project: r.project.clone(),
+                judges: totals.len(),
+                score: if totals.is_empty() || max == 0 {
+                    None
+                } else {
+                    let sum: i32 = totals.iter().sum();
+                    Some(
+                        (f64::from(sum) * 100.0 / f64::from(max * totals.len() as i32)).round()
+                            as u32,
+                    )
+                },
+            }
+        })
+        .collect();
+    judged.sort_by(|a, b| b.score.cmp(&a.score));
+
+    Ok(ResultsTemplate {
+        logged_in: Some(l.0),
+        event: ev,
+        categories,
+        judged,
+    })
+}
+
+//# Helper Functions
+
+fn get_event(conn: &SqliteConnection, eid: i32) -> Option<Event> {
+    use crate::schema::events::dsl::*;
+    events
+        .find(eid)
+        .first(conn)
+        .optional()
+        .expect("Failed to get event from database")
+}
+
+/// The showcase of an event, if it has one
+pub fn get_showcase(conn: &SqliteConnection, eid: i32) -> Option<Showcase> {
+    use crate::schema::showcases::dsl::*;
+    showcases
+        .filter(event_id.eq(eid))
+        .first(conn)
+        .optional()
+        .expect("Failed to get showcase from database")
+}
+
+/// An entry of the showcase, `None` if it is entered in another one
+fn get_entry(conn: &SqliteConnection, s: &Showcase, entry: i32) -> Option<ShowcaseEntry> {
+    use crate::schema::showcase_entries::dsl::*;
+    showcase_entries
+        .find(entry)
+        .first::<ShowcaseEntry>(conn)
+        .optional()
+        .expect("Failed to get showcase entry from database")
+        .filter(|e| e.showcase_id == s.id)
+}
+
+fn set_voting(conn: &SqliteConnection, eid: i32, open: bool) {
+    use crate::schema::showcases::dsl::*;
+    update(showcases.filter(event_id.eq(eid)))
+        .set(voting_open.eq(open))
+        .execute(conn)
+        .expect("Failed to update showcase in database");
+}
+
+/// The entries of a showcase with their projects, ordered by name
+fn entry_rows(conn: &SqliteConnection, s: &Showcase) -> Vec<EntryRow> {
+    use crate::schema::projects::dsl::{name, projects};
+    ShowcaseEntry::belonging_to(s)
+        .inner_join(projects)
+        .order(name.asc())
+        .load::<(ShowcaseEntry, Project)>(conn)
+        .expect("Failed to get showcase entries from database")
+        .into_iter()
+        .map(|(entry, project)| EntryRow { entry, project })
+        .collect()
+}
+
+fn add_entries(conn: &SqliteConnection, s: &Showcase, pids: Vec<i32>) {
+    use crate::schema::showcase_entries::dsl::*;
+    let entered: Vec<i32> = showcase_entries
+        .filter(showcase_id.eq(s.id))
+        .select(project_id)
+        .load(conn)
+        .expect("Failed to get showcase entries from database");
+    let new: Vec<NewShowcaseEntry> = pids
+        .into_iter()
+        .filter(|p| !entered.contains(p))
+        .map(|p| NewShowcaseEntry {
+            showcase_id: s.id,
+            project_id: p,
+        })
+        .collect();
+    insert_into(showcase_entries)
+        .values(&new)
+        .execute(conn)
+        .expect("Failed to insert showcase entries into database");
+}
+
+/// Delete showcase entries along with their votes and scores
+///
+/// Used when removing an entry and when deleting projects.
+pub fn delete_entries(conn: &SqliteConnection, ids: Vec<i32>) -> QueryResult<()> {
+    conn.transaction(|| {
+        {
+            use crate::schema::showcase_votes::dsl::*;
+            delete(showcase_votes.filter(entry_id.eq_any(&ids))).execute(conn)?;
+        }
+        {
+            use crate::schema::showcase_scores::dsl::*;
+            delete(showcase_scores.filter(entry_id.eq_any(&ids))).execute(conn)?;
+        }
+        use crate::schema::showcase_entries::dsl::*;
+        delete(showcase_entries.filter(id.eq_any(&ids))).execute(conn)?;
+        Ok(())
+    })
+}
+
+fn showcase_judges(conn: &SqliteConnection, s: &Showcase) -> Vec<User> {
+    use crate::schema::users::dsl::{real_name, users};
+    ShowcaseJudge::belonging_to(s)
+        .inner_join(users)
+        .order(real_name.asc())
+        .load::<(ShowcaseJudge, User)>(conn)
+        .expect("Failed to get judges from database")
+        .into_iter()
+        .map(|(_, u)| u)
+        .collect()
+}
+
+fn is_judge(conn: &SqliteConnection, s: &Showcase, uid: i32) -> bool {
+    use crate::schema::showcase_judges::dsl::*;
+    select(exists(
+        showcase_judges.filter(showcase_id.eq(s.id).and(user_id.eq(uid))),
+    ))
+    .get_result(conn)
+    .expect("Failed to get judges from database")
+}
+
+/// Did the user check in to the event with its attendance code?
+fn attended(conn: &SqliteConnection, eid: i32, uid: i32) -> bool {
+    use crate::schema::attendances::dsl::*;
+    select(exists(attendances.filter(
+        is_event.eq(true).and(event_id.eq(eid)).and(user_id.eq(uid)),
+    )))
+    .get_result(conn)
+    .expect("Failed to get attendance from database")
+}
+
+/// Delete an event's showcase along with its entries, judges, votes,
+/// and scores
+///
+/// Used when deleting the event.
+pub fn delete_showcase(conn: &SqliteConnection, eid: i32) -> QueryResult<()> {
+    let s = match get_showcase(conn, eid) {
+        Some(s) => s,
+        None => return Ok(()),
+    };
+    let entry_ids: Vec<i32> = {
+        use crate::schema::showcase_entries::dsl::*;
+        showcase_entries
+            .filter(showcase_id.eq(s.id))
+            .select(id)
+            .load(conn)?
+    };
+    {
+        use crate::schema::showcase_scores::dsl::*;
+        delete(showcase_scores.filter(entry_id.eq_any(entry_ids))).execute(conn)?;
+    }
+    {
+        use crate::schema::showcase_votes::dsl::*;
+        delete(showcase_votes.filter(showcase_id.eq(s.id))).execute(conn)?;
+    }
+    {
+        use crate::schema::showcase_judges::dsl::*;
+        delete(showcase_judges.filter(showcase_id.eq(s.id))).execute(conn)?;
+    }
+    {
+        use crate::schema::showcase_entries::dsl::*;
+        delete(showcase_entries.filter(showcase_id.eq(s.id))).execute(conn)?;
+    }
+    use crate::schema::showcases::dsl::*;
+    delete(showcases.find(s.id)).execute(conn)?;
+    Ok(())
+}
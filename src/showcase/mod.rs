@@ -0,0 +1,25 @@
+//! Demo day showcases
+//!
+//! A showcase is attached to a calendar event such as the end of semester
+//! presentations. Active projects are entered, everyone who checked in to
+//! the event gets one vote per category, and judges score each entry
+//! against a rubric. The results page is meant for the projector.
+//!
+//! ## Routes
+//! - `/calendar/<eid>/showcase`
+//! - `/calendar/<eid>/showcase/open`
+//! - `/calendar/<eid>/showcase/close`
+//! - `/calendar/<eid>/showcase/entries`
+//! - `/calendar/<eid>/showcase/entries/active`
+//! - `/calendar/<eid>/showcase/entries/<entry>`
+//! - `/calendar/<eid>/showcase/judges`
+//! - `/calendar/<eid>/showcase/judges/<uid>`
+//! - `/calendar/<eid>/showcase/votes`
+//! - `/calendar/<eid>/showcase/judge`
+//! - `/calendar/<eid>/showcase/judge/<entry>`
+//! - `/calendar/<eid>/showcase/results`
+
+pub mod handlers;
+pub mod models;
+
+mod templates;
@@ -0,0 +1,241 @@
+//! Models for showcases
+//!
+//! A showcase is attached to a calendar event in `showcases`. Projects
+//! presenting are in `showcase_entries`, the audience's votes are in
+//! `showcase_votes`, and the judges in `showcase_judges` score entries
+//! in `showcase_scores`. Categories, the rubric, and scores are stored
+//! as JSON arrays.
+
+use chrono::NaiveDateTime;
+
+use crate::models::{Event, Project, User};
+use crate::schema::*;
+
+/// A single criterion of the judging rubric
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Criterion {
+    /// What is being judged such as "Technical Difficulty"
+    pub name: String,
+    /// The highest score, scores start at 1
+    pub max: i32,
+}
+
+/// The most categories or criteria a showcase can have
+pub const MAX_CRITERIA: usize = 20;
+
+/// Parse voting categories from one per line
+///
+/// Duplicate and blank lines are ignored.
+pub fn parse_categories(text: &str) -> Vec<String> {
+    let mut categories: Vec<String> = Vec::new();
+    for c in text.lines().map(str::trim) {
+        if !c.is_empty() && !categories.iter().any(|o| o.eq_ignore_ascii_case(c)) {
+            categories.push(c.to_string());
+        }
+    }
+    categories.truncate(MAX_CRITERIA);
+    categories
+}
+
+/// Parse the rubric from the text format used in the form
+///
+/// There is one criterion per line scored from 1 to 5, or from 1 to `N`
+/// if the line ends with `: N`.
+pub fn parse_rubric(text: &str) -> Vec<Criterion> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .take(MAX_CRITERIA)
+        .map(|l| {
+            if let Some(i) = l.rfind(':') {
+                if let Ok(max) = l[i + 1..].trim().parse::<i32>() {
+                    return Criterion {
+                        name: l[..i].trim().to_string(),
+                        max: max.max(2),
+                    };
+                }
+            }
+            Criterion {
+                name: l.to_string(),
+                max: 5,
+            }
+        })
+        .collect()
+}
+
+/// A showcase such as the end of semester demo day
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Event)]
+pub struct Showcase {
+    /// ID of the showcase
+    pub id: i32,
+    /// ID of the event the showcase happens at
+    pub event_id: i32,
+    /// The voting categories as a JSON array
+    pub categories: String,
+    /// The judging rubric as a JSON array
+    pub rubric: String,
+    /// Can the audience vote right now?
+    pub voting_open: bool,
+}
+
+impl Showcase {
+    /// The parsed voting categories
+    pub fn category_list(&self) -> Vec<String> {
+        serde_json::from_str(&self.categories).unwrap_or_default()
+    }
+
+    /// The parsed judging rubric
+    pub fn criteria(&self) -> Vec<Criterion> {
+        serde_json::from_str(&self.rubric).unwrap_or_default()
+    }
+
+    /// The categories in the text format used by the form
+    pub fn categories_text(&self) -> String {
+        self.category_list().join("\n")
+    }
+
+    /// The rubric in the text format used by the form
+    pub fn rubric_text(&self) -> String {
+        self.criteria()
+            .iter()
+            .map(|c| format!("{}: {}", c.name, c.max))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Used to create or edit a showcase
+#[derive(Debug, Default, Clone, Insertable, AsChangeset)]
+#[table_name = "showcases"]
+pub struct NewShowcase {
+    pub event_id: i32,
+    pub categories: String,
+    pub rubric: String,
+}
+
+/// The showcase editor
+///
+/// Categories are one per line and the rubric is in the text format
+/// read by `parse_rubric`.
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct ShowcaseForm {
+    pub categories: String,
+    pub rubric: String,
+}
+
+/// A project presenting at a showcase
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Showcase)]
+#[belongs_to(Project)]
+#[table_name = "showcase_entries"]
+pub struct ShowcaseEntry {
+    pub id: i32,
+    pub showcase_id: i32,
+    pub project_id: i32,
+}
+
+/// Used to register a project at a showcase
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "showcase_entries"]
+pub struct NewShowcaseEntry {
+    pub showcase_id: i32,
+    pub project_id: i32,
+}
+
+/// The form to register a project
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct EntryForm {
+    pub project_id: i32,
+}
+
+/// A judge of a showcase
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Showcase)]
+#[belongs_to(User)]
+pub struct ShowcaseJudge {
+    pub id: i32,
+    pub showcase_id: i32,
+    pub user_id: i32,
+}
+
+/// Used to add a judge to a showcase
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "showcase_judges"]
+pub struct NewShowcaseJudge {
+    pub showcase_id: i32,
+    pub user_id: i32,
+}
+
+/// A vote by someone in the audience
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(Showcase)]
+#[belongs_to(ShowcaseEntry, foreign_key = "entry_id")]
+pub struct ShowcaseVote {
+    pub id: i32,
+    pub showcase_id: i32,
+    /// ID of the entry voted for
+    pub entry_id: i32,
+    /// ID of the user who voted
+    pub user_id: i32,
+    /// The category voted in
+    pub category: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// Used to vote for an entry
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "showcase_votes"]
+pub struct NewShowcaseVote {
+    pub showcase_id: i32,
+    pub entry_id: i32,
+    pub user_id: i32,
+    pub category: String,
+}
+
+/// The voting form
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct VoteForm {
+    pub category: String,
+    pub entry_id: i32,
+}
+
+/// A judge's scores for an entry
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(ShowcaseEntry, foreign_key = "entry_id")]
+pub struct ShowcaseScore {
+    pub id: i32,
+    /// ID of the entry scored
+    pub entry_id: i32,
+    /// ID of the judge
+    pub judge_id: i32,
+    /// The score for each criterion as a JSON array
+    pub scores: String,
+    /// When the scores were last submitted
+    pub submitted_at: NaiveDateTime,
+}
+
+impl ShowcaseScore {
+    /// The parsed scores in the same order as the rubric
+    pub fn score_list(&self) -> Vec<i32> {
+        serde_json::from_str(&self.scores).unwrap_or_default()
+    }
+
+    /// The total score clamped to the rubric
+    pub fn total(&self, rubric: &[Criterion]) -> i32 {
+        rubric
+            .iter()
+            .zip(self.score_list().iter())
+            .map(|(c, s)| (*s).max(0).min(c.max))
+            .sum()
+    }
+}
+
+/// Used to submit scores
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "showcase_scores"]
+pub struct NewShowcaseScore {
+    pub entry_id: i32,
+    pub judge_id: i32,
+    pub scores: String,
+}
@@ -0,0 +1,108 @@
+//! HTML templates for showcases
+
+use super::models::*;
+#[allow(unused_imports)]
+use crate::templates::{filters, FormError, OptUser};
+
+use crate::models::{Event, Project, User};
+
+/// An entry along with the project presenting
+pub struct EntryRow {
+    pub entry: ShowcaseEntry,
+    pub project: Project,
+}
+
+/// A voting category and the logged in user's vote in it
+pub struct CategoryVote {
+    pub category: String,
+    /// ID of the entry the user voted for
+    pub voted: Option<i32>,
+}
+
+/// A criterion and the score the judge gave for it
+pub struct CriterionScore {
+    /// Index of the criterion, used for the field name
+    pub index: usize,
+    pub criterion: Criterion,
+    pub score: String,
+}
+
+/// An entry along with the logged in judge's scores
+pub struct JudgeEntry {
+    pub entry: ShowcaseEntry,
+    pub project: Project,
+    pub scores: Vec<CriterionScore>,
+    /// Has the judge submitted scores for the entry?
+    pub scored: bool,
+}
+
+/// The number of votes an entry got in a category
+pub struct VoteRow {
+    pub project: Project,
+    pub votes: usize,
+}
+
+/// The votes in a category, most votes first
+pub struct CategoryResult {
+    pub category: String,
+    pub rows: Vec<VoteRow>,
+}
+
+/// The judges' scores of an entry
+pub struct JudgeRow {
+    pub project: Project,
+    /// Number of judges who scored the entry
+    pub judges: usize,
+    /// Average score as a percentage of the highest possible score
+    pub score: Option<u32>,
+}
+
+/// Showcase page template
+///
+/// HTML File: `showcase/showcase.html`
+///
+/// Lists the entries and lets attendees vote. Coordinators set up the
+/// showcase, its entries, and judges here.
+#[derive(Template)]
+#[template(path = "showcase/showcase.html")]
+pub struct ShowcaseTemplate {
+    pub logged_in: OptUser,
+    pub event: Event,
+    pub showcase: Option<Showcase>,
+    pub entries: Vec<EntryRow>,
+    pub judges: Vec<User>,
+    pub categories: Vec<CategoryVote>,
+    /// Did the logged in user attend the event?
+    pub attended: bool,
+    /// Is the logged in user a judge?
+    pub is_judge: bool,
+    /// Active projects that aren't entered yet
+    pub projects: Vec<Project>,
+    pub all_users: Vec<User>,
+    pub error: Option<FormError>,
+}
+
+/// Judging page template
+///
+/// HTML File: `showcase/judge.html`
+#[derive(Template)]
+#[template(path = "showcase/judge.html")]
+pub struct JudgeTemplate {
+    pub logged_in: OptUser,
+    pub event: Event,
+    pub entries: Vec<JudgeEntry>,
+}
+
+/// Live results template
+///
+/// HTML File: `showcase/results.html`
+///
+/// Meant to be put on the projector like `/big` and refreshes itself.
+#[derive(Template)]
+#[template(path = "showcase/results.html")]
+pub struct ResultsTemplate {
+    pub logged_in: OptUser,
+    pub event: Event,
+    pub categories: Vec<CategoryResult>,
+    pub judged: Vec<JudgeRow>,
+}
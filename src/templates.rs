@@ -54,6 +54,9 @@ pub struct DashboardTemplate {
     pub logged_in: OptUser,
     pub projects: Vec<Project>,
     pub groups: Vec<Group>,
+    pub missing_updates: Vec<Project>,
+    /// Number of unseen notifications
+    pub notifications: usize,
     pub summary: GradeSummary,
 }
 
@@ -123,6 +126,24 @@ pub enum FormError {
     InvalidCode,
     /// A date field was the wrong format invalid
     InvalidDate,
+    /// The project already has as many members as it allows
+    ProjectFull,
+    /// A repository URL couldn't be parsed
+    InvalidRepo,
+    /// A repository is already listed on another project
+    RepoExists,
+    /// An uploaded file isn't an image that can be read
+    InvalidImage,
+    /// An uploaded file is bigger than allowed
+    FileTooLarge,
+    /// The room is already booked at that time
+    RoomBooked,
+    /// An uploaded file isn't an iCalendar file or is too big
+    InvalidCalendar,
+    /// The RRULE or skipped days of a repeating event can't be read
+    InvalidRepeat,
+    /// The user already posted a status update for the project that week
+    UpdateExists,
     /// Some other unknown error
     Other,
 }
@@ -145,6 +166,15 @@ impl fmt::Display for FormError {
                 FormError::MmostExists => "mmostExists",
                 FormError::InvalidCode => "code",
                 FormError::InvalidDate => "date",
+                FormError::ProjectFull => "full",
+                FormError::InvalidRepo => "repo",
+                FormError::RepoExists => "repoExists",
+                FormError::InvalidImage => "image",
+                FormError::FileTooLarge => "tooLarge",
+                FormError::RoomBooked => "booked",
+                FormError::InvalidCalendar => "calendar",
+                FormError::InvalidRepeat => "repeat",
+                FormError::UpdateExists => "updateExists",
                 FormError::Other => "other",
             }
         )
@@ -164,6 +194,15 @@ impl<T: AsRef<str>> From<T> for FormError {
             "mmostExists" => FormError::MmostExists,
             "code" => FormError::InvalidCode,
             "date" => FormError::InvalidDate,
+            "full" => FormError::ProjectFull,
+            "repo" => FormError::InvalidRepo,
+            "repoExists" => FormError::RepoExists,
+            "image" => FormError::InvalidImage,
+            "tooLarge" => FormError::FileTooLarge,
+            "booked" => FormError::RoomBooked,
+            "calendar" => FormError::InvalidCalendar,
+            "repeat" => FormError::InvalidRepeat,
+            "updateExists" => FormError::UpdateExists,
             "other" => FormError::Other,
             _ => FormError::Other,
         }
@@ -4,7 +4,7 @@ use crate::models::*;
 use diesel::insert_into;
 use diesel::prelude::*;
 use rocket::config::{Config, Environment, LoggingLevel, Value};
-use rocket::http::Status;
+use rocket::http::{ContentType, Status};
 use rocket::local::Client;
 use std::collections::HashMap;
 use std::fs;
@@ -15,12 +15,22 @@ use std::path::Path;
 struct Embed;
 
 // Get all handlers in case we need to test other functions
+pub use crate::activity::handlers::*;
 pub use crate::attend::handlers::*;
 pub use crate::auth::handlers::*;
 pub use crate::calendar::handlers::*;
+pub use crate::evaluations::handlers::*;
+pub use crate::grades::handlers::*;
 pub use crate::groups::handlers::*;
 pub use crate::news::handlers::*;
+pub use crate::notifications::handlers::*;
 pub use crate::projects::handlers::*;
+pub use crate::proposals::handlers::*;
+pub use crate::repos::handlers::*;
+pub use crate::rooms::handlers::*;
+pub use crate::showcase::handlers::*;
+pub use crate::updates::handlers::*;
+pub use crate::uploads::handlers::*;
 pub use crate::users::handlers::*;
 
 // Embed the Migrations into the binary
@@ -104,6 +114,79 @@ fn cleanup(test_name: String) {
         .expect("Dir Deletion Error");
 }
 
+/// Add a user to a test database
+fn test_user(conn: &SqliteConnection, h: &str, t: i32) -> User {
+    use crate::schema::users::dsl::*;
+    let psalt = gen_salt();
+    insert_into(users)
+        .values(&NewUser {
+            real_name: String::from(h),
+            handle: String::from(h),
+            password_hash: hash_password(String::from("thisisapassword"), &psalt),
+            salt: psalt,
+            bio: String::new(),
+            email: format!("{}@test-rcos.io", h),
+            tier: t,
+            active: true,
+            mmost: String::from(h),
+            former: false,
+            extrn: false,
+        })
+        .execute(conn)
+        .expect("Failed to add user to database");
+    users
+        .filter(handle.eq(h))
+        .first(conn)
+        .expect("Failed to get user from database")
+}
+
+/// Add an open project owned by a user to a test database
+fn test_project(conn: &SqliteConnection, owner: &User, n: &str) -> Project {
+    use crate::schema::projects::dsl::*;
+    insert_into(projects)
+        .values(&NewProject {
+            name: String::from(n),
+            description: String::new(),
+            homepage: None,
+            owner_id: owner.id,
+            repos: String::from("[]"),
+            extrn: false,
+            join_policy: String::from("open"),
+            max_size: None,
+            language: None,
+        })
+        .execute(conn)
+        .expect("Failed to add project to database");
+    let p: Project = projects
+        .filter(name.eq(n))
+        .first(conn)
+        .expect("Failed to get project from database");
+
+    {
+        use crate::schema::relation_project_user::dsl::*;
+        insert_into(relation_project_user)
+            .values(&NewRelationProjectUser {
+                project_id: p.id,
+                user_id: owner.id,
+                role: String::from("lead"),
+                joined_at: None,
+            })
+            .execute(conn)
+            .expect("Failed to add user to project");
+    }
+    p
+}
+
+/// Log a test user in on a client
+fn log_in(client: &Client, user: &User) {
+    let response = client
+        .post("/login")
+        .header(ContentType::Form)
+        .body(format!("email={}&password=thisisapassword", user.email))
+        .dispatch();
+    assert_eq!(response.status(), Status::SeeOther);
+}
+
 #[test]
 fn launch() {
     let config = setup(String::from("test_launch"));
@@ -182,18 +265,1378 @@ fn add_user() {
         .filter(&email.eq(&*nu.email))
         .first(&conn)
         .expect("Failed to get user from database");
+    assert_eq!(join_default_groups(&conn, user.id).unwrap(), 1);
+
+    assert_eq!("JD1".to_string(), user.handle);
+
+    cleanup(String::from("test_add_user"));
+}
+
+#[test]
+fn github_api_path() {
+    use crate::repos::github::api_path;
+
+    assert_eq!(
+        api_path("https://github.com/rcos/observatory-new"),
+        Some(String::from("rcos/observatory-new"))
+    );
+    assert_eq!(
+        api_path("github.com/rcos/observatory-new.git/"),
+        Some(String::from("rcos/observatory-new"))
+    );
+    assert_eq!(api_path("https://gitlab.com/rcos/observatory-new"), None);
+}
+
+#[test]
+fn semester_of_date() {
+    use crate::semester::{Semester, Term};
+    use chrono::NaiveDate;
+
+    let fall = Semester::of(NaiveDate::from_ymd(2019, 10, 15));
+    assert_eq!(fall.term, Term::Fall);
+    assert_eq!(fall.id(), "2019-fall");
+    assert_eq!(fall.next().id(), "2020-spring");
+    assert_eq!(fall.prev().prev().id(), "2019-spring");
+    assert_eq!("2019-fall".parse::<Semester>(), Ok(fall));
+    assert!(fall.contains(NaiveDate::from_ymd(2019, 12, 31).and_hms(23, 59, 0)));
+    assert!(!fall.contains(NaiveDate::from_ymd(2020, 1, 1).and_hms(0, 0, 0)));
+}
+
+#[test]
+fn contribution_metrics_aggregation() {
+    use crate::repos::metrics::contribution_metrics;
+    use crate::semester::Semester;
+    use chrono::NaiveDate;
+
+    let config = setup(String::from("test_contribution_metrics"));
+
+    let _client = Client::new(rocket(config)).unwrap();
+    let conn_url = create_connection_url(&_client);
+
+    let conn = SqliteConnection::establish(conn_url.as_str())
+        .expect("Failed to connect to database in ContributionMetricsTest");
+    embedded_migrations::run(&conn).expect("Failed to run embedded migrations");
+
+    let user = test_user(&conn, "jd_1", 0);
+    let proj = test_project(&conn, &user, "Metrics");
+    let sem = Semester::of(NaiveDate::from_ymd(2019, 10, 15));
+    let during = NaiveDate::from_ymd(2019, 10, 15).and_hms(12, 0, 0);
+    let before = NaiveDate::from_ymd(2019, 5, 1).and_hms(12, 0, 0);
+    let url = String::from("https://github.com/rcos/metrics");
+
     {
-        use crate::schema::relation_group_user::dsl::*;
-        insert_into(relation_group_user)
-            .values(&NewRelationGroupUser {
-                group_id: 0,
-                user_id: user.id,
+        use crate::schema::repo_commits::dsl::*;
+        // Logins match case insensitively but otherwise exactly
+        let commits = [
+            ("a", "jd_1", during),
+            ("b", "JD_1", during),
+            ("c", "jdx1", during),
+            ("d", "jd_1", before),
+        ];
+        for (s, a, t) in commits.iter() {
+            insert_into(repo_commits)
+                .values(&NewRepoCommit {
+                    project_id: proj.id,
+                    repo: url.clone(),
+                    sha: s.to_string(),
+                    author: Some(a.to_string()),
+                    message: String::new(),
+                    committed_at: *t,
+                })
+                .execute(&conn)
+                .expect("Failed to add commit to database");
+        }
+    }
+    {
+        use crate::schema::repo_pulls::dsl::*;
+        for (n, m) in [(1, true), (2, false)].iter() {
+            insert_into(repo_pulls)
+                .values(&NewRepoPull {
+                    project_id: proj.id,
+                    repo: url.clone(),
+                    number: *n,
+                    author: Some(String::from("JD_1")),
+                    title: String::new(),
+                    state: String::from(if *m { "closed" } else { "open" }),
+                    merged: *m,
+                    created_at: during,
+                    updated_at: during,
+                    closed_at: if *m { Some(during) } else { None },
+                    merged_at: if *m { Some(during) } else { None },
+                    additions: 10,
+                    deletions: 2,
+                })
+                .execute(&conn)
+                .expect("Failed to add pull request to database");
+        }
+    }
+    {
+        use crate::schema::repo_reviews::dsl::*;
+        insert_into(repo_reviews)
+            .values(&NewRepoReview {
+                project_id: proj.id,
+                repo: url.clone(),
+                review_id: 1,
+                pull_number: 3,
+                author: Some(String::from("jd_1")),
+                state: String::from("APPROVED"),
+                submitted_at: during,
             })
             .execute(&conn)
-            .expect("Failed to insert new relation into database");
+            .expect("Failed to add review to database");
+    }
+    {
+        use crate::schema::repo_issues::dsl::*;
+        for (n, a, t, c) in [
+            (1, "someone", during, Some("jd_1")),
+            (2, "jd_1", before, None),
+        ]
+        .iter()
+        {
+            insert_into(repo_issues)
+                .values(&NewRepoIssue {
+                    project_id: proj.id,
+                    repo: url.clone(),
+                    number: *n,
+                    author: Some(a.to_string()),
+                    title: String::new(),
+                    state: String::from(if c.is_some() { "closed" } else { "open" }),
+                    created_at: *t,
+                    updated_at: *t,
+                    closed_at: c.map(|_| *t),
+                    closed_by: c.map(String::from),
+                })
+                .execute(&conn)
+                .expect("Failed to add issue to database");
+        }
     }
 
-    assert_eq!("JD1".to_string(), user.handle);
+    let m = contribution_metrics(&conn, &user, &proj, &sem);
+    assert_eq!(m.commits, 2);
+    assert_eq!(m.pulls_opened, 2);
+    assert_eq!(m.pulls_merged, 1);
+    assert_eq!(m.reviews_given, 1);
+    assert_eq!(m.issues_opened, 0);
+    assert_eq!(m.issues_closed, 1);
+    assert_eq!((m.lines_added, m.lines_removed), (10, 2));
 
-    cleanup(String::from("test_add_user"));
+    cleanup(String::from("test_contribution_metrics"));
+}
+
+#[test]
+fn week_of_date() {
+    use crate::semester::week_of;
+    use chrono::NaiveDate;
+
+    // 2019-11-20 was a Wednesday
+    let monday = NaiveDate::from_ymd(2019, 11, 18);
+    assert_eq!(week_of(NaiveDate::from_ymd(2019, 11, 20)), monday);
+    assert_eq!(week_of(monday), monday);
+    assert_eq!(week_of(NaiveDate::from_ymd(2019, 11, 24)), monday);
+}
+
+#[test]
+fn evaluation_questions() {
+    use crate::evaluations::models::{parse_questions, Question};
+
+    let qs = parse_questions(
+        "rating: Communication\n\nrating 10: Code quality\ntext: Comments\nAnything else?",
+    );
+    assert_eq!(
+        qs,
+        vec![
+            Question::Rating {
+                prompt: String::from("Communication"),
+                max: 5
+            },
+            Question::Rating {
+                prompt: String::from("Code quality"),
+                max: 10
+            },
+            Question::Text {
+                prompt: String::from("Comments")
+            },
+            Question::Text {
+                prompt: String::from("Anything else?")
+            },
+        ]
+    );
+}
+
+#[test]
+fn computed_grade_average() {
+    use crate::grades::handlers::computed_grade;
+    use crate::models::GradeSummary;
+    use crate::semester::Semester;
+
+    let mut summary = GradeSummary {
+        attendances: Vec::new(),
+        needed_attendances: 0,
+        commit_count: None,
+        synced_at: None,
+        semester: Semester::current(),
+        metrics: Vec::new(),
+        updates_posted: 0,
+        updates_needed: 0,
+        evaluation_score: None,
+        grade: None,
+    };
+    // Nothing to measure yet
+    assert_eq!(computed_grade(&summary), 100);
+
+    summary.needed_attendances = 4;
+    summary.updates_posted = 6;
+    summary.updates_needed = 10;
+    summary.evaluation_score = Some(90);
+    assert_eq!(computed_grade(&summary), 50);
+}
+
+#[test]
+fn proposal_review() {
+    use crate::notifications::handlers::unseen_count;
+
+    let config = setup(String::from("test_proposal_review"));
+
+    let client = Client::new(rocket(config)).unwrap();
+    let conn_url = create_connection_url(&client);
+
+    let conn = SqliteConnection::establish(conn_url.as_str())
+        .expect("Failed to connect to database in ProposalReviewTest");
+    embedded_migrations::run(&conn).expect("Failed to run embedded migrations");
+
+    let student = test_user(&conn, "proposer", 0);
+    let admin = test_user(&conn, "coordinator", 2);
+
+    log_in(&client, &student);
+    client
+        .post("/projects/new")
+        .header(ContentType::Form)
+        .body(concat!(
+            "name=Proposed&description=New&repos=%5B%5D&extrn=false",
+            "&goals=Ship&stack=Rust&license=MIT&team_size=4"
+        ))
+        .dispatch();
+
+    let p: Project = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .filter(name.eq("Proposed"))
+            .first(&conn)
+            .expect("Failed to get project from database")
+    };
+    let status_of = || -> String {
+        use crate::schema::projects::dsl::*;
+        projects
+            .find(p.id)
+            .select(status)
+            .first(&conn)
+            .expect("Failed to get project from database")
+    };
+    assert_eq!(status_of(), "proposed");
+    assert!(!p.approved());
+
+    // Students can't review their own proposal
+    client
+        .post(format!("/projects/{}/proposal/review", p.id))
+        .header(ContentType::Form)
+        .body("decision=approved&comment=")
+        .dispatch();
+    assert_eq!(status_of(), "proposed");
+
+    log_in(&client, &admin);
+    client
+        .post(format!("/projects/{}/proposal/review", p.id))
+        .header(ContentType::Form)
+        .body("decision=changes_requested&comment=More+detail")
+        .dispatch();
+    assert_eq!(status_of(), "changes_requested");
+    assert_eq!(unseen_count(&conn, &student), 1);
+    {
+        use crate::schema::proposal_comments::dsl::*;
+        let n: i64 = proposal_comments
+            .count()
+            .get_result(&conn)
+            .expect("Failed to count comments in database");
+        assert_eq!(n, 1);
+    }
+
+    // Revising the proposal puts it back in the queue
+    log_in(&client, &student);
+    client
+        .put(format!("/projects/{}/proposal", p.id))
+        .header(ContentType::Form)
+        .body("goals=Ship+more&stack=Rust&license=MIT&team_size=5")
+        .dispatch();
+    assert_eq!(status_of(), "proposed");
+    assert_eq!(unseen_count(&conn, &admin), 1);
+
+    client.post("/notifications/seen").dispatch();
+    assert_eq!(unseen_count(&conn, &student), 0);
+
+    log_in(&client, &admin);
+    client
+        .post(format!("/projects/{}/proposal/review", p.id))
+        .header(ContentType::Form)
+        .body("decision=approved&comment=")
+        .dispatch();
+    assert_eq!(status_of(), "approved");
+    assert_eq!(unseen_count(&conn, &student), 1);
+
+    cleanup(String::from("test_proposal_review"));
+}
+
+#[test]
+fn project_join_policies() {
+    use crate::notifications::handlers::unseen_count;
+
+    let config = setup(String::from("test_join_policies"));
+
+    let client = Client::new(rocket(config)).unwrap();
+    let conn_url = create_connection_url(&client);
+
+    let conn = SqliteConnection::establish(conn_url.as_str())
+        .expect("Failed to connect to database in JoinPoliciesTest");
+    embedded_migrations::run(&conn).expect("Failed to run embedded migrations");
+
+    let owner = test_user(&conn, "owner", 0);
+    let first = test_user(&conn, "first", 0);
+    let second = test_user(&conn, "second", 0);
+    let p = test_project(&conn, &owner, "Joinable");
+
+    let set_policy = |policy: &str, max: Option<i32>| {
+        use crate::schema::projects::dsl::*;
+        diesel::update(projects.find(p.id))
+            .set((join_policy.eq(policy), max_size.eq(max)))
+            .execute(&conn)
+            .expect("Failed to update project in database");
+    };
+    let members = || project_users(&conn, &p).len();
+    let join = |user: &User| {
+        log_in(&client, user);
+        client
+            .post(format!("/projects/{}/members/join", p.id))
+            .header(ContentType::Form)
+            .body("message=Hello")
+            .dispatch()
+    };
+
+    assert!(!p.is_full(100));
+    set_policy("open", Some(2));
+    join(&first);
+    assert_eq!(members(), 2);
+
+    // A full team turns people away
+    let response = join(&second);
+    assert_eq!(response.status(), Status::SeeOther);
+    assert!(response
+        .headers()
+        .get_one("Location")
+        .unwrap()
+        .ends_with("e=full"));
+    assert_eq!(members(), 2);
+
+    // With approval joining only asks the owner
+    set_policy("approval", None);
+    join(&second);
+    assert_eq!(members(), 2);
+    assert_eq!(unseen_count(&conn, &owner), 1);
+    let rid: i32 = {
+        use crate::schema::join_requests::dsl::*;
+        join_requests
+            .filter(user_id.eq(second.id))
+            .select(id)
+            .first(&conn)
+            .expect("Failed to get join request from database")
+    };
+
+    // Others can't accept the request
+    client
+        .post(format!("/projects/{}/requests/{}/accept", p.id, rid))
+        .dispatch();
+    assert_eq!(members(), 2);
+
+    log_in(&client, &owner);
+    client
+        .post(format!("/projects/{}/requests/{}/accept", p.id, rid))
+        .dispatch();
+    assert_eq!(members(), 3);
+    assert_eq!(unseen_count(&conn, &second), 1);
+
+    // Closed projects can't be joined at all
+    set_policy("closed", None);
+    let third = test_user(&conn, "third", 0);
+    join(&third);
+    assert_eq!(members(), 3);
+
+    cleanup(String::from("test_join_policies"));
+}
+
+#[test]
+fn project_roles_and_transfer() {
+    use crate::notifications::handlers::unseen_count;
+
+    let config = setup(String::from("test_project_roles"));
+
+    let client = Client::new(rocket(config)).unwrap();
+    let conn_url = create_connection_url(&client);
+
+    let conn = SqliteConnection::establish(conn_url.as_str())
+        .expect("Failed to connect to database in ProjectRolesTest");
+    embedded_migrations::run(&conn).expect("Failed to run embedded migrations");
+
+    let owner = test_user(&conn, "lead", 0);
+    let helper = test_user(&conn, "helper", 0);
+    let member = test_user(&conn, "member", 0);
+    let outsider = test_user(&conn, "outsider", 0);
+    let p = test_project(&conn, &owner, "Roles");
+    for u in [&helper, &member].iter() {
+        use crate::schema::relation_project_user::dsl::*;
+        insert_into(relation_project_user)
+            .values(&NewRelationProjectUser {
+                project_id: p.id,
+                user_id: u.id,
+                role: String::from("member"),
+                joined_at: None,
+            })
+            .execute(&conn)
+            .expect("Failed to add user to project");
+    }
+    let set_role = |uid: i32, r: &str| {
+        client
+            .post(format!("/projects/{}/members/{}/role", p.id, uid))
+            .header(ContentType::Form)
+            .body(format!("role={}", r))
+            .dispatch();
+    };
+    let transfer = |uid: i32| {
+        client
+            .post(format!("/projects/{}/transfer", p.id))
+            .header(ContentType::Form)
+            .body(format!("uid={}", uid))
+            .dispatch();
+    };
+    let owner_of = || -> i32 {
+        use crate::schema::projects::dsl::*;
+        projects
+            .find(p.id)
+            .select(owner_id)
+            .first(&conn)
+            .expect("Failed to get project from database")
+    };
+
+    // Members can't hand out roles
+    log_in(&client, &member);
+    set_role(helper.id, "maintainer");
+    assert_eq!(
+        project_role(&conn, &p, helper.id),
+        Some(ProjectRole::Member)
+    );
+
+    log_in(&client, &owner);
+    set_role(helper.id, "maintainer");
+    assert_eq!(
+        project_role(&conn, &p, helper.id),
+        Some(ProjectRole::Maintainer)
+    );
+    assert!(can_manage_project(&conn, &helper, &p));
+    assert!(!can_manage_project(&conn, &member, &p));
+
+    // Lead only changes hands by transferring the project
+    set_role(member.id, "lead");
+    assert_eq!(
+        project_role(&conn, &p, member.id),
+        Some(ProjectRole::Member)
+    );
+
+    // The new lead has to be on the project already
+    transfer(outsider.id);
+    assert_eq!(owner_of(), owner.id);
+
+    transfer(member.id);
+    assert_eq!(owner_of(), member.id);
+    assert_eq!(project_role(&conn, &p, member.id), Some(ProjectRole::Lead));
+    assert_eq!(
+        project_role(&conn, &p, owner.id),
+        Some(ProjectRole::Maintainer)
+    );
+    assert_eq!(unseen_count(&conn, &member), 1);
+
+    cleanup(String::from("test_project_roles"));
+}
+
+#[test]
+fn project_tag_parsing() {
+    use crate::projects::models::parse_tags;
+
+    assert_eq!(
+        parse_tags("Web, Machine  Learning,, web ,rust"),
+        vec!["web", "machine-learning", "rust"]
+    );
+    assert!(parse_tags(" , ").is_empty());
+}
+
+#[test]
+fn readme_decoding() {
+    use crate::repos::sync::decode_content;
+    use serde_json::json;
+
+    // The contents API wraps the base64 every 60 characters
+    let encoded = base64::encode("# Observatory\n\nTracks RCOS.\n");
+    let wrapped = format!("{}\n{}", &encoded[..10], &encoded[10..]);
+    assert_eq!(
+        decode_content(&json!({ "content": wrapped })),
+        Some(String::from("# Observatory\n\nTracks RCOS.\n"))
+    );
+
+    assert_eq!(decode_content(&json!({})), None);
+    assert_eq!(decode_content(&json!({ "content": "not base64!" })), None);
+    let binary = base64::encode(&[0xff, 0xfe, 0x00]);
+    assert_eq!(decode_content(&json!({ "content": binary })), None);
+
+    // Long files are cut off without splitting a character
+    let long = "é".repeat(40_000);
+    let text = decode_content(&json!({ "content": base64::encode(&long) })).unwrap();
+    assert!(text.len() <= 64 * 1024);
+    assert!(long.starts_with(&text));
+}
+
+#[test]
+fn repo_url_normalization() {
+    use crate::repos::url::{RepoUrl, RepoUrlError};
+
+    let norm = |u: &str| u.parse::<RepoUrl>().map(|r| r.to_string());
+    assert_eq!(
+        norm("http://www.GitHub.com/rcos/observatory.git/"),
+        Ok(String::from("https://github.com/rcos/observatory"))
+    );
+    assert_eq!(
+        norm("gitlab.com/group/sub/project"),
+        Ok(String::from("https://gitlab.com/group/sub/project"))
+    );
+    assert_eq!(
+        norm("ftp://github.com/rcos/observatory"),
+        Err(RepoUrlError::Scheme)
+    );
+    assert_eq!(
+        norm("https://localhost/rcos/observatory"),
+        Err(RepoUrlError::Host)
+    );
+    assert_eq!(norm("https://github.com/rcos"), Err(RepoUrlError::Path));
+    assert_eq!(
+        norm("https://github.com/rcos/obs?tab=1"),
+        Err(RepoUrlError::Path)
+    );
+}
+
+#[test]
+fn archive_filtering() {
+    let config = setup(String::from("test_archive_filtering"));
+
+    let client = Client::new(rocket(config)).unwrap();
+    let conn_url = create_connection_url(&client);
+
+    let conn = SqliteConnection::establish(conn_url.as_str())
+        .expect("Failed to connect to database in ArchiveFilteringTest");
+    embedded_migrations::run(&conn).expect("Failed to run embedded migrations");
+
+    let owner = test_user(&conn, "archiver", 0);
+    let other = test_user(&conn, "bystander", 0);
+    let kept = test_project(&conn, &owner, "Kept");
+    let old = test_project(&conn, &owner, "Old");
+    let listed = |archived: bool| -> Vec<String> {
+        let q = ProjectQuery {
+            archived: Some(archived),
+            ..Default::default()
+        };
+        search_projects(&conn, &q)
+            .into_iter()
+            .map(|l| l.project.name)
+            .collect()
+    };
+    let archived_of = |p: &Project| -> bool {
+        use crate::schema::projects::dsl::*;
+        projects
+            .find(p.id)
+            .select(archived)
+            .first(&conn)
+            .expect("Failed to get project from database")
+    };
+
+    log_in(&client, &owner);
+    client
+        .post(format!("/projects/{}/updates/new", old.id))
+        .header(ContentType::Form)
+        .body("week=2019-11-20&body=Before")
+        .dispatch();
+
+    // Only the owner and coordinators can archive a project
+    log_in(&client, &other);
+    client
+        .post(format!("/projects/{}/archive", old.id))
+        .dispatch();
+    assert!(!archived_of(&old));
+
+    log_in(&client, &owner);
+    client
+        .post(format!("/projects/{}/archive", old.id))
+        .dispatch();
+    assert!(archived_of(&old));
+    assert_eq!(listed(false), vec!["Kept"]);
+    assert_eq!(listed(true), vec!["Old"]);
+    assert_eq!(
+        missing_updates(&conn, &owner)
+            .into_iter()
+            .map(|p| p.id)
+            .collect::<Vec<i32>>(),
+        vec![kept.id]
+    );
+
+    // Archived projects are read-only
+    let uid: i32 = {
+        use crate::schema::status_updates::dsl::*;
+        status_updates
+            .filter(project_id.eq(old.id))
+            .select(id)
+            .first(&conn)
+            .expect("Failed to get status update from database")
+    };
+    client
+        .put(format!("/updates/{}", uid))
+        .header(ContentType::Form)
+        .body("week=2019-11-20&body=After")
+        .dispatch();
+    {
+        use crate::schema::status_updates::dsl::*;
+        let text: String = status_updates
+            .find(uid)
+            .select(body)
+            .first(&conn)
+            .expect("Failed to get status update from database");
+        assert_eq!(text, "Before");
+    }
+    log_in(&client, &other);
+    client
+        .post(format!("/projects/{}/members/join", old.id))
+        .header(ContentType::Form)
+        .body("message=")
+        .dispatch();
+    assert!(!project_users(&conn, &old).contains(&other));
+
+    log_in(&client, &owner);
+    client
+        .post(format!("/projects/{}/unarchive", old.id))
+        .dispatch();
+    assert!(!archived_of(&old));
+    assert_eq!(listed(false), vec!["Kept", "Old"]);
+
+    cleanup(String::from("test_archive_filtering"));
+}
+
+#[test]
+fn upload_sniffing() {
+    use crate::uploads::process::sniff;
+    use crate::uploads::storage::valid_name;
+
+    assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+    assert_eq!(sniff(b"\xff\xd8\xff\xe0\0\x10JFIF"), Some("image/jpeg"));
+    assert_eq!(sniff(b"GIF89a\x01\0\x01\0"), Some("image/gif"));
+    assert_eq!(sniff(b"RIFF\x24\0\0\0WEBPVP8 "), Some("image/webp"));
+    assert_eq!(sniff(b"<svg xmlns=\"http://www.w3.org/2000/svg\">"), None);
+    assert_eq!(sniff(b"RIFF"), None);
+
+    assert!(valid_name("logo-0123abcd.webp"));
+    assert!(!valid_name("../observ.sqlite"));
+    assert!(!valid_name(".hidden"));
+    assert!(!valid_name(""));
+}
+
+#[test]
+fn export_page_selection() {
+    use crate::export::site::export;
+
+    let config = setup(String::from("test_export"));
+
+    let client = Client::new(rocket(config)).unwrap();
+    let conn_url = create_connection_url(&client);
+
+    let conn = SqliteConnection::establish(conn_url.as_str())
+        .expect("Failed to connect to database in ExportTest");
+    embedded_migrations::run(&conn).expect("Failed to run embedded migrations");
+
+    let owner = test_user(&conn, "exporter", 0);
+    let public = test_project(&conn, &owner, "Public");
+    let hidden = test_project(&conn, &owner, "Hidden");
+    let pending = test_project(&conn, &owner, "Pending");
+    {
+        use crate::schema::projects::dsl::*;
+        diesel::update(projects.find(hidden.id))
+            .set(archived.eq(true))
+            .execute(&conn)
+            .expect("Failed to update project in database");
+        diesel::update(projects.find(pending.id))
+            .set(status.eq("proposed"))
+            .execute(&conn)
+            .expect("Failed to update project in database");
+    }
+
+    let dir = Path::new("./test_export/site");
+    export(client.rocket(), dir).expect("Failed to export site");
+
+    // Only approved projects that aren't archived are published
+    let page = |p: &Project| dir.join(format!("projects/{}/index.html", p.id));
+    assert!(page(&public).is_file());
+    assert!(!page(&hidden).exists());
+    assert!(!page(&pending).exists());
+    assert!(dir.join("index.html").is_file());
+    assert!(dir.join("static").is_dir());
+
+    let data = fs::read_to_string(dir.join("data.json")).unwrap();
+    assert!(data.contains("Public"));
+    assert!(!data.contains("Hidden"));
+    assert!(!data.contains("Pending"));
+
+    fs::remove_dir_all(dir).expect("Dir Deletion Error");
+    cleanup(String::from("test_export"));
+}
+
+#[test]
+fn activity_kinds() {
+    use crate::activity::models::ActivityKind;
+
+    for k in ActivityKind::all().iter() {
+        assert_eq!(k.as_str().parse::<ActivityKind>(), Ok(*k));
+    }
+    assert_eq!("commits".parse::<ActivityKind>(), Err(()));
+}
+
+#[test]
+fn showcase_rubric() {
+    use crate::showcase::models::{parse_rubric, Criterion};
+
+    assert_eq!(
+        parse_rubric("Design\n\nTechnical Difficulty: 10\nDemo: 1"),
+        vec![
+            Criterion {
+                name: "Design".to_string(),
+                max: 5
+            },
+            Criterion {
+                name: "Technical Difficulty".to_string(),
+                max: 10
+            },
+            Criterion {
+                name: "Demo".to_string(),
+                max: 2
+            },
+        ]
+    );
+}
+
+#[test]
+fn schedule_occurrences() {
+    use crate::models::GroupSchedule;
+    use chrono::{NaiveDate, NaiveTime};
+
+    let d = |m, day| NaiveDate::from_ymd(2020, m, day);
+    let sched = GroupSchedule {
+        id: 1,
+        group_id: 1,
+        // Tuesdays
+        weekday: 1,
+        start_time: NaiveTime::from_hms(16, 0, 0),
+        location: None,
+        starts_on: d(1, 13),
+        ends_on: d(2, 11),
+        skip_dates: "[\"2020-01-28\"]".to_string(),
+        room_id: None,
+    };
+
+    assert_eq!(
+        sched.occurrences(d(1, 1), d(12, 31)),
+        vec![d(1, 14), d(1, 21), d(2, 4), d(2, 11)]
+    );
+    assert_eq!(sched.occurrences(d(1, 22), d(2, 5)), vec![d(2, 4)]);
+    assert!(sched.occurrences(d(2, 12), d(12, 31)).is_empty());
+}
+
+#[test]
+fn meeting_edit_and_delete() {
+    let config = setup(String::from("test_meeting_edit_and_delete"));
+
+    let client = Client::new(rocket(config)).unwrap();
+    let conn_url = create_connection_url(&client);
+
+    let conn = SqliteConnection::establish(conn_url.as_str())
+        .expect("Failed to connect to database in MeetingEditAndDeleteTest");
+    embedded_migrations::run(&conn).expect("Failed to run embedded migrations");
+
+    let mentor = test_user(&conn, "groupmentor", 1);
+    let other = test_user(&conn, "othermentor", 1);
+    let admin = test_user(&conn, "groupadmin", 2);
+    let student = test_user(&conn, "groupstudent", 0);
+
+    let gid: i32 = {
+        use crate::schema::groups::dsl::*;
+        insert_into(groups)
+            .values(&NewGroup {
+                name: "Small Group".to_string(),
+                owner_id: mentor.id,
+                ..Default::default()
+            })
+            .execute(&conn)
+            .expect("Failed to insert group into database");
+        groups
+            .select(id)
+            .order(id.desc())
+            .first(&conn)
+            .expect("Failed to get group from database")
+    };
+    let new_meeting = |c: &str| -> Meeting {
+        use crate::schema::meetings::dsl::*;
+        insert_into(meetings)
+            .values(&NewMeeting {
+                code: c.to_string(),
+                group_id: gid,
+            })
+            .execute(&conn)
+            .expect("Failed to insert meeting into database");
+        meetings
+            .order(id.desc())
+            .first(&conn)
+            .expect("Failed to get meeting from database")
+    };
+    let find_meeting = |mid: i32| -> Option<Meeting> {
+        use crate::schema::meetings::dsl::*;
+        meetings
+            .find(mid)
+            .first(&conn)
+            .optional()
+            .expect("Failed to get meeting from database")
+    };
+    let attendance_count = |mid: i32| -> i64 {
+        use crate::schema::attendances::dsl::*;
+        attendances
+            .filter(meeting_id.eq(mid))
+            .count()
+            .get_result(&conn)
+            .expect("Failed to count attendances")
+    };
+
+    let empty = new_meeting("EMPTY1");
+    let attended = new_meeting("ATTND1");
+    {
+        use crate::schema::attendances::dsl::*;
+        insert_into(attendances)
+            .values(&NewAttendance {
+                user_id: student.id,
+                is_event: false,
+                meeting_id: Some(attended.id),
+                event_id: None,
+            })
+            .execute(&conn)
+            .expect("Failed to insert attendance into database");
+    }
+
+    // Only the group's owner and admins can edit its meetings
+    let edit = "title=Planning&happened_at=2020-03-12+16:00&location=DCC+318\
+                &agenda=&notes=Went+well";
+    log_in(&client, &other);
+    client
+        .put(format!("/groups/{}/meetings/{}", gid, empty.id))
+        .header(ContentType::Form)
+        .body(edit)
+        .dispatch();
+    assert_eq!(find_meeting(empty.id), Some(empty.clone()));
+
+    log_in(&client, &mentor);
+    client
+        .put(format!("/groups/{}/meetings/{}", gid, empty.id))
+        .header(ContentType::Form)
+        .body(edit)
+        .dispatch();
+    let edited = find_meeting(empty.id).expect("Meeting was deleted by edit");
+    assert_eq!(edited.title, Some("Planning".to_string()));
+    assert_eq!(edited.location, Some("DCC 318".to_string()));
+    assert_eq!(edited.agenda, None);
+    assert_eq!(edited.notes, Some("Went well".to_string()));
+    assert_eq!(
+        edited.happened_at.format("%F %R").to_string(),
+        "2020-03-12 16:00"
+    );
+
+    // A bad time leaves the meeting as it was
+    client
+        .put(format!("/groups/{}/meetings/{}", gid, empty.id))
+        .header(ContentType::Form)
+        .body("happened_at=next+tuesday")
+        .dispatch();
+    assert_eq!(find_meeting(empty.id), Some(edited));
+
+    // Mentors can't delete a meeting someone attended
+    client
+        .delete(format!("/groups/{}/meetings/{}", gid, attended.id))
+        .dispatch();
+    assert!(find_meeting(attended.id).is_some());
+    assert_eq!(attendance_count(attended.id), 1);
+
+    client
+        .delete(format!("/groups/{}/meetings/{}", gid, empty.id))
+        .dispatch();
+    assert_eq!(find_meeting(empty.id), None);
+
+    // Admins can, which deletes the attendance with it
+    log_in(&client, &admin);
+    client
+        .delete(format!("/groups/{}/meetings/{}", gid, attended.id))
+        .dispatch();
+    assert_eq!(find_meeting(attended.id), None);
+    assert_eq!(attendance_count(attended.id), 0);
+
+    cleanup(String::from("test_meeting_edit_and_delete"));
+}
+
+#[test]
+fn group_assignment() {
+    use crate::groups::assign::*;
+
+    let slots = vec![
+        Slot {
+            group_id: 1,
+            capacity: Some(4),
+            preferred: vec![],
+        },
+        Slot {
+            group_id: 2,
+            capacity: Some(4),
+            preferred: vec![20],
+        },
+    ];
+    // Project 10 has three students, project 20 has two, student 6 has none
+    let teams = build_teams(
+        &[1, 2, 3, 4, 5, 6],
+        &[(10, vec![1, 2, 3, 100]), (20, vec![3, 4, 5])],
+        true,
+    );
+    assert_eq!(teams.len(), 3);
+
+    let assigned = assign(&slots, &teams, &[(6, 2)], true);
+    assert_eq!(
+        assigned,
+        vec![(1, 1), (2, 1), (3, 1), (4, 2), (5, 2), (6, 2)]
+    );
+
+    let (adds, removes) = diff(&[(1, 2), (6, 2), (100, 2)], &assigned);
+    assert_eq!(adds, vec![(1, 1), (2, 1), (3, 1), (4, 2), (5, 2)]);
+    assert_eq!(removes, vec![(1, 2)]);
+}
+
+#[test]
+fn room_bookings() {
+    use crate::rooms::bookings::{Booked, Booking};
+    use chrono::NaiveDate;
+
+    let at = |h, m| NaiveDate::from_ymd(2020, 3, 31).and_hms(h, m, 0);
+    let booking = Booking {
+        title: "Small Group".to_string(),
+        start: at(16, 0),
+        end: at(18, 0),
+        url: "/groups/1/meetings/2".to_string(),
+        booked: Booked::Meeting(2),
+        schedule_id: Some(3),
+        series_id: None,
+    };
+
+    assert!(booking.overlaps(at(17, 0), at(19, 0)));
+    assert!(booking.overlaps(at(15, 0), at(20, 0)));
+    // Back to back bookings are fine
+    assert!(!booking.overlaps(at(18, 0), at(19, 0)));
+    assert!(!booking.overlaps(at(14, 0), at(16, 0)));
+
+    assert!(booking.is_part_of(Some(Booked::Meeting(2))));
+    assert!(booking.is_part_of(Some(Booked::Schedule(3))));
+    assert!(!booking.is_part_of(Some(Booked::Event(2))));
+    assert!(!booking.is_part_of(Some(Booked::Series(3))));
+    assert!(!booking.is_part_of(None));
+}
+
+#[test]
+fn group_hierarchy() {
+    let group = |gid, parent| Group {
+        id: gid,
+        name: format!("Group {}", gid),
+        owner_id: 1,
+        location: None,
+        archived: false,
+        capacity: None,
+        parent_id: parent,
+        join_on_signup: false,
+    };
+    // 4 and 5 point at each other so they loop
+    let all = vec![
+        group(1, None),
+        group(2, Some(1)),
+        group(3, Some(2)),
+        group(4, Some(5)),
+        group(5, Some(4)),
+    ];
+
+    assert_eq!(group_subtree(1, &all), vec![1, 2, 3]);
+    assert_eq!(group_subtree(3, &all), vec![3]);
+    assert_eq!(group_subtree(4, &all), vec![4, 5]);
+}
+
+#[test]
+fn membership_intervals() {
+    use chrono::NaiveDate;
+
+    let day = |d| NaiveDate::from_ymd(2020, 4, d).and_hms(16, 0, 0);
+    let relation = |rid, uid, joined, left| RelationGroupUser {
+        id: rid,
+        group_id: 1,
+        user_id: uid,
+        joined_at: joined,
+        left_at: left,
+    };
+    let relations = vec![
+        // From before join dates were kept and still a member
+        relation(1, 1, None, None),
+        // Left then joined again
+        relation(2, 2, Some(day(2)), Some(day(6))),
+        relation(3, 2, Some(day(10)), None),
+        relation(4, 3, Some(day(8)), None),
+    ];
+
+    assert!(relations[1].member_at(day(2)));
+    assert!(!relations[1].member_at(day(6)));
+    assert_eq!(members_at(&relations, 1, day(1)), vec![1]);
+    assert_eq!(members_at(&relations, 1, day(4)), vec![1, 2]);
+    assert_eq!(members_at(&relations, 1, day(8)), vec![1, 3]);
+    assert_eq!(members_at(&relations, 1, day(12)), vec![1, 2, 3]);
+    assert!(members_at(&relations, 2, day(12)).is_empty());
+}
+
+#[test]
+fn semester_projects() {
+    use crate::semester::{Semester, Term};
+    use chrono::{NaiveDate, NaiveDateTime};
+    use diesel::update;
+
+    let config = setup(String::from("test_semester_projects"));
+
+    let client = Client::new(rocket(config)).unwrap();
+    let conn_url = create_connection_url(&client);
+
+    let conn = SqliteConnection::establish(conn_url.as_str())
+        .expect("Failed to connect to database in SemesterProjectsTest");
+    embedded_migrations::run(&conn).expect("Failed to run embedded migrations");
+
+    let lead = test_user(&conn, "historylead", 0);
+    let student = test_user(&conn, "historian", 0);
+    let day = |m, d| NaiveDate::from_ymd(2020, m, d).and_hms(12, 0, 0);
+    let join = |p: &Project, joined: NaiveDateTime, left: Option<NaiveDateTime>| {
+        use crate::schema::relation_project_user::dsl::*;
+        insert_into(relation_project_user)
+            .values(&NewRelationProjectUser {
+                project_id: p.id,
+                user_id: student.id,
+                role: String::from("member"),
+                joined_at: Some(joined),
+            })
+            .execute(&conn)
+            .expect("Failed to add user to project");
+        update(relation_project_user.filter(project_id.eq(p.id).and(user_id.eq(student.id))))
+            .set(left_at.eq(left))
+            .execute(&conn)
+            .expect("Failed to update relation in database");
+    };
+
+    // Left during the spring
+    let spring = test_project(&conn, &lead, "Spring");
+    join(&spring, day(1, 20), Some(day(3, 1)));
+    // Joined in the summer and still a member
+    let summer = test_project(&conn, &lead, "Summer");
+    join(&summer, day(7, 1), None);
+    // Joined and left before the spring
+    let old = test_project(&conn, &lead, "Old");
+    join(
+        &old,
+        day(1, 1) - chrono::Duration::days(30),
+        Some(day(1, 1)),
+    );
+
+    let during = |term| -> Vec<String> {
+        let sem = Semester { year: 2020, term };
+        user_projects_during(&conn, &student, &sem)
+            .into_iter()
+            .map(|p| p.name)
+            .collect()
+    };
+    assert_eq!(during(Term::Spring), vec!["Spring"]);
+    assert_eq!(during(Term::Summer), vec!["Summer"]);
+    assert_eq!(during(Term::Fall), vec!["Summer"]);
+    assert_eq!(
+        user_projects(&conn, &student)
+            .into_iter()
+            .map(|p| p.name)
+            .collect::<Vec<String>>(),
+        vec!["Summer"]
+    );
+
+    cleanup(String::from("test_semester_projects"));
+}
+
+#[test]
+fn ics_writing() {
+    use crate::calendar::ics::*;
+    use chrono::NaiveDate;
+
+    assert_eq!(
+        escape("Lunch; pizza, soda\nRoom \\ 2"),
+        r"Lunch\; pizza\, soda\nRoom \\ 2"
+    );
+    let long = format!("DESCRIPTION:{}", "é".repeat(60));
+    let folded = fold(&long);
+    assert!(folded.ends_with("\r\n"));
+    assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+    assert_eq!(folded.replace("\r\n ", "").trim_end(), long);
+
+    let start = NaiveDate::from_ymd(2020, 4, 21).and_hms(16, 0, 0);
+    let ics = write_calendar(
+        "RCOS",
+        &[IcsEvent {
+            uid: String::from("meeting-5"),
+            summary: String::from("Small Group: Meeting"),
+            description: None,
+            location: Some(String::from("DCC 308")),
+            start,
+            end: start + chrono::Duration::hours(2),
+            url: String::from("/groups/1/meetings/5"),
+            cancelled: true,
+        }],
+    );
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert!(ics.contains("\r\nUID:meeting-5@rcos.io\r\n"));
+    assert!(ics.contains("\r\nURL:https://rcos.io/groups/1/meetings/5\r\n"));
+    assert!(ics.contains("\r\nSTATUS:CANCELLED\r\n"));
+    assert!(!ics.contains("DESCRIPTION"));
+}
+
+#[test]
+fn ics_parsing() {
+    use crate::calendar::ics::*;
+    use chrono::NaiveDate;
+
+    let ics = "BEGIN:VCALENDAR\r\n\
+               VERSION:2.0\r\n\
+               BEGIN:VEVENT\r\n\
+               UID:talk-1@example.com\r\n\
+               SUMMARY:Guest Talk\\, Part 1\r\n\
+               DESCRIPTION:First line\\nsecond \r\n \
+               line\r\n\
+               DTSTART;TZID=America/New_York:20200505T160000\r\n\
+               DURATION:PT1H30M\r\n\
+               BEGIN:VALARM\r\n\
+               DESCRIPTION:Reminder\r\n\
+               END:VALARM\r\n\
+               END:VEVENT\r\n\
+               BEGIN:VEVENT\r\n\
+               UID:day-off\r\n\
+               SUMMARY:Day Off\r\n\
+               DTSTART;VALUE=DATE:20200510\r\n\
+               END:VEVENT\r\n\
+               BEGIN:VEVENT\r\n\
+               SUMMARY:No UID\r\n\
+               DTSTART:20200505T160000\r\n\
+               END:VEVENT\r\n\
+               BEGIN:VEVENT\r\n\
+               UID:weekly\r\n\
+               SUMMARY:Weekly\r\n\
+               DTSTART:20200505T160000\r\n\
+               RRULE:FREQ=WEEKLY;COUNT=4\r\n\
+               EXDATE:20200512T160000,20200519T160000\r\n\
+               END:VEVENT\r\n\
+               BEGIN:VEVENT\r\n\
+               UID:weekly\r\n\
+               SUMMARY:Weekly\r\n\
+               RECURRENCE-ID:20200512T160000\r\n\
+               DTSTART:20200512T170000\r\n\
+               END:VEVENT\r\n\
+               BEGIN:VEVENT\r\n\
+               UID:backwards\r\n\
+               SUMMARY:Backwards\r\n\
+               DTSTART:20200505T160000\r\n\
+               DTEND:20200505T150000\r\n\
+               END:VEVENT\r\n\
+               END:VCALENDAR\r\n";
+    let (parsed, errors) = parse_calendar(ics);
+
+    let talk = NaiveDate::from_ymd(2020, 5, 5).and_hms(16, 0, 0);
+    assert_eq!(parsed.len(), 3);
+    assert_eq!(parsed[0].uid, "talk-1@example.com");
+    assert_eq!(parsed[0].summary, "Guest Talk, Part 1");
+    assert_eq!(
+        parsed[0].description,
+        Some(String::from("First line\nsecond line"))
+    );
+    assert_eq!(parsed[0].start, talk);
+    assert_eq!(parsed[0].end, talk + chrono::Duration::minutes(90));
+
+    let day = NaiveDate::from_ymd(2020, 5, 10).and_hms(0, 0, 0);
+    assert_eq!(parsed[1].start, day);
+    assert_eq!(parsed[1].end, day + chrono::Duration::days(1));
+
+    assert_eq!(parsed[2].rrule, Some(String::from("FREQ=WEEKLY;COUNT=4")));
+    assert_eq!(
+        parsed[2].exdates,
+        vec![
+            NaiveDate::from_ymd(2020, 5, 12),
+            NaiveDate::from_ymd(2020, 5, 19)
+        ]
+    );
+
+    assert_eq!(errors.len(), 3);
+    assert!(errors[0].starts_with("Entry 3 (No UID)"));
+    assert!(errors[1].starts_with("Entry 5 (Weekly)"));
+    assert!(errors[2].starts_with("Entry 6 (Backwards)"));
+}
+
+#[test]
+fn recurrence_rules() {
+    use crate::calendar::recurrence::Recurrence;
+    use chrono::NaiveDate;
+
+    let day = |m, d| NaiveDate::from_ymd(2020, m, d);
+    let days = |rule: &str, first: NaiveDate| -> Vec<NaiveDate> {
+        Recurrence::parse(rule)
+            .unwrap()
+            .times(first.and_hms(16, 0, 0), day(12, 31).and_hms(0, 0, 0))
+            .iter()
+            .map(|t| t.date())
+            .collect()
+    };
+
+    assert_eq!(
+        days("FREQ=WEEKLY;BYDAY=TU,TH;COUNT=5", day(5, 5)),
+        vec![day(5, 5), day(5, 7), day(5, 12), day(5, 14), day(5, 19)]
+    );
+    assert_eq!(
+        days("RRULE:FREQ=WEEKLY;INTERVAL=2;UNTIL=20200602", day(5, 5)),
+        vec![day(5, 5), day(5, 19), day(6, 2)]
+    );
+    // Second Tuesday and last Friday of the month
+    assert_eq!(
+        days("FREQ=MONTHLY;BYDAY=2TU,-1FR;COUNT=4", day(5, 12)),
+        vec![day(5, 12), day(5, 29), day(6, 9), day(6, 26)]
+    );
+    // Months without the day are skipped
+    assert_eq!(
+        days("FREQ=MONTHLY;COUNT=3", day(5, 31)),
+        vec![day(5, 31), day(7, 31), day(8, 31)]
+    );
+    assert_eq!(
+        days("FREQ=DAILY;BYDAY=MO,WE;UNTIL=20200513", day(5, 4)),
+        vec![day(5, 4), day(5, 6), day(5, 11), day(5, 13)]
+    );
+
+    let rule = Recurrence::parse("freq=weekly;byday=tu;count=10").unwrap();
+    assert_eq!(rule.to_string(), "FREQ=WEEKLY;COUNT=10;BYDAY=TU");
+    assert_eq!(rule.describe(), "Every week on Tuesday, 10 times");
+    assert_eq!(
+        rule.ending_before(day(5, 19)).to_string(),
+        "FREQ=WEEKLY;UNTIL=20200518T235959;BYDAY=TU"
+    );
+
+    assert_eq!(Recurrence::parse("FREQ=HOURLY"), None);
+    assert_eq!(Recurrence::parse("FREQ=WEEKLY;BYDAY=2TU"), None);
+    assert_eq!(Recurrence::parse("FREQ=DAILY;COUNT=2;UNTIL=20200601"), None);
+    assert_eq!(Recurrence::parse("FREQ=WEEKLY;BYSETPOS=1"), None);
+    assert_eq!(Recurrence::parse("BYDAY=TU"), None);
+}
+
+#[test]
+fn series_deletion() {
+    use crate::calendar::series::*;
+    use chrono::NaiveDate;
+
+    let config = setup(String::from("test_series_deletion"));
+
+    let client = Client::new(rocket(config)).unwrap();
+    let conn_url = create_connection_url(&client);
+
+    let conn = SqliteConnection::establish(conn_url.as_str())
+        .expect("Failed to connect to database in SeriesDeletionTest");
+    embedded_migrations::run(&conn).expect("Failed to run embedded migrations");
+
+    let host = test_user(&conn, "serieshost", 2);
+    let student = test_user(&conn, "seriesstudent", 0);
+    let find_event = |eid: i32| -> Option<Event> {
+        use crate::schema::events::dsl::*;
+        events
+            .find(eid)
+            .first(&conn)
+            .optional()
+            .expect("Failed to get event from database")
+    };
+    let attend = |eid: i32| {
+        use crate::schema::attendances::dsl::*;
+        insert_into(attendances)
+            .values(&NewAttendance {
+                user_id: student.id,
+                is_event: true,
+                meeting_id: None,
+                event_id: Some(eid),
+            })
+            .execute(&conn)
+            .expect("Failed to insert attendance into database");
+    };
+    let attendance_count = |eid: i32| -> i64 {
+        use crate::schema::attendances::dsl::*;
+        attendances
+            .filter(event_id.eq(eid))
+            .count()
+            .get_result(&conn)
+            .expect("Failed to count attendances")
+    };
+
+    let sid = add_series(
+        &conn,
+        &NewEvent {
+            title: String::from("Large Group"),
+            start: String::from("2020-05-05 16:00:00"),
+            end: String::from("2020-05-05 18:00:00"),
+            hosted_by: host.id,
+            code: String::from("SERIES"),
+            rrule: Some(String::from("FREQ=WEEKLY;COUNT=3")),
+            exdates: String::from("[]"),
+            ..Default::default()
+        },
+    )
+    .expect("Failed to add repeating event");
+    let s = find_event(sid).expect("Repeating event wasn't added");
+    let day = NaiveDate::from_ymd(2020, 5, 12);
+    let tid = make_time(&conn, &s, day)
+        .expect("Failed to make time of repeating event")
+        .expect("Repeating event doesn't happen on the day");
+    attend(sid);
+    attend(tid);
+
+    // Skipping a time deletes its attendance and keeps the day from
+    // being made again
+    skip_time(&conn, &find_event(tid).unwrap()).expect("Failed to skip time");
+    assert_eq!(find_event(tid), None);
+    assert_eq!(attendance_count(tid), 0);
+    let s = find_event(sid).expect("Repeating event was deleted");
+    assert_eq!(s.skip_list(), vec![day]);
+    assert_eq!(
+        make_time(&conn, &s, day).expect("Failed to make time of repeating event"),
+        None
+    );
+    assert_eq!(attendance_count(sid), 1);
+
+    delete_series(&conn, sid).expect("Failed to delete repeating event");
+    assert_eq!(find_event(sid), None);
+    assert_eq!(attendance_count(sid), 0);
+
+    cleanup(String::from("test_series_deletion"));
 }
@@ -0,0 +1,450 @@
+//! HTTP handlers for status updates
+
+use chrono::{Local, NaiveDate};
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel::{insert_into, select, update};
+use rocket::http::Status;
+use rocket::request::Form;
+use rocket::response::Redirect;
+
+use crate::activity::handlers::log_activity;
+use crate::activity::models::ActivityKind;
+use crate::guards::*;
+use crate::models::{Project, User};
+use crate::projects::handlers::project_users;
+use crate::semester::{this_week, week_of, Semester};
+use crate::templates::FormError;
+use crate::users::handlers::user_projects;
+use crate::ObservDbConn;
+
+use super::models::*;
+use super::templates::*;
+
+/// GET handler for `/projects/<h>/updates`
+///
+/// All of the status updates posted for a project, newest first.
+#[get("/projects/<h>/updates")]
+pub fn project_updates(conn: ObservDbConn, l: UserGuard, h: i32) -> Option<ProjectUpdatesTemplate> {
+    use crate::schema::projects::dsl::*;
+
+    let p: Project = projects
+        .find(h)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get project from database")?;
+
+    let updates = {
+        use crate::schema::status_updates::dsl::*;
+        StatusUpdate::belonging_to(&p)
+            .order((week.desc(), created_at.desc()))
+            .load(&*conn)
+            .expect("Failed to get status updates from database")
+    };
+
+    Some(ProjectUpdatesTemplate {
+        logged_in: Some(l.0),
+        updates: with_authors(&*conn, updates),
+        project: p,
+    })
+}
+
+/// GET handler for `/projects/<h>/updates/new`
+///
+/// The form to post a status update.
+///
+/// Restricted to members of the project.
+#[get("/projects/<h>/updates/new?<e>")]
+pub fn update_new(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    e: Option<FormError>,
+) -> Result<NewStatusUpdateTemplate, Status> {
+    use crate::schema::projects::dsl::*;
+
+    let p: Project = projects
+        .find(h)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get project from database")
+        .ok_or(Status::NotFound)?;
+
+    // Archived projects are read-only
+    if p.archived {
+        return Err(Status::Conflict);
+    }
+
+    if project_users(&*conn, &p).contains(&l.0) {
+        Ok(NewStatusUpdateTemplate {
+            logged_in: Some(l.0),
+            project: p,
+            week: this_week(),
+            error: e,
+        })
+    } else {
+        Err(Status::Unauthorized)
+    }
+}
+
+/// POST handler for `/projects/<h>/updates/new`
+///
+/// Posts a status update for the week the given date falls in.
+/// If the user has already posted one for that week they are sent to
+/// edit it instead.
+///
+/// Restricted to members of the project.
+#[post("/projects/<h>/updates/new", data = "<form>")]
+pub fn update_new_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    form: Form<StatusUpdateForm>,
+) -> Result<Redirect, Status> {
+    let form = form.into_inner();
+
+    let p: Project = {
+        use crate::schema::projects::dsl::*;
+        projects
+            .find(h)
+            .first(&*conn)
+            .expect("Failed to get project from database")
+    };
+
+    if !project_users(&*conn, &p).contains(&l.0) {
+        return Err(Status::Unauthorized);
+    }
+    if p.archived {
+        return Err(Status::Conflict);
+    }
+
+    let w = if let Ok(d) = NaiveDate::parse_from_str(&form.week, "%F") {
+        week_of(d)
+    } else {
+        return Ok(Redirect::to(format!(
+            "/projects/{}/updates/new?e={}",
+            h,
+            FormError::InvalidDate
+        )));
+    };
+
+    use crate::schema::status_updates::dsl::*;
+
+    let existing: Option<i32> = status_updates
+        .filter(user_id.eq(l.0.id).and(project_id.eq(h)).and(week.eq(w)))
+        .select(id)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get status update from database");
+    if let Some(uid) = existing {
+        return Ok(Redirect::to(format!(
+            "/updates/{}/edit?e={}",
+            uid,
+            FormError::UpdateExists
+        )));
+    }
+
+    insert_into(status_updates)
+        .values(&NewStatusUpdate {
+            user_id: l.0.id,
+            project_id: h,
+            week: w,
+            body: form.body,
+        })
+        .execute(&*conn)
+        .expect("Failed to insert status update into database");
+    let posted: i32 = status_updates
+        .filter(user_id.eq(l.0.id).and(project_id.eq(h)).and(week.eq(w)))
+        .select(id)
+        .first(&*conn)
+        .expect("Failed to get status update from database");
+    log_activity(
+        &*conn,
+        h,
+        Some(l.0.id),
+        ActivityKind::Update,
+        &format!("posted a status update for the week of {}", w),
+        &format!("/updates/{}", posted),
+    );
+
+    Ok(Redirect::to(format!("/projects/{}/updates", h)))
+}
+
+/// GET handler for `/updates/<uid>`
+///
+/// A single status update and the comments on it.
+#[get("/updates/<uid>")]
+pub fn status_update(conn: ObservDbConn, l: UserGuard, uid: i32) -> Option<StatusUpdateTemplate> {
+    let u: StatusUpdate = {
+        use crate::schema::status_updates::dsl::*;
+        status_updates
+            .find(uid)
+            .first(&*conn)
+            .optional()
+            .expect("Failed to get status update from database")?
+    };
+
+    let comments = {
+        use crate::schema::status_update_comments::dsl::*;
+        StatusUpdateComment::belonging_to(&u)
+            .order(created_at.asc())
+            .load::<StatusUpdateComment>(&*conn)
+            .expect("Failed to get comments from database")
+            .into_iter()
+            .map(|c| CommentEntry {
+                author: get_user(&*conn, c.user_id),
+                comment: c,
+            })
+            .collect()
+    };
+
+    Some(StatusUpdateTemplate {
+        logged_in: Some(l.0),
+        project: {
+            use crate::schema::projects::dsl::*;
+            projects
+                .find(u.project_id)
+                .first(&*conn)
+                .expect("Failed to get project from database")
+        },
+        author: get_user(&*conn, u.user_id),
+        reviewer: u.reviewed_by.map(|r| get_user(&*conn, r)),
+        comments,
+        update: u,
+    })
+}
+
+/// GET handler for `/updates/<uid>/edit`
+///
+/// Restricted to the author of the update.
+#[get("/updates/<uid>/edit?<e>")]
+pub fn update_edit(
+    conn: ObservDbConn,
+    l: UserGuard,
+    uid: i32,
+    e: Option<FormError>,
+) -> Result<EditStatusUpdateTemplate, Status> {
+    use crate::schema::status_updates::dsl::*;
+
+    let u: StatusUpdate = status_updates
+        .find(uid)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get status update from database")
+        .ok_or(Status::NotFound)?;
+
+    if u.user_id == l.0.id {
+        Ok(EditStatusUpdateTemplate {
+            logged_in: Some(l.0),
+            update: u,
+            error: e,
+        })
+    } else {
+        Err(Status::Unauthorized)
+    }
+}
+
+/// PUT handler for `/updates/<uid>`
+///
+/// Editing an update clears its review so that the mentor looks at it
+/// again.
+///
+/// Restricted to the author of the update.
+#[put("/updates/<uid>", data = "<form>")]
+pub fn update_edit_put(
+    conn: ObservDbConn,
+    l: UserGuard,
+    uid: i32,
+    form: Form<StatusUpdateForm>,
+) -> Result<Redirect, Status> {
+    use crate::schema::status_updates::dsl::*;
+
+    let form = form.into_inner();
+    let u: StatusUpdate = status_updates
+        .find(uid)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get status update from database")
+        .ok_or(Status::NotFound)?;
+
+    if u.user_id != l.0.id {
+        return Err(Status::Unauthorized);
+    }
+
+    // Archived projects are read-only
+    let locked: bool = {
+        use crate::schema::projects::dsl::{archived, projects};
+        projects
+            .find(u.project_id)
+            .select(archived)
+            .first(&*conn)
+            .expect("Failed to get project from database")
+    };
+    if locked {
+        return Err(Status::Conflict);
+    }
+
+    let w = if let Ok(d) = NaiveDate::parse_from_str(&form.week, "%F") {
+        week_of(d)
+    } else {
+        return Ok(Redirect::to(format!(
+            "/updates/{}/edit?e={}",
+            uid,
+            FormError::InvalidDate
+        )));
+    };
+
+    // Each user has one update per project each week
+    let taken: bool = select(exists(
+        status_updates.filter(
+            user_id
+                .eq(u.user_id)
+                .and(project_id.eq(u.project_id))
+                .and(week.eq(w))
+                .and(id.ne(uid)),
+        ),
+    ))
+    .get_result(&*conn)
+    .expect("Failed to get status updates from database");
+    if taken {
+        return Ok(Redirect::to(format!(
+            "/updates/{}/edit?e={}",
+            uid,
+            FormError::UpdateExists
+        )));
+    }
+
+    update(status_updates.find(uid))
+        .set((
+            week.eq(w),
+            body.eq(form.body),
+            reviewed_by.eq(None::<i32>),
+            reviewed_at.eq(None::<chrono::NaiveDateTime>),
+        ))
+        .execute(&*conn)
+        .expect("Failed to update status update in database");
+
+    Ok(Redirect::to(format!("/updates/{}", uid)))
+}
+
+/// POST handler for `/updates/<uid>/comments`
+///
+/// Restricted to Mentors and the author of the update.
+#[post("/updates/<uid>/comments", data = "<form>")]
+pub fn update_comment_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    uid: i32,
+    form: Form<CommentForm>,
+) -> Result<Redirect, Status> {
+    let author: i32 = {
+        use crate::schema::status_updates::dsl::*;
+        status_updates
+            .find(uid)
+            .select(user_id)
+            .first(&*conn)
+            .expect("Failed to get status update from database")
+    };
+
+    if l.0.tier > 0 || l.0.id == author {
+        use crate::schema::status_update_comments::dsl::*;
+        insert_into(status_update_comments)
+            .values(&NewStatusUpdateComment {
+                update_id: uid,
+                user_id: l.0.id,
+                body: form.into_inner().body,
+            })
+            .execute(&*conn)
+            .expect("Failed to insert comment into database");
+        Ok(Redirect::to(format!("/updates/{}", uid)))
+    } else {
+        Err(Status::Unauthorized)
+    }
+}
+
+/// POST handler for `/updates/<uid>/review`
+///
+/// Marks the update as reviewed by the logged in mentor.
+///
+/// Restricted to Mentors.
+#[post("/updates/<uid>/review")]
+pub fn update_review_post(
+    conn: ObservDbConn,
+    l: MentorGuard,
+    uid: i32,
+) -> Result<Redirect, Status> {
+    use crate::schema::status_updates::dsl::*;
+
+    status_updates
+        .find(uid)
+        .select(id)
+        .first::<i32>(&*conn)
+        .optional()
+        .expect("Failed to get status update from database")
+        .ok_or(Status::NotFound)?;
+
+    update(status_updates.find(uid))
+        .set((
+            reviewed_by.eq(Some(l.0.id)),
+            reviewed_at.eq(Some(Local::now().naive_local())),
+        ))
+        .execute(&*conn)
+        .expect("Failed to update status update in database");
+    Ok(Redirect::to(format!("/updates/{}", uid)))
+}
+
+//# Helper Functions
+
+fn get_user(conn: &SqliteConnection, uid: i32) -> User {
+    use crate::schema::users::dsl::*;
+    users
+        .find(uid)
+        .first(conn)
+        .expect("Failed to get user from database")
+}
+
+fn with_authors(conn: &SqliteConnection, updates: Vec<StatusUpdate>) -> Vec<UpdateEntry> {
+    updates
+        .into_iter()
+        .map(|u| UpdateEntry {
+            author: get_user(conn, u.user_id),
+            update: u,
+        })
+        .collect()
+}
+
+/// The user's active projects they haven't posted an update for this week
+pub fn missing_updates(conn: &SqliteConnection, user: &User) -> Vec<Project> {
+    use crate::schema::status_updates::dsl::*;
+
+    let posted: Vec<i32> = status_updates
+        .filter(user_id.eq(user.id).and(week.eq(this_week())))
+        .select(project_id)
+        .load(conn)
+        .expect("Failed to get status updates from database");
+
+    user_projects(conn, user)
+        .into_iter()
+        .filter(|p| p.active && !p.archived && !posted.contains(&p.id))
+        .collect()
+}
+
+/// Count the weeks of a semester the user posted at least one update
+///
+/// Returns the number of weeks with an update and the number of weeks
+/// in the semester so far.
+pub fn updates_summary(conn: &SqliteConnection, user: &User, sem: &Semester) -> (usize, usize) {
+    use crate::schema::status_updates::dsl::*;
+
+    let weeks = sem.weeks_so_far();
+    let mut posted: Vec<NaiveDate> = status_updates
+        .filter(user_id.eq(user.id))
+        .filter(week.ge(week_of(sem.start())).and(week.lt(sem.end())))
+        .select(week)
+        .load(conn)
+        .expect("Failed to get status updates from database");
+    posted.sort();
+    posted.dedup();
+
+    (posted.len(), weeks.len())
+}
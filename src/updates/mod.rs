@@ -0,0 +1,18 @@
+//! Weekly status updates
+//!
+//! Each week students post a short update on what they did for each of
+//! their projects. Mentors read them, leave comments, and mark them as
+//! reviewed. The number of weeks with an update counts toward grading.
+//!
+//! ## Routes
+//! - `/projects/<h>/updates`
+//! - `/projects/<h>/updates/new`
+//! - `/updates/<uid>`
+//! - `/updates/<uid>/edit`
+//! - `/updates/<uid>/comments`
+//! - `/updates/<uid>/review`
+
+pub mod handlers;
+pub mod models;
+
+mod templates;
@@ -0,0 +1,85 @@
+//! Models for status updates
+//!
+//! Status updates are stored in the `status_updates` table with one row
+//! per student, per project, per week. Mentors can leave comments on them
+//! which are stored in `status_update_comments`.
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::models::{Project, User};
+use crate::schema::*;
+
+/// A weekly status update
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(User)]
+#[belongs_to(Project)]
+pub struct StatusUpdate {
+    /// ID of the update
+    pub id: i32,
+    /// ID of the user who posted the update
+    pub user_id: i32,
+    /// ID of the project the update is about
+    pub project_id: i32,
+    /// The Monday of the week the update is for
+    pub week: NaiveDate,
+    /// The update itself as Markdown
+    pub body: String,
+    /// When the update was posted
+    pub created_at: NaiveDateTime,
+    /// ID of the mentor who reviewed the update
+    pub reviewed_by: Option<i32>,
+    /// When the update was reviewed
+    pub reviewed_at: Option<NaiveDateTime>,
+}
+
+/// Used to post a new status update
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "status_updates"]
+pub struct NewStatusUpdate {
+    pub user_id: i32,
+    pub project_id: i32,
+    pub week: NaiveDate,
+    pub body: String,
+}
+
+/// The status update form
+///
+/// The week is a date in any day of the week the update is for.
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct StatusUpdateForm {
+    pub week: String,
+    pub body: String,
+}
+
+/// A comment on a status update
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(StatusUpdate, foreign_key = "update_id")]
+#[belongs_to(User)]
+#[table_name = "status_update_comments"]
+pub struct StatusUpdateComment {
+    /// ID of the comment
+    pub id: i32,
+    /// ID of the update being commented on
+    pub update_id: i32,
+    /// ID of the user who wrote the comment
+    pub user_id: i32,
+    /// The comment as Markdown
+    pub body: String,
+    /// When the comment was posted
+    pub created_at: NaiveDateTime,
+}
+
+/// Used to post a new comment
+#[derive(Debug, Default, Clone, Insertable)]
+#[table_name = "status_update_comments"]
+pub struct NewStatusUpdateComment {
+    pub update_id: i32,
+    pub user_id: i32,
+    pub body: String,
+}
+
+/// The comment form
+#[derive(Debug, Default, Clone, FromForm)]
+pub struct CommentForm {
+    pub body: String,
+}
@@ -0,0 +1,67 @@
+use super::models::*;
+#[allow(unused_imports)]
+use crate::templates::{filters, FormError, OptUser};
+
+use chrono::NaiveDate;
+
+use crate::models::{Project, User};
+
+/// A status update along with the user who posted it
+pub struct UpdateEntry {
+    pub update: StatusUpdate,
+    pub author: User,
+}
+
+/// A comment along with the user who left it
+pub struct CommentEntry {
+    pub comment: StatusUpdateComment,
+    pub author: User,
+}
+
+/// Status updates of a project
+///
+/// HTML File: `updates/project-updates.html`
+#[derive(Template)]
+#[template(path = "updates/project-updates.html")]
+pub struct ProjectUpdatesTemplate {
+    pub logged_in: OptUser,
+    pub project: Project,
+    pub updates: Vec<UpdateEntry>,
+}
+
+/// A single status update and its comments
+///
+/// HTML File: `updates/update.html`
+#[derive(Template)]
+#[template(path = "updates/update.html")]
+pub struct StatusUpdateTemplate {
+    pub logged_in: OptUser,
+    pub update: StatusUpdate,
+    pub project: Project,
+    pub author: User,
+    pub reviewer: Option<User>,
+    pub comments: Vec<CommentEntry>,
+}
+
+/// Form to post a status update
+///
+/// HTML File: `updates/new-update.html`
+#[derive(Template)]
+#[template(path = "updates/new-update.html")]
+pub struct NewStatusUpdateTemplate {
+    pub logged_in: OptUser,
+    pub project: Project,
+    pub week: NaiveDate,
+    pub error: Option<FormError>,
+}
+
+/// Form to edit a status update
+///
+/// HTML File: `updates/edit-update.html`
+#[derive(Template)]
+#[template(path = "updates/edit-update.html")]
+pub struct EditStatusUpdateTemplate {
+    pub logged_in: OptUser,
+    pub update: StatusUpdate,
+    pub error: Option<FormError>,
+}
@@ -0,0 +1,369 @@
+use std::io::{Cursor, Read};
+
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel::update;
+use rocket::data::Data;
+use rocket::http::{ContentType, Status};
+use rocket::response::{Redirect, Response};
+use rocket::State;
+
+use crate::activity::handlers::log_activity;
+use crate::activity::models::ActivityKind;
+use crate::guards::*;
+use crate::models::{NewsStory, Project, User};
+use crate::projects::handlers::can_manage_project;
+use crate::templates::FormError;
+use crate::ObservDbConn;
+
+use super::models::*;
+use super::process::{process, sniff, ImageError, UploadKind, MAX_UPLOAD};
+use super::storage::{valid_name, UploadStore};
+
+/// GET handler for `/uploads/<name>`
+///
+/// Files never change once uploaded so they can be cached forever.
+#[get("/uploads/<name>")]
+pub fn upload(store: State<UploadStore>, name: String) -> Option<Response<'static>> {
+    if !valid_name(&name) || !name.ends_with(".webp") {
+        return None;
+    }
+    let bytes = store.0.get(&name).ok()?;
+
+    Some(
+        Response::build()
+            .status(Status::Ok)
+            .header(ContentType::new("image", "webp"))
+            .raw_header("Cache-Control", "public, max-age=31536000, immutable")
+            .sized_body(Cursor::new(bytes))
+            .finalize(),
+    )
+}
+
+/// POST handler for `/projects/<h>/logo`
+///
+/// The body is the image file itself.
+#[post("/projects/<h>/logo", data = "<data>")]
+pub fn project_logo_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    store: State<UploadStore>,
+    h: i32,
+    data: Data,
+) -> Result<Redirect, Status> {
+    use crate::schema::projects::dsl::*;
+
+    let p: Project = projects
+        .find(h)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get project from database")
+        .ok_or(Status::NotFound)?;
+    if !can_manage_project(&*conn, &l.0, &p) {
+        return Err(Status::Unauthorized);
+    }
+
+    let key = match save_upload(&*conn, &*store, data, UploadKind::Logo, &l.0) {
+        Ok(k) => k,
+        Err(e) => return Ok(Redirect::to(format!("/projects/{}/edit?e={}", h, e))),
+    };
+    update(projects.find(h))
+        .set(logo.eq(&key))
+        .execute(&*conn)
+        .expect("Failed to update project in database");
+    replaced(&*conn, &*store, p.logo, &key);
+    log_activity(
+        &*conn,
+        h,
+        Some(l.0.id),
+        ActivityKind::Edit,
+        "changed the logo",
+        &format!("/projects/{}", h),
+    );
+
+    Ok(Redirect::to(format!("/projects/{}/edit", h)))
+}
+
+/// DELETE handler for `/projects/<h>/logo`
+///
+/// Removes the project's logo.
+#[delete("/projects/<h>/logo")]
+pub fn project_logo_delete(
+    conn: ObservDbConn,
+    l: UserGuard,
+    store: State<UploadStore>,
+    h: i32,
+) -> Result<Redirect, Status> {
+    use crate::schema::projects::dsl::*;
+
+    let p: Project = projects
+        .find(h)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get project from database")
+        .ok_or(Status::NotFound)?;
+    if !can_manage_project(&*conn, &l.0, &p) {
+        return Err(Status::Unauthorized);
+    }
+
+    update(projects.find(h))
+        .set(logo.eq(None::<String>))
+        .execute(&*conn)
+        .expect("Failed to update project in database");
+    if let Some(old) = p.logo {
+        release(&*conn, &*store, &old);
+    }
+
+    Ok(Redirect::to(format!("/projects/{}/edit", h)))
+}
+
+/// POST handler for `/users/<h>/avatar`
+///
+/// The body is the image file itself.
+#[post("/users/<h>/avatar", data = "<data>")]
+pub fn user_avatar_post(
+    conn: ObservDbConn,
+    l: UserGuard,
+    store: State<UploadStore>,
+    h: i32,
+    data: Data,
+) -> Result<Redirect, Status> {
+    use crate::schema::users::dsl::*;
+
+    if !(l.0.tier > 1 || l.0.id == h) {
+        return Err(Status::Unauthorized);
+    }
+    let u: User = users
+        .find(h)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get user from database")
+        .ok_or(Status::NotFound)?;
+
+    let key = match save_upload(&*conn, &*store, data, UploadKind::Avatar, &l.0) {
+        Ok(k) => k,
+        Err(e) => return Ok(Redirect::to(format!("/users/{}/edit?e={}", h, e))),
+    };
+    update(users.find(h))
+        .set(avatar.eq(&key))
+        .execute(&*conn)
+        .expect("Failed to update user in database");
+    replaced(&*conn, &*store, u.avatar, &key);
+
+    Ok(Redirect::to(format!("/users/{}/edit", h)))
+}
+
+/// DELETE handler for `/users/<h>/avatar`
+///
+/// Removes the user's avatar.
+#[delete("/users/<h>/avatar")]
+pub fn user_avatar_delete(
+    conn: ObservDbConn,
+    l: UserGuard,
+    store: State<UploadStore>,
+    h: i32,
+) -> Result<Redirect, Status> {
+    use crate::schema::users::dsl::*;
+
+    if !(l.0.tier > 1 || l.0.id == h) {
+        return Err(Status::Unauthorized);
+    }
+    let u: User = users
+        .find(h)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get user from database")
+        .ok_or(Status::NotFound)?;
+
+    update(users.find(h))
+        .set(avatar.eq(None::<String>))
+        .execute(&*conn)
+        .expect("Failed to update user in database");
+    if let Some(old) = u.avatar {
+        release(&*conn, &*store, &old);
+    }
+
+    Ok(Redirect::to(format!("/users/{}/edit", h)))
+}
+
+/// POST handler for `/news/<nid>/image`
+///
+/// The body is the image file itself.
+#[post("/news/<nid>/image", data = "<data>")]
+pub fn story_image_post(
+    conn: ObservDbConn,
+    l: AdminGuard,
+    store: State<UploadStore>,
+    nid: i32,
+    data: Data,
+) -> Result<Redirect, Status> {
+    use crate::schema::news::dsl::*;
+
+    let story: NewsStory = news
+        .find(nid)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get news story from database")
+        .ok_or(Status::NotFound)?;
+
+    let key = match save_upload(&*conn, &*store, data, UploadKind::News, &l.0) {
+        Ok(k) => k,
+        Err(e) => return Ok(Redirect::to(format!("/news/{}/edit?e={}", nid, e))),
+    };
+    update(news.find(nid))
+        .set(image.eq(&key))
+        .execute(&*conn)
+        .expect("Failed to update news story in database");
+    replaced(&*conn, &*store, story.image, &key);
+
+    Ok(Redirect::to(format!("/news/{}/edit", nid)))
+}
+
+/// DELETE handler for `/news/<nid>/image`
+///
+/// Removes the news story's image.
+#[delete("/news/<nid>/image")]
+pub fn story_image_delete(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    store: State<UploadStore>,
+    nid: i32,
+) -> Result<Redirect, Status> {
+    use crate::schema::news::dsl::*;
+
+    let story: NewsStory = news
+        .find(nid)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get news story from database")
+        .ok_or(Status::NotFound)?;
+
+    update(news.find(nid))
+        .set(image.eq(None::<String>))
+        .execute(&*conn)
+        .expect("Failed to update news story in database");
+    if let Some(old) = story.image {
+        release(&*conn, &*store, &old);
+    }
+
+    Ok(Redirect::to(format!("/news/{}/edit", nid)))
+}
+
+//# Helper Functions
+
+/// Read, check, and store an uploaded image
+///
+/// Returns the key of the upload. Identical images of the same kind are
+/// only stored once.
+pub fn save_upload(
+    conn: &SqliteConnection,
+    store: &UploadStore,
+    data: Data,
+    kind: UploadKind,
+    by: &User,
+) -> Result<String, FormError> {
+    use crate::schema::uploads::dsl::*;
+    use ring::digest;
+
+    // Read one byte past the limit to tell if the upload is too big
+    let mut bytes = Vec::new();
+    data.open()
+        .take(MAX_UPLOAD + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|_| FormError::Other)?;
+    if bytes.len() as u64 > MAX_UPLOAD {
+        return Err(FormError::FileTooLarge);
+    }
+    let ctype = sniff(&bytes).ok_or(FormError::InvalidImage)?;
+
+    let hash: String = digest::digest(&digest::SHA256, &bytes)
+        .as_ref()
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let ukey = format!("{}-{}", kind, hash);
+
+    let exists: Option<Upload> = uploads
+        .filter(key.eq(&ukey))
+        .first(conn)
+        .optional()
+        .expect("Failed to get upload from database");
+    if exists.is_some() {
+        return Ok(ukey);
+    }
+
+    let img = process(&bytes, kind).map_err(|e| match e {
+        ImageError::TooLarge => FormError::FileTooLarge,
+        ImageError::Invalid => FormError::InvalidImage,
+    })?;
+
+    store
+        .0
+        .put(&full_name(&ukey), &img.full)
+        .and_then(|_| store.0.put(&thumb_name(&ukey), &img.thumb))
+        .map_err(|_| FormError::Other)?;
+
+    insert_into(uploads)
+        .values(&NewUpload {
+            key: ukey.clone(),
+            kind: String::from(kind.as_str()),
+            content_type: String::from(ctype),
+            size: bytes.len() as i32,
+            width: img.width as i32,
+            height: img.height as i32,
+            uploaded_by: by.id,
+        })
+        .execute(conn)
+        .expect("Failed to insert upload into database");
+
+    Ok(ukey)
+}
+
+/// Release the old upload after it was replaced by a new one
+fn replaced(conn: &SqliteConnection, store: &UploadStore, old: Option<String>, new: &str) {
+    if let Some(old) = old {
+        if old != new {
+            release(conn, store, &old);
+        }
+    }
+}
+
+/// Delete an upload if nothing uses it any more
+///
+/// Called after a logo, avatar, or image is removed or replaced.
+pub fn release(conn: &SqliteConnection, store: &UploadStore, ukey: &str) {
+    use diesel::delete;
+    use diesel::dsl::{exists, select};
+
+    let used = {
+        use crate::schema::projects::dsl::*;
+        select(exists(projects.filter(logo.eq(ukey))))
+            .get_result(conn)
+            .expect("Failed to get projects from database")
+    } || {
+        use crate::schema::users::dsl::*;
+        select(exists(users.filter(avatar.eq(ukey))))
+            .get_result(conn)
+            .expect("Failed to get users from database")
+    } || {
+        use crate::schema::news::dsl::*;
+        select(exists(news.filter(image.eq(ukey))))
+            .get_result(conn)
+            .expect("Failed to get news from database")
+    };
+    if used {
+        return;
+    }
+
+    use crate::schema::uploads::dsl::*;
+    delete(uploads.filter(key.eq(ukey)))
+        .execute(conn)
+        .expect("Failed to delete upload from database");
+    // A file left behind is harmless so don't fail the request over it
+    for name in [full_name(ukey), thumb_name(ukey)].iter() {
+        if let Err(e) = store.0.delete(name) {
+            eprintln!("\tFailed to delete upload {}: {}", name, e);
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Uploaded images
+//!
+//! Project logos, user avatars, and news images are uploaded by users.
+//! Uploads are checked, resized, and converted to WebP in a full size and
+//! a thumbnail, then saved through a `Storage` backend. Files are named by
+//! the kind of image and a hash of what was uploaded so they never change
+//! and can be cached forever.
+//!
+//! ## Routes
+//! - `/uploads/<name>`
+//! - `/projects/<h>/logo`
+//! - `/users/<h>/avatar`
+//! - `/news/<nid>/image`
+
+pub mod handlers;
+pub mod models;
+pub mod process;
+pub mod storage;
@@ -0,0 +1,47 @@
+use chrono::NaiveDateTime;
+
+use crate::models::User;
+use crate::schema::*;
+
+/// An uploaded image
+///
+/// The full size image is stored as `<key>.webp` and the thumbnail as
+/// `<key>-thumb.webp`. Identical uploads of the same kind share a key so
+/// a row may be used by more than one project, user, or story.
+#[derive(Debug, PartialEq, Clone, Queryable, Identifiable, Associations, Serialize)]
+#[belongs_to(User, foreign_key = "uploaded_by")]
+#[table_name = "uploads"]
+pub struct Upload {
+    pub id: i32,
+    pub key: String,
+    pub kind: String,
+    /// The type sniffed from the original file, it is always stored as WebP
+    pub content_type: String,
+    pub size: i32,
+    pub width: i32,
+    pub height: i32,
+    pub uploaded_by: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "uploads"]
+pub struct NewUpload {
+    pub key: String,
+    pub kind: String,
+    pub content_type: String,
+    pub size: i32,
+    pub width: i32,
+    pub height: i32,
+    pub uploaded_by: i32,
+}
+
+/// Name of the full size file for a key
+pub fn full_name(key: &str) -> String {
+    format!("{}.webp", key)
+}
+
+/// Name of the thumbnail file for a key
+pub fn thumb_name(key: &str) -> String {
+    format!("{}-thumb.webp", key)
+}
@@ -0,0 +1,140 @@
+//! Checking and resizing uploaded images
+//!
+//! Uploads are never trusted: the type is sniffed from the bytes rather
+//! than taken from the request, and every image is decoded and re-encoded
+//! as WebP so that only pixels make it into storage.
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView};
+use std::fmt;
+use std::io::Cursor;
+
+/// The largest upload accepted, in bytes
+pub const MAX_UPLOAD: u64 = 5 * 1024 * 1024;
+
+/// The most pixels an image may have before it is decoded
+///
+/// Small files can describe huge images so this is checked separately
+/// from the size of the upload.
+pub const MAX_PIXELS: u64 = 40_000_000;
+
+/// Quality passed to the WebP encoder
+const QUALITY: f32 = 80.0;
+
+/// Find the type of an image from its first bytes
+///
+/// Returns the MIME type of the formats that are accepted.
+pub fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// What an image is used for, which decides how big it is kept
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UploadKind {
+    Logo,
+    Avatar,
+    News,
+}
+
+impl UploadKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UploadKind::Logo => "logo",
+            UploadKind::Avatar => "avatar",
+            UploadKind::News => "news",
+        }
+    }
+
+    /// Largest width and height of the full image
+    pub fn full_size(self) -> u32 {
+        match self {
+            UploadKind::Logo | UploadKind::Avatar => 512,
+            UploadKind::News => 1200,
+        }
+    }
+
+    /// Largest width and height of the thumbnail
+    pub fn thumb_size(self) -> u32 {
+        match self {
+            UploadKind::Logo | UploadKind::Avatar => 128,
+            UploadKind::News => 320,
+        }
+    }
+}
+
+impl fmt::Display for UploadKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Why an upload was rejected
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ImageError {
+    /// Bigger than `MAX_UPLOAD` or `MAX_PIXELS`
+    TooLarge,
+    /// Not an image, or one that couldn't be decoded
+    Invalid,
+}
+
+/// An image ready to be stored
+pub struct Processed {
+    pub full: Vec<u8>,
+    pub thumb: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Decode an upload and encode the full size and thumbnail as WebP
+///
+/// Images are only ever made smaller, never stretched.
+pub fn process(bytes: &[u8], kind: UploadKind) -> Result<Processed, ImageError> {
+    if bytes.len() as u64 > MAX_UPLOAD {
+        return Err(ImageError::TooLarge);
+    }
+    sniff(bytes).ok_or(ImageError::Invalid)?;
+
+    let reader = image::io::Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|_| ImageError::Invalid)?;
+    let (w, h) = reader.into_dimensions().map_err(|_| ImageError::Invalid)?;
+    if w as u64 * h as u64 > MAX_PIXELS {
+        return Err(ImageError::TooLarge);
+    }
+
+    let img = image::load_from_memory(bytes).map_err(|_| ImageError::Invalid)?;
+    let full = shrink(&img, kind.full_size());
+    let thumb = shrink(&full, kind.thumb_size());
+
+    Ok(Processed {
+        width: full.width(),
+        height: full.height(),
+        full: encode(&full),
+        thumb: encode(&thumb),
+    })
+}
+
+fn shrink(img: &DynamicImage, max: u32) -> DynamicImage {
+    if img.width() > max || img.height() > max {
+        img.resize(max, max, FilterType::Lanczos3)
+    } else {
+        img.clone()
+    }
+}
+
+fn encode(img: &DynamicImage) -> Vec<u8> {
+    let rgba = img.to_rgba();
+    webp::Encoder::from_rgba(&rgba, rgba.width(), rgba.height())
+        .encode(QUALITY)
+        .to_vec()
+}
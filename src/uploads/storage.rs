@@ -0,0 +1,87 @@
+//! Where uploaded files are kept
+//!
+//! Only local storage is supported for now, other backends like object
+//! storage can be added by implementing `Storage`.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// A place to keep uploaded files
+///
+/// Names are always checked with `valid_name` before they are used.
+pub trait Storage: Send + Sync {
+    /// Save a file, replacing it if it exists
+    fn put(&self, name: &str, bytes: &[u8]) -> io::Result<()>;
+    /// Read a whole file
+    fn get(&self, name: &str) -> io::Result<Vec<u8>>;
+    /// Remove a file, it is not an error if it doesn't exist
+    fn delete(&self, name: &str) -> io::Result<()>;
+}
+
+/// The storage backend used by the handlers
+///
+/// This is managed by Rocket and set up by the `Uploads` fairing.
+pub struct UploadStore(pub Box<dyn Storage>);
+
+/// Is the name safe to use as a file name?
+///
+/// Only letters, numbers, dashes, and dots are allowed, so names can't
+/// point outside the storage directory.
+pub fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+fn invalid(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid upload name {}", name),
+    )
+}
+
+/// Keeps files in a directory on the local filesystem
+pub struct LocalStorage {
+    dir: PathBuf,
+}
+
+impl LocalStorage {
+    /// Use the directory, creating it if needed
+    pub fn new<P: Into<PathBuf>>(dir: P) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+}
+
+impl Storage for LocalStorage {
+    fn put(&self, name: &str, bytes: &[u8]) -> io::Result<()> {
+        if !valid_name(name) {
+            return Err(invalid(name));
+        }
+        // Write to a temporary file first so a file is never half written
+        let tmp = self.dir.join(format!(".{}.tmp", name));
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, self.dir.join(name))
+    }
+
+    fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+        if !valid_name(name) {
+            return Err(invalid(name));
+        }
+        fs::read(self.dir.join(name))
+    }
+
+    fn delete(&self, name: &str) -> io::Result<()> {
+        if !valid_name(name) {
+            return Err(invalid(name));
+        }
+        match fs::remove_file(self.dir.join(name)) {
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            res => res,
+        }
+    }
+}
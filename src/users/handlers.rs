@@ -5,18 +5,29 @@ use diesel::{delete, update};
 use rocket::http::Status;
 use rocket::request::Form;
 use rocket::response::Redirect;
+use rocket::State;
 
 use rocket_contrib::json::Json;
 
 use crate::auth::crypto::*;
 use crate::guards::*;
+use crate::repos::metrics::contribution_metrics;
+use crate::semester::Semester;
+use crate::templates::FormError;
+use crate::uploads::handlers::release;
+use crate::uploads::storage::UploadStore;
 use crate::ObservDbConn;
 
 use super::models::*;
 use super::templates::*;
 
-#[get("/users/<h>")]
-pub fn user(conn: ObservDbConn, l: MaybeLoggedIn, h: i32) -> Option<UserTemplate> {
+#[get("/users/<h>?<semester>")]
+pub fn user(
+    conn: ObservDbConn,
+    l: MaybeLoggedIn,
+    h: i32,
+    semester: Option<Semester>,
+) -> Option<UserTemplate> {
     use crate::schema::users::dsl::*;
 
     let u = users
@@ -29,7 +40,8 @@ pub fn user(conn: ObservDbConn, l: MaybeLoggedIn, h: i32) -> Option<UserTemplate
         logged_in: l.user(),
         projects: user_projects(&*conn, &u),
         groups: user_groups(&*conn, &u),
-        summary: grade_summary(&*conn, &u),
+        history: user_history(&*conn, &u),
+        summary: grade_summary(&*conn, &u, &semester.unwrap_or_default()),
         user: u,
     })
 }
@@ -47,8 +59,13 @@ pub fn user_by_handle(conn: ObservDbConn, _l: MaybeLoggedIn, h: String) -> Optio
     Some(Redirect::to(format!("/users/{}", u.id)))
 }
 
-#[get("/users/<h>/edit")]
-pub fn user_edit(conn: ObservDbConn, l: UserGuard, h: i32) -> Option<EditUserTemplate> {
+#[get("/users/<h>/edit?<e>")]
+pub fn user_edit(
+    conn: ObservDbConn,
+    l: UserGuard,
+    h: i32,
+    e: Option<FormError>,
+) -> Option<EditUserTemplate> {
     use crate::schema::users::dsl::*;
 
     Some(EditUserTemplate {
@@ -58,6 +75,7 @@ pub fn user_edit(conn: ObservDbConn, l: UserGuard, h: i32) -> Option<EditUserTem
             .first(&*conn)
             .optional()
             .expect("Failed to get user from database")?,
+        error: e,
     })
 }
 
@@ -107,11 +125,26 @@ pub fn user_edit_put(
 }
 
 #[delete("/users/<h>")]
-pub fn user_delete(conn: ObservDbConn, _l: AdminGuard, h: i32) -> Redirect {
+pub fn user_delete(
+    conn: ObservDbConn,
+    _l: AdminGuard,
+    store: State<UploadStore>,
+    h: i32,
+) -> Redirect {
     use crate::schema::users::dsl::*;
+    let old: Option<String> = users
+        .find(h)
+        .select(avatar)
+        .first(&*conn)
+        .optional()
+        .expect("Failed to get user from database")
+        .flatten();
     delete(users.find(h))
         .execute(&*conn)
         .expect("Failed to delete user from database");
+    if let Some(old) = old {
+        release(&*conn, &*store, &old);
+    }
     Redirect::to("/users")
 }
 
@@ -147,7 +180,9 @@ pub fn filter_users(conn: &SqliteConnection, term: Option<String>) -> Vec<User>
 
 use crate::models::{Project, RelationProjectUser};
 pub fn user_projects(conn: &SqliteConnection, user: &User) -> Vec<Project> {
+    use crate::schema::relation_project_user::dsl::left_at;
     RelationProjectUser::belonging_to(user)
+        .filter(left_at.is_null())
         .load::<RelationProjectUser>(conn)
         .expect("Failed to load relations from database")
         .iter()
@@ -161,9 +196,35 @@ pub fn user_projects(conn: &SqliteConnection, user: &User) -> Vec<Project> {
         .collect()
 }
 
+/// Every project the user was a member of at some point in the semester
+pub fn user_projects_during(conn: &SqliteConnection, user: &User, sem: &Semester) -> Vec<Project> {
+    let (from, until) = (sem.start().and_hms(0, 0, 0), sem.end().and_hms(0, 0, 0));
+    let mut pids: Vec<i32> = RelationProjectUser::belonging_to(user)
+        .load::<RelationProjectUser>(conn)
+        .expect("Failed to load relations from database")
+        .iter()
+        .filter(|r| {
+            r.joined_at.map(|j| j < until).unwrap_or(true)
+                && r.left_at.map(|l| from < l).unwrap_or(true)
+        })
+        .map(|r| r.project_id)
+        .collect();
+    // Someone who left and rejoined has a relation for each time
+    pids.sort();
+    pids.dedup();
+
+    use crate::schema::projects::dsl::*;
+    projects
+        .filter(id.eq_any(pids))
+        .load(conn)
+        .expect("Failed to load projects from database")
+}
+
 use crate::models::{Group, RelationGroupUser};
 pub fn user_groups(conn: &SqliteConnection, user: &User) -> Vec<Group> {
+    use crate::schema::relation_group_user::dsl::left_at;
     RelationGroupUser::belonging_to(user)
+        .filter(left_at.is_null())
         .load::<RelationGroupUser>(conn)
         .expect("Failed to get relations from database")
         .iter()
@@ -177,7 +238,52 @@ pub fn user_groups(conn: &SqliteConnection, user: &User) -> Vec<Group> {
         .collect()
 }
 
-pub fn grade_summary(conn: &SqliteConnection, user: &User) -> GradeSummary {
+/// Every group and project the user is or was in, most recent first
+pub fn user_history(conn: &SqliteConnection, user: &User) -> Vec<MembershipRow> {
+    let mut rows: Vec<MembershipRow> = {
+        use crate::schema::groups::dsl::groups;
+        RelationGroupUser::belonging_to(user)
+            .inner_join(groups)
+            .load::<(RelationGroupUser, Group)>(conn)
+            .expect("Failed to get relations from database")
+            .into_iter()
+            .map(|(r, g)| MembershipRow {
+                kind: "Group",
+                name: g.name,
+                url: format!("/groups/{}", g.id),
+                joined_at: r.joined_at,
+                left_at: r.left_at,
+            })
+            .collect()
+    };
+    {
+        use crate::schema::projects::dsl::projects;
+        rows.extend(
+            RelationProjectUser::belonging_to(user)
+                .inner_join(projects)
+                .load::<(RelationProjectUser, Project)>(conn)
+                .expect("Failed to get relations from database")
+                .into_iter()
+                .map(|(r, p)| MembershipRow {
+                    kind: "Project",
+                    name: p.name,
+                    url: format!("/projects/{}", p.id),
+                    joined_at: r.joined_at,
+                    left_at: r.left_at,
+                }),
+        );
+    }
+    // Current memberships first, then by when they were left
+    rows.sort_by(|a, b| match (a.left_at, b.left_at) {
+        (None, None) => b.joined_at.cmp(&a.joined_at),
+        (None, Some(_)) => std::cmp::Ordering::Less,
+        (Some(_), None) => std::cmp::Ordering::Greater,
+        (Some(x), Some(y)) => y.cmp(&x),
+    });
+    rows
+}
+
+pub fn grade_summary(conn: &SqliteConnection, user: &User, sem: &Semester) -> GradeSummary {
     use crate::models::Attendable;
     use crate::models::Attendance;
 
@@ -206,42 +312,53 @@ pub fn grade_summary(conn: &SqliteConnection, user: &User) -> GradeSummary {
                 ) as Box<dyn Attendable>
             }
         })
+        .filter(|a| sem.contains(a.time()))
         .collect();
 
-    let nat: usize = user_groups(conn, user).iter().fold(0, |a, g| {
-        use crate::schema::meetings::dsl::*;
-        a + meetings
-            .filter(group_id.eq(g.id))
-            .count()
-            .get_result::<i64>(conn)
-            .expect("Failed to get a count of meetings") as usize
-    });
+    let nat = required_meetings(conn, user.id, sem);
+
+    let (updates_posted, updates_needed) = updates_summary(conn, user, sem);
+    let projects = user_projects_during(conn, user, sem);
 
     GradeSummary {
         attendances: at,
         needed_attendances: nat,
         commit_count: user_commits_count(conn, user),
+        synced_at: last_synced(conn, &projects.iter().map(|p| p.id).collect::<Vec<i32>>()),
+        semester: *sem,
+        metrics: projects
+            .iter()
+            .map(|p| contribution_metrics(conn, user, p, sem))
+            .collect(),
+        updates_posted,
+        updates_needed,
+        evaluation_score: evaluation_score(conn, user, sem),
+        grade: submitted_grade(conn, user, sem),
     }
 }
 
-use crate::handlers::project_commits;
+use crate::evaluations::handlers::evaluation_score;
+use crate::grades::handlers::submitted_grade;
+use crate::groups::handlers::required_meetings;
+use crate::repos::handlers::last_synced;
+use crate::updates::handlers::updates_summary;
+
+/// Count the user's commits to their projects
+///
+/// This only reads the repository cache so it never waits on GitHub.
+/// Returns `None` if none of the user's projects have been synced yet.
 pub fn user_commits_count(conn: &SqliteConnection, user: &User) -> Option<usize> {
+    let pids: Vec<i32> = user_projects(conn, user).iter().map(|p| p.id).collect();
+    last_synced(conn, &pids)?;
+
+    use crate::repos::metrics::lower;
+    use crate::schema::repo_commits::dsl::*;
     Some(
-        user_projects(conn, &user)
-            .iter()
-            .filter_map(|p| project_commits(conn, p))
-            .flatten()
-            .collect::<Vec<serde_json::Value>>()
-            .first()?
-            .as_array()?
-            .iter()
-            .filter_map(|c| {
-                if c.get("author")?.get("login")?.as_str()? == user.handle {
-                    Some(c)
-                } else {
-                    None
-                }
-            })
-            .count(),
+        repo_commits
+            .filter(project_id.eq_any(&pids))
+            .filter(lower(author).eq(lower(&user.handle)))
+            .count()
+            .get_result::<i64>(conn)
+            .expect("Failed to count commits in database") as usize,
     )
 }
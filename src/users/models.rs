@@ -18,6 +18,8 @@ pub struct User {
     pub mmost: String,
     pub former: bool,
     pub extrn: bool,
+    /// Key of the uploaded avatar, see `uploads`
+    pub avatar: Option<String>,
 }
 
 #[derive(Debug, Default, Clone, FromForm, Insertable, AsChangeset)]
@@ -37,10 +39,32 @@ pub struct NewUser {
 }
 
 use crate::models::Attendable;
+use crate::repos::metrics::ContributionMetrics;
+use crate::semester::Semester;
 
 #[derive(Debug, Default)]
 pub struct GradeSummary {
     pub attendances: Vec<Box<dyn Attendable>>,
     pub needed_attendances: usize,
     pub commit_count: Option<usize>,
+    pub synced_at: Option<NaiveDateTime>,
+    /// The semester the contribution metrics are for
+    pub semester: Semester,
+    /// Contributions to each of the user's projects
+    pub metrics: Vec<ContributionMetrics>,
+    /// Weeks of the semester with at least one status update
+    pub updates_posted: usize,
+    /// Weeks of the semester so far
+    pub updates_needed: usize,
+    /// Average score of graded evaluations as a percentage
+    pub evaluation_score: Option<u32>,
+    /// The final grade once it has been submitted
+    pub grade: Option<i32>,
+}
+
+impl GradeSummary {
+    /// Contributions to all of the user's projects combined
+    pub fn contributions(&self) -> ContributionMetrics {
+        ContributionMetrics::total(&self.metrics)
+    }
 }
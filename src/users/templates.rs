@@ -1,12 +1,14 @@
 //!
 
+use chrono::NaiveDateTime;
+
 use super::models::*;
 use crate::models::{Group, Project};
 
 #[allow(unused_imports)]
 use crate::models::Attendable;
 #[allow(unused_imports)]
-use crate::templates::{filters, OptUser};
+use crate::templates::{filters, FormError, OptUser};
 
 #[derive(Template)]
 #[template(path = "user/user.html")]
@@ -16,6 +18,20 @@ pub struct UserTemplate {
     pub projects: Vec<Project>,
     pub summary: GradeSummary,
     pub groups: Vec<Group>,
+    /// Every group and project the user is or was in
+    pub history: Vec<MembershipRow>,
+}
+
+/// A group or project the user is or was in
+pub struct MembershipRow {
+    /// Either `Group` or `Project`
+    pub kind: &'static str,
+    pub name: String,
+    pub url: String,
+    /// When they joined, `None` if it was before this was kept
+    pub joined_at: Option<NaiveDateTime>,
+    /// When they left, `None` if they are still in it
+    pub left_at: Option<NaiveDateTime>,
 }
 
 #[derive(Template)]
@@ -23,6 +39,7 @@ pub struct UserTemplate {
 pub struct EditUserTemplate {
     pub logged_in: OptUser,
     pub user: User,
+    pub error: Option<FormError>,
 }
 
 #[derive(Template)]
@@ -19,6 +19,21 @@ document.addEventListener('DOMContentLoaded', () => {
         });
     });
 
+    // File inputs with `data-upload` send the chosen file as soon as
+    // it is picked, the response redirects back with any errors
+    document.querySelectorAll("input[data-upload]").forEach(input => {
+        input.addEventListener('change', (e) => {
+            if (input.files.length === 0) {
+                return;
+            }
+            input.disabled = true;
+            fetch(input.dataset.upload, {
+                method: 'POST',
+                body: input.files[0]
+            }).then((res) => window.location = res.url)
+        });
+    });
+
     // Rows in tables can be clicked on to navigate to them
     document.querySelectorAll("tr.clickable-row").forEach(row => {
         row.addEventListener('click', (e) => {